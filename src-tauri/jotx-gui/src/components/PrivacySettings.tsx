@@ -7,6 +7,9 @@ interface PrivacyConfig {
     excludes_ends_with_string: string[];
     excludes_regex: string[];
     exclude_folders: string[];
+    clipboard_blocked_apps: string[];
+    sensitive_window_titles: string[];
+    clipboard_excludes_regex: string[];
 }
 
 type ExclusionType = keyof PrivacyConfig;
@@ -18,6 +21,9 @@ export default function PrivacySettings() {
         excludes_ends_with_string: [],
         excludes_regex: [],
         exclude_folders: [],
+        clipboard_blocked_apps: [],
+        sensitive_window_titles: [],
+        clipboard_excludes_regex: [],
     });
 
     const [editingType, setEditingType] = useState<ExclusionType | null>(null);
@@ -116,6 +122,24 @@ export default function PrivacySettings() {
                 description: 'Exclude all files in these folders',
                 placeholder: 'e.g., /etc/secrets, ~/.ssh',
             },
+            {
+                key: 'clipboard_blocked_apps',
+                title: 'Clipboard App Blocklist',
+                description: 'Never capture the clipboard of these apps',
+                placeholder: 'e.g., KeePassXC, 1Password',
+            },
+            {
+                key: 'sensitive_window_titles',
+                title: 'Sensitive Window Titles',
+                description: 'Never capture clips copied while a window with this in its title is focused',
+                placeholder: 'e.g., (Incognito), Private Browsing',
+            },
+            {
+                key: 'clipboard_excludes_regex',
+                title: 'Clipboard Content Regexes',
+                description: 'Exclude clips matching these regex patterns',
+                placeholder: 'e.g., ^\\d{6}$',
+            },
         ];
 
     return (
@@ -129,7 +153,8 @@ export default function PrivacySettings() {
             <div className="privacy-intro">
                 <p>
                     Configure privacy rules to prevent sensitive information from being captured.
-                    These exclusions apply to both clipboard and shell history.
+                    The string, regex and folder exclusions apply to shell history; the
+                    app, window title and clipboard regex rules apply to the clipboard.
                 </p>
             </div>
 
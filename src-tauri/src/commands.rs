@@ -1,6 +1,8 @@
-use jotx::ask::{ask_gui, search_gui};
-use jotx::types::{GUISearchResult, PathInfo};
+use jotx::ask::{ask_gui, ask_gui_stream, search_gui};
+use jotx::ipc::{IpcRequest, send_command};
+use jotx::types::{ClipboardItem, DaemonStatus, DeleteSummary, ExportResult, GUISearchResult, HistoryFilters, HistoryPage, PathInfo, PluginCheckReport, PluginInfo, Stats, TagCount};
 use jotx::utils::{load_settings, is_ollama_running};
+use tauri::{AppHandle, Emitter};
 
 #[tauri::command]
 pub async fn ask_command(
@@ -13,6 +15,23 @@ pub async fn ask_command(
     }
 }
 
+/// Streaming variant of `ask_command`: progress is delivered as `ask-stream` events
+#[tauri::command]
+pub async fn ask_stream_command(
+    app: AppHandle,
+    query: String,
+    directory: String
+) -> Result<(), String> {
+    let emit = |event| {
+        let _ = app.emit("ask-stream", event);
+    };
+
+    match ask_gui_stream(&query, &directory, emit).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
 #[tauri::command]
 pub fn search_command(
     query: String,
@@ -24,6 +43,11 @@ pub fn search_command(
     }
 }
 
+#[tauri::command]
+pub fn get_history(page: usize, filters: Option<HistoryFilters>) -> Result<HistoryPage, String> {
+    jotx::utils::get_history(page, filters.unwrap_or_default())
+}
+
 #[tauri::command]
 pub fn get_settings() -> Result<jotx::settings::Settings, String> {
     load_settings()
@@ -44,6 +68,16 @@ pub fn save_privacy_config(updated: jotx::config::PrivacyConfig) -> Result<(), S
     jotx::utils::save_privacy_config(updated)
 }
 
+#[tauri::command]
+pub fn get_config() -> Result<jotx::config::Config, String> {
+    jotx::utils::get_config()
+}
+
+#[tauri::command]
+pub fn set_config_section(section: String, value: serde_json::Value) -> Result<(), String> {
+    jotx::utils::set_config_section(&section, value)
+}
+
 #[tauri::command]
 pub async fn check_ollama_status() -> Result<jotx::types::OllamaStatus, String> {
     match is_ollama_running().await {
@@ -60,9 +94,14 @@ pub fn remove_model(model: String) -> Result<(), String> {
     }
 }
 
+/// Progress is emitted as `model-download-progress` events while the model downloads
 #[tauri::command]
-pub fn download_model(model: String) -> Result<(), String> {
-    match jotx::llm::download_model_with_string(&model) {
+pub async fn download_model(app: AppHandle, model: String) -> Result<(), String> {
+    let emit = |progress| {
+        let _ = app.emit("model-download-progress", progress);
+    };
+
+    match jotx::llm::pull_model_with_progress(&model, emit).await {
         Ok(_) => Ok(()),
         Err(e) => Err(e.to_string()),
     }
@@ -89,3 +128,113 @@ pub fn get_all_paths() -> Result<Vec<PathInfo>, String> {
     jotx::utils::get_paths()
 }
 
+
+#[tauri::command]
+pub fn daemon_status() -> Result<DaemonStatus, String> {
+    jotx::utils::get_daemon_status()
+}
+
+#[tauri::command]
+pub async fn start_daemon() -> Result<DaemonStatus, String> {
+    jotx::utils::start_daemon()
+}
+
+#[tauri::command]
+pub async fn stop_daemon() -> Result<DaemonStatus, String> {
+    jotx::utils::stop_daemon()
+}
+
+#[tauri::command]
+pub fn set_capture_paused(paused: bool) -> Result<(), String> {
+    jotx::utils::set_capture_paused(paused)
+}
+
+/// Run maintenance in the daemon, which owns the database writer
+#[tauri::command]
+pub async fn run_maintenance() -> Result<(), String> {
+    send_command(IpcRequest::Maintenance).map(|_| ())
+}
+
+#[tauri::command]
+pub fn list_plugins() -> Result<Vec<PluginInfo>, String> {
+    jotx::utils::list_plugins()
+}
+
+#[tauri::command]
+pub fn enable_plugin(name: String) -> Result<(), String> {
+    jotx::utils::set_plugin_enabled(&name, true)
+}
+
+#[tauri::command]
+pub fn disable_plugin(name: String) -> Result<(), String> {
+    jotx::utils::set_plugin_enabled(&name, false)
+}
+
+#[tauri::command]
+pub fn check_plugin(name: Option<String>) -> Result<Vec<PluginCheckReport>, String> {
+    jotx::utils::check_plugin(name.as_deref())
+}
+
+#[tauri::command]
+pub fn create_plugin(name: String) -> Result<String, String> {
+    jotx::utils::create_plugin(&name)
+}
+
+#[tauri::command]
+pub fn get_stats(days: Option<i64>) -> Result<Stats, String> {
+    jotx::utils::get_stats(days)
+}
+
+#[tauri::command]
+pub fn delete_entries(ids: Vec<i64>) -> Result<DeleteSummary, String> {
+    jotx::utils::delete_entries(&ids)
+}
+
+/// Call with `confirm: false` first to get the match count and a preview for the dialog
+#[tauri::command]
+pub fn delete_matching(pattern: String, confirm: bool) -> Result<DeleteSummary, String> {
+    jotx::utils::delete_matching(&pattern, confirm)
+}
+
+/// `format` is json, csv, md or atuin. Pass the path from a save dialog to write a file,
+/// or omit it to get the exported bytes back.
+#[tauri::command]
+pub async fn export_history(
+    format: String,
+    filters: Option<HistoryFilters>,
+    path: Option<String>
+) -> Result<ExportResult, String> {
+    jotx::utils::export_history(&format, filters.unwrap_or_default(), path)
+}
+
+#[tauri::command]
+pub fn list_clipboard(limit: Option<usize>, search: Option<String>) -> Result<Vec<ClipboardItem>, String> {
+    jotx::utils::list_clipboard(limit, search)
+}
+
+/// Returns whether the copied text is case-folded, so the UI can say the stored
+/// capitalization may differ from what was originally copied
+#[tauri::command]
+pub fn copy_to_clipboard(id: i64) -> Result<bool, String> {
+    jotx::utils::copy_entry_to_clipboard(id)
+}
+
+#[tauri::command]
+pub fn list_tags() -> Result<Vec<TagCount>, String> {
+    jotx::utils::list_tags()
+}
+
+#[tauri::command]
+pub fn tag_entry(id: i64, tag: String) -> Result<Vec<String>, String> {
+    jotx::utils::tag_entry(id, &tag)
+}
+
+#[tauri::command]
+pub fn untag_entry(id: i64, tag: String) -> Result<Vec<String>, String> {
+    jotx::utils::untag_entry(id, &tag)
+}
+
+#[tauri::command]
+pub fn search_by_tag(tag: String, page: Option<usize>) -> Result<HistoryPage, String> {
+    jotx::utils::search_by_tag(&tag, page.unwrap_or(0))
+}
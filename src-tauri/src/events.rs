@@ -0,0 +1,21 @@
+use jotx::plugin::BlockedCaptureWatcher;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const BLOCKED_POLL_SECS: u64 = 1;
+
+/// Emit a `capture-blocked` event for every capture a plugin or privacy rule blocks,
+/// so the frontend can show a toast. Captures are filtered in the shell hook process,
+/// so this follows the blocked log rather than listening in-process.
+pub fn watch_blocked_captures(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut watcher = BlockedCaptureWatcher::new();
+
+        loop {
+            for event in watcher.poll() {
+                let _ = app.emit("capture-blocked", event);
+            }
+            std::thread::sleep(Duration::from_secs(BLOCKED_POLL_SECS));
+        }
+    });
+}
@@ -1,23 +1,57 @@
 mod commands;
+mod events;
 mod setup;
+mod tray;
 
 fn main() {
     tauri::Builder::default()
+        .setup(|app| {
+            tray::create_tray(app.handle())?;
+            events::watch_blocked_captures(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::ask_command,
+            commands::ask_stream_command,
             commands::search_command,
+            commands::get_history,
             commands::get_settings,
             commands::save_settings,
             commands::get_privacy_config,
             commands::save_privacy_config,
+            commands::get_config,
+            commands::set_config_section,
             commands::check_ollama_status,
             commands::download_model,
             commands::remove_model,
             commands::install_ollama,
             commands::start_ollama,
             commands::get_all_paths,
+            commands::daemon_status,
+            commands::start_daemon,
+            commands::stop_daemon,
+            commands::set_capture_paused,
+            commands::run_maintenance,
+            commands::list_plugins,
+            commands::enable_plugin,
+            commands::disable_plugin,
+            commands::check_plugin,
+            commands::create_plugin,
+            commands::get_stats,
+            commands::delete_entries,
+            commands::delete_matching,
+            commands::export_history,
+            commands::list_clipboard,
+            commands::copy_to_clipboard,
+            commands::list_tags,
+            commands::tag_entry,
+            commands::untag_entry,
+            commands::search_by_tag,
             setup::run_setup,
             setup::check_setup_status,
+            setup::get_setup_stages,
+            setup::run_setup_wizard,
+            setup::retry_setup_stage,
             setup::setup_hooks_gui,
             setup::uninstall_jotx
         ])
@@ -1,5 +1,29 @@
-use jotx::setup::{setup_hooks, uninstall, full_setup};
-use std::{fs, path::PathBuf, process::Command};
+use jotx::llm::PullProgress;
+use jotx::setup::{
+    completed_setup_stages, full_setup, run_setup_stage, setup_hooks, uninstall, SetupStage,
+};
+use serde::Serialize;
+use std::{fs, path::PathBuf, process::Command, str::FromStr};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupStageStatus {
+    pub stage: SetupStage,
+    pub label: String,
+    pub completed: bool,
+}
+
+/// Payload of the `setup-progress` event
+#[derive(Debug, Clone, Serialize)]
+struct SetupProgress {
+    stage: SetupStage,
+    label: String,
+    index: usize,
+    total: usize,
+    /// "started", "completed" or "failed"
+    status: String,
+    error: Option<String>,
+}
 
 pub fn is_setup_complete() -> bool {
     let jotx_dir = PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".jotx");
@@ -64,3 +88,83 @@ pub fn uninstall_jotx() -> Result<(), String> {
         .map_err(|e| format!("Uninstall failed: {}", e))?;
     Ok(())
 }
+
+#[tauri::command]
+pub fn get_setup_stages() -> Vec<SetupStageStatus> {
+    let completed = completed_setup_stages();
+
+    SetupStage::ALL
+        .iter()
+        .map(|stage| SetupStageStatus {
+            stage: *stage,
+            label: stage.label().to_string(),
+            completed: completed.contains(stage),
+        })
+        .collect()
+}
+
+/// Run every stage that has not completed yet, in order, emitting `setup-progress`
+/// events. Stops at the first failure; call again (or `retry_setup_stage`) to resume.
+#[tauri::command]
+pub async fn run_setup_wizard(app: AppHandle) -> Result<(), String> {
+    let completed = completed_setup_stages();
+
+    for stage in SetupStage::ALL.iter().filter(|s| !completed.contains(s)) {
+        run_stage_with_events(&app, *stage)?;
+    }
+
+    mark_setup_complete().map_err(|e| format!("Setup failed: {}", e))
+}
+
+#[tauri::command]
+pub async fn retry_setup_stage(app: AppHandle, stage: String) -> Result<(), String> {
+    let stage = SetupStage::from_str(&stage)?;
+    run_stage_with_events(&app, stage)?;
+
+    let completed = completed_setup_stages();
+    if SetupStage::ALL.iter().all(|s| completed.contains(s)) {
+        mark_setup_complete().map_err(|e| format!("Setup failed: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn run_stage_with_events(app: &AppHandle, stage: SetupStage) -> Result<(), String> {
+    let index = SetupStage::ALL
+        .iter()
+        .position(|s| *s == stage)
+        .unwrap_or(0);
+
+    let emit = |status: &str, error: Option<String>| {
+        let _ = app.emit(
+            "setup-progress",
+            SetupProgress {
+                stage,
+                label: stage.label().to_string(),
+                index,
+                total: SetupStage::ALL.len(),
+                status: status.to_string(),
+                error,
+            },
+        );
+    };
+
+    emit("started", None);
+
+    // Same event as the download_model command, so the model stage shows its progress
+    let on_model_progress = |progress: PullProgress| {
+        let _ = app.emit("model-download-progress", progress);
+    };
+
+    match run_setup_stage(stage, on_model_progress) {
+        Ok(_) => {
+            emit("completed", None);
+            Ok(())
+        }
+        Err(e) => {
+            let message = format!("{} failed: {}", stage.label(), e);
+            emit("failed", Some(message.clone()));
+            Err(message)
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use jotx::ipc::{IpcRequest, send_command};
+use jotx::utils::get_daemon_status;
+use tauri::menu::{Menu, MenuItem};
+use tauri::tray::{TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+const TRAY_ID: &str = "jotx-tray";
+
+/// Build the system tray icon with daemon status and quick actions. Pause, resume and
+/// maintenance are sent to the daemon over its control socket, so the GUI never
+/// touches the database or the pause file itself.
+pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
+    let status = MenuItem::with_id(app, "status", "Daemon: checking...", false, None::<&str>)?;
+    let toggle = MenuItem::with_id(app, "toggle_pause", "Pause Capture", true, None::<&str>)?;
+    let settings = MenuItem::with_id(app, "open_settings", "Open Settings", true, None::<&str>)?;
+    let maintenance = MenuItem::with_id(app, "run_maintenance", "Run Maintenance", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+
+    let menu = Menu::with_items(app, &[&status, &toggle, &settings, &maintenance, &quit])?;
+
+    refresh_menu(&status, &toggle);
+
+    let menu_status = status.clone();
+    let menu_toggle = toggle.clone();
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Jotx")
+        .menu(&menu)
+        .on_menu_event(move |app, event| {
+            match event.id.as_ref() {
+                "toggle_pause" => {
+                    let paused = get_daemon_status().map(|s| s.paused).unwrap_or(false);
+                    let command = if paused { IpcRequest::Resume } else { IpcRequest::Pause };
+                    if let Err(e) = send_command(command) {
+                        eprintln!("{}", e);
+                    }
+                }
+                "open_settings" => {
+                    if let Some(window) = app.get_webview_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                    }
+                    let _ = app.emit("open-settings", ());
+                }
+                "run_maintenance" => {
+                    let app = app.clone();
+                    std::thread::spawn(move || {
+                        let result = send_command(IpcRequest::Maintenance);
+                        let _ = app.emit("maintenance-finished", result);
+                    });
+                }
+                "quit" => app.exit(0),
+                _ => {}
+            }
+
+            refresh_menu(&menu_status, &menu_toggle);
+        })
+        .on_tray_icon_event(move |_tray, event| {
+            // Status can change outside the app (CLI, daemon exit), so refresh on open
+            if let TrayIconEvent::Click { .. } = event {
+                refresh_menu(&status, &toggle);
+            }
+        });
+
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+
+    builder.build(app)?;
+    Ok(())
+}
+
+fn refresh_menu(status: &MenuItem<Wry>, toggle: &MenuItem<Wry>) {
+    let (status_text, toggle_text) = match get_daemon_status() {
+        Ok(s) if s.running && s.paused => ("Daemon: running (paused)", "Resume Capture"),
+        Ok(s) if s.running && s.quiet.is_some() => ("Daemon: running (quiet window)", "Pause Capture"),
+        Ok(s) if s.running && !s.problems.is_empty() => ("Daemon: running (see status)", "Pause Capture"),
+        Ok(s) if s.running => ("Daemon: running", "Pause Capture"),
+        Ok(s) if s.paused => ("Daemon: stopped (paused)", "Resume Capture"),
+        Ok(_) => ("Daemon: stopped", "Pause Capture"),
+        Err(_) => ("Daemon: unknown", "Pause Capture"),
+    };
+
+    let _ = status.set_text(status_text);
+    let _ = toggle.set_text(toggle_text);
+}
@@ -0,0 +1,607 @@
+//! Local HTTP API served by the daemon when `remote.api_listen` is set. It lives in
+//! `[remote]` rather than an `[api]` section of its own because it shares the token and
+//! `allowed_interfaces` with MCP and D-Bus. Every route returns JSON; send
+//! `Authorization: Bearer <remote.token>` when a token is configured. Binds go through
+//! `remote::check_bind`, so it stays on localhost unless the address is explicitly allowed.
+//!
+//! A web page can reach localhost too, so requests with an `Origin` header or a `Host`
+//! other than the bound address or localhost (DNS rebinding) are refused, and the POST
+//! routes, which change state, need `remote.token` even on localhost.
+//!
+//! `GET /v1/health` → `{"ok": true}`
+//!
+//! `GET /v1/workspace/history?folder=<abs path>[&query=<text>][&limit=<n>]` → shell
+//! commands run in `folder` or below it, newest first, one per command:
+//! `{"folder": "...", "commands": [{"command", "working_dir", "last_run", "times_run"}]}`
+//! (`last_run` is unix seconds). This is what an editor uses for "commands I've run in
+//! this repo".
+//!
+//! `POST /v1/capture` with a form body
+//! `cmd=<command>&container=<name>[&pwd=..][&user=..][&exit_code=..][&duration_ms=..]`
+//! → stores a command run inside a container, with host `container:<name>`. The
+//! snippets from `jotx shim` send these from `docker exec` shells and devcontainers,
+//! with the bearer token, which this route always needs.
+//!
+//! `GET /v1/search?q=<text>[&type=shell|clipboard][&dir=<abs path>][&tag=<tag>][&limit=<n>]` →
+//! keyword search results, best first, as `jotx search` ranks them. With `tag` only
+//! entries carrying that tag are searched, and `q` may be left out.
+//!
+//! `GET /v1/ask?q=<question>[&dir=<abs path>]` → the GUI's answer to a question, run
+//! on the daemon's LLM so clients don't load their own.
+//!
+//! `GET /v1/entries/<id>` → one history entry, or 404.
+//!
+//! `GET /v1/status` → the same status `jotx status` shows.
+//!
+//! `GET /v1/settings` → the capture settings; `POST /v1/settings` with any of their
+//! fields as a form body (`capture_shell=false&shell_limit=5000`) changes and saves
+//! those and returns the result.
+//!
+//! `GET /metrics` → the daemon's counters in Prometheus' text format, as
+//! `jotx status --metrics` prints them.
+//!
+//! Each connection gets its own thread, so a slow `/v1/ask` doesn't hold up the rest.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tracing::{info, warn};
+
+use crate::ask::ask_handler::ask_gui;
+use crate::ask::search_handler::{SearchFilters, keyword_search};
+use crate::config::GLOBAL_CONFIG;
+use crate::db::{READ_POOL, USER_DB};
+use crate::ipc::{daemon_metrics, daemon_status};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::normalize::{fold, normalize};
+use crate::remote::{authorized, check_bind_global};
+use crate::settings::{GLOBAL_SETTINGS, Settings};
+use crate::shell::capture::capture_command;
+use crate::types::{EntryType, HistoryEntry};
+use crate::utils::{load_settings, save_settings};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+/// Request heads larger than this are rejected
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+/// Request bodies (captured commands) larger than this are rejected
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceHistory {
+    pub folder: String,
+    pub commands: Vec<WorkspaceCommand>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceCommand {
+    pub command: String,
+    pub working_dir: String,
+    pub last_run: i64,
+    pub times_run: i64,
+}
+
+/// Start the API on its own thread if `remote.api_listen` is set. Called from the
+/// daemon's runtime, which `/v1/ask` runs on.
+pub fn spawn_api_server() -> Result<(), String> {
+    let listen = GLOBAL_CONFIG
+        .read()
+        .map(|config| config.remote.api_listen.clone())
+        .map_err(|_| "Failed to read config".to_string())?;
+    let Some(listen) = listen else {
+        return Ok(());
+    };
+
+    let addr: SocketAddr = listen
+        .parse()
+        .map_err(|e| format!("Invalid remote.api_listen {}: {}", listen, e))?;
+    check_bind_global(&addr)?;
+
+    let listener = TcpListener::bind(addr).map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+    info!("HTTP API listening on http://{}", addr);
+    let runtime = Handle::try_current().ok();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let runtime = runtime.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = handle(stream, &addr, runtime.as_ref()) {
+                            warn!("API request failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("API accept failed: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn handle(stream: TcpStream, addr: &SocketAddr, runtime: Option<&Handle>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut token = None;
+    let mut host = None;
+    let mut has_origin = false;
+    let mut content_length = 0;
+    let mut head_bytes = request_line.len();
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line)?;
+        head_bytes += read;
+        if read == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if head_bytes > MAX_HEAD_BYTES {
+            return respond(stream, 431, &error_body("request head too large"));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("authorization") {
+                token = value.trim().strip_prefix("Bearer ").map(str::to_string);
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                has_origin = true;
+            } else if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return respond(stream, 413, &error_body("request body too large"));
+    }
+
+    let remote = match GLOBAL_CONFIG.read() {
+        Ok(config) => config.remote.clone(),
+        Err(_) => return respond(stream, 500, &error_body("failed to read config")),
+    };
+    if has_origin {
+        return respond(
+            stream,
+            403,
+            &error_body("requests from web pages are not accepted"),
+        );
+    }
+    if !host.is_some_and(|host| expected_host(&host, addr)) {
+        return respond(stream, 403, &error_body("unexpected Host header"));
+    }
+    if !authorized(&remote, addr, token.as_deref()) {
+        return respond(stream, 401, &error_body("missing or wrong bearer token"));
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    if method == "POST" && remote.token.is_none() {
+        return respond(
+            stream,
+            403,
+            &error_body("set remote.token to use POST routes"),
+        );
+    }
+
+    let (status, body) = match method {
+        "GET" if target == "/metrics" => {
+            let metrics = daemon_metrics().to_prometheus();
+            return respond_with(stream, 200, PROMETHEUS_CONTENT_TYPE, &metrics);
+        }
+        "GET" => route(target, runtime),
+        "POST" => {
+            let mut body = Vec::with_capacity(content_length);
+            reader.take(content_length as u64).read_to_end(&mut body)?;
+            route_post(target, &String::from_utf8_lossy(&body))
+        }
+        _ => (405, error_body("only GET and POST are supported")),
+    };
+    respond(stream, status, &body)
+}
+
+/// Whether a `Host` header names the address the API is bound to (any address when bound
+/// to all of them), or localhost. Anything else is a DNS name pointed at us by someone
+/// else, e.g. a rebinding web page.
+fn expected_host(host: &str, addr: &SocketAddr) -> bool {
+    let name = host
+        .rsplit_once(':')
+        .filter(|(_, port)| port.parse() == Ok(addr.port()))
+        .map_or(host, |(name, _)| name);
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+
+    // Docker Desktop forwards host.docker.internal to the host's localhost
+    let local_names = if addr.ip().is_loopback() {
+        ["localhost", "host.docker.internal"].as_slice()
+    } else {
+        ["localhost"].as_slice()
+    };
+    local_names
+        .iter()
+        .any(|local| name.eq_ignore_ascii_case(local))
+        || name.parse::<IpAddr>().is_ok_and(|ip| match addr.ip() {
+            bound if bound.is_unspecified() => true,
+            bound if bound.is_loopback() => ip.is_loopback(),
+            bound => ip == bound,
+        })
+}
+
+/// `key=value&...` pairs of a query string or form body, decoded
+fn form_params(encoded: &str) -> Vec<(String, String)> {
+    encoded
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+fn find_param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Status and JSON body for a POST with a form encoded body
+fn route_post(path: &str, body: &str) -> (u16, String) {
+    let params = form_params(body);
+    let param = |name: &str| find_param(&params, name).filter(|v| !v.is_empty());
+
+    match path {
+        "/v1/capture" => {
+            let (Some(cmd), Some(container)) = (param("cmd"), param("container")) else {
+                return (400, error_body("cmd and container are required"));
+            };
+            capture_command(
+                cmd.trim(),
+                param("pwd").map(str::to_string),
+                param("user").map(str::to_string),
+                Some(format!("container:{}", container)),
+                param("exit_code").and_then(|c| c.parse().ok()),
+                param("duration_ms").and_then(|d| d.parse().ok()),
+            );
+            (200, r#"{"ok":true}"#.to_string())
+        }
+        "/v1/settings" => match update_settings(&params).and_then(|settings| {
+            save_settings(&settings)?;
+            Ok(settings)
+        }) {
+            Ok(settings) => json_response(&settings),
+            Err(e) => (400, error_body(&e)),
+        },
+        _ => (404, error_body("no such route")),
+    }
+}
+
+/// Status and JSON body for a GET request target (path plus query string)
+fn route(target: &str, runtime: Option<&Handle>) -> (u16, String) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = form_params(query);
+    let param = |name: &str| find_param(&params, name);
+
+    match path {
+        "/v1/health" => (200, r#"{"ok":true}"#.to_string()),
+        "/v1/workspace/history" => {
+            let Some(folder) = param("folder").filter(|f| !f.is_empty()) else {
+                return (400, error_body("folder is required"));
+            };
+            let limit = param("limit")
+                .and_then(|l| l.parse().ok())
+                .unwrap_or(DEFAULT_LIMIT)
+                .min(MAX_LIMIT);
+
+            let case_sensitive = lock_or_recover("settings", &GLOBAL_SETTINGS).shell_case_sensitive;
+            let query = param("query").map(|query| {
+                if case_sensitive {
+                    normalize(query)
+                } else {
+                    fold(query)
+                }
+            });
+
+            let history = READ_POOL.get().map_err(|e| e.to_string()).and_then(|conn| {
+                workspace_history_in(&conn, folder, query.as_deref(), limit)
+                    .map_err(|e| e.to_string())
+            });
+            match history.and_then(|h| serde_json::to_string(&h).map_err(|e| e.to_string())) {
+                Ok(body) => (200, body),
+                Err(e) => (500, error_body(&e)),
+            }
+        }
+        "/v1/search" => {
+            let tag = param("tag").filter(|t| !t.is_empty());
+            let query = param("q").unwrap_or("");
+            if query.trim().is_empty() && tag.is_none() {
+                return (400, error_body("q or tag is required"));
+            }
+            let entry_type = match param("type").filter(|t| !t.is_empty()) {
+                Some(t) => match t.parse::<EntryType>() {
+                    Ok(entry_type) => entry_type,
+                    Err(e) => return (400, error_body(&e)),
+                },
+                None => EntryType::Any,
+            };
+            let limit = param("limit")
+                .and_then(|l| l.parse().ok())
+                .unwrap_or(DEFAULT_LIMIT)
+                .min(MAX_LIMIT);
+
+            let filters = SearchFilters {
+                tag: tag.map(str::to_string),
+                ..Default::default()
+            };
+            match keyword_search(query, entry_type, param("dir").unwrap_or(""), &filters) {
+                Ok(mut results) => {
+                    results.truncate(limit);
+                    json_response(&results)
+                }
+                Err(e) => (500, error_body(&e.to_string())),
+            }
+        }
+        "/v1/ask" => {
+            let Some(query) = param("q").filter(|q| !q.trim().is_empty()) else {
+                return (400, error_body("q is required"));
+            };
+            let Some(runtime) = runtime else {
+                return (500, error_body("the API is not running in the daemon"));
+            };
+            match runtime.block_on(ask_gui(query, param("dir").unwrap_or(""))) {
+                Ok(results) => json_response(&results),
+                Err(e) => (500, error_body(&e.to_string())),
+            }
+        }
+        "/v1/status" => json_response(&daemon_status()),
+        "/v1/settings" => match load_settings() {
+            Ok(settings) => json_response(&settings),
+            Err(e) => (500, error_body(&e)),
+        },
+        _ => match path.strip_prefix("/v1/entries/") {
+            Some(id) => entry_response(id),
+            None => (404, error_body("no such route")),
+        },
+    }
+}
+
+fn entry_response(id: &str) -> (u16, String) {
+    let Ok(id) = id.parse::<i64>() else {
+        return (400, error_body("entry id must be a number"));
+    };
+    let entry = lock_or_recover("database", &USER_DB).get_entry(id);
+    match entry {
+        Ok(Some(entry)) => json_response(&HistoryEntry::from(entry)),
+        Ok(None) => (404, error_body(&format!("no entry with id {}", id))),
+        Err(e) => (500, error_body(&e.to_string())),
+    }
+}
+
+/// The current settings with the fields present in `params` changed
+fn update_settings(params: &[(String, String)]) -> Result<Settings, String> {
+    let mut settings = load_settings()?;
+    for (key, value) in params {
+        let flag = || {
+            value
+                .parse::<bool>()
+                .map_err(|_| format!("{} must be true or false", key))
+        };
+        let number = || {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("{} must be a number", key))
+        };
+        match key.as_str() {
+            "capture_clipboard" => settings.capture_clipboard = flag()?,
+            "capture_shell" => settings.capture_shell = flag()?,
+            "capture_shell_history_with_files" => {
+                settings.capture_shell_history_with_files = flag()?
+            }
+            "shell_case_sensitive" => settings.shell_case_sensitive = flag()?,
+            "clipboard_case_sensitive" => settings.clipboard_case_sensitive = flag()?,
+            "clipboard_limit" => settings.clipboard_limit = number()?,
+            "shell_limit" => settings.shell_limit = number()?,
+            _ => return Err(format!("unknown setting {}", key)),
+        }
+    }
+    Ok(settings)
+}
+
+fn json_response<T: Serialize>(value: &T) -> (u16, String) {
+    match serde_json::to_string(value) {
+        Ok(body) => (200, body),
+        Err(e) => (500, error_body(&e.to_string())),
+    }
+}
+
+/// Shell commands run in `folder` or a directory below it, newest first, one per
+/// command, optionally only those containing `query`, which must already be normalized
+/// (or case-folded) the way shell commands are stored
+pub(crate) fn workspace_history_in(
+    conn: &Connection,
+    folder: &str,
+    query: Option<&str>,
+    limit: usize,
+) -> rusqlite::Result<WorkspaceHistory> {
+    let folder = folder.trim_end_matches('/');
+    let folder = if folder.is_empty() { "/" } else { folder };
+    let query = query.unwrap_or_default();
+
+    // Prefix compare rather than LIKE, so '%' and '_' in paths are taken literally
+    let mut stmt = conn.prepare_cached(
+        "SELECT content, working_dir, timestamp, times_run FROM entries
+         WHERE entry_type = ?1
+           AND (working_dir = ?2 OR substr(working_dir, 1, length(?3)) = ?3)
+           AND (?4 = '' OR instr(content, ?4) > 0)
+         ORDER BY timestamp DESC",
+    )?;
+    let below = format!("{}/", folder.trim_end_matches('/'));
+
+    let mut seen = HashSet::new();
+    let mut commands = Vec::new();
+    let mut rows = stmt.query(rusqlite::params![
+        EntryType::Shell.to_string(),
+        folder,
+        below,
+        query
+    ])?;
+    while let Some(row) = rows.next()? {
+        let command: String = row.get(0)?;
+        if !seen.insert(command.clone()) {
+            continue;
+        }
+        commands.push(WorkspaceCommand {
+            command,
+            working_dir: row.get(1)?,
+            last_run: row.get(2)?,
+            times_run: row.get(3)?,
+        });
+        if commands.len() >= limit {
+            break;
+        }
+    }
+
+    Ok(WorkspaceHistory {
+        folder: folder.to_string(),
+        commands,
+    })
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+fn respond(stream: TcpStream, status: u16, body: &str) -> std::io::Result<()> {
+    respond_with(stream, status, "application/json", body)
+}
+
+fn respond_with(
+    mut stream: TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &str,
+) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Decode `%XX` escapes and `+` in a query string component
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("%2Fhome%2Fdev+x"), "/home/dev x");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn test_route_rejects_bad_requests() {
+        assert_eq!(route("/v1/search", None).0, 400);
+        assert_eq!(route("/v1/search?q=git&type=files", None).0, 400);
+        assert_eq!(route("/v1/ask?q=why", None).0, 500);
+        assert_eq!(route("/v1/entries/abc", None).0, 400);
+        assert_eq!(route("/v1/nope", None).0, 404);
+        assert!(update_settings(&form_params("capture_shell=maybe")).is_err());
+        assert!(update_settings(&form_params("theme=dark")).is_err());
+    }
+
+    #[test]
+    fn test_expected_host() {
+        let local: SocketAddr = "127.0.0.1:7311".parse().unwrap();
+        assert!(expected_host("127.0.0.1:7311", &local));
+        assert!(expected_host("localhost:7311", &local));
+        assert!(expected_host("[::1]:7311", &local));
+        assert!(expected_host("localhost", &local));
+        assert!(!expected_host("localhost:8080", &local));
+        assert!(!expected_host("attacker.example:7311", &local));
+        assert!(!expected_host("192.168.1.20:7311", &local));
+        assert!(expected_host("host.docker.internal:7311", &local));
+
+        let lan: SocketAddr = "192.168.1.20:7311".parse().unwrap();
+        assert!(expected_host("192.168.1.20:7311", &lan));
+        assert!(!expected_host("127.0.0.1:7311", &lan));
+        assert!(!expected_host("host.docker.internal:7311", &lan));
+    }
+
+    #[test]
+    fn test_workspace_history_scope() {
+        let db = TempDb::new("api");
+        let now = chrono::Utc::now().timestamp() as u64;
+        let shell = |content: &str, working_dir: &str, age: u64| {
+            db.shell(ShellRow::new(content, now - age).in_dir(working_dir));
+        };
+
+        shell("cargo build", "/home/dev/repo", 30);
+        shell("cargo test", "/home/dev/repo/crates/core", 20);
+        shell("cargo test", "/home/dev/repo", 10);
+        shell("ls", "/home/dev/repo-old", 5);
+        shell("make", "/srv", 1);
+
+        let history = workspace_history_in(&db.conn, "/home/dev/repo/", None, 10).unwrap();
+        assert_eq!(history.folder, "/home/dev/repo");
+        let commands: Vec<&str> = history.commands.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(commands, ["cargo test", "cargo build"]);
+
+        let filtered = workspace_history_in(&db.conn, "/home/dev/repo", Some("build"), 10).unwrap();
+        assert_eq!(filtered.commands.len(), 1);
+
+        // Case-insensitive capture stores commands folded, so the query is folded too
+        let folded = workspace_history_in(&db.conn, "/home/dev/repo", Some(&fold("BUILD")), 10);
+        assert_eq!(folded.unwrap().commands.len(), 1);
+    }
+}
@@ -3,8 +3,9 @@ use std::time::SystemTime;
 use crate::commands::get_working_directory;
 use crate::db::USER_DB;
 use crate::embeds::EMBEDDING_MODEL;
-use crate::llm::{GLOBAL_LLM, LLMQueryParams};
-use crate::types::{EntryType, GUISearchResult};
+use crate::llm::{GLOBAL_LLM, LLMQueryParams, QueryFilters};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::types::{AskStreamEvent, EntryType, GUISearchResult};
 
 use super::fingerprint::QueryFingerprint;
 use super::intent::{Intent, classify_intent};
@@ -19,10 +20,13 @@ pub enum AskResponse {
     SearchResults(Option<String>),
 }
 
+/// Answer or search for `query`. `git_scope` (repository and branch filters, from
+/// `--repo`/`--branch`) overrides what the LLM picked.
 pub async fn ask(
     query: &str,
-    search_clipboard: bool,
+    entry_type: EntryType,
     directory: &str,
+    git_scope: &QueryFilters,
     print_only: bool,
     test: bool,
 ) -> Result<AskResponse, Box<dyn std::error::Error>> {
@@ -30,12 +34,6 @@ pub async fn ask(
         return Err("Query cannot be empty".into());
     }
 
-    let entry_type = if search_clipboard {
-        EntryType::Clipboard
-    } else {
-        EntryType::Shell
-    };
-
     let intent = classify_intent(query);
 
     // Initialize LLM early - we'll need it regardless
@@ -57,8 +55,9 @@ pub async fn ask(
         Intent::Retrieval => {
             // Tier 1: Single word -> direct search (no LLM needed)
             let word_count = query.split_whitespace().count();
-            if word_count <= 1 {
-                let result = search(query, search_clipboard, directory, print_only);
+            let scoped = git_scope.git_repo.is_some() || git_scope.git_branch.is_some();
+            if word_count <= 1 && !scoped {
+                let result = search(query, entry_type, directory, None, print_only);
                 return Ok(AskResponse::SearchResults(result));
             }
 
@@ -69,6 +68,7 @@ pub async fn ask(
                 if !print_only {
                     println!("✓ Cache hit");
                 }
+                let params = with_git_scope(params, git_scope);
                 let results = execute_search(&params, entry_type, query, print_only)?;
                 return Ok(AskResponse::SearchResults(results));
             }
@@ -97,6 +97,7 @@ pub async fn ask(
                 }
             }
 
+            let params = with_git_scope(params, git_scope);
             let results = execute_search(&params, entry_type, query, print_only)?;
             Ok(AskResponse::SearchResults(results))
         }
@@ -125,9 +126,7 @@ fn try_cache_lookup(query: &str) -> Result<Option<LLMQueryParams>, Box<dyn std::
     let fingerprint = QueryFingerprint::new(query, query_embedding);
 
     // Search cache
-    let mut db = USER_DB
-        .lock()
-        .map_err(|e| format!("DB lock failed: {}", e))?;
+    let mut db = lock_or_recover("database", &USER_DB);
 
     db.cache.warm_up_cache()?;
 
@@ -181,6 +180,12 @@ fn execute_search(
     print_only: bool,
 ) -> Result<Option<String>, Box<dyn std::error::Error>> {
     let directory = get_working_directory();
+    // Only shell commands have an exit status
+    let params = &if entry_type != EntryType::Clipboard {
+        with_failure_filter(params, query)
+    } else {
+        params.clone()
+    };
     let results = if params.use_semantic {
         let query_text = params.keywords.join(" ");
         let result = semantic_search(&query_text);
@@ -201,6 +206,50 @@ fn execute_search(
     return Ok(results);
 }
 
+/// Put the repository and branch given on the command line into `params`
+fn with_git_scope(mut params: LLMQueryParams, git_scope: &QueryFilters) -> LLMQueryParams {
+    if git_scope.git_repo.is_some() || git_scope.git_branch.is_some() {
+        let filters = params.filters.get_or_insert_with(QueryFilters::default);
+        if git_scope.git_repo.is_some() {
+            filters.git_repo = git_scope.git_repo.clone();
+        }
+        if git_scope.git_branch.is_some() {
+            filters.git_branch = git_scope.git_branch.clone();
+        }
+        // Only keyword search can filter by repository
+        params.use_semantic = false;
+    }
+    params
+}
+
+/// Words that make a query about commands that failed rather than about their text
+const FAILURE_WORDS: &[&str] = &[
+    "fail", "failed", "failing", "failure", "errored", "broke", "broken",
+];
+
+/// "what was the command that failed yesterday": only commands that exited non-zero,
+/// and the failure words dropped from the keywords (no command contains "failed").
+/// Small models often miss `filters.failed`, so the query wording decides too.
+fn with_failure_filter(params: &LLMQueryParams, query: &str) -> LLMQueryParams {
+    let mut params = params.clone();
+    let asks_for_failures = query
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| FAILURE_WORDS.contains(&word.to_lowercase().as_str()));
+
+    if asks_for_failures || params.filters.as_ref().and_then(|f| f.failed) == Some(true) {
+        params
+            .filters
+            .get_or_insert_with(QueryFilters::default)
+            .failed = Some(true);
+        params
+            .keywords
+            .retain(|keyword| !FAILURE_WORDS.contains(&keyword.to_lowercase().as_str()));
+        // Failed commands are found by exit status, not similarity
+        params.use_semantic = false;
+    }
+    params
+}
+
 #[allow(dead_code)]
 pub async fn ask_gui(
     query: &str,
@@ -254,7 +303,7 @@ pub async fn ask_gui(
 
             // 2. Handle Cache Hit
             if let Some(params) = cached_params {
-                let results = execute_search_gui(&params, EntryType::Any)?;
+                let results = execute_search_gui(query, &params, EntryType::Any)?;
                 return Ok(results);
             }
 
@@ -264,17 +313,98 @@ pub async fn ask_gui(
             // Cache the result for next time
             let _ = cache_query_params(query, &params);
 
-            let results = execute_search_gui(&params, EntryType::Any)?;
+            let results = execute_search_gui(query, &params, EntryType::Any)?;
             Ok(results)
         }
     }
 }
 
+/// Like `ask_gui`, but reports progress through `on_event` so the GUI stays responsive:
+/// LLM answers are streamed token by token, and instant keyword results are sent
+/// before the (slower) LLM-refined results.
+pub async fn ask_gui_stream<F>(
+    query: &str,
+    directory: &str,
+    on_event: F,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn(AskStreamEvent) + Send + Sync,
+{
+    if query.trim().is_empty() {
+        return Err("Query cannot be empty".into());
+    }
+
+    let intent = classify_intent(query);
+
+    // Instant keyword pass while the LLM spins up
+    if intent == Intent::Retrieval {
+        if let Ok(results) = search_gui(query, directory) {
+            on_event(AskStreamEvent::Results {
+                stage: "keyword".to_string(),
+                results,
+            });
+        }
+
+        if query.split_whitespace().count() <= 1 {
+            on_event(AskStreamEvent::Done);
+            return Ok(());
+        }
+    }
+
+    let mut llm_daemon = GLOBAL_LLM.lock().await;
+
+    if let Err(e) = llm_daemon.get_llm().await {
+        return Err(format!(
+            "LLM initialization failed: {}. Use jotx handle-llm to fix",
+            e
+        )
+        .into());
+    }
+
+    match intent {
+        Intent::Knowledge => {
+            let on_token = |token: &str| {
+                on_event(AskStreamEvent::Token {
+                    text: token.to_string(),
+                })
+            };
+            llm_daemon.answer_question_stream(query, &on_token).await?;
+        }
+
+        Intent::Retrieval => {
+            let params = match try_cache_lookup(query).unwrap_or(None) {
+                Some(params) => params,
+                None => {
+                    let params = llm_daemon.interpret_query(query, directory).await?;
+                    let _ = cache_query_params(query, &params);
+                    params
+                }
+            };
+
+            let results = execute_search_gui(query, &params, EntryType::Any)?;
+            on_event(AskStreamEvent::Results {
+                stage: "refined".to_string(),
+                results,
+            });
+        }
+    }
+
+    on_event(AskStreamEvent::Done);
+    Ok(())
+}
+
 pub fn execute_search_gui(
+    query: &str,
     params: &LLMQueryParams,
     entry_type: EntryType,
 ) -> Result<Vec<GUISearchResult>, Box<dyn std::error::Error>> {
     let directory = get_working_directory();
+    // Only shell commands have an exit status
+    let params = &if entry_type != EntryType::Clipboard {
+        with_failure_filter(params, query)
+    } else {
+        params.clone()
+    };
     let results = if params.use_semantic {
         let query_text = params.keywords.join(" ");
         let result = semantic_search(&query_text);
@@ -0,0 +1,86 @@
+use crate::config::FtsTokenizer;
+
+/// Quote `term` as an FTS5 string, so `"`, `-`, `:`, `*`, parentheses and keywords
+/// like AND/OR/NOT/NEAR are matched literally instead of parsed as query syntax.
+/// Returns `None` for blank terms.
+pub fn quote_term(term: &str, prefix: bool) -> Option<String> {
+    let term = term.trim();
+    if term.is_empty() {
+        return None;
+    }
+
+    let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+    Some(if prefix { quoted + "*" } else { quoted })
+}
+
+/// Query matching entries that contain every word of a user's search text.
+/// Word indexes prefix-match each word; a trigram index matches the text as one substring.
+pub fn match_all(query: &str, tokenizer: FtsTokenizer) -> Option<String> {
+    match tokenizer {
+        FtsTokenizer::Trigram => quote_term(query, false),
+        FtsTokenizer::Unicode61 => {
+            let terms: Vec<String> = query
+                .split_whitespace()
+                .filter_map(|word| quote_term(word, true))
+                .collect();
+            (!terms.is_empty()).then(|| terms.join(" "))
+        }
+    }
+}
+
+/// Query matching entries that contain any of `keywords` (e.g. from the LLM). Each
+/// keyword is kept as a phrase. Trigram indexes can't match terms shorter than 3
+/// characters, so those are left out.
+pub fn match_any(keywords: &[String], tokenizer: FtsTokenizer) -> Option<String> {
+    let terms: Vec<String> = keywords
+        .iter()
+        .filter(|k| tokenizer != FtsTokenizer::Trigram || k.trim().chars().count() >= 3)
+        .filter_map(|k| quote_term(k, tokenizer == FtsTokenizer::Unicode61))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_special_characters_are_literal() {
+        assert_eq!(quote_term("say \"hi\"", false).unwrap(), "\"say \"\"hi\"\"\"");
+        assert_eq!(
+            match_all("git -C NOT:main", FtsTokenizer::Unicode61).unwrap(),
+            "\"git\"* \"-C\"* \"NOT:main\"*"
+        );
+        let short = ["OR".to_string(), " ".to_string(), "ls".to_string()];
+        assert_eq!(match_any(&short, FtsTokenizer::Trigram), None);
+        assert_eq!(match_all("   ", FtsTokenizer::Unicode61), None);
+    }
+
+    #[test]
+    fn test_queries_are_valid_fts() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE docs USING fts5(content);
+             INSERT INTO docs VALUES ('docker run -it --rm ubuntu:22.04');
+             INSERT INTO docs VALUES ('echo \"NOT done\" AND more');",
+        )
+        .unwrap();
+
+        let count = |query: &str| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM docs WHERE docs MATCH ?1", [query], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+
+        for input in ["ubuntu:22", "--rm", "\"NOT", "AND more", "run -it"] {
+            let query = match_all(input, FtsTokenizer::Unicode61).unwrap();
+            assert_eq!(count(&query), 1, "{} -> {}", input, query);
+        }
+
+        let keywords = vec!["NEAR".to_string(), "docker run".to_string()];
+        assert_eq!(count(&match_any(&keywords, FtsTokenizer::Unicode61).unwrap()), 1);
+    }
+}
@@ -3,6 +3,18 @@ pub mod search_handler;
 pub mod semantic;
 pub mod intent;
 pub mod fingerprint;
+pub mod fts_query;
+pub mod prompt_info;
+pub mod result_cache;
+pub mod search_format;
+pub mod search_query;
+pub mod suggest;
 
-pub use ask_handler::{ask, ask_gui, AskResponse};
-pub use search_handler::{search, search_gui};
\ No newline at end of file
+pub use ask_handler::{ask, ask_gui, ask_gui_stream, AskResponse};
+pub use prompt_info::prompt_info;
+pub use search_format::SearchFormat;
+pub use search_handler::{search, search_formatted, search_gui};
+pub use suggest::suggest;
+
+#[cfg(test)]
+mod ranking_tests;
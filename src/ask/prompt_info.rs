@@ -0,0 +1,115 @@
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::db::open_quick_reader;
+use crate::timezone::QueryTimezone;
+use crate::types::EntryType;
+
+/// Longer last commands are cut so the prompt stays on one line
+const MAX_COMMAND_CHARS: usize = 40;
+
+/// What `jotx prompt-info` knows about a directory
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PromptInfo {
+    pub directory: String,
+    /// Most recent command run in the directory
+    pub last_command: Option<String>,
+    pub last_run: Option<i64>,
+    /// Distinct commands last run here since midnight (in `search.timezone`)
+    pub today: i64,
+}
+
+impl PromptInfo {
+    /// One compact line for a prompt segment, empty when there is nothing to show
+    pub fn line(&self) -> String {
+        let Some(command) = self.last_command.as_deref() else {
+            return String::new();
+        };
+
+        let first_line = command.lines().next().unwrap_or_default();
+        let mut shown: String = first_line.chars().take(MAX_COMMAND_CHARS).collect();
+        if shown.len() < command.len() {
+            shown.push('…');
+        }
+
+        if self.today > 0 {
+            format!("↺ {} · {} today", shown, self.today)
+        } else {
+            format!("↺ {}", shown)
+        }
+    }
+}
+
+/// `jotx prompt-info`: called on every prompt, so it reads through a bare read-only
+/// connection and two indexed queries
+pub fn prompt_info(directory: &str) -> Result<PromptInfo, Box<dyn std::error::Error>> {
+    let conn = open_quick_reader()?;
+    let today_start = QueryTimezone::from_config().day_start(Utc::now(), 0);
+    Ok(prompt_info_in(&conn, directory, today_start)?)
+}
+
+pub(crate) fn prompt_info_in(
+    conn: &Connection,
+    directory: &str,
+    today_start: i64,
+) -> rusqlite::Result<PromptInfo> {
+    let shell = EntryType::Shell.to_string();
+
+    let last: Option<(String, i64)> = conn
+        .prepare_cached(
+            "SELECT content, timestamp FROM entries
+             WHERE working_dir = ?1 AND entry_type = ?2
+             ORDER BY timestamp DESC
+             LIMIT 1",
+        )?
+        .query_row(rusqlite::params![directory, shell], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?;
+
+    let today: i64 = conn
+        .prepare_cached(
+            "SELECT COUNT(*) FROM entries
+             WHERE working_dir = ?1 AND entry_type = ?2 AND timestamp >= ?3",
+        )?
+        .query_row(rusqlite::params![directory, shell, today_start], |row| row.get(0))?;
+
+    let (last_command, last_run) = last.unzip();
+    Ok(PromptInfo {
+        directory: directory.to_string(),
+        last_command,
+        last_run,
+        today,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_prompt_info_for_directory() {
+        let db = TempDb::new("prompt");
+        let now = Utc::now().timestamp() as u64;
+        let shell = |content: &str, working_dir: &str, age: u64| {
+            db.shell(ShellRow::new(content, now - age).in_dir(working_dir));
+        };
+
+        shell("make release", "/home/dev/repo", 3 * 86400);
+        shell("cargo build", "/home/dev/repo", 120);
+        shell("git commit -m \"a rather long commit message here\"", "/home/dev/repo", 60);
+        shell("ls", "/tmp", 1);
+
+        let today_start = (now - 3600) as i64;
+        let info = prompt_info_in(&db.conn, "/home/dev/repo", today_start).unwrap();
+        assert_eq!(info.today, 2);
+        assert_eq!(info.line(), "↺ git commit -m \"a rather long commit mess… · 2 today");
+
+        let empty = prompt_info_in(&db.conn, "/nowhere", today_start).unwrap();
+        assert_eq!(empty.last_command, None);
+        assert_eq!(empty.line(), "");
+    }
+}
@@ -0,0 +1,354 @@
+//! Ranking invariants and goldens. Corpora come from a fixed-seed generator, so a
+//! failure here means a ranking change, not flakiness. Update the goldens only when
+//! the new order is intended.
+
+use super::search_handler::{
+    SearchFilters, calculate_relevance_score, keyword_search_in, keyword_search_with_params_in,
+};
+use crate::db::test_db::TempDb;
+use crate::db::{SampleSelector, SampleStrategy, ShellRow};
+use crate::llm::{LLMQueryParams, QueryFilters};
+use crate::types::EntryType;
+
+const HALF_LIFE_HOURS: f64 = 72.0;
+
+const TOOLS: &[&str] = &["git", "cargo", "docker", "kubectl", "npm", "ssh", "make"];
+const WORDS: &[&str] = &[
+    "status", "build", "push", "logs", "install", "test", "deploy", "clean", "release", "apply",
+];
+
+/// xorshift64, so corpora are identical on every run and platform
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[(self.next() % items.len() as u64) as usize]
+    }
+
+    fn command(&mut self) -> String {
+        let words = 1 + self.next() % 3;
+        let mut command = self.pick(TOOLS).to_string();
+        for _ in 0..words {
+            command.push(' ');
+            command.push_str(self.pick(WORDS));
+        }
+        command
+    }
+}
+
+fn score(content: &str, query: &str, result_pwd: &str, context_pwd: &str) -> f32 {
+    calculate_relevance_score(content, query, result_pwd, context_pwd, 3600, HALF_LIFE_HOURS)
+}
+
+#[test]
+fn test_exact_beats_prefix_beats_word_beats_substring() {
+    let mut rng = Rng(0x5eed_0001);
+
+    for _ in 0..500 {
+        let query = rng.command();
+        let exact = score(&query, &query, "", "");
+        let prefix = score(&format!("{} --verbose", query), &query, "", "");
+        let word = score(&format!("sudo {} now", query), &query, "", "");
+        let substring = score(&format!("sudo x{}x", query), &query, "", "");
+
+        assert!(exact > prefix, "exact <= prefix for {:?}", query);
+        assert!(prefix > word, "prefix <= word for {:?}", query);
+        assert!(word > substring, "word <= substring for {:?}", query);
+    }
+}
+
+#[test]
+fn test_pwd_boost_is_monotonic() {
+    let mut rng = Rng(0x5eed_0002);
+    let context = "/home/dev/project";
+
+    for _ in 0..500 {
+        let content = rng.command();
+        let query = content.split_whitespace().next().unwrap().to_string();
+
+        let same = score(&content, &query, context, context);
+        let nested = score(&content, &query, "/home/dev/project/src", context);
+        let unrelated = score(&content, &query, "/srv/other", context);
+        let unknown = score(&content, &query, "", context);
+
+        assert!(same > nested, "{:?}", content);
+        assert!(nested > unrelated, "{:?}", content);
+        assert_eq!(unrelated, unknown, "{:?}", content);
+    }
+}
+
+#[test]
+fn test_recency_breaks_ties_without_reordering_tiers() {
+    let score_at = |content: &str, age_secs: i64| {
+        calculate_relevance_score(content, "git push", "", "", age_secs, HALF_LIFE_HOURS)
+    };
+
+    // The same command ranks higher when run recently; at equal age, match quality decides
+    assert!(score_at("git push", 60) > score_at("git push", 180 * 86400));
+    assert!(score_at("git push", 60) > score_at("git push origin", 60));
+}
+
+/// Store `content` as run `age_secs` ago in `working_dir`
+fn shell(temp: &TempDb, content: &str, age_secs: u64, working_dir: &str) -> i64 {
+    let now = chrono::Utc::now().timestamp() as u64;
+    temp.shell(
+        ShellRow::new(content, now - age_secs)
+            .in_dir(working_dir)
+            .on_host("host"),
+    )
+}
+
+#[test]
+fn test_keyword_search_golden() {
+    let temp = TempDb::new("ranking-golden");
+    shell(&temp, "git push origin main", 7200, "/srv/other");
+    shell(&temp, "git push", 180 * 86400, "/srv/other");
+    shell(&temp, "sudo git push --force", 60, "/srv/other");
+    shell(&temp, "git pull", 60, "/home/dev/project");
+    shell(&temp, "git push", 60, "/home/dev/project");
+    shell(&temp, "docker push registry/git", 60, "/home/dev/project");
+
+    let results = keyword_search_in(
+        &temp.conn,
+        "git push",
+        EntryType::Shell,
+        "/home/dev/project",
+        &SearchFilters::default(),
+    )
+    .unwrap();
+    let order: Vec<&str> = results.iter().map(|r| r.content.as_str()).collect();
+
+    assert_eq!(
+        order,
+        [
+            "git push",
+            "git push origin main",
+            "sudo git push --force",
+            "docker push registry/git",
+        ]
+    );
+}
+
+#[test]
+fn test_keyword_search_by_tag() {
+    let temp = TempDb::new("ranking-tag");
+    let tagged = shell(&temp, "kubectl rollout restart deploy/api", 60, "/srv/ops");
+    shell(&temp, "kubectl rollout status deploy/api", 60, "/srv/ops");
+    temp.add_tag(tagged, "deploy").unwrap();
+
+    let search = |tag: Option<&str>| {
+        let filters = SearchFilters {
+            tag: tag.map(str::to_string),
+            ..Default::default()
+        };
+        keyword_search_in(
+            &temp.conn,
+            "kubectl rollout",
+            EntryType::Shell,
+            "",
+            &filters,
+        )
+        .unwrap()
+    };
+    assert_eq!(search(None).len(), 2);
+    let results = search(Some("Deploy"));
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, tagged);
+    assert!(search(Some("incident-2024")).is_empty());
+}
+
+#[test]
+fn test_keyword_search_within_project() {
+    let temp = TempDb::new("ranking-within");
+    let root = shell(&temp, "make deploy", 60, "/home/dev/shop");
+    let nested = shell(&temp, "make deploy-api", 60, "/home/dev/shop/api");
+    shell(&temp, "make deploy-web", 60, "/home/dev/shopfront");
+
+    let search = |within: Option<&str>| -> Vec<i64> {
+        let filters = SearchFilters {
+            within: within.map(str::to_string),
+            ..Default::default()
+        };
+        let mut ids: Vec<i64> =
+            keyword_search_in(&temp.conn, "make", EntryType::Shell, "", &filters)
+                .unwrap()
+                .iter()
+                .map(|r| r.id)
+                .collect();
+        ids.sort();
+        ids
+    };
+    assert_eq!(search(None).len(), 3);
+    assert_eq!(search(Some("/home/dev/shop")), [root, nested]);
+    assert_eq!(search(Some("/home/dev/shop/")), [root, nested]);
+    assert_eq!(search(Some("/home/dev/shop/api")), [nested]);
+}
+
+#[test]
+fn test_keyword_search_by_host_and_time() {
+    let temp = TempDb::new("ranking-time");
+    let recent = shell(&temp, "terraform plan", 60, "/srv/infra");
+    let last_week = shell(&temp, "terraform apply", 5 * 86400, "/srv/infra");
+    let old = shell(&temp, "terraform init", 40 * 86400, "/srv/infra");
+    let week_ago = chrono::Utc::now().timestamp() - 7 * 86400;
+
+    let search = |filters: SearchFilters| -> Vec<i64> {
+        let mut ids: Vec<i64> =
+            keyword_search_in(&temp.conn, "terraform", EntryType::Shell, "", &filters)
+                .unwrap()
+                .iter()
+                .map(|r| r.id)
+                .collect();
+        ids.sort();
+        ids
+    };
+    let after = SearchFilters {
+        after: Some(week_ago),
+        ..Default::default()
+    };
+    assert_eq!(search(after.clone()), [recent, last_week]);
+    let before = SearchFilters {
+        before: Some(week_ago),
+        ..Default::default()
+    };
+    assert_eq!(search(before), [old]);
+
+    let on_host = |host: &str| SearchFilters {
+        host: Some(host.to_string()),
+        ..after.clone()
+    };
+    assert_eq!(search(on_host("HOST")), [recent, last_week]);
+    assert!(search(on_host("laptop")).is_empty());
+}
+
+#[test]
+fn test_keyword_search_by_git_scope() {
+    let temp = TempDb::new("ranking-git");
+    let jot = shell(&temp, "cargo test", 60, "/home/dev/jot");
+    temp.set_git_context(jot, "/home/dev/jot", Some("main"))
+        .unwrap();
+    let other = shell(&temp, "cargo test --release", 60, "/home/dev/other");
+    temp.set_git_context(other, "/home/dev/other", Some("feature"))
+        .unwrap();
+
+    let search = |git_repo: Option<&str>, git_branch: Option<&str>| -> Vec<i64> {
+        let params = LLMQueryParams {
+            keywords: vec!["cargo".to_string()],
+            time_range: None,
+            custom_start: None,
+            custom_end: None,
+            filters: Some(QueryFilters {
+                git_repo: git_repo.map(str::to_string),
+                git_branch: git_branch.map(str::to_string),
+                ..Default::default()
+            }),
+            use_semantic: false,
+        };
+        keyword_search_with_params_in(&temp.conn, &params, EntryType::Shell, "")
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect()
+    };
+    assert_eq!(search(None, None).len(), 2);
+    assert_eq!(search(Some("jot"), None), [jot]);
+    assert_eq!(search(Some("/home/dev/other/"), None), [other]);
+    assert_eq!(search(None, Some("feature")), [other]);
+    assert!(search(Some("jot"), Some("feature")).is_empty());
+}
+
+#[test]
+fn test_dedup_is_stable() {
+    let temp = TempDb::new("ranking-dedup");
+    let mut rng = Rng(0x5eed_0003);
+    for i in 0..300 {
+        let dir = format!("/home/dev/project-{}", i % 4);
+        shell(&temp, &rng.command(), (i * 37) % 5000, &dir);
+    }
+
+    for query in ["git", "cargo build", "deploy", "npm install test"] {
+        let search = || {
+            keyword_search_in(
+                &temp.conn,
+                query,
+                EntryType::Shell,
+                "/home/dev/project-1",
+                &SearchFilters::default(),
+            )
+            .unwrap()
+        };
+        let first = search();
+        let second = search();
+
+        let contents: Vec<&String> = first.iter().map(|r| &r.content).collect();
+        let mut unique = contents.clone();
+        unique.sort();
+        unique.dedup();
+
+        assert_eq!(contents.len(), unique.len(), "duplicates for {:?}", query);
+        assert_eq!(
+            contents,
+            second.iter().map(|r| &r.content).collect::<Vec<_>>(),
+            "order changed between runs for {:?}",
+            query
+        );
+        assert!(
+            first.windows(2).all(|w| w[0].similarity >= w[1].similarity),
+            "not sorted by score for {:?}",
+            query
+        );
+    }
+}
+
+fn sample_candidates(seed: u64, count: usize) -> Vec<(i64, String, f32, f32, i32)> {
+    let mut rng = Rng(seed);
+    (0..count)
+        .map(|i| {
+            let similarity = (rng.next() % 1000) as f32 / 1000.0;
+            let quality = (rng.next() % 1000) as f32 / 1000.0;
+            (i as i64, rng.command(), similarity, quality, 0)
+        })
+        .collect()
+}
+
+#[test]
+fn test_sample_strategies() {
+    let selector = SampleSelector::new();
+    let candidates = sample_candidates(0x5eed_0004, 200);
+
+    let by_similarity =
+        selector.select_by_strategy(candidates.clone(), 10, SampleStrategy::Similarity);
+    assert_eq!(by_similarity.len(), 10);
+    assert!(by_similarity.windows(2).all(|w| w[0].similarity >= w[1].similarity));
+    let best = candidates.iter().map(|c| c.2).fold(f32::MIN, f32::max);
+    assert_eq!(by_similarity[0].similarity, best);
+
+    let balanced = selector.select_by_strategy(candidates.clone(), 10, SampleStrategy::Balanced);
+    let blend = |s: &crate::db::Sample| s.similarity * 0.6 + s.quality_score * 0.4;
+    assert!(balanced.windows(2).all(|w| blend(&w[0]) >= blend(&w[1])));
+
+    let diverse = selector.select_by_strategy(candidates.clone(), 10, SampleStrategy::Diverse);
+    for (i, a) in diverse.iter().enumerate() {
+        for b in &diverse[i + 1..] {
+            let words_a: std::collections::HashSet<_> = a.command.split_whitespace().collect();
+            let words_b: std::collections::HashSet<_> = b.command.split_whitespace().collect();
+            let overlap = words_a.intersection(&words_b).count() as f32
+                / words_a.union(&words_b).count() as f32;
+            assert!(overlap < 0.7, "{:?} and {:?} are too similar", a.command, b.command);
+        }
+    }
+
+    // Same input, same selection
+    let again = selector.select_by_strategy(candidates, 10, SampleStrategy::Diverse);
+    assert_eq!(
+        diverse.iter().map(|s| &s.command).collect::<Vec<_>>(),
+        again.iter().map(|s| &s.command).collect::<Vec<_>>()
+    );
+}
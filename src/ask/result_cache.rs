@@ -0,0 +1,117 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::db::write_generation;
+use crate::metrics::METRICS;
+use crate::types::SearchResult;
+
+/// How long a cached result set is served. Writes made by this process invalidate
+/// immediately; the TTL bounds staleness from writes made by another process.
+const RESULT_TTL: Duration = Duration::from_secs(30);
+const MAX_CACHED_QUERIES: usize = 128;
+
+pub static RESULT_CACHE: Lazy<ResultCache> = Lazy::new(ResultCache::new);
+
+struct Cached {
+    stored_at: Instant,
+    generation: u64,
+    results: Vec<SearchResult>,
+}
+
+/// Recent search results keyed by (search kind, query, entry type, directory, filters)
+pub struct ResultCache {
+    entries: Mutex<HashMap<String, Cached>>,
+}
+
+impl ResultCache {
+    fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn key(kind: &str, query: &str, entry_type: &str, directory: &str, filters: &str) -> String {
+        format!("{kind}\u{1f}{query}\u{1f}{entry_type}\u{1f}{directory}\u{1f}{filters}")
+    }
+
+    /// Serve `key` from the cache when fresh, otherwise run `search` and remember its results
+    pub fn get_or_search<F>(
+        &self,
+        key: String,
+        search: F,
+    ) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>>
+    where
+        F: FnOnce() -> Result<Vec<SearchResult>, Box<dyn std::error::Error>>,
+    {
+        let generation = write_generation();
+
+        if let Ok(entries) = self.entries.lock()
+            && let Some(cached) = entries.get(&key)
+            && cached.generation == generation
+            && cached.stored_at.elapsed() < RESULT_TTL
+        {
+            METRICS.search_cache_hits.inc();
+            return Ok(cached.results.clone());
+        }
+
+        METRICS.search_cache_misses.inc();
+        let results = search()?;
+
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|_, cached| {
+                cached.generation == generation && cached.stored_at.elapsed() < RESULT_TTL
+            });
+            if entries.len() < MAX_CACHED_QUERIES {
+                entries.insert(
+                    key,
+                    Cached {
+                        stored_at: Instant::now(),
+                        generation,
+                        results: results.clone(),
+                    },
+                );
+            }
+        }
+
+        Ok(results)
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_hit_and_invalidation() {
+        let cache = ResultCache::new();
+        let key = ResultCache::key("keyword", "git", "shell", "/tmp", "");
+        let mut calls = 0;
+
+        for _ in 0..2 {
+            cache
+                .get_or_search(key.clone(), || {
+                    calls += 1;
+                    Ok(Vec::new())
+                })
+                .unwrap();
+        }
+        assert_eq!(calls, 1);
+
+        cache.clear();
+        cache
+            .get_or_search(key, || {
+                calls += 1;
+                Ok(Vec::new())
+            })
+            .unwrap();
+        assert_eq!(calls, 2);
+    }
+}
@@ -0,0 +1,151 @@
+use serde::Serialize;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::timezone::QueryTimezone;
+use crate::types::SearchResult;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+/// Launchers show one line per item; longer titles are cut here
+const MAX_TITLE_CHARS: usize = 200;
+
+/// Machine-readable output of `jotx search --format`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchFormat {
+    /// One SearchResult per line (same as --json)
+    Json,
+    /// Alfred Script Filter JSON (`{"items": [...]}`), which Raycast script filters read too
+    Launcher,
+    /// One result per line for `rofi -dmenu` / `wofi --dmenu`; the picked line is the command
+    Dmenu,
+}
+
+impl FromStr for SearchFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SearchFormat::Json),
+            "launcher" | "alfred" | "raycast" => Ok(SearchFormat::Launcher),
+            "dmenu" | "rofi" | "wofi" => Ok(SearchFormat::Dmenu),
+            _ => Err(format!("Unknown search format: {} (expected json, launcher or dmenu)", s)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct LauncherItems {
+    items: Vec<LauncherItem>,
+}
+
+#[derive(Serialize)]
+struct LauncherItem {
+    uid: String,
+    title: String,
+    subtitle: String,
+    /// What the launcher pastes, copies or runs on selection
+    arg: String,
+}
+
+/// Write `results` to `out` in `format`
+pub fn write_results<W: Write>(
+    results: &[SearchResult],
+    format: SearchFormat,
+    out: &mut W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        SearchFormat::Json => {
+            for result in results {
+                writeln!(out, "{}", serde_json::to_string(result)?)?;
+            }
+        }
+        SearchFormat::Launcher => {
+            let tz = QueryTimezone::from_config();
+            let items = results
+                .iter()
+                .map(|result| LauncherItem {
+                    uid: result.id.to_string(),
+                    title: title(&result.content),
+                    subtitle: subtitle(result, &tz),
+                    arg: result.content.clone(),
+                })
+                .collect();
+            writeln!(out, "{}", serde_json::to_string(&LauncherItems { items })?)?;
+        }
+        SearchFormat::Dmenu => {
+            // Multi-line entries can't survive a line-per-item protocol
+            for result in results.iter().filter(|r| !r.content.contains('\n')) {
+                writeln!(out, "{}", result.content)?;
+            }
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// First line of the content, shortened for display
+fn title(content: &str) -> String {
+    let first_line = content.lines().next().unwrap_or_default();
+    let mut title: String = first_line.chars().take(MAX_TITLE_CHARS).collect();
+    if title.len() < content.len() {
+        title.push('…');
+    }
+    title
+}
+
+fn subtitle(result: &SearchResult, tz: &QueryTimezone) -> String {
+    let mut parts = vec![
+        result.entry_type.clone(),
+        tz.format_timestamp(result.timestamp, TIME_FORMAT),
+    ];
+    if let Some(dir) = result.working_dir.as_deref().filter(|d| !d.is_empty()) {
+        parts.push(dir.to_string());
+    }
+    if result.times_run > 1 {
+        parts.push(format!("run {}×", result.times_run));
+    }
+    parts.join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(content: &str) -> SearchResult {
+        SearchResult {
+            id: 7,
+            entry_type: "shell".to_string(),
+            content: content.to_string(),
+            timestamp: 1700000000,
+            times_run: 3,
+            working_dir: Some("/home/dev/project".to_string()),
+            host: None,
+            app_name: None,
+            window_title: None,
+            similarity: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_launcher_items() {
+        let mut out = Vec::new();
+        write_results(&[result("git push\n--force")], SearchFormat::Launcher, &mut out).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let item = &json["items"][0];
+        assert_eq!(item["uid"], "7");
+        assert_eq!(item["title"], "git push…");
+        assert_eq!(item["arg"], "git push\n--force");
+        let subtitle = item["subtitle"].as_str().unwrap();
+        assert!(subtitle.starts_with("shell · ") && subtitle.ends_with("/home/dev/project · run 3×"));
+    }
+
+    #[test]
+    fn test_dmenu_skips_multiline() {
+        let mut out = Vec::new();
+        write_results(&[result("ls"), result("a\nb"), result("pwd")], SearchFormat::Dmenu, &mut out)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "ls\npwd\n");
+    }
+}
@@ -1,23 +1,71 @@
-use chrono::{Duration, Local};
+use chrono::Utc;
 use console::Term;
 use dialoguer::Select;
+use rusqlite::Connection;
 use std::collections::HashSet;
+use std::io::Write;
 
 use crate::config::GLOBAL_CONFIG;
-use crate::db::USER_DB;
+use crate::ask::fts_query::{match_all, match_any};
+use crate::ask::result_cache::{RESULT_CACHE, ResultCache};
+use crate::ask::search_format::{SearchFormat, write_results};
+use crate::ask::search_query::parse_query;
+use crate::db::{READ_POOL, entry_tags_in, fts_tokenizer, normalize_tag, pinned_in};
+use crate::normalize::{fold, normalize};
 use crate::llm::{LLMQueryParams, SimpleTimeRange};
+use crate::managers::lock_manager::lock_or_recover;
 use crate::plugin::GLOBAL_PLUGIN_MANAGER;
+use crate::project_config::ProjectConfig;
+use crate::timezone::QueryTimezone;
 use crate::types::{EntryType, GUISearchResult, SearchResult};
 
 const MAX_RESULTS: usize = 10;
+/// Entries listed by `jotx search --format <format> ""`
+const HISTORY_LIST_LIMIT: usize = 5000;
+/// Points added to an entry used just now, decaying with `recency_half_life_hours`
+const RECENCY_WEIGHT: f32 = 25.0;
+
+/// Restrictions on a keyword search besides its text
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilters {
+    /// Only entries carrying this tag
+    pub tag: Option<String>,
+    /// Only entries captured in this directory or below it
+    pub within: Option<String>,
+    /// Only entries captured on this host (case-insensitive)
+    pub host: Option<String>,
+    /// Only entries from this unix time on
+    pub after: Option<i64>,
+    /// Only entries from before this unix time
+    pub before: Option<i64>,
+}
+
+impl SearchFilters {
+    pub fn is_empty(&self) -> bool {
+        *self == SearchFilters::default()
+    }
+}
 
+/// Interactive keyword search. The query can hold field prefixes like `dir:` or
+/// `after:` (see `search_query`); with only filters, e.g. a `tag`, the newest matching
+/// entries are listed. The `.jotx.toml` of the project `directory` is in can set a
+/// default tag and limit results to the project.
 pub fn search(
     query: &str,
-    search_clipboard: bool,
+    entry_type: EntryType,
     directory: &str,
+    tag: Option<&str>,
     print_only: bool,
 ) -> Option<String> {
-    if query.is_empty() {
+    let (text, filters) = match search_filters(query, directory, tag) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            return None;
+        }
+    };
+
+    if text.is_empty() && filters.is_empty() {
         if !print_only {
             println!("No query provided. Use jotx search <query>");
         }
@@ -26,17 +74,19 @@ pub fn search(
 
     // Only show UI messages if NOT print_only mode
     if !print_only {
-        println!("🔍 Searching for: {}\n", query);
+        match &filters.tag {
+            Some(tag) => println!("🔍 Searching for: {} (tagged {})\n", query, tag),
+            None => println!("🔍 Searching for: {}\n", query),
+        }
     }
 
-    let entry_type = if search_clipboard {
-        EntryType::Clipboard
+    let results = if text.is_empty() {
+        filtered_history(entry_type, &filters)
     } else {
-        EntryType::Shell
+        keyword_search(&text, entry_type, directory, &filters)
     };
 
-    // Try keyword search first
-    match keyword_search(query, entry_type, directory) {
+    match results {
         Ok(results) if !results.is_empty() => {
             return display_results_interactive(
                 query,
@@ -64,7 +114,12 @@ pub fn search_gui(
     }
 
     // Try keyword search first
-    match keyword_search(query, EntryType::Clipboard, directory) {
+    match keyword_search(
+        query,
+        EntryType::Clipboard,
+        directory,
+        &SearchFilters::default(),
+    ) {
         Ok(results) if !results.is_empty() => Ok(results
             .into_iter()
             .map(|r| GUISearchResult {
@@ -79,20 +134,159 @@ pub fn search_gui(
     }
 }
 
-// Keyword search using SQLite FTS5
+/// `jotx search --format`: results for other tools, best first. A query without text
+/// lists the whole history (or the entries matching its filters), newest first, for
+/// tools like fzf that do their own filtering. Field prefixes and project settings
+/// apply as in `search`. Returns the number of results written.
+pub fn search_formatted<W: Write>(
+    query: &str,
+    entry_type: EntryType,
+    directory: &str,
+    tag: Option<&str>,
+    format: SearchFormat,
+    out: &mut W,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let (text, filters) = search_filters(query, directory, tag)?;
+
+    let results = if text.is_empty() {
+        let conn = READ_POOL.get()?;
+        recent_history_in(&conn, entry_type, &filters, HISTORY_LIST_LIMIT)?
+    } else {
+        keyword_search(&text, entry_type, directory, &filters)?
+    };
+
+    write_results(&results, format, out)?;
+    Ok(results.len())
+}
+
+/// The text and filters of a search. Field prefixes in `query` win over the `tag`
+/// argument, which wins over the defaults of the project `directory` is in.
+fn search_filters(
+    query: &str,
+    directory: &str,
+    tag: Option<&str>,
+) -> Result<(String, SearchFilters), String> {
+    let (text, mut filters) =
+        parse_query(query, directory, &QueryTimezone::from_config(), Utc::now())?;
+    let project = ProjectConfig::for_dir(directory);
+
+    if filters.tag.is_none() {
+        filters.tag = tag
+            .map(str::to_string)
+            .or_else(|| project.as_ref().and_then(|p| p.search.tag.clone()));
+    }
+    if filters.within.is_none() {
+        filters.within = project.as_ref().and_then(ProjectConfig::search_root);
+    }
+    Ok((text, filters))
+}
+
+/// Newest entries matching `filters`, for a search without text
+fn filtered_history(
+    entry_type: EntryType,
+    filters: &SearchFilters,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let conn = READ_POOL.get()?;
+    recent_history_in(&conn, entry_type, filters, HISTORY_LIST_LIMIT)
+}
+
+/// Newest entries of `entry_type` matching `filters`, one per distinct content
+pub(crate) fn recent_history_in(
+    conn: &Connection,
+    entry_type: EntryType,
+    filters: &SearchFilters,
+    limit: usize,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let tag = filters.tag.as_deref().map(normalize_tag).transpose()?;
+    let within = filters.within.as_deref().map(|dir| dir.trim_end_matches('/'));
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, entry_type, content, timestamp, times_run,
+                working_dir, host, app_name, window_title
+         FROM entries
+         WHERE entry_type = ?1
+           AND (?3 IS NULL OR id IN (SELECT entry_id FROM entry_tags WHERE tag = ?3))
+           AND (?4 IS NULL OR working_dir = ?4
+                OR substr(working_dir, 1, length(?4) + 1) = ?4 || '/')
+           AND (?5 IS NULL OR host = ?5 COLLATE NOCASE)
+           AND (?6 IS NULL OR timestamp >= ?6)
+           AND (?7 IS NULL OR timestamp < ?7)
+         ORDER BY timestamp DESC
+         LIMIT ?2",
+    )?;
+
+    let entry_type = entry_type.to_string();
+    let params = rusqlite::params![
+        entry_type,
+        limit,
+        tag,
+        within,
+        filters.host,
+        filters.after,
+        filters.before
+    ];
+    let mut results: Vec<SearchResult> = stmt
+        .query_map(params, |row| {
+            Ok(SearchResult {
+                id: row.get(0)?,
+                entry_type: row.get(1)?,
+                content: row.get(2)?,
+                timestamp: row.get(3)?,
+                times_run: row.get(4)?,
+                working_dir: row.get(5)?,
+                host: row.get(6)?,
+                app_name: row.get(7)?,
+                window_title: row.get(8)?,
+                similarity: 0.0,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut seen = HashSet::new();
+    results.retain(|item| seen.insert(item.content.clone()));
+
+    Ok(results)
+}
+
+// Keyword search using SQLite FTS5, optionally only over entries matching `filters`
 pub fn keyword_search(
     query: &str,
     entry_type: EntryType,
     directory: &str,
+    filters: &SearchFilters,
 ) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
-    let db = USER_DB
-        .lock()
-        .map_err(|e| format!("DB lock error: {}", e))?;
+    let hide_failed = hide_failed();
+    let key = ResultCache::key(
+        "keyword",
+        query,
+        &entry_type.to_string(),
+        directory,
+        &format!(
+            "{}|{:?}",
+            if hide_failed { "hide_failed" } else { "" },
+            filters
+        ),
+    );
+    RESULT_CACHE.get_or_search(key, || {
+        let conn = READ_POOL.get()?;
+        keyword_search_in(&conn, query, entry_type, directory, filters)
+    })
+}
 
+/// Uncached keyword search against `conn`
+pub fn keyword_search_in(
+    conn: &Connection,
+    query: &str,
+    entry_type: EntryType,
+    directory: &str,
+    filters: &SearchFilters,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let tag = filters.tag.as_deref().map(normalize_tag).transpose()?;
+    let within = filters.within.as_deref().map(|dir| dir.trim_end_matches('/'));
     // STRATEGY SWITCH:
     // If query is very short (1-2 chars), FTS often fails (especially with trigrams).
     // Use standard SQL LIKE for short queries, FTS for long ones.
     let use_fts = query.len() >= 3;
+    let hide_failed = hide_failed();
 
     let mut stmt;
 
@@ -102,9 +296,12 @@ pub fn keyword_search(
 
     if use_fts {
         // --- EXISTING FTS LOGIC ---
-        let fts_query = format!("{}*", query);
+        // User text is quoted term by term, so FTS syntax characters are matched literally
+        let Some(fts_query) = match_all(query, fts_tokenizer(conn)?) else {
+            return Ok(Vec::new());
+        };
 
-        stmt = db.conn.prepare(
+        stmt = conn.prepare_cached(
             "SELECT e.id, e.entry_type, e.content, e.timestamp, e.times_run, 
                     e.working_dir, e.host, e.app_name, e.window_title,
                     CASE 
@@ -114,13 +311,30 @@ pub fn keyword_search(
              FROM entries_fts 
              JOIN entries e ON entries_fts.rowid = e.id
              WHERE entries_fts MATCH ?1 AND e.entry_type = ?3
+               AND (?4 = 0 OR e.exit_code IS NULL OR e.exit_code = 0)
+               AND (?5 IS NULL OR e.id IN (SELECT entry_id FROM entry_tags WHERE tag = ?5))
+               AND (?6 IS NULL OR e.working_dir = ?6
+                    OR substr(e.working_dir, 1, length(?6) + 1) = ?6 || '/')
+               AND (?7 IS NULL OR e.host = ?7 COLLATE NOCASE)
+               AND (?8 IS NULL OR e.timestamp >= ?8)
+               AND (?9 IS NULL OR e.timestamp < ?9)
              ORDER BY pwd_boost DESC, e.times_run DESC, e.timestamp DESC
              LIMIT 50",
         )?;
 
         results = stmt
             .query_map(
-                rusqlite::params![&fts_query, directory, entry_type_str],
+                rusqlite::params![
+                    &fts_query,
+                    directory,
+                    entry_type_str,
+                    hide_failed,
+                    tag,
+                    within,
+                    filters.host,
+                    filters.after,
+                    filters.before
+                ],
                 |row| {
                     Ok(SearchResult {
                         id: row.get(0)?,
@@ -139,9 +353,9 @@ pub fn keyword_search(
             .collect::<Result<Vec<_>, _>>()?;
     } else {
         // --- FALLBACK LIKE LOGIC (For 1-2 char queries) ---
-        let like_query = format!("%{}%", query);
+        let like_query = format!("%{}%", normalize(query));
 
-        stmt = db.conn.prepare(
+        stmt = conn.prepare_cached(
             "SELECT e.id, e.entry_type, e.content, e.timestamp, e.times_run, 
                     e.working_dir, e.host, e.app_name, e.window_title,
                     CASE 
@@ -150,13 +364,30 @@ pub fn keyword_search(
                     END as pwd_boost
              FROM entries e
              WHERE e.content LIKE ?1 AND e.entry_type = ?3
+               AND (?4 = 0 OR e.exit_code IS NULL OR e.exit_code = 0)
+               AND (?5 IS NULL OR e.id IN (SELECT entry_id FROM entry_tags WHERE tag = ?5))
+               AND (?6 IS NULL OR e.working_dir = ?6
+                    OR substr(e.working_dir, 1, length(?6) + 1) = ?6 || '/')
+               AND (?7 IS NULL OR e.host = ?7 COLLATE NOCASE)
+               AND (?8 IS NULL OR e.timestamp >= ?8)
+               AND (?9 IS NULL OR e.timestamp < ?9)
              ORDER BY pwd_boost DESC, e.times_run DESC, e.timestamp DESC
              LIMIT 50",
         )?;
 
         results = stmt
             .query_map(
-                rusqlite::params![&like_query, directory, entry_type_str],
+                rusqlite::params![
+                    &like_query,
+                    directory,
+                    entry_type_str,
+                    hide_failed,
+                    tag,
+                    within,
+                    filters.host,
+                    filters.after,
+                    filters.before
+                ],
                 |row| {
                     Ok(SearchResult {
                         id: row.get(0)?,
@@ -175,13 +406,21 @@ pub fn keyword_search(
             .collect::<Result<Vec<_>, _>>()?;
     }
 
-    let query_lower = query.to_lowercase();
+    let query_lower = fold(query);
+    let now = Utc::now().timestamp();
+    let half_life_hours = recency_half_life_hours();
 
     // Calculate detailed relevance scores for top 50 results only
     for result in &mut results {
         let working_dir = result.working_dir.as_deref().unwrap_or("");
-        let base_score =
-            calculate_relevance_score(&result.content, &query_lower, working_dir, directory);
+        let base_score = calculate_relevance_score(
+            &result.content,
+            &query_lower,
+            working_dir,
+            directory,
+            now - result.timestamp,
+            half_life_hours,
+        );
 
         // Add frequency bonus (times_run)
         let frequency_bonus = (result.times_run as f32).min(10.0) * 2.0; // Max +20 points
@@ -205,13 +444,39 @@ pub fn keyword_search(
     Ok(results)
 }
 
-fn calculate_relevance_score(
+/// `search.hide_failed`
+fn hide_failed() -> bool {
+    GLOBAL_CONFIG
+        .read()
+        .map(|config| config.search.hide_failed)
+        .unwrap_or(false)
+}
+
+fn recency_half_life_hours() -> f64 {
+    GLOBAL_CONFIG
+        .read()
+        .map(|config| config.search.recency_half_life_hours)
+        .unwrap_or(72.0)
+}
+
+/// Exponential decay in [0, 1]: 1 for an entry seen just now, 0.5 after one half-life
+fn recency_factor(age_secs: i64, half_life_hours: f64) -> f32 {
+    if half_life_hours <= 0.0 {
+        return 0.0;
+    }
+    let age_hours = age_secs.max(0) as f64 / 3600.0;
+    0.5f64.powf(age_hours / half_life_hours) as f32
+}
+
+pub(crate) fn calculate_relevance_score(
     content: &str,
     query: &str,
     result_pwd: &str,
     context_pwd: &str,
+    age_secs: i64,
+    half_life_hours: f64,
 ) -> f32 {
-    let content_lower = content.to_lowercase();
+    let content_lower = fold(content);
     let mut score;
 
     // 1. Exact match = highest score
@@ -268,6 +533,9 @@ fn calculate_relevance_score(
         }
     }
 
+    // Recency boost, so the same command run minutes ago beats one from months back
+    score += recency_factor(age_secs, half_life_hours) * RECENCY_WEIGHT;
+
     score
 }
 
@@ -292,15 +560,36 @@ pub fn display_results_interactive<'a>(
         );
     }
 
+    let ids: Vec<i64> = results.iter().map(|r| r.id).collect();
+    let (tags, pinned) = match READ_POOL.get() {
+        Ok(conn) => (
+            entry_tags_in(&conn, &ids).unwrap_or_default(),
+            pinned_in(&conn, &ids).unwrap_or_default(),
+        ),
+        Err(_) => Default::default(),
+    };
+
     let mut items: Vec<String> = results
         .iter()
         .map(|r| {
             let icon = match r.entry_type.as_str() {
                 "clipboard" => "📋",
                 "shell" => "💻",
+                "note" => "📝",
                 _ => "📄",
             };
-            format!("{} {}", icon, r.content)
+            let icon = if pinned.contains(&r.id) {
+                format!("{}📌", icon)
+            } else {
+                icon.to_string()
+            };
+            match tags.get(&r.id) {
+                Some(tags) => {
+                    let tags: Vec<String> = tags.iter().map(|tag| format!("#{}", tag)).collect();
+                    format!("{} {}  {}", icon, r.content, tags.join(" "))
+                }
+                None => format!("{} {}", icon, r.content),
+            }
         })
         .collect();
 
@@ -330,9 +619,7 @@ pub fn display_results_interactive<'a>(
 fn trigger_plugins(query: &str, results: &[SearchResult]) {
     let mut vec: Vec<SearchResult> = results.to_vec();
 
-    if let Ok(plugins) = GLOBAL_PLUGIN_MANAGER.lock() {
-        plugins.trigger_search_after(query, vec.as_mut());
-    }
+    lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER).trigger_search_after(query, vec.as_mut());
 }
 
 /// Keyword search using LLM-extracted parameters
@@ -341,74 +628,90 @@ pub fn keyword_search_with_params(
     entry_type: EntryType,
     directory: &str,
 ) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
-    let db = USER_DB
-        .lock()
-        .map_err(|e| format!("DB lock error: {}", e))?;
+    let key = ResultCache::key(
+        "params",
+        &params.keywords.join(" "),
+        &entry_type.to_string(),
+        directory,
+        &format!("{:?} {}", params, hide_failed()),
+    );
+    RESULT_CACHE.get_or_search(key, || {
+        let conn = READ_POOL.get()?;
+        keyword_search_with_params_in(&conn, params, entry_type, directory)
+    })
+}
 
-    // Build FTS5 query from keywords
-    let fts_query = if params.keywords.is_empty() {
-        "*".to_string()
-    } else {
-        // Join keywords with OR for broader matching
-        params
-            .keywords
-            .iter()
-            .map(|k| format!("{}*", k))
-            .collect::<Vec<_>>()
-            .join(" OR ")
-    };
+/// Uncached `keyword_search_with_params` against `conn`
+pub(crate) fn keyword_search_with_params_in(
+    conn: &Connection,
+    params: &LLMQueryParams,
+    entry_type: EntryType,
+    directory: &str,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    // Keywords are joined with OR for broader matching. Without usable keywords the
+    // other filters (type, time, directory) still apply.
+    let fts_query = match_any(&params.keywords, fts_tokenizer(conn)?);
 
     // Build WHERE clauses for filters
-    let mut where_clauses = vec!["entries_fts MATCH ?1".to_string()];
-    let mut bind_params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(fts_query.clone())];
-    let mut param_index = 2;
+    let mut where_clauses = vec![];
+    let mut bind_params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
+    let mut param_index = 1;
 
-    // Entry type filter
+    if let Some(fts_query) = fts_query {
+        where_clauses.push(format!("entries_fts MATCH ?{}", param_index));
+        bind_params.push(Box::new(fts_query));
+        param_index += 1;
+    }
+
+    // Entry type filter. Notes were saved to be found again, so they match any type.
     if entry_type != EntryType::Any {
-        where_clauses.push(format!("entry_type = ?{}", param_index));
+        where_clauses.push(format!("entry_type IN (?{}, 'note')", param_index));
         bind_params.push(Box::new(entry_type.to_string()));
         param_index += 1;
     }
 
+    // Exit status: failed commands when asked for, otherwise per search.hide_failed
+    match params.filters.as_ref().and_then(|f| f.failed) {
+        Some(true) => where_clauses.push("e.exit_code != 0".to_string()),
+        Some(false) => where_clauses.push("(e.exit_code IS NULL OR e.exit_code = 0)".to_string()),
+        None if hide_failed() => {
+            where_clauses.push("(e.exit_code IS NULL OR e.exit_code = 0)".to_string())
+        }
+        None => {}
+    }
+
+    // Git scope: a repository by its root path or just its directory name
+    let filters = params.filters.clone().unwrap_or_default();
+    if let Some(repo) = filters.git_repo.filter(|r| !r.trim().is_empty()) {
+        where_clauses.push(format!(
+            "(e.git_repo = ?{0} OR e.git_repo LIKE '%/' || ?{0})",
+            param_index
+        ));
+        bind_params.push(Box::new(repo.trim().trim_end_matches('/').to_string()));
+        param_index += 1;
+    }
+    if let Some(branch) = filters.git_branch.filter(|b| !b.trim().is_empty()) {
+        where_clauses.push(format!("e.git_branch = ?{}", param_index));
+        bind_params.push(Box::new(branch.trim().to_string()));
+        param_index += 1;
+    }
+
     // Time range filter
     let (time_boost_start, time_boost_end, time_penalty) =
         if let Some(ref time_range) = params.time_range {
+            // Day boundaries follow the configured timezone; stored timestamps are UTC
+            let tz = QueryTimezone::from_config();
+            let now = Utc::now();
             let (start_ts, end_ts) = match time_range {
-                SimpleTimeRange::Today => {
-                    let now = Local::now();
-                    let start = now
-                        .date_naive()
-                        .and_hms_opt(0, 0, 0)
-                        .unwrap()
-                        .and_local_timezone(Local)
-                        .unwrap();
-                    (start.timestamp(), now.timestamp())
-                }
-                SimpleTimeRange::Yesterday => {
-                    let now = Local::now();
-                    let today_start = now
-                        .date_naive()
-                        .and_hms_opt(0, 0, 0)
-                        .unwrap()
-                        .and_local_timezone(Local)
-                        .unwrap();
-                    let yesterday_start = today_start - Duration::days(1);
-                    (yesterday_start.timestamp(), today_start.timestamp())
-                }
-                SimpleTimeRange::LastWeek => {
-                    let now = Local::now();
-                    (now.timestamp() - (7 * 24 * 60 * 60), now.timestamp())
-                }
+                SimpleTimeRange::Today => (tz.day_start(now, 0), now.timestamp()),
+                SimpleTimeRange::Yesterday => (tz.day_start(now, 1), tz.day_start(now, 0)),
+                SimpleTimeRange::LastWeek => (now.timestamp() - (7 * 24 * 60 * 60), now.timestamp()),
                 SimpleTimeRange::LastMonth => {
-                    let now = Local::now();
                     (now.timestamp() - (30 * 24 * 60 * 60), now.timestamp())
                 }
                 SimpleTimeRange::Custom => {
                     let start = *params.custom_start.as_ref().unwrap_or(&0);
-                    let end = *params
-                        .custom_end
-                        .as_ref()
-                        .unwrap_or(&Local::now().timestamp());
+                    let end = *params.custom_end.as_ref().unwrap_or(&now.timestamp());
                     (start, end)
                 }
             };
@@ -418,7 +721,11 @@ pub fn keyword_search_with_params(
         };
 
     // Build final SQL query
-    let where_clause = where_clauses.join(" AND ");
+    let where_clause = if where_clauses.is_empty() {
+        "1 = 1".to_string()
+    } else {
+        where_clauses.join(" AND ")
+    };
 
     let sql = if time_penalty {
         format!(
@@ -475,8 +782,8 @@ pub fn keyword_search_with_params(
         bind_params.push(Box::new(time_boost_end.unwrap()));
     }
 
-    // Prepare statement
-    let mut stmt = db.conn.prepare(&sql)?;
+    // Prepare statement (cached per filter combination, as the SQL text differs)
+    let mut stmt = conn.prepare_cached(&sql)?;
 
     // Execute query with dynamic parameters
     let params_refs: Vec<&dyn rusqlite::ToSql> = bind_params.iter().map(|b| b.as_ref()).collect();
@@ -499,12 +806,20 @@ pub fn keyword_search_with_params(
         .collect::<Result<Vec<_>, _>>()?;
 
     // Calculate relevance scores
-    let query_str = params.keywords.join(" ").to_lowercase();
+    let query_str = fold(&params.keywords.join(" "));
+    let now = Utc::now().timestamp();
+    let half_life_hours = recency_half_life_hours();
 
     for result in &mut results {
         let working_dir = result.working_dir.as_deref().unwrap_or("");
-        let base_score =
-            calculate_relevance_score(&result.content, &query_str, working_dir, directory);
+        let base_score = calculate_relevance_score(
+            &result.content,
+            &query_str,
+            working_dir,
+            directory,
+            now - result.timestamp,
+            half_life_hours,
+        );
         let frequency_bonus = (result.times_run as f32).min(10.0) * 2.0;
         result.similarity = base_score + frequency_bonus;
     }
@@ -523,3 +838,18 @@ pub fn keyword_search_with_params(
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recency_decay() {
+        let recent = calculate_relevance_score("git push", "git push", "", "", 600, 72.0);
+        let old = calculate_relevance_score("git push", "git push", "", "", 180 * 86400, 72.0);
+        assert!(recent > old);
+
+        assert!((recency_factor(72 * 3600, 72.0) - 0.5).abs() < 1e-6);
+        assert_eq!(recency_factor(600, 0.0), 0.0);
+    }
+}
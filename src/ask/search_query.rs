@@ -0,0 +1,180 @@
+//! Field prefixes in `jotx search` queries, e.g.
+//! `content:docker dir:~/work host:laptop before:2024-01-01`. Each known prefix becomes
+//! a filter of the keyword search; everything else is searched as text.
+//!
+//! - `content:` text to search for, for words that would otherwise look like a field
+//! - `dir:` the directory a command ran in or one below it; `~` and relative paths work
+//! - `host:` the machine it was captured on
+//! - `tag:` entries carrying the tag
+//! - `after:` / `before:` a date (YYYY-MM-DD, in `search.timezone`), `today`,
+//!   `yesterday` or a time ago like `7d` or `2h`. `after:` includes its day,
+//!   `before:` doesn't.
+//!
+//! A value with spaces is quoted: `dir:"~/my project"`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use std::path::{Component, Path, PathBuf};
+
+use crate::ask::search_handler::SearchFilters;
+use crate::notes::expand_home;
+use crate::pid_controller::parse_pause_duration;
+use crate::timezone::QueryTimezone;
+
+const FIELDS: &[&str] = &["content", "dir", "host", "tag", "after", "before"];
+
+/// Split `query` into the text to search for and its filters. Relative `dir:` values
+/// are taken from `directory`.
+pub fn parse_query(
+    query: &str,
+    directory: &str,
+    tz: &QueryTimezone,
+    now: DateTime<Utc>,
+) -> Result<(String, SearchFilters), String> {
+    let mut text = Vec::new();
+    let mut filters = SearchFilters::default();
+
+    for token in tokens(query) {
+        let Some((field, value)) = field_value(&token) else {
+            text.push(token);
+            continue;
+        };
+
+        match field {
+            "content" => text.push(value.to_string()),
+            "dir" => filters.within = Some(resolve_dir(value, directory)),
+            "host" => filters.host = Some(value.to_string()),
+            "tag" => filters.tag = Some(value.to_string()),
+            "after" => filters.after = Some(parse_time(field, value, tz, now)?),
+            "before" => filters.before = Some(parse_time(field, value, tz, now)?),
+            _ => unreachable!("{} is not in FIELDS", field),
+        }
+    }
+
+    Ok((text.join(" "), filters))
+}
+
+/// Whitespace separated words, keeping a quoted field value together
+fn tokens(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut words = query.split_whitespace();
+    while let Some(word) = words.next() {
+        let mut token = word.to_string();
+        // `field_value` strips the quote, so look at the raw value after the colon
+        let quoted = field_value(word).is_some()
+            && word
+                .split_once(':')
+                .is_some_and(|(_, value)| value.starts_with('"'));
+        if quoted {
+            while token.matches('"').count() < 2
+                && let Some(next) = words.next()
+            {
+                token.push(' ');
+                token.push_str(next);
+            }
+        }
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// `field:value` for a known field and a non-empty value, without the value's quotes
+fn field_value(token: &str) -> Option<(&str, &str)> {
+    let (field, value) = token.split_once(':')?;
+    let field = FIELDS.iter().copied().find(|known| *known == field)?;
+    let value = match value.strip_prefix('"') {
+        Some(rest) => rest.strip_suffix('"').unwrap_or(rest),
+        None => value,
+    };
+    (!value.is_empty()).then_some((field, value))
+}
+
+/// `dir:` as an absolute path, without `.` and `..` parts or a trailing slash
+fn resolve_dir(value: &str, directory: &str) -> String {
+    let path = match value {
+        "~" => expand_home("~/"),
+        _ => expand_home(value),
+    };
+    let path = if path.is_relative() && !directory.is_empty() {
+        Path::new(directory).join(path)
+    } else {
+        path
+    };
+
+    let mut resolved = PathBuf::new();
+    for part in path.components() {
+        match part {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            part => resolved.push(part),
+        }
+    }
+    resolved.to_string_lossy().into_owned()
+}
+
+/// Unix time of an `after:` or `before:` value
+fn parse_time(
+    field: &str,
+    value: &str,
+    tz: &QueryTimezone,
+    now: DateTime<Utc>,
+) -> Result<i64, String> {
+    match value.to_lowercase().as_str() {
+        "today" => return Ok(tz.day_start(now, 0)),
+        "yesterday" => return Ok(tz.day_start(now, 1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(tz.date_start(date));
+    }
+
+    parse_pause_duration(value)
+        .map(|ago| (now - ago).timestamp())
+        .map_err(|_| {
+            format!(
+                "Invalid {}:{}: use a date (2024-01-31), today, yesterday or a time ago (7d, 2h)",
+                field, value
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_query() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+        let parse = |query| parse_query(query, "/home/dev/shop", &QueryTimezone::Utc, now);
+
+        let (text, filters) =
+            parse("content:docker dir:api host:laptop before:2024-01-01 logs").unwrap();
+        assert_eq!(text, "docker logs");
+        assert_eq!(filters.within.as_deref(), Some("/home/dev/shop/api"));
+        assert_eq!(filters.host.as_deref(), Some("laptop"));
+        assert_eq!(
+            filters.before,
+            Some(
+                Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+                    .unwrap()
+                    .timestamp()
+            )
+        );
+        assert_eq!(filters.after, None);
+
+        let (text, filters) = parse("after:2d dir:\"../my shop/\" tag:deploy").unwrap();
+        assert_eq!(text, "");
+        assert_eq!(filters.after, Some(now.timestamp() - 2 * 86400));
+        assert_eq!(filters.within.as_deref(), Some("/home/dev/my shop"));
+        assert_eq!(filters.tag.as_deref(), Some("deploy"));
+
+        // Unknown prefixes, empty values and `content:` keep field-like words as text
+        let (text, filters) = parse("git:main dir: content:host:x https://example.org").unwrap();
+        assert_eq!(text, "git:main dir: host:x https://example.org");
+        assert_eq!(filters, SearchFilters::default());
+
+        assert!(parse("after:lastweek").is_err());
+    }
+}
@@ -1,33 +1,50 @@
+use crate::ask::result_cache::{RESULT_CACHE, ResultCache};
 use crate::config::GLOBAL_CONFIG;
-use crate::db::USER_DB;
-use crate::embeds::{cosine_similarity, generate_embedding};
+use crate::db::READ_POOL;
+use crate::embeds::{TopK, cosine_similarity_blob, embedding_norm, generate_embedding};
 use crate::types::SearchResult;
 use rusqlite::params;
 use std::collections::HashSet;
 
+/// Matches kept by the fallback scan. Results are deduplicated by content and cut to
+/// 20 afterwards, so this leaves room for repeats.
+pub(crate) const SEMANTIC_CANDIDATES: usize = 100;
+
 /// Perform semantic search using embeddings
 pub fn semantic_search(query: &str) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
-    // Generate embedding for query
-    let query_embedding = generate_embedding(query)?;
-
     let mut similarity_threshold = 0.8;
     if let Ok(config) = GLOBAL_CONFIG.read() {
         similarity_threshold = config.search.similarity_threshold;
     }
 
-    let db = USER_DB
-        .lock()
-        .map_err(|e| format!("DB lock error: {}", e))?;
+    // Cached results also skip generating the query embedding
+    let key = ResultCache::key("semantic", query, "", "", &similarity_threshold.to_string());
+    RESULT_CACHE.get_or_search(key, || run_semantic_search(query, similarity_threshold))
+}
+
+fn run_semantic_search(
+    query: &str,
+    similarity_threshold: f32,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    // Generate embedding for query
+    let query_embedding = generate_embedding(query)?;
+
+    let conn = READ_POOL.get()?;
 
     // Try vector search first, fallback to manual search
-    let results = match semantic_search_vector(&db.conn, &query_embedding, similarity_threshold) {
+    let results = match semantic_search_vector(&conn, &query_embedding, similarity_threshold) {
         Ok(results) => {
             // println!("✓ Using sqlite-vec for semantic search");
             results
         }
         Err(_) => {
             // println!("ℹ Using fallback semantic search");
-            semantic_search_fallback(&db.conn, &query_embedding, similarity_threshold)?
+            semantic_search_fallback(
+                &conn,
+                &query_embedding,
+                similarity_threshold,
+                SEMANTIC_CANDIDATES,
+            )?
         }
     };
 
@@ -87,14 +104,16 @@ fn semantic_search_vector(
     Ok(results)
 }
 
-/// Fallback semantic search (compute similarity in Rust)
-fn semantic_search_fallback(
+/// Fallback semantic search (compute similarity in Rust). Rows are streamed and
+/// scored straight from the stored blob; only the best `limit` matches are kept.
+pub(crate) fn semantic_search_fallback(
     conn: &rusqlite::Connection,
     query_embedding: &[f32],
     threshold: f32,
+    limit: usize,
 ) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
     // Get recent entries with embeddings
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, entry_type, content, timestamp, times_run, 
                 working_dir, host, app_name, window_title, embedding
          FROM entries
@@ -103,18 +122,24 @@ fn semantic_search_fallback(
          LIMIT 1000",
     )?;
 
-    let mut results: Vec<SearchResult> = stmt
-        .query_map([], |row| {
-            let embedding_blob: Option<Vec<u8>> = row.get(9)?;
+    let query_norm = embedding_norm(query_embedding);
+    let mut top = TopK::new(limit);
+    let mut rows = stmt.query([])?;
 
-            let similarity = if let Some(blob) = embedding_blob {
-                let embedding = blob_to_vec(&blob);
-                cosine_similarity(query_embedding, &embedding)
-            } else {
-                0.0
-            };
+    while let Some(row) = rows.next()? {
+        let similarity = match row.get_ref(9)?.as_blob_or_null()? {
+            Some(blob) => cosine_similarity_blob(query_embedding, query_norm, blob),
+            None => 0.0,
+        };
 
-            Ok(SearchResult {
+        // Only rows that make the cut get their columns copied out
+        if similarity <= threshold || !top.accepts(similarity) {
+            continue;
+        }
+
+        top.push(
+            similarity,
+            SearchResult {
                 id: row.get(0)?,
                 entry_type: row.get(1)?,
                 content: row.get(2)?,
@@ -125,25 +150,14 @@ fn semantic_search_fallback(
                 app_name: row.get(7)?,
                 window_title: row.get(8)?,
                 similarity,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
-
-    // Sort by similarity (highest first)
-    results.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap());
-
-    // Filter by threshold
-    results.retain(|r| r.similarity > threshold);
+            },
+        );
+    }
 
-    Ok(results)
+    // Highest similarity first
+    Ok(top.into_sorted_vec())
 }
 
 fn vec_to_blob(vec: &[f32]) -> Vec<u8> {
     vec.iter().flat_map(|f| f.to_le_bytes()).collect()
 }
-
-fn blob_to_vec(blob: &[u8]) -> Vec<f32> {
-    blob.chunks_exact(4)
-        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
-        .collect()
-}
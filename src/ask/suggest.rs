@@ -0,0 +1,78 @@
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::db::open_quick_reader;
+use crate::normalize::normalize;
+use crate::types::EntryType;
+
+/// The greatest character, so `prefix..prefix + MAX` spans the commands starting
+/// with `prefix`
+const PREFIX_END: char = char::MAX;
+
+/// `jotx suggest`: the command a shell should offer as the completion of `prefix`.
+/// Runs on every keystroke, so it's a single indexed prefix lookup with no LLM,
+/// embeddings, plugins or result cache.
+pub fn suggest(prefix: &str, directory: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    if prefix.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let conn = open_quick_reader()?;
+    Ok(suggest_in(&conn, prefix, directory)?)
+}
+
+/// Most relevant shell command starting with `prefix`: ones run in `directory` first,
+/// then the most used, then the most recent
+pub(crate) fn suggest_in(
+    conn: &Connection,
+    prefix: &str,
+    directory: &str,
+) -> rusqlite::Result<Option<String>> {
+    let prefix = normalize(prefix);
+    let end = format!("{}{}", prefix, PREFIX_END);
+
+    // A range on content (rather than LIKE) can use idx_content and stays case-sensitive,
+    // like the buffer it completes
+    conn.prepare_cached(
+        "SELECT content FROM entries
+         WHERE content >= ?1 AND content < ?2 AND content != ?1 AND entry_type = ?3
+         ORDER BY (working_dir = ?4 AND ?4 != '') DESC, times_run DESC, timestamp DESC
+         LIMIT 1",
+    )?
+    .query_row(
+        rusqlite::params![prefix, end, EntryType::Shell.to_string(), directory],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_suggest_prefers_directory_then_usage() {
+        let db = TempDb::new("suggest");
+        let now = chrono::Utc::now().timestamp() as u64;
+        let shell = |content: &str, working_dir: &str| {
+            db.shell(ShellRow::new(content, now).in_dir(working_dir));
+        };
+
+        shell("git push origin main", "/srv/other");
+        shell("git push origin main", "/srv/other");
+        shell("git pull --rebase", "/home/dev/project");
+        shell("Git is not a command", "/home/dev/project");
+
+        assert_eq!(
+            suggest_in(&db.conn, "git p", "/home/dev/project").unwrap().as_deref(),
+            Some("git pull --rebase")
+        );
+        assert_eq!(
+            suggest_in(&db.conn, "git p", "/elsewhere").unwrap().as_deref(),
+            Some("git push origin main")
+        );
+        assert_eq!(suggest_in(&db.conn, "git pull --rebase", "").unwrap(), None);
+        assert_eq!(suggest_in(&db.conn, "cargo", "").unwrap(), None);
+    }
+}
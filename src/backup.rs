@@ -0,0 +1,228 @@
+//! `jotx backup` and `jotx restore`: gzipped tarballs of jotx.db and config.toml kept
+//! in ~/.jotx/backups, the oldest deleted once there are more than
+//! `storage.backups_to_keep`.
+
+use chrono::Local;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use rusqlite::OpenFlags;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::db::open_db;
+use crate::permissions::{create_private_dir, create_private_file, restrict, restrict_db};
+
+const BACKUP_PREFIX: &str = "jotx-backup-";
+const BACKUP_SUFFIX: &str = ".tar.gz";
+/// Names of the files inside a backup
+const DB_NAME: &str = "jotx.db";
+const CONFIG_NAME: &str = "config.toml";
+
+pub fn backups_dir() -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".jotx").join("backups")
+}
+
+/// A backup unpacked and checked by `unpack_backup`, ready for `swap_in`
+#[derive(Debug)]
+pub struct Snapshot {
+    pub db: PathBuf,
+    pub config: Option<PathBuf>,
+    pub entries: usize,
+}
+
+/// Write a consistent copy of the database (taken with VACUUM INTO, so the daemon can
+/// keep writing meanwhile) and the config to a new backup in `dir`, then delete the
+/// oldest backups beyond `keep`. Returns the new backup's path.
+pub fn create_backup(
+    db_path: &Path,
+    config_path: &Path,
+    dir: &Path,
+    keep: usize,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    create_private_dir(dir)?;
+
+    let copy = dir.join(format!(".snapshot-{}.db", std::process::id()));
+    remove_if_exists(&copy)?;
+    let conn = open_db(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.execute("VACUUM INTO ?1", [copy.to_string_lossy().to_string()])?;
+    drop(conn);
+
+    let path = new_backup_path(dir);
+    let result = write_archive(&path, &copy, config_path);
+    remove_if_exists(&copy)?;
+    result?;
+
+    rotate(dir, keep)?;
+    Ok(path)
+}
+
+/// `jotx-backup-<local time>.tar.gz`, named so they sort oldest first
+fn new_backup_path(dir: &Path) -> PathBuf {
+    loop {
+        let stamp = Local::now().format("%Y%m%d-%H%M%S-%3f");
+        let path = dir.join(format!("{}{}{}", BACKUP_PREFIX, stamp, BACKUP_SUFFIX));
+        if !path.exists() {
+            return path;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+}
+
+fn write_archive(path: &Path, db_copy: &Path, config_path: &Path) -> io::Result<()> {
+    let encoder = GzEncoder::new(create_private_file(path)?, Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+    archive.append_path_with_name(db_copy, DB_NAME)?;
+    if config_path.exists() {
+        archive.append_path_with_name(config_path, CONFIG_NAME)?;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Backups in `dir`, oldest first
+pub fn list_backups(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut backups: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_SUFFIX)
+                    })
+            })
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    backups.sort();
+    Ok(backups)
+}
+
+/// Delete the oldest backups so at most `keep` remain. Returns the deleted ones.
+fn rotate(dir: &Path, keep: usize) -> io::Result<Vec<PathBuf>> {
+    let backups = list_backups(dir)?;
+    let excess = backups.len().saturating_sub(keep.max(1));
+    let removed: Vec<PathBuf> = backups.into_iter().take(excess).collect();
+    for path in &removed {
+        fs::remove_file(path)?;
+    }
+    Ok(removed)
+}
+
+/// Unpack `archive` into `staging` and check it: the database must pass SQLite's
+/// integrity check and have an entries table, and the config must parse. Only the
+/// two known file names are extracted.
+pub fn unpack_backup(
+    archive: &Path,
+    staging: &Path,
+) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    create_private_dir(staging)?;
+    let mut db = None;
+    let mut config = None;
+
+    let mut tarball = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+    for entry in tarball.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        let target = match name.as_str() {
+            DB_NAME => &mut db,
+            CONFIG_NAME => &mut config,
+            _ => continue,
+        };
+        let path = staging.join(&name);
+        io::copy(&mut entry, &mut create_private_file(&path)?)?;
+        *target = Some(path);
+    }
+
+    let db = db.ok_or_else(|| format!("{} has no {}", archive.display(), DB_NAME))?;
+    let entries = check_database(&db)
+        .map_err(|e| format!("The database in {} is damaged: {}", archive.display(), e))?;
+    if let Some(config) = &config {
+        toml::from_str::<Config>(&fs::read_to_string(config)?)
+            .map_err(|e| format!("The config in {} is invalid: {}", archive.display(), e))?;
+    }
+
+    Ok(Snapshot {
+        db,
+        config,
+        entries,
+    })
+}
+
+/// Run SQLite's integrity check and count the entries
+fn check_database(path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+    let conn = open_db(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if integrity != "ok" {
+        return Err(integrity.into());
+    }
+    let entries: i64 = conn.query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))?;
+    Ok(entries as usize)
+}
+
+/// Move an unpacked snapshot over the live database and config. Nothing may have the
+/// database open: its WAL belongs to the database being replaced and is removed.
+pub fn swap_in(snapshot: &Snapshot, db_path: &Path, config_path: &Path) -> io::Result<()> {
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.as_os_str().to_owned();
+        sidecar.push(suffix);
+        remove_if_exists(Path::new(&sidecar))?;
+    }
+    fs::rename(&snapshot.db, db_path)?;
+    restrict_db(db_path)?;
+
+    if let Some(config) = &snapshot.config {
+        fs::rename(config, config_path)?;
+        restrict(config_path)?;
+    }
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_backup_rotate_and_restore() {
+        let mut temp = TempDb::new("backup");
+        temp.shell(ShellRow::new("git status", 1));
+        temp.close();
+        let dir = temp.dir();
+
+        let db_path = dir.join("jotx.db");
+        let config_path = dir.join("config.toml");
+        let backups = dir.join("backups");
+        let first = create_backup(&db_path, &config_path, &backups, 2).unwrap();
+        for _ in 0..2 {
+            create_backup(&db_path, &config_path, &backups, 2).unwrap();
+        }
+        let kept = list_backups(&backups).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert!(!kept.contains(&first));
+
+        let snapshot = unpack_backup(&kept[0], &dir.join("staging")).unwrap();
+        assert_eq!(snapshot.entries, 1);
+        assert!(snapshot.config.is_none());
+
+        let restored = dir.join("restored.db");
+        swap_in(&snapshot, &restored, &config_path).unwrap();
+        assert_eq!(check_database(&restored).unwrap(), 1);
+
+        std::fs::write(dir.join("garbage.tar.gz"), b"not a backup").unwrap();
+        assert!(unpack_backup(&dir.join("garbage.tar.gz"), &dir.join("staging")).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use colored::*;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::ask::search_handler::{SearchFilters, keyword_search_in};
+use crate::ask::semantic::{SEMANTIC_CANDIDATES, semantic_search_fallback};
+use crate::db::{Database, ShellRow};
+use crate::embeds::generate_embedding;
+use crate::types::EntryType;
+
+const EMBEDDING_DIM: usize = 384;
+const EMBEDDING_SAMPLES: usize = 32;
+
+const TOOLS: &[&str] = &[
+    "git", "cargo", "docker", "kubectl", "npm", "ssh", "grep", "curl", "python", "make",
+];
+const ACTIONS: &[&str] = &[
+    "status", "build", "run", "push", "logs", "install", "test", "deploy", "fetch", "clean",
+];
+const QUERIES: &[&str] = &[
+    "git push", "cargo build", "docker logs", "kubectl", "npm install", "deploy", "curl",
+    "python run", "make clean", "ssh",
+];
+
+/// Timings for one synthetic database size
+struct BenchResult {
+    entries: usize,
+    inserts_per_sec: f64,
+    fts_p50: Duration,
+    fts_p95: Duration,
+    semantic_p50: Duration,
+}
+
+/// Build throwaway databases of each size and time the hot paths against them.
+/// Nothing touches ~/.jotx.
+pub fn run_bench(sizes: &[usize], queries: usize) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "╔════════════════════════════════════════╗".cyan());
+    println!("{}", "║        JotX Benchmark                  ║".cyan());
+    println!("{}", "╚════════════════════════════════════════╝".cyan());
+    println!();
+
+    let mut results = Vec::new();
+    for &entries in sizes {
+        println!("Benchmarking {} entries...", entries);
+        results.push(bench_size(entries, queries.max(1))?);
+    }
+
+    println!("Measuring embedding throughput...");
+    let embeddings_per_sec = bench_embeddings();
+
+    println!();
+    print!("{:<28}", "Metric".bold());
+    for result in &results {
+        print!("{:>14}", result.entries.to_string().bold());
+    }
+    println!();
+
+    print_row("Insert (entries/s)", &results, |r| format!("{:.0}", r.inserts_per_sec));
+    print_row("FTS query p50 (ms)", &results, |r| millis(r.fts_p50));
+    print_row("FTS query p95 (ms)", &results, |r| millis(r.fts_p95));
+    print_row("Semantic fallback p50 (ms)", &results, |r| millis(r.semantic_p50));
+
+    println!();
+    match embeddings_per_sec {
+        Some(rate) => println!("Embedding throughput: {:.1} texts/s", rate),
+        None => println!("⚠️  Embedding throughput: n/a (model unavailable)"),
+    }
+
+    Ok(())
+}
+
+fn bench_size(entries: usize, queries: usize) -> Result<BenchResult, Box<dyn std::error::Error>> {
+    let dir = bench_dir(entries);
+    let result = run_in(&dir, entries, queries);
+    std::fs::remove_dir_all(&dir).ok();
+    result
+}
+
+fn run_in(
+    dir: &Path,
+    entries: usize,
+    queries: usize,
+) -> Result<BenchResult, Box<dyn std::error::Error>> {
+    let db = Database::open_in(dir)?;
+    let now = chrono::Utc::now().timestamp() as u64;
+
+    // Inserts go through the same Database methods as the writer thread
+    let started = Instant::now();
+    for i in 0..entries {
+        let content = synthetic_entry(i);
+        let timestamp = now.saturating_sub((entries - i) as u64 * 60);
+        let embedding = Some(synthetic_embedding(i as u64));
+
+        if i % 4 == 3 {
+            db.insert_clipboard(&content, timestamp, "bench", "bench", embedding)?;
+        } else {
+            let working_dir = format!("/home/bench/project-{}", i % 25);
+            let row = ShellRow {
+                working_dir: Some(&working_dir),
+                user: Some("bench"),
+                host: Some("localhost"),
+                app_name: "bench",
+                window_title: "bench",
+                ..ShellRow::new(&content, timestamp)
+            };
+            db.insert_shell(&row, embedding)?;
+        }
+    }
+    let inserts_per_sec = entries as f64 / started.elapsed().as_secs_f64().max(f64::EPSILON);
+
+    let mut fts = Vec::with_capacity(queries);
+    for i in 0..queries {
+        let query = QUERIES[i % QUERIES.len()];
+        let started = Instant::now();
+        keyword_search_in(
+            &db.conn,
+            query,
+            EntryType::Shell,
+            "/home/bench/project-3",
+            &SearchFilters::default(),
+        )?;
+        fts.push(started.elapsed());
+    }
+
+    let mut semantic = Vec::with_capacity(queries);
+    for i in 0..queries {
+        let query_embedding = synthetic_embedding(u64::MAX - i as u64);
+        let started = Instant::now();
+        semantic_search_fallback(&db.conn, &query_embedding, 0.0, SEMANTIC_CANDIDATES)?;
+        semantic.push(started.elapsed());
+    }
+
+    Ok(BenchResult {
+        entries,
+        inserts_per_sec,
+        fts_p50: percentile(&mut fts, 50),
+        fts_p95: percentile(&mut fts, 95),
+        semantic_p50: percentile(&mut semantic, 50),
+    })
+}
+
+/// Texts embedded per second, or None when the model can't be loaded
+fn bench_embeddings() -> Option<f64> {
+    // The first call loads the model; keep it out of the timing
+    generate_embedding(&synthetic_entry(0)).ok()?;
+
+    let started = Instant::now();
+    for i in 1..=EMBEDDING_SAMPLES {
+        generate_embedding(&synthetic_entry(i)).ok()?;
+    }
+    Some(EMBEDDING_SAMPLES as f64 / started.elapsed().as_secs_f64().max(f64::EPSILON))
+}
+
+fn bench_dir(entries: usize) -> PathBuf {
+    std::env::temp_dir().join(format!("jotx-bench-{}-{}", std::process::id(), entries))
+}
+
+/// Command-like text; every 7th entry repeats an earlier one so the
+/// increment path gets exercised too
+fn synthetic_entry(i: usize) -> String {
+    let n = if i % 7 == 6 { i / 2 } else { i };
+    let tool = TOOLS[n % TOOLS.len()];
+    let action = ACTIONS[(n / TOOLS.len()) % ACTIONS.len()];
+    format!("{} {} --target item-{}", tool, action, n % 500)
+}
+
+/// Deterministic pseudo-random unit vector (xorshift), so runs are comparable
+fn synthetic_embedding(seed: u64) -> Vec<f32> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    let mut vector: Vec<f32> = (0..EMBEDDING_DIM)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 2000) as f32 / 1000.0 - 1.0
+        })
+        .collect();
+
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt().max(f32::EPSILON);
+    vector.iter_mut().for_each(|x| *x /= norm);
+    vector
+}
+
+fn percentile(samples: &mut [Duration], pct: usize) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    samples.sort();
+    let index = (samples.len() * pct / 100).min(samples.len() - 1);
+    samples[index]
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.2}", duration.as_secs_f64() * 1000.0)
+}
+
+fn print_row(label: &str, results: &[BenchResult], value: impl Fn(&BenchResult) -> String) {
+    print!("{:<28}", label);
+    for result in results {
+        print!("{:>14}", value(result));
+    }
+    println!();
+}
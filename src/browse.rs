@@ -0,0 +1,476 @@
+//! `jotx browse`: a full-screen browser over the stored history. Typing narrows the
+//! list as you go, Tab switches between all entries, shell commands, clipboard
+//! items and notes, and the pane under the list shows where and when the selected entry was
+//! captured. Ctrl-P pins the selected entry so cleanup never trims it.
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::collections::HashSet;
+use std::io::{Stderr, Write, stderr};
+
+use crate::db::{USER_DB, pinned_in};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::timezone::QueryTimezone;
+use crate::types::{Entry, EntryType, QueryParams};
+use crate::utils::{CASE_FOLDED_NOTE, copy_entry_to_clipboard, delete_entries, pin_entry};
+
+/// Entries loaded per search; narrowing the search finds older ones
+const BROWSE_LIMIT: usize = 1000;
+/// Most rows the preview pane takes
+const PREVIEW_ROWS: u16 = 10;
+const KEYS_HELP: &str =
+    "↑↓ move · Tab type · Enter copy & quit · Ctrl-Y copy · Ctrl-P pin · Ctrl-D delete · Esc quit";
+
+struct Browser {
+    query: String,
+    entry_type: EntryType,
+    entries: Vec<Entry>,
+    /// Ids of the loaded entries that are pinned
+    pinned: HashSet<i64>,
+    total: usize,
+    selected: usize,
+    scroll: usize,
+    /// Entry that the next Ctrl-D deletes, after the first one asked for confirmation
+    pending_delete: Option<i64>,
+    status: Option<String>,
+    /// Printed once the browser has closed
+    exit_note: Option<String>,
+    tz: QueryTimezone,
+}
+
+/// Restores the terminal however the browser exits
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter(out: &mut Stderr) -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(stderr(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Open the browser, starting with `query` typed in
+pub fn browse(query: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut browser = Browser {
+        query: query.unwrap_or_default(),
+        entry_type: EntryType::Any,
+        entries: Vec::new(),
+        pinned: HashSet::new(),
+        total: 0,
+        selected: 0,
+        scroll: 0,
+        pending_delete: None,
+        status: None,
+        exit_note: None,
+        tz: QueryTimezone::from_config(),
+    };
+    browser.reload()?;
+
+    let mut out = stderr();
+    let guard = TerminalGuard::enter(&mut out)?;
+
+    loop {
+        browser.draw(&mut out)?;
+        let Event::Key(key) = event::read()? else {
+            // Resizes just redraw
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match browser.handle_key(key) {
+            Ok(true) => {}
+            Ok(false) => {
+                drop(guard);
+                if let Some(note) = browser.exit_note {
+                    eprintln!("ℹ️  {}", note);
+                }
+                return Ok(());
+            }
+            Err(e) => browser.status = Some(format!("❌ {}", e)),
+        }
+    }
+}
+
+impl Browser {
+    fn reload(&mut self) -> Result<(), String> {
+        let query = self.query.trim();
+        let params = QueryParams {
+            entry_type: Some(self.entry_type.clone()),
+            content_search: (!query.is_empty()).then(|| query.to_string()),
+            limit: Some(BROWSE_LIMIT),
+            ..Default::default()
+        };
+
+        let db = lock_or_recover("database", &USER_DB);
+        self.total = db
+            .count_entries(&params)
+            .map_err(|e| format!("Failed to count history: {}", e))?;
+        self.entries = db
+            .query_entries(&params)
+            .map_err(|e| format!("Failed to load history: {}", e))?;
+        let ids: Vec<i64> = self.entries.iter().map(|entry| entry.id).collect();
+        self.pinned =
+            pinned_in(&db.conn, &ids).map_err(|e| format!("Failed to load pins: {}", e))?;
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+        Ok(())
+    }
+
+    fn selected_entry(&self) -> Option<&Entry> {
+        self.entries.get(self.selected)
+    }
+
+    /// Returns false when the browser should close
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool, String> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if !(ctrl && key.code == KeyCode::Char('d')) {
+            self.pending_delete = None;
+        }
+        self.status = None;
+
+        match key.code {
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if ctrl => return Ok(false),
+            KeyCode::Enter => {
+                if let Some(entry) = self.selected_entry()
+                    && copy_entry_to_clipboard(entry.id)?
+                {
+                    self.exit_note = Some(CASE_FOLDED_NOTE.to_string());
+                }
+                return Ok(false);
+            }
+            KeyCode::Char('y') if ctrl => {
+                if let Some(entry) = self.selected_entry() {
+                    self.status = Some(if copy_entry_to_clipboard(entry.id)? {
+                        format!("📋 Copied to clipboard. {}", CASE_FOLDED_NOTE)
+                    } else {
+                        "📋 Copied to clipboard".to_string()
+                    });
+                }
+            }
+            KeyCode::Char('p') if ctrl => self.toggle_pin()?,
+            KeyCode::Char('d') if ctrl => self.delete_selected()?,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.move_down(1),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(self.page()),
+            KeyCode::PageDown => self.move_down(self.page()),
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.entries.len().saturating_sub(1),
+            KeyCode::Tab => {
+                self.entry_type = next_entry_type(&self.entry_type);
+                self.selected = 0;
+                self.reload()?;
+            }
+            KeyCode::Backspace if self.query.pop().is_some() => {
+                self.selected = 0;
+                self.reload()?;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.selected = 0;
+                self.reload()?;
+            }
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    /// The first Ctrl-D asks for confirmation, a second one on the same entry deletes it
+    fn delete_selected(&mut self) -> Result<(), String> {
+        let Some(id) = self.selected_entry().map(|entry| entry.id) else {
+            return Ok(());
+        };
+        if self.pending_delete != Some(id) {
+            self.pending_delete = Some(id);
+            self.status = Some("Press Ctrl-D again to delete this entry".to_string());
+            return Ok(());
+        }
+
+        self.pending_delete = None;
+        delete_entries(&[id])?;
+        self.reload()?;
+        self.status = Some("🗑️  Entry deleted".to_string());
+        Ok(())
+    }
+
+    fn toggle_pin(&mut self) -> Result<(), String> {
+        let Some(id) = self.selected_entry().map(|entry| entry.id) else {
+            return Ok(());
+        };
+        let pin = !self.pinned.contains(&id);
+        pin_entry(id, pin)?;
+        if pin {
+            self.pinned.insert(id);
+            self.status = Some("📌 Pinned; cleanup will keep this entry".to_string());
+        } else {
+            self.pinned.remove(&id);
+            self.status = Some("Unpinned".to_string());
+        }
+        Ok(())
+    }
+
+    fn move_down(&mut self, rows: usize) {
+        self.selected = (self.selected + rows).min(self.entries.len().saturating_sub(1));
+    }
+
+    fn page(&self) -> usize {
+        let (_, rows) = terminal::size().unwrap_or((80, 24));
+        list_rows(rows).max(1) as usize
+    }
+
+    fn draw(&mut self, out: &mut Stderr) -> std::io::Result<()> {
+        let (cols, rows) = terminal::size()?;
+        let width = cols as usize;
+        let list_rows = list_rows(rows) as usize;
+
+        // Keep the selection on screen
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + list_rows {
+            self.scroll = self.selected + 1 - list_rows;
+        }
+
+        queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+        let header = format!(
+            "🔍 {}▏  [{}]  {} of {}",
+            self.query,
+            type_label(&self.entry_type),
+            self.entries.len(),
+            self.total
+        );
+        queue!(out, Print(fit(&header, width)))?;
+        queue!(out, MoveTo(0, 1), Print("─".repeat(width)))?;
+
+        for (row, (index, entry)) in self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(list_rows)
+            .enumerate()
+        {
+            let pin = if self.pinned.contains(&entry.id) {
+                "📌 "
+            } else {
+                ""
+            };
+            let line = format!(
+                "{} {}  {}{}",
+                type_icon(&entry.entry_type),
+                self.tz.format_timestamp(entry.timestamp, "%m-%d %H:%M"),
+                pin,
+                first_line(&entry.content)
+            );
+            queue!(out, MoveTo(0, 2 + row as u16))?;
+            if index == self.selected {
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(pad(&line, width)),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(out, Print(fit(&line, width)))?;
+            }
+        }
+        if self.entries.is_empty() {
+            queue!(out, MoveTo(0, 2), Print("No matching entries"))?;
+        }
+
+        let preview_top = 2 + list_rows as u16;
+        queue!(out, MoveTo(0, preview_top), Print("─".repeat(width)))?;
+        if let Some(entry) = self.selected_entry() {
+            let lines = preview_lines(entry, &self.tz);
+            for (i, line) in lines.iter().take(preview_rows(rows) as usize).enumerate() {
+                queue!(
+                    out,
+                    MoveTo(0, preview_top + 1 + i as u16),
+                    Print(fit(line, width))
+                )?;
+            }
+        }
+
+        let footer = self.status.as_deref().unwrap_or(KEYS_HELP);
+        queue!(
+            out,
+            MoveTo(0, rows.saturating_sub(1)),
+            SetAttribute(Attribute::Dim),
+            Print(fit(footer, width)),
+            SetAttribute(Attribute::Reset)
+        )?;
+        out.flush()
+    }
+}
+
+/// Rows left for the list after the header, two separators, the preview and the footer
+fn list_rows(rows: u16) -> u16 {
+    rows.saturating_sub(4 + preview_rows(rows))
+}
+
+fn preview_rows(rows: u16) -> u16 {
+    PREVIEW_ROWS.min(rows / 3)
+}
+
+/// All → Shell → Clipboard → Note → All
+fn next_entry_type(entry_type: &EntryType) -> EntryType {
+    match entry_type {
+        EntryType::Any => EntryType::Shell,
+        EntryType::Shell => EntryType::Clipboard,
+        EntryType::Clipboard => EntryType::Note,
+        EntryType::Note => EntryType::Any,
+    }
+}
+
+fn type_label(entry_type: &EntryType) -> &'static str {
+    match entry_type {
+        EntryType::Any => "all",
+        EntryType::Shell => "shell",
+        EntryType::Clipboard => "clipboard",
+        EntryType::Note => "notes",
+    }
+}
+
+fn type_icon(entry_type: &EntryType) -> &'static str {
+    match entry_type {
+        EntryType::Clipboard => "📋",
+        EntryType::Shell => "💻",
+        EntryType::Note => "📝",
+        EntryType::Any => "📄",
+    }
+}
+
+/// First line of multi-line content, marked when there is more
+fn first_line(content: &str) -> String {
+    let mut lines = content.trim().lines();
+    let first = lines.next().unwrap_or_default();
+    if lines.next().is_some() {
+        format!("{} ↵", first)
+    } else {
+        first.to_string()
+    }
+}
+
+/// Cut a line to the terminal width
+fn fit(line: &str, width: usize) -> String {
+    console::truncate_str(line, width, "…").into_owned()
+}
+
+/// Cut or space-fill a line to exactly the terminal width, for the highlighted row
+fn pad(line: &str, width: usize) -> String {
+    let line = fit(line, width);
+    let fill = width.saturating_sub(console::measure_text_width(&line));
+    format!("{}{}", line, " ".repeat(fill))
+}
+
+/// The selected entry's content followed by where and when it was captured
+fn preview_lines(entry: &Entry, tz: &QueryTimezone) -> Vec<String> {
+    let mut lines: Vec<String> = entry
+        .content
+        .trim()
+        .lines()
+        .take(3)
+        .map(|line| line.to_string())
+        .collect();
+    lines.push(String::new());
+
+    lines.push(format!(
+        "Captured  {}{}",
+        tz.format_timestamp(entry.timestamp, "%Y-%m-%d %H:%M:%S"),
+        if entry.times_run > 1 {
+            format!("  (run {} times)", entry.times_run)
+        } else {
+            String::new()
+        }
+    ));
+
+    let mut push = |label: &str, value: Option<String>| {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            lines.push(format!("{:<9} {}", label, value));
+        }
+    };
+    push("Directory", entry.working_dir.clone());
+    push(
+        "Host",
+        match (&entry.user, &entry.host) {
+            (Some(user), Some(host)) => Some(format!("{}@{}", user, host)),
+            (None, host) => host.clone(),
+            (user, None) => user.clone(),
+        },
+    );
+    push(
+        "Git",
+        entry.git_repo.clone().map(|repo| match &entry.git_branch {
+            Some(branch) => format!("{} ({})", repo, branch),
+            None => repo,
+        }),
+    );
+    push(
+        "App",
+        entry.app_name.clone().map(|app| match &entry.window_title {
+            Some(title) if !title.is_empty() => format!("{} – {}", app, title),
+            _ => app,
+        }),
+    );
+    push(
+        "Exit",
+        entry.exit_code.map(|code| match entry.duration_ms {
+            Some(ms) => format!("{} after {} ms", code, ms),
+            None => code.to_string(),
+        }),
+    );
+    push("Id", Some(entry.id.to_string()));
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_lines() {
+        let entry = Entry {
+            id: 42,
+            entry_type: EntryType::Shell,
+            content: "cargo test".to_string(),
+            timestamp: 0,
+            times_run: 3,
+            working_dir: Some("/home/me/jot".to_string()),
+            git_repo: None,
+            git_branch: None,
+            user: Some("me".to_string()),
+            host: Some("laptop".to_string()),
+            app_name: None,
+            window_title: None,
+            exit_code: Some(101),
+            duration_ms: Some(5300),
+            embedding: None,
+        };
+
+        let lines = preview_lines(&entry, &QueryTimezone::Utc);
+        assert_eq!(lines[0], "cargo test");
+        assert_eq!(lines[2], "Captured  1970-01-01 00:00:00  (run 3 times)");
+        assert_eq!(
+            &lines[3..],
+            [
+                "Directory /home/me/jot",
+                "Host      me@laptop",
+                "Exit      101 after 5300 ms",
+                "Id        42",
+            ]
+        );
+        assert_eq!(first_line("ssh a\nssh b"), "ssh a ↵");
+        assert_eq!(next_entry_type(&EntryType::Clipboard), EntryType::Note);
+        assert_eq!(next_entry_type(&EntryType::Note), EntryType::Any);
+    }
+}
@@ -2,11 +2,21 @@ use copypasta::{ClipboardContext, ClipboardProvider};
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, error, warn};
 
 use crate::db::{DB_WRITER};
 
 use crate::context::get_context;
-use crate::types::{ClipboardEntry, SimplifiedWindowInfo};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::plugin::{
+    ClipboardContext as PluginClipboardContext, GLOBAL_PLUGIN_MANAGER, audit_content_hash,
+    record_blocked,
+};
+use crate::config::{GLOBAL_CONFIG, PrivacyMode};
+use crate::metrics::METRICS;
+use crate::normalize::fold;
+use crate::privacy::{find_secret, lowercase_redacted, redact_secrets};
+use crate::types::{BlockedCapture, ClipboardEntry, SimplifiedWindowInfo};
 
 pub struct ClipMon {
     ctx: ClipboardContext,
@@ -24,35 +34,105 @@ impl ClipMon {
     }
 
     pub fn check(&mut self, case_sensitive: bool) -> Result<(), Box<dyn std::error::Error>> {
-        let clip = self.ctx.get_contents().unwrap_or_default();
+        let raw_clip = self.ctx.get_contents().unwrap_or_default();
         let clip = if case_sensitive {
-            clip
+            raw_clip.clone()
         } else {
-            clip.to_lowercase()
+            fold(&raw_clip)
         };
         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
 
         let current_context = match get_context() {
             Ok(info) => info,
             Err(e) => {
-                eprintln!("Failed to get context: {}", e);
+                warn!("Failed to get context: {}", e);
                 return Ok(());
             }
         };
 
+        let privacy = GLOBAL_CONFIG.read().map(|c| c.privacy.clone()).ok();
+
+        // Clipboards of blocked apps (password managers...), private-browsing or other
+        // sensitive windows, and clips matching the clipboard regexes are never stored
+        let blocked_by = privacy.as_ref().and_then(|p| {
+            p.clipboard_rule(&raw_clip, &current_context.info.name, &current_context.title)
+        });
+        if !clip.is_empty()
+            && clip != self.last_clip
+            && let Some((plugin, rule)) = blocked_by
+        {
+            record_blocked(&BlockedCapture {
+                timestamp,
+                source: "clipboard".to_string(),
+                plugin: plugin.to_string(),
+                rule: Some(rule),
+                content_hash: audit_content_hash(&raw_clip),
+                redacted: false,
+            });
+            self.last_clip = clip;
+            self.last_context = Some(current_context);
+            return Ok(());
+        }
+
+        // Known secrets are never stored: depending on privacy.mode the clip is redacted or
+        // dropped. A dropped clip is remembered so it is only reported once.
+        let mut content = clip.clone();
+        if !clip.is_empty()
+            && clip != self.last_clip
+            && let Some(id) = find_secret(&raw_clip)
+        {
+            let redacted = match privacy.as_ref().map(|p| p.mode) {
+                Some(PrivacyMode::Redact) => redact_secrets(&raw_clip),
+                _ => None,
+            };
+
+            record_blocked(&BlockedCapture {
+                timestamp,
+                source: "clipboard".to_string(),
+                plugin: "secret-patterns".to_string(),
+                rule: Some(format!("secret pattern \"{}\"", id)),
+                content_hash: audit_content_hash(&raw_clip),
+                redacted: redacted.is_some(),
+            });
+
+            match redacted {
+                Some(redacted) if case_sensitive => content = redacted,
+                Some(redacted) => content = lowercase_redacted(&redacted),
+                None => {
+                    self.last_clip = clip;
+                    return Ok(());
+                }
+            }
+        }
+
         // New clipboard?
         if !clip.is_empty() && clip != self.last_clip {
+            // Plugins may block or rewrite it; a blocked clip is logged by the manager
+            let mut plugin_context = PluginClipboardContext {
+                content,
+                app: current_context.info.name.clone(),
+                window_title: current_context.title.clone(),
+                timestamp,
+            };
+            if !lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER)
+                .trigger_clipboard_captured(&mut plugin_context)
+            {
+                self.last_clip = clip;
+                self.last_context = Some(current_context);
+                return Ok(());
+            }
+
             let entry = ClipboardEntry {
                 timestamp,
                 context: current_context.clone(),
-                content: clip.clone(),
+                content: plugin_context.content,
             };
 
-            println!("New clipboard entry: {:?}", entry);
+            debug!("New clipboard entry from {}", entry.context.info.name);
 
             // Write directly to DB
             if let Err(e) = self.add_to_db(&entry) {
-                eprintln!("Failed to save clipboard to DB: {}", e);
+                error!("Failed to save clipboard to DB: {}", e);
             }
 
             self.last_clip = clip;
@@ -61,7 +141,7 @@ impl ClipMon {
         // Context changed but same clipboard?
         else if let Some(ref prev) = self.last_context {
             if prev != &current_context {
-                println!(
+                debug!(
                     "Context → {} – {}",
                     current_context.info.name, current_context.title
                 );
@@ -74,6 +154,25 @@ impl ClipMon {
         Ok(())
     }
 
+    /// Put `content` back on the system clipboard and remember it, compared the way
+    /// `check` compares clips, so the next check doesn't store it again. Only the
+    /// daemon's monitor captures, so other processes go through `IpcRequest::CopyEntry`.
+    pub fn copy_back(
+        &mut self,
+        content: &str,
+        case_sensitive: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.ctx
+            .set_contents(content.to_string())
+            .map_err(|e| e.to_string())?;
+        self.last_clip = if case_sensitive {
+            content.to_string()
+        } else {
+            fold(content)
+        };
+        Ok(())
+    }
+
     pub fn add_to_db(&self, entry: &ClipboardEntry) -> Result<(), Box<dyn std::error::Error>> {
         // Queue immediately - returns in <1ms
         DB_WRITER.insert_clipboard(
@@ -81,7 +180,9 @@ impl ClipMon {
             entry.timestamp,
             entry.context.info.name.clone(),
             entry.context.title.clone(),
-        )
+        )?;
+        METRICS.clipboard_captures.inc();
+        Ok(())
     }
 }
 
@@ -1,9 +1,46 @@
 use crate::settings::GLOBAL_SETTINGS;
-use crate::config::GLOBAL_CONFIG;
+use crate::backup::{backups_dir, create_backup, list_backups, swap_in, unpack_backup};
+use crate::config::{Config, GLOBAL_CONFIG, PrivacyMode, get_config_path};
+use crate::db::{DB_WRITER, FtsCheck, fts_tokenizer};
+use crate::db::{
+    USER_DB, decrypt_database, embedding_dimension, encrypt_database, encryption_enabled,
+    get_db_path,
+};
+use crate::embeds::generate_embeddings;
+use crate::export::ExportFormat;
+use crate::import::{ImportSource, merge_runs, read_history};
+use crate::journal::day_range;
+use crate::logging::{LOG_NAME, follow, last_lines, logs_dir};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::permissions::{exposed_paths, restrict};
+use crate::pid_controller::{
+    ERR_FILE, LOG_FILE, is_paused, is_running, parse_pause_duration, pause_until, set_paused,
+    stop_daemon,
+};
+use crate::normalize::normalize;
+use crate::plugin::{CommandContext, GLOBAL_PLUGIN_MANAGER, read_blocked_log};
+use crate::privacy::lowercase_redacted;
+use crate::privacy::vault::{VaultKey, create_vault_key, seal, vault_exists};
+use crate::service::{ServiceManager, start_daemon};
+use crate::shell::capture::is_own_command;
+use crate::snippets::{fill, normalize_snippet_name, parameterize};
+use crate::sync::{DeviceKey, add_peer, load_peers};
+use crate::timezone::QueryTimezone;
+use crate::types::{Entry, EntryType, HistoryFilters, QueryParams, Workflow};
+use crate::utils::{
+    add_note, export_history, list_tags, pin_entry, tag_entry, untag_entry, write_history,
+};
+use crate::workflows::mine_workflows;
 use colored::*;
+use console::Term;
+use dialoguer::{Confirm, Input, MultiSelect, Password, Select};
 use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fs,
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
 pub fn show_settings() {
@@ -17,7 +54,7 @@ pub fn show_settings() {
         println!();
 
         println!("═══════════════════════════════════");
-        let settings = GLOBAL_SETTINGS.lock().unwrap();
+        let settings = lock_or_recover("settings", &GLOBAL_SETTINGS);
         println!(
             "1. Capture Clipboard: {}",
             if settings.capture_clipboard {
@@ -73,19 +110,17 @@ pub fn show_settings() {
         io::stdin().read_line(&mut input).unwrap();
 
         match input.trim() {
-            "1" => GLOBAL_SETTINGS.lock().unwrap().toggle_clipboard(),
-            "2" => GLOBAL_SETTINGS.lock().unwrap().toggle_shell(),
-            "3" => GLOBAL_SETTINGS.lock().unwrap().toggle_shell_history(),
-            "4" => GLOBAL_SETTINGS.lock().unwrap().toggle_clipboard_case_sensitive(),
-            "5" => GLOBAL_SETTINGS.lock().unwrap().toggle_shell_case_sensitive(),
+            "1" => lock_or_recover("settings", &GLOBAL_SETTINGS).toggle_clipboard(),
+            "2" => lock_or_recover("settings", &GLOBAL_SETTINGS).toggle_shell(),
+            "3" => lock_or_recover("settings", &GLOBAL_SETTINGS).toggle_shell_history(),
+            "4" => lock_or_recover("settings", &GLOBAL_SETTINGS).toggle_clipboard_case_sensitive(),
+            "5" => lock_or_recover("settings", &GLOBAL_SETTINGS).toggle_shell_case_sensitive(),
             "6" => {
                 print!("Enter new limit: ");
                 io::stdout().flush().unwrap();
                 let mut input = String::new();
                 io::stdin().read_line(&mut input).unwrap();
-                GLOBAL_SETTINGS
-                    .lock()
-                    .unwrap()
+                lock_or_recover("settings", &GLOBAL_SETTINGS)
                     .set_clipboard_limit(input.trim().parse().unwrap());
             }
             "7" => {
@@ -93,9 +128,7 @@ pub fn show_settings() {
                 io::stdout().flush().unwrap();
                 let mut input = String::new();
                 io::stdin().read_line(&mut input).unwrap();
-                GLOBAL_SETTINGS
-                    .lock()
-                    .unwrap()
+                lock_or_recover("settings", &GLOBAL_SETTINGS)
                     .set_shell_limit(input.trim().parse().unwrap());
             }
             "0" => break,
@@ -251,6 +284,37 @@ pub fn show_privacy_settings() -> Result<(), Box<dyn std::error::Error>> {
             "5. Folder Exclusions ({})", 
             current_privacy.exclude_folders.len().to_string().yellow()
         );
+        println!(
+            "6. Clipboard App Blocklist ({})",
+            current_privacy.clipboard_blocked_apps.len().to_string().yellow()
+        );
+        println!(
+            "7. Host Exclusions, e.g. prod-* or root@* ({})",
+            current_privacy.exclude_hosts.len().to_string().yellow()
+        );
+        println!(
+            "8. On Match: {}",
+            match current_privacy.mode {
+                PrivacyMode::Skip => "skip entry".yellow(),
+                PrivacyMode::Redact => "redact sensitive parts".yellow(),
+            }
+        );
+        println!(
+            "9. Excluded Folders: {}",
+            if current_privacy.hash_excluded_folders {
+                "store hash only".yellow()
+            } else {
+                "skip".yellow()
+            }
+        );
+        println!(
+            "10. Sensitive Window Titles, e.g. (Incognito) ({})",
+            current_privacy.sensitive_window_titles.len().to_string().yellow()
+        );
+        println!(
+            "11. Clipboard Content Regexes ({})",
+            current_privacy.clipboard_excludes_regex.len().to_string().yellow()
+        );
         println!("═══════════════════════════════════");
         println!("0. Save and Exit");
         println!();
@@ -286,6 +350,33 @@ pub fn show_privacy_settings() -> Result<(), Box<dyn std::error::Error>> {
                 "Folder Exclusions", 
                 &mut current_privacy.exclude_folders
             )),
+            "6" => list_to_edit = Some((
+                "Clipboard App Blocklist",
+                &mut current_privacy.clipboard_blocked_apps
+            )),
+            "7" => list_to_edit = Some((
+                "Host Exclusions",
+                &mut current_privacy.exclude_hosts
+            )),
+            "8" => {
+                current_privacy.mode = match current_privacy.mode {
+                    PrivacyMode::Skip => PrivacyMode::Redact,
+                    PrivacyMode::Redact => PrivacyMode::Skip,
+                };
+                GLOBAL_CONFIG.write().unwrap().update_privacy_settings(current_privacy)?;
+            }
+            "9" => {
+                current_privacy.hash_excluded_folders = !current_privacy.hash_excluded_folders;
+                GLOBAL_CONFIG.write().unwrap().update_privacy_settings(current_privacy)?;
+            }
+            "10" => list_to_edit = Some((
+                "Sensitive Window Titles",
+                &mut current_privacy.sensitive_window_titles
+            )),
+            "11" => list_to_edit = Some((
+                "Clipboard Content Regexes",
+                &mut current_privacy.clipboard_excludes_regex
+            )),
             "0" => {
                 // Save the modified config before breaking
                 let mut config_guard = GLOBAL_CONFIG.write().unwrap();
@@ -309,4 +400,1362 @@ pub fn show_privacy_settings() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("Privacy settings saved!");
     Ok(())
-}
\ No newline at end of file
+}
+/// `jotx vault <ID>...`: encrypt entries into the vault, creating the vault key on first use.
+pub fn vault_entries(ids: &[i64]) -> Result<(), Box<dyn std::error::Error>> {
+    if ids.is_empty() {
+        return Err("Pass the ids of the entries to vault, or --list".into());
+    }
+
+    if !vault_exists() {
+        println!("{}", "No vault yet. Choose a passphrase to protect it.".yellow());
+        let passphrase = Password::new()
+            .with_prompt("New vault passphrase")
+            .with_confirmation("Confirm passphrase", "Passphrases don't match")
+            .interact()?;
+        create_vault_key(&passphrase)?;
+        println!("✅ Vault created");
+    }
+
+    let db = lock_or_recover("database", &USER_DB);
+
+    for &id in ids {
+        let Some(entry) = db.get_entry(id)? else {
+            eprintln!("❌ Entry #{} not found", id);
+            continue;
+        };
+
+        let ciphertext = seal(&entry.content)?;
+        if db.vault_entry(id, &ciphertext)? {
+            println!("🔒 Entry #{} moved to the vault", id);
+        } else {
+            println!("Entry #{} is already in the vault", id);
+        }
+    }
+
+    println!(
+        "{}",
+        "Tip: add a privacy exclusion (jotx privacy) so new runs of these commands aren't captured in plaintext.".dimmed()
+    );
+    Ok(())
+}
+
+/// `jotx vault --list`: show vaulted entries without decrypting them
+pub fn list_vault() -> Result<(), Box<dyn std::error::Error>> {
+    let db = lock_or_recover("database", &USER_DB);
+
+    let ids = db.vault_ids()?;
+    if ids.is_empty() {
+        println!("The vault is empty");
+        return Ok(());
+    }
+
+    let tz = QueryTimezone::from_config();
+    for id in ids {
+        if let Some(entry) = db.get_entry(id)? {
+            println!(
+                "{}  {:<9}  {}",
+                format!("#{}", id).yellow(),
+                entry.entry_type.to_string(),
+                tz.format_timestamp(entry.timestamp, "%Y-%m-%d %H:%M")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// `jotx unlock [ID]`: decrypt one or all vaulted entries after a passphrase prompt.
+/// With `restore`, the decrypted content is written back and the entry leaves the vault.
+pub fn unlock_vault(id: Option<i64>, restore: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if !vault_exists() {
+        return Err("No vault found. Add entries with `jotx vault <ID>`".into());
+    }
+
+    let db = lock_or_recover("database", &USER_DB);
+
+    let ids = match id {
+        Some(id) => vec![id],
+        None => db.vault_ids()?,
+    };
+    if ids.is_empty() {
+        println!("The vault is empty");
+        return Ok(());
+    }
+
+    let passphrase = Password::new().with_prompt("Vault passphrase").interact()?;
+    let key = VaultKey::unlock(&passphrase)?;
+
+    for id in ids {
+        let Some(ciphertext) = db.vault_ciphertext(id)? else {
+            eprintln!("❌ Entry #{} is not in the vault", id);
+            continue;
+        };
+
+        let content = key.open(&ciphertext)?;
+
+        if restore {
+            db.restore_from_vault(id, &content)?;
+            println!("🔓 Entry #{} restored: {}", id, content);
+        } else {
+            println!("{} {}", format!("#{}", id).yellow(), content);
+        }
+    }
+
+    Ok(())
+}
+
+/// `jotx privacy --audit`: what has been blocked (and, with `privacy.audit_redactions`,
+/// redacted), grouped by rule, plus the latest events. Only rule names, timestamps and
+/// (optionally) salted hashes are kept, never the content.
+pub fn show_privacy_audit() {
+    const RECENT_EVENTS: usize = 10;
+
+    let events = read_blocked_log();
+    if events.is_empty() {
+        println!("Nothing has been blocked yet");
+        return;
+    }
+
+    let tz = QueryTimezone::from_config();
+
+    let action = |redacted: bool| if redacted { "redacted" } else { "blocked" };
+
+    // (action, source, plugin, rule) -> (count, last seen)
+    let mut by_rule: HashMap<(&str, String, String, String), (usize, u64)> = HashMap::new();
+    for event in &events {
+        let key = (
+            action(event.redacted),
+            event.source.clone(),
+            event.plugin.clone(),
+            event.rule.clone().unwrap_or_else(|| "-".to_string()),
+        );
+        let stats = by_rule.entry(key).or_insert((0, 0));
+        stats.0 += 1;
+        stats.1 = stats.1.max(event.timestamp);
+    }
+
+    let mut rows: Vec<_> = by_rule.into_iter().collect();
+    rows.sort_by_key(|row| Reverse(row.1.0));
+
+    println!("{}", "╔════════════════════════════════════════╗".cyan());
+    println!("{}", "║        JotX Privacy Audit              ║".cyan());
+    println!("{}", "╚════════════════════════════════════════╝".cyan());
+    println!();
+    let redacted = events.iter().filter(|event| event.redacted).count();
+    println!(
+        "{} blocked and {} redacted captures\n",
+        (events.len() - redacted).to_string().yellow(),
+        redacted.to_string().yellow()
+    );
+
+    println!(
+        "{:>6}  {:<8}  {:<9}  {:<18}  {:<40}  LAST SEEN",
+        "COUNT", "ACTION", "SOURCE", "PLUGIN", "RULE"
+    );
+    for ((action, source, plugin, rule), (count, last_seen)) in rows {
+        println!(
+            "{:>6}  {:<8}  {:<9}  {:<18}  {:<40}  {}",
+            count,
+            action,
+            source,
+            plugin,
+            rule,
+            tz.format_timestamp(last_seen as i64, "%Y-%m-%d %H:%M")
+        );
+    }
+
+    println!("\nMost recent:");
+    for event in events.iter().rev().take(RECENT_EVENTS) {
+        let hash = event
+            .content_hash
+            .as_deref()
+            .map(|h| format!("  [{}]", &h[..h.len().min(12)]))
+            .unwrap_or_default();
+        println!(
+            "  {}  {:<8}  {:<9}  {}{}",
+            tz.format_timestamp(event.timestamp as i64, "%Y-%m-%d %H:%M:%S"),
+            action(event.redacted),
+            event.source,
+            event.rule.as_deref().unwrap_or(&event.plugin),
+            hash.dimmed()
+        );
+    }
+}
+
+/// `jotx doctor`: check the installation for problems, repairing them with `fix`.
+/// Returns false if any problem is left.
+pub fn run_doctor(fix: bool) -> bool {
+    println!("{}", "╔════════════════════════════════════════╗".cyan());
+    println!("{}", "║        JotX Doctor                     ║".cyan());
+    println!("{}", "╚════════════════════════════════════════╝".cyan());
+    println!();
+
+    match report_stale_embeddings() {
+        Ok(true) => println!("   Run {} to re-embed them", "jotx reindex".cyan()),
+        Ok(false) => {}
+        Err(e) => eprintln!("⚠️  Could not check embeddings: {}", e),
+    }
+
+    let home = std::env::var("HOME").unwrap_or_default();
+    let roots = vec![
+        PathBuf::from(home).join(".jotx"),
+        PathBuf::from(LOG_FILE),
+        PathBuf::from(ERR_FILE),
+    ];
+
+    let exposed = exposed_paths(&roots);
+    if exposed.is_empty() {
+        println!("✅ History files are only readable by you");
+        return true;
+    }
+
+    println!(
+        "⚠️  {} jotx files or folders are readable by other users:",
+        exposed.len().to_string().yellow()
+    );
+    for item in &exposed {
+        println!("   {:o}  {}", item.mode, item.path.display());
+    }
+
+    if !fix {
+        println!();
+        println!("Run {} to restrict them to your user", "jotx doctor --fix".cyan());
+        return false;
+    }
+
+    let mut ok = true;
+    for item in &exposed {
+        if let Err(e) = restrict(&item.path) {
+            eprintln!("❌ Failed to fix {}: {}", item.path.display(), e);
+            ok = false;
+        }
+    }
+
+    if ok {
+        println!("✅ Permissions fixed");
+    }
+    ok
+}
+
+/// `jotx pair [CODE]`: without a code, print this device's pairing code; with one,
+/// trust the device it belongs to. Both devices have to enter each other's code.
+pub fn pair_device(code: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let device = DeviceKey::load_or_create()?;
+
+    let Some(code) = code else {
+        println!("{}", "╔════════════════════════════════════════╗".cyan());
+        println!("{}", "║        JotX Device Pairing             ║".cyan());
+        println!("{}", "╚════════════════════════════════════════╝".cyan());
+        println!();
+        println!("This device: {}", device.device_id().yellow());
+        println!("Pairing code:");
+        println!("   {}", device.pairing_code().cyan());
+        println!();
+        println!("On the other device run {}", "jotx pair <CODE>".cyan());
+        return Ok(());
+    };
+
+    let peer = add_peer(code)?;
+    println!("✅ Paired with device {}", peer.device_id.yellow());
+    println!(
+        "   Finish by running {} on that device",
+        format!("jotx pair {}", device.pairing_code()).cyan()
+    );
+    Ok(())
+}
+
+/// `jotx pair --list`
+pub fn list_peers() {
+    let peers = load_peers();
+    if peers.is_empty() {
+        println!("No paired devices. Run {} to get started", "jotx pair".cyan());
+        return;
+    }
+
+    let tz = QueryTimezone::from_config();
+    for peer in peers {
+        println!(
+            "{}  paired {}",
+            peer.device_id.yellow(),
+            tz.format_timestamp(peer.paired_at, "%Y-%m-%d %H:%M").dimmed()
+        );
+    }
+}
+
+/// `jotx fts [--rebuild]`: show which tokenizer the full-text index uses, rebuilding
+/// it with the configured one on request
+pub fn fts_index(rebuild: bool, check: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let wanted = GLOBAL_CONFIG
+        .read()
+        .map(|config| config.search.fts_tokenizer)
+        .map_err(|_| "Failed to read config")?;
+
+    let mut db = lock_or_recover("database", &USER_DB);
+    let current = fts_tokenizer(&db.conn)?;
+
+    if rebuild {
+        println!("Rebuilding full-text index with {}...", wanted.as_sql().yellow());
+        let result = db.rebuild_fts(wanted)?;
+        if result.is_healthy() {
+            println!("✅ Indexed {} entries", result.entries);
+        } else {
+            print_fts_check(&result);
+            return Err("index does not match entries after rebuild".into());
+        }
+        return Ok(());
+    }
+
+    if check {
+        let result = db.repair_fts()?;
+        if result.is_healthy() {
+            println!("✅ All {} entries are indexed", result.entries);
+        } else {
+            print_fts_check(&result);
+            let repaired = db.check_fts()?;
+            if !repaired.is_healthy() {
+                return Err("index still does not match entries after re-indexing".into());
+            }
+            println!("✅ Re-indexed {} entries", repaired.entries);
+        }
+        return Ok(());
+    }
+
+    println!("Full-text index tokenizer: {}", current.as_sql().yellow());
+    if current != wanted {
+        println!(
+            "⚠️  search.fts_tokenizer is {}; run {} to apply it",
+            wanted.as_sql().yellow(),
+            "jotx fts --rebuild".cyan()
+        );
+    }
+    Ok(())
+}
+
+fn print_fts_check(check: &FtsCheck) {
+    println!("⚠️  Full-text index is out of sync with {} entries:", check.entries);
+    if check.missing > 0 {
+        println!("  - {} entries missing from the index", check.missing);
+    }
+    if check.orphaned > 0 {
+        println!("  - {} index rows for deleted entries", check.orphaned);
+    }
+    if check.corrupt {
+        println!("  - indexed terms don't match stored content");
+    }
+}
+
+/// `jotx export`: write history to `output`, or stream it to stdout so it can be
+/// piped. `entry_type`, `since` and `until` narrow it down; the days are inclusive.
+pub fn export(
+    format: &str,
+    output: Option<&str>,
+    entry_type: Option<&str>,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format_kind = ExportFormat::from_str(format)?;
+    let entry_type = entry_type.map(EntryType::from_str).transpose()?;
+    // Atuin only imports shell history
+    if format_kind == ExportFormat::Atuin && entry_type == Some(EntryType::Clipboard) {
+        return Err("The atuin format only holds shell history".into());
+    }
+
+    let tz = QueryTimezone::from_config();
+    let filters = HistoryFilters {
+        entry_type: match format_kind {
+            ExportFormat::Atuin => Some(EntryType::Shell.to_string()),
+            _ => entry_type.map(|t| t.to_string()),
+        },
+        since: since
+            .map(|date| day_range(Some(date), &tz))
+            .transpose()?
+            .map(|(_, start, _)| start),
+        until: until
+            .map(|date| day_range(Some(date), &tz))
+            .transpose()?
+            .map(|(_, _, end)| end),
+        ..Default::default()
+    };
+
+    let Some(output) = output else {
+        let mut stdout = io::BufWriter::new(io::stdout().lock());
+        write_history(format_kind, filters, &mut stdout)?;
+        stdout.flush()?;
+        return Ok(());
+    };
+
+    let result = export_history(format, filters, Some(output.to_string()))?;
+    eprintln!("✅ Exported {} entries to {}", result.count, output);
+    if format_kind == ExportFormat::Atuin {
+        eprintln!("Import with: {}", format!("HISTFILE={} atuin import zsh", output).cyan());
+    }
+    Ok(())
+}
+
+/// Commands written per transaction by `jotx import`
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// `jotx import --from <SOURCE>`: add another tool's history. Commands go through the
+/// same plugins and case setting as captured ones.
+pub fn import_history(from: &str, path: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let source = ImportSource::from_str(from)?;
+    let path = path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| source.default_path());
+    let runs = read_history(source, &path)?;
+    let total = runs.len();
+
+    let case_sensitive = lock_or_recover("settings", &GLOBAL_SETTINGS).shell_case_sensitive;
+    let plugins = lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER);
+    let mut kept = Vec::with_capacity(total);
+    for mut run in runs {
+        if is_own_command(&run.content) {
+            continue;
+        }
+
+        let mut context = CommandContext {
+            command: run.content.clone(),
+            working_dir: run.working_dir.clone().unwrap_or_default(),
+            user: run.user.clone().unwrap_or_default(),
+            host: run.host.clone().unwrap_or_default(),
+            timestamp: run.timestamp,
+        };
+        if !plugins.trigger_command_captured(&mut context) {
+            continue;
+        }
+
+        run.content = if case_sensitive {
+            normalize(&context.command)
+        } else {
+            lowercase_redacted(&context.command)
+        };
+        kept.push(run);
+    }
+    drop(plugins);
+
+    let skipped = total - kept.len();
+    let commands = merge_runs(kept);
+    if commands.is_empty() {
+        println!("Nothing to import from {}", path.display());
+        return Ok(());
+    }
+
+    println!(
+        "Importing {} runs of {} commands from {}...",
+        total - skipped,
+        commands.len(),
+        path.display()
+    );
+    let mut added = 0;
+    let mut done = 0;
+    for batch in commands.chunks(IMPORT_BATCH_SIZE) {
+        added += DB_WRITER.import_shell(batch.to_vec())?;
+        done += batch.len();
+        print!("\r  {}/{}", done, commands.len());
+        let _ = io::stdout().flush();
+    }
+    println!();
+
+    println!(
+        "✅ {} new commands, {} already in history",
+        added,
+        commands.len() - added
+    );
+    if skipped > 0 {
+        println!(
+            "   Skipped {} runs of jotx itself or blocked by plugins",
+            skipped
+        );
+    }
+    if added > 0 {
+        println!(
+            "Run {} to make them searchable by meaning",
+            "jotx reindex".cyan()
+        );
+    }
+    Ok(())
+}
+
+/// `jotx backup`: snapshot the database and config into ~/.jotx/backups, keeping
+/// `keep` (or `storage.backups_to_keep`) of them. With `list`, show the backups instead.
+pub fn backup(list: bool, keep: Option<usize>) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = backups_dir();
+
+    if list {
+        let backups = list_backups(&dir)?;
+        if backups.is_empty() {
+            println!("No backups yet. Take one with `jotx backup`");
+        }
+        for path in backups {
+            let size = fs::metadata(&path)
+                .map(|meta| meta.len())
+                .unwrap_or_default();
+            println!("{}  {}", path.display(), format_size(size).dimmed());
+        }
+        return Ok(());
+    }
+
+    let keep = keep.unwrap_or_else(backups_to_keep);
+    let path = create_backup(&get_db_path(), &get_config_path(), &dir, keep)?;
+    let size = fs::metadata(&path)
+        .map(|meta| meta.len())
+        .unwrap_or_default();
+    println!("✅ Backed up to {} ({})", path.display(), format_size(size));
+    Ok(())
+}
+
+fn backups_to_keep() -> usize {
+    match GLOBAL_CONFIG.read() {
+        Ok(config) => config.storage.backups_to_keep,
+        Err(_) => Config::default().storage.backups_to_keep,
+    }
+}
+
+/// `jotx restore <FILE>`: check a backup, save the current state as a new backup, then
+/// swap the snapshot in with capture paused and the daemon stopped. The daemon is
+/// started again afterwards if it was running.
+pub fn restore_backup(file: &str, yes: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = backups_dir();
+    // A bare name refers to a file in the backups directory
+    let archive = match PathBuf::from(file) {
+        path if path.exists() => path,
+        _ => dir.join(file),
+    };
+    if !archive.exists() {
+        return Err(format!("No backup at {} (see jotx backup --list)", file).into());
+    }
+
+    let staging = dir.join(format!(".restore-{}", std::process::id()));
+    let result = restore_from(&archive, &staging, &dir, yes);
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn restore_from(
+    archive: &Path,
+    staging: &Path,
+    dir: &Path,
+    yes: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let snapshot = unpack_backup(archive, staging)?;
+    println!(
+        "{} holds {} entries{}",
+        archive.display(),
+        snapshot.entries,
+        if snapshot.config.is_some() {
+            " and a config"
+        } else {
+            ""
+        }
+    );
+    if !yes
+        && !Confirm::new()
+            .with_prompt("Replace the current history with it?")
+            .default(false)
+            .interact()?
+    {
+        println!("❌ Cancelled");
+        return Ok(());
+    }
+
+    let db_path = get_db_path();
+    let config_path = get_config_path();
+    if db_path.exists() {
+        // One more than usual, so the backup being restored isn't rotated away
+        match create_backup(&db_path, &config_path, dir, backups_to_keep() + 1) {
+            Ok(path) => println!("Current state saved to {}", path.display()),
+            Err(e) => eprintln!("⚠️  Could not back up the current database: {}", e),
+        }
+    }
+
+    let was_paused = is_paused();
+    let daemon_was_running = is_running();
+    // Keep a pause the user started, and when it ends
+    if !was_paused {
+        set_paused(true)?;
+    }
+    if daemon_was_running {
+        println!("Stopping the daemon...");
+        stop_daemon();
+    }
+
+    let swapped = swap_in(&snapshot, &db_path, &config_path);
+
+    if !was_paused {
+        set_paused(false)?;
+    }
+    if daemon_was_running {
+        start_daemon(&std::env::current_exe()?)?;
+        println!("Daemon restarted");
+    }
+    swapped?;
+
+    println!("✅ Restored {}", archive.display());
+    Ok(())
+}
+
+fn service_manager() -> Result<ServiceManager, Box<dyn std::error::Error>> {
+    ServiceManager::current()
+        .ok_or_else(|| "jotx service needs systemd (Linux) or launchd (macOS)".into())
+}
+
+/// `jotx service install`: run the daemon under systemd or launchd from now on
+pub fn install_service() -> Result<(), Box<dyn std::error::Error>> {
+    let manager = service_manager()?;
+    let path = manager.install(&std::env::current_exe()?)?;
+    println!("✅ Installed {}", path.display());
+    println!("   jotx now starts on login and is restarted if it crashes");
+    if manager == ServiceManager::Systemd {
+        println!("   Its output also goes to {}", "journalctl --user -u jotx".cyan());
+    }
+    Ok(())
+}
+
+/// `jotx service uninstall`: stop the service and remove it
+pub fn uninstall_service() -> Result<(), Box<dyn std::error::Error>> {
+    match service_manager()?.uninstall()? {
+        Some(path) => println!("✅ Removed {}", path.display()),
+        None => println!("The jotx service is not installed"),
+    }
+    Ok(())
+}
+
+/// `jotx service status`: whether the service is installed, enabled and running
+pub fn service_status() -> Result<(), Box<dyn std::error::Error>> {
+    let status = service_manager()?.status();
+    if !status.installed {
+        println!(
+            "⏹️ No {} service installed; run {} to add one",
+            status.manager.name(),
+            "jotx service install".cyan()
+        );
+        return Ok(());
+    }
+
+    println!("{} service: {}", status.manager.name(), status.path.display());
+    println!(
+        "   {}",
+        if status.enabled {
+            "✅ Starts on login"
+        } else {
+            "⚠️  Not enabled, won't start on login"
+        }
+    );
+    println!(
+        "   {}",
+        if status.running {
+            "✅ Running"
+        } else {
+            "⏹️ Stopped"
+        }
+    );
+    Ok(())
+}
+
+/// `jotx logs`: the last `lines` lines of the daemon's log; with `tail`, keep printing
+/// what it writes
+pub fn show_logs(lines: usize, tail: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let path = logs_dir().join(LOG_NAME);
+    if !path.exists() {
+        return Err(format!(
+            "No log at {} yet; the daemon writes it once started",
+            path.display()
+        )
+        .into());
+    }
+
+    for line in last_lines(&path, lines)? {
+        println!("{}", line);
+    }
+    if tail {
+        follow(&path, &mut std::io::stdout())?;
+    }
+    Ok(())
+}
+
+/// `jotx snippet save <ID> --name <NAME>`: turn a history entry into a snippet
+pub fn save_snippet(id: i64, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let name = normalize_snippet_name(name)?;
+    let db = lock_or_recover("database", &USER_DB);
+
+    let entry = db.get_entry(id)?.ok_or_else(|| format!("Entry #{} not found", id))?;
+    if db.is_vaulted(id)? {
+        return Err(format!("Entry #{} is in the vault; unlock it with --restore first", id).into());
+    }
+
+    let snippet = parameterize(&name, &entry.content, Some(id));
+    let replaced = db.get_snippet(&name)?.is_some();
+    db.save_snippet(&snippet)?;
+
+    println!(
+        "✅ Snippet {} {}: {}",
+        name.yellow(),
+        if replaced { "replaced" } else { "saved" },
+        snippet.template
+    );
+    for placeholder in &snippet.placeholders {
+        println!("  {{{{{}}}}} = {}", placeholder.name.cyan(), placeholder.default);
+    }
+    Ok(())
+}
+
+/// `jotx snippet run <NAME>`: ask for each placeholder (defaulting to the original
+/// value) and run the result in $SHELL, or just print it with `print_only`
+pub fn run_snippet(name: &str, print_only: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let name = normalize_snippet_name(name)?;
+    let snippet = lock_or_recover("database", &USER_DB)
+        .get_snippet(&name)?
+        .ok_or_else(|| format!("No snippet named '{}' (see jotx snippet list)", name))?;
+
+    let mut values = Vec::new();
+    for placeholder in &snippet.placeholders {
+        let value: String = Input::new()
+            .with_prompt(&placeholder.name)
+            .default(placeholder.default.clone())
+            .interact_text()?;
+        values.push((placeholder.name.clone(), value));
+    }
+    let command = fill(&snippet, &values);
+
+    if print_only {
+        print!("{}", command);
+        return Ok(());
+    }
+
+    if !snippet.placeholders.is_empty() {
+        println!("{}", command.cyan());
+        if !Confirm::new().with_prompt("Run it?").default(true).interact()? {
+            return Ok(());
+        }
+    }
+
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    let status = std::process::Command::new(shell).arg("-c").arg(&command).status()?;
+    if !status.success() {
+        return Err(format!("'{}' exited with {}", command, status).into());
+    }
+    Ok(())
+}
+
+/// `jotx snippet list`
+pub fn list_snippets() -> Result<(), Box<dyn std::error::Error>> {
+    let snippets = lock_or_recover("database", &USER_DB).list_snippets()?;
+    if snippets.is_empty() {
+        println!("No snippets yet. Save one with `jotx snippet save <ID> --name <NAME>`");
+        return Ok(());
+    }
+
+    let width = snippets.iter().map(|s| s.name.len()).max().unwrap_or(0);
+    for snippet in snippets {
+        println!("{:<width$}  {}", snippet.name.yellow(), snippet.template, width = width);
+    }
+    Ok(())
+}
+
+/// `jotx tag <ID> <TAG>`: add or remove a tag and print the entry's tags afterwards
+pub fn edit_tags(id: i64, tag: &str, remove: bool) -> Result<(), String> {
+    let tags = if remove {
+        untag_entry(id, tag)?
+    } else {
+        tag_entry(id, tag)?
+    };
+
+    if tags.is_empty() {
+        println!("✅ Entry #{} has no tags", id);
+    } else {
+        let labels: Vec<String> = tags.iter().map(|t| format!("#{}", t)).collect();
+        println!("✅ Entry #{}: {}", id, labels.join(" ").cyan());
+    }
+    Ok(())
+}
+
+/// `jotx note <TEXT>`
+pub fn note(text: &str) -> Result<(), String> {
+    let (id, embedded) = add_note(text, Some(&get_working_directory()))?;
+    println!("📝 Saved note #{}", id);
+    if !embedded {
+        println!("   Semantic search won't find it until `jotx reindex` can embed it");
+    }
+    Ok(())
+}
+
+/// `jotx pin <ID>`
+pub fn pin(id: i64, remove: bool) -> Result<(), String> {
+    pin_entry(id, !remove)?;
+    if remove {
+        println!("✅ Entry #{} unpinned", id);
+    } else {
+        println!("📌 Entry #{} pinned; cleanup will keep it", id);
+    }
+    Ok(())
+}
+
+/// `jotx tag`: every tag with the number of entries carrying it
+pub fn show_tags() -> Result<(), String> {
+    let tags = list_tags()?;
+    if tags.is_empty() {
+        println!("No tags yet. Add one with `jotx tag <ID> <TAG>`");
+        return Ok(());
+    }
+
+    let width = tags.iter().map(|t| t.tag.len() + 1).max().unwrap_or(0);
+    for tag in tags {
+        println!("{:<width$}  {}", format!("#{}", tag.tag).cyan(), tag.count, width = width);
+    }
+    Ok(())
+}
+
+/// `jotx plugin --list`
+pub fn show_plugins() {
+    let mut plugins = lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER).info();
+    if plugins.is_empty() {
+        println!("No plugins. Create one with `jotx plugin --create <NAME>`");
+        return;
+    }
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let width = plugins.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    for plugin in plugins {
+        let status = if plugin.enabled {
+            "enabled".green()
+        } else {
+            "disabled".red()
+        };
+        let version = if plugin.version.is_empty() {
+            "-"
+        } else {
+            &plugin.version
+        };
+        println!(
+            "{:<width$}  {:<8}  {:<8}  {}",
+            plugin.name.yellow(),
+            version,
+            plugin.kind,
+            status,
+            width = width
+        );
+    }
+}
+
+/// `jotx snippet delete <NAME>`
+pub fn delete_snippet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let name = normalize_snippet_name(name)?;
+    if !lock_or_recover("database", &USER_DB).delete_snippet(&name)? {
+        return Err(format!("No snippet named '{}'", name).into());
+    }
+    println!("🗑️  Snippet {} deleted", name.yellow());
+    Ok(())
+}
+
+/// Entries `jotx forget --interactive` offers to pick from
+/// Entries embedded per model call and database transaction by `jotx reindex`
+const REINDEX_BATCH_SIZE: usize = 64;
+
+const FORGET_PICK_LIMIT: usize = 200;
+/// Matches listed before `jotx forget` asks for confirmation
+const FORGET_PREVIEW_SIZE: usize = 10;
+
+/// `jotx forget`: delete entries by id, by content pattern and/or day range, or picked
+/// from a list (narrowed by the same filters). Asks before deleting unless `yes`.
+pub fn forget(
+    ids: &[i64],
+    pattern: Option<&str>,
+    since: Option<&str>,
+    until: Option<&str>,
+    interactive: bool,
+    yes: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tz = QueryTimezone::from_config();
+
+    let entries: Vec<Entry> = if !ids.is_empty() {
+        let db = lock_or_recover("database", &USER_DB);
+        let mut entries = Vec::new();
+        for &id in ids {
+            match db.get_entry(id)? {
+                Some(entry) => entries.push(entry),
+                None => eprintln!("⚠️  No entry with id {}", id),
+            }
+        }
+        entries
+    } else {
+        let params = QueryParams {
+            content_search: pattern.map(str::to_string),
+            // --since from the start of that day, --until through the end of it
+            since: since
+                .map(|date| day_range(Some(date), &tz))
+                .transpose()?
+                .map(|(_, start, _)| start),
+            until: until
+                .map(|date| day_range(Some(date), &tz))
+                .transpose()?
+                .map(|(_, _, end)| end),
+            limit: interactive.then_some(FORGET_PICK_LIMIT),
+            ..Default::default()
+        };
+        let filtered =
+            params.content_search.is_some() || params.since.is_some() || params.until.is_some();
+        if !filtered && !interactive {
+            return Err(
+                "Say what to forget: entry ids, --pattern, --since/--until or --interactive".into(),
+            );
+        }
+        lock_or_recover("database", &USER_DB).query_entries(&params)?
+    };
+
+    if entries.is_empty() {
+        println!("Nothing matched, nothing forgotten");
+        return Ok(());
+    }
+
+    let describe = |entry: &Entry| {
+        format!(
+            "#{} {}  {}",
+            entry.id,
+            tz.format_timestamp(entry.timestamp, "%Y-%m-%d %H:%M"),
+            entry.content.lines().next().unwrap_or_default()
+        )
+    };
+
+    let ids: Vec<i64> = if interactive {
+        let items: Vec<String> = entries.iter().map(describe).collect();
+        let picked = MultiSelect::new()
+            .with_prompt("Space to pick entries, Enter to forget them")
+            .items(&items)
+            .interact_on_opt(&Term::stderr())?;
+        match picked {
+            Some(picked) if !picked.is_empty() => {
+                picked.into_iter().map(|i| entries[i].id).collect()
+            }
+            _ => {
+                println!("Nothing forgotten");
+                return Ok(());
+            }
+        }
+    } else {
+        for entry in entries.iter().take(FORGET_PREVIEW_SIZE) {
+            println!("  {}", describe(entry));
+        }
+        if entries.len() > FORGET_PREVIEW_SIZE {
+            println!("  … and {} more", entries.len() - FORGET_PREVIEW_SIZE);
+        }
+        let confirmed = yes
+            || Confirm::new()
+                .with_prompt(format!("Forget {} entries? This cannot be undone", entries.len()))
+                .default(false)
+                .interact()?;
+        if !confirmed {
+            println!("Nothing forgotten");
+            return Ok(());
+        }
+        entries.iter().map(|entry| entry.id).collect()
+    };
+
+    let deleted = lock_or_recover("database", &USER_DB).delete_entries(&ids)?;
+    println!("🗑️  Forgot {} entries", deleted);
+    Ok(())
+}
+
+/// `jotx encrypt`: convert jotx.db to SQLCipher with a key kept in the OS keyring, or
+/// back to plaintext with `decrypt`. The daemon must not be running meanwhile.
+pub fn encrypt_db(decrypt: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if is_running() {
+        return Err("Stop the daemon first (jotx exit), then run this again".into());
+    }
+
+    let encrypted = encryption_enabled();
+    if encrypted != decrypt {
+        return Err(if decrypt {
+            "The database is not encrypted"
+        } else {
+            "The database is already encrypted"
+        }
+        .into());
+    }
+
+    let path = get_db_path();
+    if decrypt {
+        decrypt_database(&path)?;
+    } else {
+        encrypt_database(&path)?;
+    }
+
+    if let Err(e) = GLOBAL_CONFIG.write().unwrap().set_encrypt(!decrypt) {
+        return Err(format!(
+            "{} was converted but config.toml could not be updated ({}); set encrypt = {} under [storage] by hand",
+            path.display(),
+            e,
+            !decrypt
+        )
+        .into());
+    }
+
+    if decrypt {
+        println!("🔓 {} is plaintext again and its key was removed", path.display());
+    } else {
+        println!("🔐 {} is encrypted; the key is in your OS keyring", path.display());
+    }
+    Ok(())
+}
+
+/// Embed entries that have no embedding (or one of the wrong size), or every entry with
+/// `all`, then rebuild the vector index from the stored embeddings
+pub fn reindex(all: bool) -> Result<(), Box<dyn std::error::Error>> {
+    report_stale_embeddings()?;
+
+    let total = lock_or_recover("database", &USER_DB).count_entries_to_embed(all)?;
+    if total == 0 {
+        println!("✅ Every entry already has an embedding");
+    } else {
+        println!("Embedding {} entries...", total);
+    }
+
+    let mut done = 0;
+    let mut failed = 0;
+    let mut after_id = 0;
+    loop {
+        // Hold the database only between model calls, so the daemon can keep writing
+        let batch = lock_or_recover("database", &USER_DB).entries_to_embed(
+            all,
+            after_id,
+            REINDEX_BATCH_SIZE,
+        )?;
+        let Some(&(last_id, _)) = batch.last() else {
+            break;
+        };
+        after_id = last_id;
+
+        let texts: Vec<String> = batch.iter().map(|(_, content)| content.clone()).collect();
+        match generate_embeddings(&texts) {
+            Ok(embeddings) => {
+                let rows: Vec<(i64, Vec<f32>)> =
+                    batch.iter().map(|(id, _)| *id).zip(embeddings).collect();
+                lock_or_recover("database", &USER_DB).set_embeddings(&rows)?;
+                done += rows.len();
+            }
+            Err(e) => {
+                eprintln!("⚠️  Failed to embed entries up to #{}: {}", last_id, e);
+                failed += batch.len();
+            }
+        }
+
+        print!("\r  {}/{}", done + failed, total);
+        let _ = io::stdout().flush();
+    }
+    if total > 0 {
+        println!();
+    }
+
+    match lock_or_recover("database", &USER_DB).rebuild_vec_index()? {
+        Some(indexed) => println!(
+            "✅ Embedded {} entries; {} in the vector index",
+            done, indexed
+        ),
+        None => println!(
+            "✅ Embedded {} entries (sqlite-vec is unavailable, so search scans them directly)",
+            done
+        ),
+    }
+
+    if failed > 0 {
+        return Err(format!("{} entries could not be embedded", failed).into());
+    }
+    Ok(())
+}
+
+/// Warn about stored embeddings whose size doesn't match `embeddings.dimension`, as
+/// left behind by a change of embedding model. Returns whether there were any.
+fn report_stale_embeddings() -> Result<bool, Box<dyn std::error::Error>> {
+    let dimension = embedding_dimension();
+    let stale: Vec<(usize, usize)> = lock_or_recover("database", &USER_DB)
+        .embedding_dimensions()?
+        .into_iter()
+        .filter(|&(size, _)| size != dimension)
+        .collect();
+
+    for (size, count) in &stale {
+        println!(
+            "⚠️  {} entries have {}-float embeddings from another model (expected {})",
+            count.to_string().yellow(),
+            size,
+            dimension
+        );
+    }
+    Ok(!stale.is_empty())
+}
+
+/// `jotx stats`
+pub fn show_stats(days: i64, top: usize, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let stats = lock_or_recover("database", &USER_DB).get_stats(days, top)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    println!("{}", "╔════════════════════════════════════════╗".cyan());
+    println!("{}", "║        JotX Stats                      ║".cyan());
+    println!("{}", "╚════════════════════════════════════════╝".cyan());
+    println!();
+
+    println!(
+        "{} entries, {} on disk",
+        stats.total_entries.to_string().yellow(),
+        format_size(stats.db_size_bytes)
+    );
+    for count in &stats.counts_by_type {
+        println!("   {:<10} {}", count.entry_type, count.count);
+    }
+
+    let section = |title: &str| {
+        println!();
+        println!("{}", title.bold());
+    };
+
+    section("Most run commands");
+    for (i, command) in stats.top_commands.iter().enumerate() {
+        println!(
+            "  {:>2}. {:>6}×  {}",
+            i + 1,
+            command.times_run,
+            command.command.lines().next().unwrap_or_default()
+        );
+    }
+
+    section("Busiest directories");
+    for place in &stats.top_directories {
+        println!("  {:>6}  {}", place.times_run, place.name);
+    }
+
+    if stats.top_hosts.len() > 1 {
+        section("Busiest hosts");
+        for place in &stats.top_hosts {
+            println!("  {:>6}  {}", place.times_run, place.name);
+        }
+    }
+
+    section(&format!("Captures per weekday (last {} days)", days));
+    let busiest = stats
+        .weekday_captures
+        .iter()
+        .map(|day| day.count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for day in &stats.weekday_captures {
+        let bar = "█".repeat((day.count * 30).div_ceil(busiest));
+        println!("  {}  {:>6}  {}", day.weekday, day.count, bar.green());
+    }
+
+    section("Sessions");
+    println!(
+        "  {} sessions, {:.1} commands each on average, {} command links",
+        stats.sessions.sessions, stats.sessions.average_length, stats.sessions.associations
+    );
+
+    section("Query cache");
+    println!(
+        "  {} cached queries, {} hits / {} misses ({:.0}% hit rate)",
+        stats.cache.entries,
+        stats.cache.hits,
+        stats.cache.misses,
+        stats.cache.hit_rate * 100.0
+    );
+
+    if stats.dropped_captures > 0 {
+        println!();
+        println!(
+            "⚠️  {} captures were dropped because the database writer fell behind",
+            stats.dropped_captures.to_string().yellow()
+        );
+    }
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// `jotx next`: the commands usually run after `command`, or after the last captured one
+pub fn next_commands(
+    command: Option<&str>,
+    limit: usize,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = lock_or_recover("database", &USER_DB);
+    let command = match command {
+        Some(command) => command.trim().to_string(),
+        None => db
+            .last_shell_command()?
+            .ok_or("No shell commands captured yet")?,
+    };
+    let related = db.get_related_commands(&command, limit)?;
+    drop(db);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&related)?);
+        return Ok(());
+    }
+
+    if related.is_empty() {
+        println!("Nothing usually follows {}", command.cyan());
+        return Ok(());
+    }
+
+    let tz = QueryTimezone::from_config();
+    println!("After {} you usually run:", command.cyan());
+    for (i, next) in related.iter().enumerate() {
+        println!(
+            "  {:>2}. {}  {}",
+            i + 1,
+            next.content.lines().next().unwrap_or_default(),
+            format!(
+                "({}×, last {})",
+                next.strength,
+                tz.format_timestamp(next.last_seen, "%Y-%m-%d")
+            )
+            .dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Workflows shown by `jotx workflows` and numbered for `jotx workflows run`
+const MAX_WORKFLOWS: usize = 10;
+
+fn find_workflows(days: i64, min_runs: usize) -> Result<Vec<Workflow>, Box<dyn std::error::Error>> {
+    let since = chrono::Utc::now().timestamp() - days * 86_400;
+    let sessions = lock_or_recover("database", &USER_DB).session_commands(since)?;
+    Ok(mine_workflows(&sessions, min_runs, MAX_WORKFLOWS))
+}
+
+/// `jotx workflows`: command sequences you keep running together
+pub fn list_workflows(
+    days: i64,
+    min_runs: usize,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let workflows = find_workflows(days, min_runs)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&workflows)?);
+        return Ok(());
+    }
+
+    if workflows.is_empty() {
+        println!(
+            "No command sequence was run in {} or more sessions in the last {} days",
+            min_runs, days
+        );
+        return Ok(());
+    }
+
+    for (i, workflow) in workflows.iter().enumerate() {
+        println!(
+            "{:>2}. {}",
+            i + 1,
+            format!("({} sessions)", workflow.sessions).dimmed()
+        );
+        for step in &workflow.steps {
+            println!("      {}", step.lines().next().unwrap_or_default());
+        }
+    }
+    println!();
+    println!("Replay one with `jotx workflows run <N>`");
+    Ok(())
+}
+
+/// `jotx workflows run <N>`: go through a workflow's steps, asking before each one
+pub fn run_workflow(
+    number: usize,
+    days: i64,
+    min_runs: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let workflows = find_workflows(days, min_runs)?;
+    let workflow = number
+        .checked_sub(1)
+        .and_then(|i| workflows.get(i))
+        .ok_or_else(|| format!("No workflow {} (see jotx workflows)", number))?;
+
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    let total = workflow.steps.len();
+    for (i, step) in workflow.steps.iter().enumerate() {
+        println!();
+        println!(
+            "{} {}",
+            format!("[{}/{}]", i + 1, total).dimmed(),
+            step.cyan()
+        );
+
+        let choice = Select::new()
+            .items(["Run", "Edit and run", "Skip", "Stop"])
+            .default(0)
+            .interact()?;
+        let command = match choice {
+            0 => step.clone(),
+            1 => Input::new()
+                .with_prompt("Command")
+                .with_initial_text(step)
+                .interact_text()?,
+            2 => continue,
+            _ => return Ok(()),
+        };
+
+        let status = std::process::Command::new(&shell)
+            .arg("-c")
+            .arg(&command)
+            .status()?;
+        if !status.success()
+            && i + 1 < total
+            && !Confirm::new()
+                .with_prompt(format!("'{}' exited with {}. Continue?", command, status))
+                .default(false)
+                .interact()?
+        {
+            return Ok(());
+        }
+    }
+    println!();
+    println!("✅ Workflow done");
+    Ok(())
+}
+
+/// `jotx pause [DURATION]`: stop capturing until the duration has passed, or until
+/// `jotx resume`. The daemon and shell hooks pick it up without a restart.
+pub fn pause_capture(duration: Option<&str>) -> Result<(), String> {
+    let end = match duration {
+        Some(duration) => Some(chrono::Utc::now() + parse_pause_duration(duration)?),
+        None => None,
+    };
+    pause_until(end).map_err(|e| format!("Failed to pause capture: {}", e))?;
+
+    match end {
+        Some(end) => println!(
+            "⏸️  Capture paused until {} (`jotx resume` to start again sooner)",
+            end.with_timezone(&chrono::Local).format("%H:%M")
+        ),
+        None => println!("⏸️  Capture paused until `jotx resume`"),
+    }
+    Ok(())
+}
+
+/// `jotx resume`: end a pause started with `jotx pause`
+pub fn resume_capture() -> Result<(), String> {
+    if !is_paused() {
+        println!("Capture isn't paused");
+        return Ok(());
+    }
+    set_paused(false).map_err(|e| format!("Failed to resume capture: {}", e))?;
+    println!("▶️  Capture resumed");
+    Ok(())
+}
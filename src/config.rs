@@ -1,8 +1,13 @@
 use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::RwLock;
+use tracing::warn;
+
+use crate::permissions::{create_private_dir, write_private};
+use crate::privacy::glob_match;
 
 // The main config struct - mirrors your TOML file structure
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -11,14 +16,31 @@ pub struct Config {
     pub search: SearchConfig,
     pub storage: StorageConfig,
     pub privacy: PrivacyConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    #[serde(default)]
+    pub remote: RemoteConfig,
+    #[serde(default)]
+    pub notes: NotesConfig,
+    #[serde(default)]
+    pub embeddings: EmbeddingsConfig,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LlmConfig {
-    pub provider: String, // "ollama", "openai", "anthropic"
+    pub provider: String, // "ollama", "openai", "anthropic", "llamacpp"
     pub api_key: Option<String>,
     pub api_base: Option<String>,
     pub model: String,
+    /// GGUF file for the "llamacpp" provider
+    #[serde(default)]
+    pub model_path: Option<String>,
     pub max_tokens: u32,
     pub temperature: f32,
     pub max_history_results: usize,
@@ -31,6 +53,110 @@ pub struct PrivacyConfig {
     pub excludes_ends_with_string: Vec<String>,
     pub excludes_regex: Vec<String>,
     pub exclude_folders: Vec<String>,
+    /// Commands captured on matching machines are never stored. Entries are `host` or
+    /// `user@host` globs, e.g. "prod-*" or "root@*"
+    #[serde(default)]
+    pub exclude_hosts: Vec<String>,
+    /// Store commands from `exclude_folders` as a salted hash instead of skipping them, so
+    /// frequency and session statistics still count them without keeping the text
+    #[serde(default)]
+    pub hash_excluded_folders: bool,
+    /// Block captures matching the built-in known-secret patterns (API keys, tokens, private keys...)
+    #[serde(default = "default_true")]
+    pub builtin_secret_patterns: bool,
+    /// Ids of built-in secret patterns to ignore, e.g. ["jwt"] or ["high-entropy"]
+    #[serde(default)]
+    pub disabled_secret_patterns: Vec<String>,
+    /// Bits of entropy per character above which a long random-looking token is treated
+    /// as a secret (the "high-entropy" pattern); 0 turns the check off
+    #[serde(default = "default_secret_entropy_threshold")]
+    pub secret_entropy_threshold: f64,
+    #[serde(default)]
+    pub mode: PrivacyMode,
+    /// Store a salted hash of blocked content in the audit log, so repeated blocks of the
+    /// same command can be told apart without keeping the command itself
+    #[serde(default)]
+    pub audit_hash_content: bool,
+    /// Also log captures that were stored redacted or hashed, not only the dropped ones
+    #[serde(default)]
+    pub audit_redactions: bool,
+    /// Apps (window owner names, case-insensitive substring) whose clipboard is never captured
+    #[serde(default = "default_clipboard_blocked_apps")]
+    pub clipboard_blocked_apps: Vec<String>,
+    /// Window titles (case-insensitive substring) that mark private browsing or other
+    /// secret-bearing windows; clips copied while one is focused are not captured
+    #[serde(default = "default_sensitive_window_titles")]
+    pub sensitive_window_titles: Vec<String>,
+    /// Clips whose content matches one of these regexes are not captured. The
+    /// `excludes_*` rules above only apply to shell commands.
+    #[serde(default)]
+    pub clipboard_excludes_regex: Vec<String>,
+    /// Periods during which nothing is captured, checked by the daemon
+    #[serde(default)]
+    pub quiet_windows: Vec<QuietWindow>,
+}
+
+/// A do-not-capture rule. Every condition that is set must hold, e.g. weekdays
+/// 09:00-10:00, or "while Zoom is focused".
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct QuietWindow {
+    /// "mon".."sun", "weekdays" or "weekends"; empty means every day
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// "HH:MM" in `search.timezone`; a window may wrap past midnight (22:00-06:00)
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// Focused app name (case-insensitive substring)
+    pub app: Option<String>,
+}
+
+fn default_clipboard_blocked_apps() -> Vec<String> {
+    ["KeePassXC", "KeePass", "Bitwarden", "1Password", "LastPass", "Dashlane", "Keychain Access"]
+        .iter()
+        .map(|app| app.to_string())
+        .collect()
+}
+
+fn default_secret_entropy_threshold() -> f64 {
+    4.3
+}
+
+fn default_sensitive_window_titles() -> Vec<String> {
+    [
+        "(Incognito)",
+        "Private Browsing",
+        "InPrivate",
+        "Private Window",
+        "Tor Browser",
+        "Password Manager",
+    ]
+    .iter()
+    .map(|title| title.to_string())
+    .collect()
+}
+
+/// What happens to a capture that matches a privacy rule
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyMode {
+    /// Drop the whole entry
+    #[default]
+    Skip,
+    /// Replace secrets and `excludes_contains_string` matches with `•••REDACTED•••` and
+    /// store the rest. Folder, regex, starts/ends-with rules still skip the entry.
+    Redact,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_embedding_model() -> String {
+    "all-MiniLM-L6-v2".to_string()
+}
+
+fn default_embedding_dimension() -> usize {
+    384
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -38,11 +164,263 @@ pub struct SearchConfig {
     pub similarity_threshold: f32,
     pub max_results: usize,
     pub fuzzy_matching: bool,
+    /// "local", "utc" or an IANA name (e.g. "Europe/Berlin") used for day boundaries and display
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Tokenizer for the full-text index. "trigram" finds any substring (e.g. "stal" in
+    /// "pip install") at the cost of a larger index; apply a change with `jotx fts --rebuild`
+    #[serde(default)]
+    pub fts_tokenizer: FtsTokenizer,
+    /// Hours after which an entry's recency boost halves; 0 disables recency ranking
+    #[serde(default = "default_recency_half_life_hours")]
+    pub recency_half_life_hours: f64,
+    /// Leave commands that exited non-zero out of search results
+    #[serde(default)]
+    pub hide_failed: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FtsTokenizer {
+    /// Whole words, matched by prefix
+    #[default]
+    Unicode61,
+    /// Any run of 3+ characters
+    Trigram,
+}
+
+impl FtsTokenizer {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            FtsTokenizer::Unicode61 => "unicode61",
+            FtsTokenizer::Trigram => "trigram",
+        }
+    }
+}
+
+fn default_timezone() -> String {
+    "local".to_string()
+}
+
+fn default_recency_half_life_hours() -> f64 {
+    72.0
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct StorageConfig {
     pub maintenance_interval_days: u64,
+    /// Seconds of inactivity after which a new command session is started
+    #[serde(default = "default_session_timeout_secs")]
+    pub session_timeout_secs: i64,
+    /// How many previous commands in a session get associated with a new one
+    #[serde(default = "default_association_depth")]
+    pub association_depth: i64,
+    /// jotx.db is encrypted with SQLCipher; switched by `jotx encrypt`, not by hand
+    #[serde(default)]
+    pub encrypt: bool,
+    /// Snapshots `jotx backup` keeps in ~/.jotx/backups before deleting the oldest
+    #[serde(default = "default_backups_to_keep")]
+    pub backups_to_keep: usize,
+}
+
+/// Poll intervals for the background daemon loops
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DaemonConfig {
+    #[serde(default = "default_clipboard_poll_secs")]
+    pub clipboard_poll_secs: u64,
+    #[serde(default = "default_shell_scan_interval_secs")]
+    pub shell_scan_interval_secs: u64,
+    #[serde(default = "default_app_loop_secs")]
+    pub app_loop_secs: u64,
+}
+
+/// The daemon's log in ~/.jotx/logs (see `jotx logs`). Read when the daemon starts.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LoggingConfig {
+    /// "error", "warn", "info", "debug" or "trace"
+    #[serde(default = "default_log_level")]
+    pub level: String,
+    /// The log is rotated each day and once it grows past this size
+    #[serde(default = "default_log_max_file_mb")]
+    pub max_file_mb: u64,
+    /// Rotated logs kept besides the current one
+    #[serde(default = "default_log_keep_files")]
+    pub keep_files: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct PluginsConfig {
+    /// Plugins that aren't loaded (built-in ones stay loaded but are skipped)
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    /// Hosts Rhai plugins may reach with `http_get`/`http_post`, subdomains included.
+    /// Empty means none.
+    #[serde(default)]
+    pub http_allowlist: Vec<String>,
+}
+
+/// Who may reach the HTTP API, MCP and D-Bus surfaces (see `remote::check_bind`)
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct RemoteConfig {
+    /// Non-loopback addresses the daemon may listen on, e.g. ["192.168.1.20"].
+    /// Localhost is always allowed.
+    #[serde(default)]
+    pub allowed_interfaces: Vec<String>,
+    /// Token clients must present; required for any address in `allowed_interfaces`
+    pub token: Option<String>,
+    /// Address the daemon serves the HTTP API on, e.g. "127.0.0.1:7311". Off when unset.
+    #[serde(default)]
+    pub api_listen: Option<String>,
+}
+
+/// Markdown notes written into an Obsidian (or any notes) vault by `jotx notes`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NotesConfig {
+    /// Folder inside the vault to write notes to. Off when unset.
+    #[serde(default)]
+    pub dir: Option<String>,
+    #[serde(default)]
+    pub layout: NotesLayout,
+    /// How many days back each export rewrites
+    #[serde(default = "default_notes_days")]
+    pub days: i64,
+    /// Also export during daemon maintenance
+    #[serde(default)]
+    pub on_maintenance: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NotesLayout {
+    /// One note per day, `YYYY-MM-DD.md`
+    #[default]
+    Day,
+    /// One note per project per day, `<project>/YYYY-MM-DD.md`, linking back to the day
+    Project,
+}
+
+/// The local model that embeds entries for semantic search
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmbeddingsConfig {
+    /// A fastembed model, e.g. "all-MiniLM-L6-v2" or "BAAI/bge-small-en-v1.5"
+    #[serde(default = "default_embedding_model")]
+    pub model: String,
+    /// Floats per embedding; must match the model. The vector index is built with it.
+    #[serde(default = "default_embedding_dimension")]
+    pub dimension: usize,
+    /// Where models are downloaded to, ~/.jotx/models when unset
+    #[serde(default)]
+    pub cache_dir: Option<String>,
+}
+
+/// An endpoint that gets a JSON POST for daemon events (see `webhooks::notify`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Events to send; every event when empty
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    /// Signs each body as `X-Jotx-Signature: sha256=<hex HMAC-SHA256>`
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A privacy rule or plugin kept a capture out of the database
+    CaptureBlocked,
+    /// The previous day's totals, sent after midnight (in `search.timezone`)
+    DailySummary,
+    /// The daemon started after the previous one died without shutting down
+    DaemonRestarted,
+}
+
+impl WebhookConfig {
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+impl Default for NotesConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            layout: NotesLayout::default(),
+            days: default_notes_days(),
+            on_maintenance: false,
+        }
+    }
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            model: default_embedding_model(),
+            dimension: default_embedding_dimension(),
+            cache_dir: None,
+        }
+    }
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            clipboard_poll_secs: default_clipboard_poll_secs(),
+            shell_scan_interval_secs: default_shell_scan_interval_secs(),
+            app_loop_secs: default_app_loop_secs(),
+        }
+    }
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: default_log_level(),
+            max_file_mb: default_log_max_file_mb(),
+            keep_files: default_log_keep_files(),
+        }
+    }
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_log_max_file_mb() -> u64 {
+    10
+}
+
+fn default_log_keep_files() -> usize {
+    7
+}
+
+fn default_clipboard_poll_secs() -> u64 {
+    1
+}
+
+fn default_shell_scan_interval_secs() -> u64 {
+    3600 // 1 hour
+}
+
+fn default_app_loop_secs() -> u64 {
+    10
+}
+
+fn default_notes_days() -> i64 {
+    7
+}
+
+fn default_session_timeout_secs() -> i64 {
+    300 // 5 minutes
+}
+
+fn default_association_depth() -> i64 {
+    3
+}
+
+fn default_backups_to_keep() -> usize {
+    7
 }
 
 impl Default for Config {
@@ -65,6 +443,7 @@ impl Default for Config {
                 api_key: None,
                 api_base: Some("http://localhost:11434".to_string()),
                 model: "qwen2.5:3b".to_string(),
+                model_path: None,
                 max_tokens: 500,
                 temperature: 0.3,
                 max_history_results: 10,
@@ -73,9 +452,17 @@ impl Default for Config {
                 similarity_threshold: 0.5,
                 max_results: 10,
                 fuzzy_matching: true,
+                timezone: default_timezone(),
+                fts_tokenizer: FtsTokenizer::default(),
+                recency_half_life_hours: default_recency_half_life_hours(),
+                hide_failed: false,
             },
             storage: StorageConfig {
                 maintenance_interval_days: 7,
+                session_timeout_secs: default_session_timeout_secs(),
+                association_depth: default_association_depth(),
+                encrypt: false,
+                backups_to_keep: default_backups_to_keep(),
             },
             privacy: PrivacyConfig {
                 excludes_contains_string: contains_string,
@@ -83,11 +470,98 @@ impl Default for Config {
                 excludes_ends_with_string: vec![],
                 excludes_regex: vec![],
                 exclude_folders: folder_excludes,
+                exclude_hosts: vec![],
+                hash_excluded_folders: false,
+                builtin_secret_patterns: true,
+                disabled_secret_patterns: vec![],
+                secret_entropy_threshold: default_secret_entropy_threshold(),
+                mode: PrivacyMode::default(),
+                audit_hash_content: false,
+                audit_redactions: false,
+                clipboard_blocked_apps: default_clipboard_blocked_apps(),
+                sensitive_window_titles: default_sensitive_window_titles(),
+                clipboard_excludes_regex: vec![],
+                quiet_windows: vec![],
             },
+            daemon: DaemonConfig::default(),
+            logging: LoggingConfig::default(),
+            plugins: PluginsConfig::default(),
+            remote: RemoteConfig::default(),
+            notes: NotesConfig::default(),
+            embeddings: EmbeddingsConfig::default(),
+            webhooks: Vec::new(),
         }
     }
 }
 
+impl PrivacyConfig {
+    /// The `exclude_hosts` entry matching this host/user, if any
+    pub fn excluded_host(&self, host: &str, user: &str) -> Option<&str> {
+        self.exclude_hosts
+            .iter()
+            .find(|rule| match rule.split_once('@') {
+                Some((user_glob, host_glob)) => {
+                    glob_match(user_glob, user) && glob_match(host_glob, host)
+                }
+                None => glob_match(rule, host),
+            })
+            .map(|rule| rule.as_str())
+    }
+
+    /// The `clipboard_blocked_apps` entry matching this app name, if any
+    pub fn blocked_clipboard_app(&self, app_name: &str) -> Option<&str> {
+        let app_name = app_name.to_lowercase();
+        self.clipboard_blocked_apps
+            .iter()
+            .find(|blocked| !blocked.is_empty() && app_name.contains(&blocked.to_lowercase()))
+            .map(|blocked| blocked.as_str())
+    }
+
+    /// The `sensitive_window_titles` entry matching this window title, if any
+    pub fn sensitive_window_title(&self, title: &str) -> Option<&str> {
+        let title = title.to_lowercase();
+        self.sensitive_window_titles
+            .iter()
+            .find(|pattern| !pattern.is_empty() && title.contains(&pattern.to_lowercase()))
+            .map(|pattern| pattern.as_str())
+    }
+
+    /// The `clipboard_excludes_regex` entry matching this clip, if any
+    pub fn excluded_clipboard_content(&self, content: &str) -> Option<&str> {
+        self.clipboard_excludes_regex
+            .iter()
+            .find(|pattern| match Regex::new(pattern) {
+                Ok(re) => re.is_match(content),
+                Err(e) => {
+                    warn!("Invalid clipboard regex '{}': {}", pattern, e);
+                    false
+                }
+            })
+            .map(|pattern| pattern.as_str())
+    }
+
+    /// The clipboard rule that keeps a clip out of the history, as the name it is
+    /// audited under and a description: a blocked app, a sensitive window title or a
+    /// content regex
+    pub fn clipboard_rule(
+        &self,
+        content: &str,
+        app_name: &str,
+        title: &str,
+    ) -> Option<(&'static str, String)> {
+        self.blocked_clipboard_app(app_name)
+            .map(|app| ("app-blocklist", format!("app \"{}\"", app)))
+            .or_else(|| {
+                self.sensitive_window_title(title)
+                    .map(|title| ("window-title", format!("title \"{}\"", title)))
+            })
+            .or_else(|| {
+                self.excluded_clipboard_content(content)
+                    .map(|pattern| ("clipboard-regex", format!("regex \"{}\"", pattern)))
+            })
+    }
+}
+
 impl Config {
     /// Load config from file, create default if doesn't exist
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
@@ -114,12 +588,12 @@ impl Config {
 
         // Ensure directory exists
         if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)?;
+            create_private_dir(parent)?;
         }
 
         // Serialize to pretty TOML
         let content = toml::to_string_pretty(self)?;
-        fs::write(config_path, content)?;
+        write_private(&config_path, content)?;
 
         Ok(())
     }
@@ -145,11 +619,98 @@ impl Config {
         Ok(())
     }
 
+    /// Replace one top-level section (e.g. "llm", "search") from JSON and save.
+    /// Used by the GUI, which edits config one section at a time.
+    pub fn set_section(
+        &mut self,
+        section: &str,
+        value: serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match section {
+            "llm" => self.llm = serde_json::from_value(value)?,
+            "search" => {
+                let search: SearchConfig = serde_json::from_value(value)?;
+                crate::timezone::QueryTimezone::parse(&search.timezone)?;
+                let half_life = search.recency_half_life_hours;
+                if !half_life.is_finite() || half_life < 0.0 {
+                    return Err("recency_half_life_hours must be 0 or a positive number".into());
+                }
+                self.search = search;
+            }
+            "storage" => {
+                let mut storage: StorageConfig = serde_json::from_value(value)?;
+                // Only `jotx encrypt` changes this, since the database is converted with it
+                storage.encrypt = self.storage.encrypt;
+                self.storage = storage;
+            }
+            "privacy" => {
+                let privacy: PrivacyConfig = serde_json::from_value(value)?;
+                for window in &privacy.quiet_windows {
+                    crate::privacy::schedule::validate(window)?;
+                }
+                self.privacy = privacy;
+            }
+            "daemon" => self.daemon = serde_json::from_value(value)?,
+            "plugins" => self.plugins = serde_json::from_value(value)?,
+            "remote" => self.remote = serde_json::from_value(value)?,
+            "embeddings" => {
+                let embeddings: EmbeddingsConfig = serde_json::from_value(value)?;
+                crate::embeds::check_config(&embeddings)?;
+                self.embeddings = embeddings;
+            }
+            "notes" => {
+                let notes: NotesConfig = serde_json::from_value(value)?;
+                if notes
+                    .dir
+                    .as_deref()
+                    .is_some_and(|dir| dir.trim().is_empty())
+                {
+                    return Err("notes.dir must be a folder, or unset to turn notes off".into());
+                }
+                if notes.days < 1 {
+                    return Err("notes.days must be at least 1".into());
+                }
+                self.notes = notes;
+            }
+            "webhooks" => {
+                // Unknown event names fail to deserialize
+                let webhooks: Vec<WebhookConfig> = serde_json::from_value(value)?;
+                for hook in &webhooks {
+                    let url = reqwest::Url::parse(&hook.url)
+                        .map_err(|e| format!("Invalid webhook url {}: {}", hook.url, e))?;
+                    if !matches!(url.scheme(), "http" | "https") {
+                        return Err(
+                            format!("Webhook url {} must be http or https", hook.url).into()
+                        );
+                    }
+                }
+                self.webhooks = webhooks;
+            }
+            "logging" => {
+                let logging: LoggingConfig = serde_json::from_value(value)?;
+                logging
+                    .level
+                    .parse::<tracing::level_filters::LevelFilter>()
+                    .map_err(|_| format!("Invalid logging.level: {}", logging.level))?;
+                self.logging = logging;
+            }
+            _ => return Err(format!("Unknown config section: {}", section).into()),
+        }
+
+        self.save()
+    }
+
     pub fn update_llm_model(&mut self, model: String) -> Result<(), Box<dyn std::error::Error>> {
         self.llm.model = model;
         self.save()?;
         Ok(())
     }
+
+    pub fn set_encrypt(&mut self, encrypt: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.storage.encrypt = encrypt;
+        self.save()?;
+        Ok(())
+    }
 }
 
 // Global config singleton
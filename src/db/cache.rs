@@ -1,6 +1,7 @@
 // cache.rs
 use crate::ask::fingerprint::QueryFingerprint;
 use crate::llm::LLMQueryParams;
+use crate::types::CacheStats;
 use rusqlite::{Connection, params};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
@@ -117,19 +118,19 @@ impl FingerprintCache {
             let embedding_blob = vec_to_blob(&fingerprint.embedding);
             let params_json = serde_json::to_string(&params)?;
 
-            db.execute(
+            db.prepare_cached(
                 "INSERT OR REPLACE INTO fingerprint_cache 
                  (query, keywords, temporal, embedding, params_json, last_used)
                  VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![
-                    &fingerprint.query,
-                    keywords_json,
-                    temporal_json,
-                    embedding_blob,
-                    params_json,
-                    timestamp
-                ],
-            )?;
+            )?
+            .execute(params![
+                &fingerprint.query,
+                keywords_json,
+                temporal_json,
+                embedding_blob,
+                params_json,
+                timestamp
+            ])?;
         }
 
         // Add to hot cache
@@ -203,14 +204,38 @@ impl FingerprintCache {
         }
     }
 
+    /// Hit/miss totals. Every cached query was a miss once (hit_count starts at 1),
+    /// and every later lookup that matched it was a hit.
+    pub fn stats(&self) -> Result<CacheStats, Box<dyn std::error::Error>> {
+        let db = self.db.lock().map_err(|_| "Failed to access cache")?;
+
+        let (entries, hits): (i64, i64) = db.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(hit_count - 1), 0) FROM fingerprint_cache",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let lookups = hits + entries;
+        Ok(CacheStats {
+            entries: entries as usize,
+            hits: hits as u64,
+            misses: entries as u64,
+            hit_rate: if lookups > 0 {
+                hits as f64 / lookups as f64
+            } else {
+                0.0
+            },
+        })
+    }
+
     pub fn update_hit_count(&mut self, query: &str) -> Result<(), Box<dyn std::error::Error>> {
         if let Ok(db) = self.db.lock() {
-            db.execute(
+            db.prepare_cached(
                 "UPDATE fingerprint_cache 
                     SET hit_count = hit_count + 1, last_used = ?1 
                     WHERE query = ?2",
-                params![now(), query],
-            )?;
+            )?
+            .execute(params![now(), query])?;
             Ok(())
         } else {
             Err("Failed to update hit count".into())
@@ -1,16 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::thread;
 use std::time::Duration;
-use crossbeam_channel::{bounded, Sender, Receiver};
+use crossbeam_channel::{bounded, Sender, Receiver, TrySendError};
 use once_cell::sync::Lazy;
-use crate::db::Database;
+use tracing::{error, warn};
+use crate::db::{Database, ShellRow, normalize_tag};
+use crate::db::journal::{Journal, take_orphaned};
 use crate::embeds::generate_embedding;
+use crate::managers::lock_manager::{lock_or_recover, retry_with_backoff};
+use crate::metrics::METRICS;
+use crate::normalize::normalize;
+use crate::pid_controller::record_dropped;
+use crate::plugin::{EntryContext, GLOBAL_PLUGIN_MANAGER};
+use crate::types::{CommandRun, ImportedCommand, ShellEntry};
 
 // Global DB writer instance
 pub static DB_WRITER: Lazy<DbWriter> = Lazy::new(|| {
     DbWriter::new().expect("Failed to initialize DB writer")
 });
 
-#[derive(Debug, Clone)]
+/// Bumped after every batch the writer commits and every delete, so cached search
+/// results can tell they are out of date
+static WRITE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn write_generation() -> u64 {
+    WRITE_GENERATION.load(Ordering::Acquire)
+}
+
+/// Inserts are retried this many times (with backoff) before the entry is dropped
+const MAX_WRITE_ATTEMPTS: u32 = 3;
+
+pub(crate) fn note_write() {
+    WRITE_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DbEntry {
     Shell {
         content: String,
@@ -20,6 +46,10 @@ pub enum DbEntry {
         host: Option<String>,
         app_name: String,
         window_title: String,
+        /// Stored as separate fields, each missing in entries journaled before it
+        /// was recorded
+        #[serde(flatten)]
+        run: CommandRun,
     },
     Clipboard {
         content: String,
@@ -27,23 +57,97 @@ pub enum DbEntry {
         app_name: String,
         window_title: String,
     },
+    /// Bump `times_run` of an existing shell entry
+    IncrementShell { id: i64 },
+    /// Commands from `jotx import`, written in one transaction. `done` gets the
+    /// number of new entries, or the error.
+    Import {
+        commands: Vec<ImportedCommand>,
+        #[serde(skip)]
+        done: Option<Sender<Result<usize, String>>>,
+    },
 }
 
+/// A queued entry with its journal sequence number (0 when journaling is unavailable)
+type Queued = (u64, DbEntry);
+
 pub struct DbWriter {
     pub is_running: bool,
-    sender: Sender<DbEntry>,
+    sender: Sender<Queued>,
+    /// Kept so a full queue can make room by discarding its oldest entry
+    overflow: Receiver<Queued>,
+    journal: Option<Arc<Journal>>,
 }
 
 impl DbWriter {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let (sender, receiver) = bounded(1000); // Queue size: 1000 entries
+        let overflow = receiver.clone();
+
+        let journal = match Journal::open() {
+            Ok(journal) => Some(Arc::new(journal)),
+            Err(e) => {
+                warn!("Capture journal unavailable, queued entries won't survive a crash: {}", e);
+                None
+            }
+        };
+        let worker_journal = journal.clone();
         
         // Spawn background worker thread
         thread::spawn(move || {
-            worker_thread(receiver);
+            worker_thread(receiver, worker_journal);
         });
         
-        Ok(Self { is_running: false, sender })
+        Ok(Self { is_running: false, sender, overflow, journal })
+    }
+
+    /// Queue entries left unwritten by processes that exited or were killed
+    /// (e.g. a shell hook that returned before its entry was stored)
+    pub fn replay_journals(&self) -> usize {
+        let entries = take_orphaned();
+        let count = entries.len();
+        for entry in entries {
+            if let Err(e) = self.enqueue(entry) {
+                error!("Failed to replay journaled entry: {}", e);
+            }
+        }
+        count
+    }
+
+    /// Journal `entry` and queue it. If the journal can't be written, the entry is
+    /// still queued, just without crash protection.
+    fn enqueue(&self, entry: DbEntry) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(journal) = &self.journal {
+            match journal.append_then(&entry, |seq| self.try_enqueue((seq, entry.clone()))) {
+                Ok(queued) => return queued,
+                Err(e) => error!("Failed to journal entry: {}", e),
+            }
+        }
+        self.try_enqueue((0, entry))
+    }
+
+    /// Queue `entry` without ever blocking the capture path. When the writer has
+    /// fallen behind and the queue is full, the oldest queued entry is dropped to
+    /// make room (or this one, if another producer refilled the slot first).
+    fn try_enqueue(&self, entry: Queued) -> Result<(), Box<dyn std::error::Error>> {
+        let entry = match self.sender.try_send(entry) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(entry)) => entry,
+            Err(TrySendError::Disconnected(_)) => return Err("DB writer has stopped".into()),
+        };
+
+        if self.overflow.try_recv().is_ok() {
+            record_dropped(1);
+        }
+
+        match self.sender.try_send(entry) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                record_dropped(1);
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => Err("DB writer has stopped".into()),
+        }
     }
 
     pub fn update_is_running(&mut self, is_running: bool) {
@@ -51,27 +155,19 @@ impl DbWriter {
     }
     
     /// Queue a shell entry for insertion
-    pub fn insert_shell(
-        &self,
-        content: String,
-        timestamp: u64,
-        working_dir: Option<String>,
-        user: Option<String>,
-        host: Option<String>,
-        app_name: String,
-        window_title: String,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn insert_shell(&self, entry: &ShellEntry) -> Result<(), Box<dyn std::error::Error>> {
         let entry = DbEntry::Shell {
-            content,
-            timestamp,
-            working_dir,
-            user,
-            host,
-            app_name,
-            window_title,
+            content: normalize(&entry.content),
+            timestamp: entry.timestamp,
+            working_dir: entry.working_dir.clone(),
+            user: entry.user.clone(),
+            host: entry.host.clone(),
+            app_name: "Terminal".to_string(),
+            window_title: "unknown".to_string(),
+            run: entry.run.clone(),
         };
         
-        self.sender.send(entry)
+        self.enqueue(entry)
             .map_err(|e| format!("Failed to queue shell entry: {}", e).into())
     }
     
@@ -84,16 +180,38 @@ impl DbWriter {
         window_title: String,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let entry = DbEntry::Clipboard {
-            content,
+            content: normalize(&content),
             timestamp,
             app_name,
             window_title,
         };
         
-        self.sender.send(entry)
+        self.enqueue(entry)
             .map_err(|e| format!("Failed to queue clipboard entry: {}", e).into())
     }
     
+    /// Queue a `times_run` increment for an existing shell entry
+    pub fn increment_shell(&self, id: i64) -> Result<(), Box<dyn std::error::Error>> {
+        self.enqueue(DbEntry::IncrementShell { id })
+            .map_err(|e| format!("Failed to queue shell increment: {}", e).into())
+    }
+
+    /// Write imported commands and wait until they are stored. Unlike captures this
+    /// blocks while the queue is full, since an import must not lose entries to it.
+    /// Returns how many new entries were added.
+    pub fn import_shell(
+        &self,
+        commands: Vec<ImportedCommand>,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let (done, result) = bounded(1);
+        self.sender
+            .send((0, DbEntry::Import { commands, done: Some(done) }))
+            .map_err(|_| "DB writer has stopped")?;
+
+        let added = result.recv().map_err(|_| "DB writer has stopped")??;
+        Ok(added)
+    }
+
     /// Get queue size (for monitoring)
     pub fn queue_len(&self) -> usize {
         self.sender.len()
@@ -101,17 +219,17 @@ impl DbWriter {
 }
 
 /// Background worker thread that processes the queue
-fn worker_thread(receiver: Receiver<DbEntry>) {
+fn worker_thread(receiver: Receiver<Queued>, journal: Option<Arc<Journal>>) {
     // Create own DB instance for this thread
     let mut db = match Database::new() {
         Ok(db) => db,
         Err(e) => {
-            eprintln!("DB writer thread failed to initialize database: {}", e);
+            error!("DB writer thread failed to initialize database: {}", e);
             return;
         }
     };
     
-    let mut batch: Vec<DbEntry> = Vec::new();
+    let mut batch: Vec<Queued> = Vec::new();
     let batch_size = 10; // Process in batches
     let batch_timeout = Duration::from_millis(500); // Or flush after 500ms
     
@@ -130,18 +248,18 @@ fn worker_thread(receiver: Receiver<DbEntry>) {
                 }
                 
                 // Process batch
-                process_batch(&mut db, &mut batch);
+                flush(&mut db, &mut batch, journal.as_deref(), &receiver);
             }
             Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
                 // Timeout - flush any pending entries
                 if !batch.is_empty() {
-                    process_batch(&mut db, &mut batch);
+                    flush(&mut db, &mut batch, journal.as_deref(), &receiver);
                 }
             }
             Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
                 // Channel closed - flush and exit
                 if !batch.is_empty() {
-                    process_batch(&mut db, &mut batch);
+                    flush(&mut db, &mut batch, journal.as_deref(), &receiver);
                 }
                 break;
             }
@@ -149,9 +267,30 @@ fn worker_thread(receiver: Receiver<DbEntry>) {
     }
 }
 
+/// Write a batch, then acknowledge it in the journal. The queue is FIFO, so the
+/// last sequence number covers the whole batch.
+fn flush(
+    db: &mut Database,
+    batch: &mut Vec<Queued>,
+    journal: Option<&Journal>,
+    receiver: &Receiver<Queued>,
+) {
+    let last_seq = batch.iter().map(|(seq, _)| *seq).max();
+    process_batch(db, batch.drain(..).map(|(_, entry)| entry).collect());
+
+    if let (Some(journal), Some(seq)) = (journal, last_seq)
+        && let Err(e) = journal.ack(seq, || receiver.is_empty())
+    {
+        error!("Failed to update capture journal: {}", e);
+    }
+}
+
 /// Process a batch of entries
-fn process_batch(db: &mut Database, batch: &mut Vec<DbEntry>) {
-    for entry in batch.drain(..) {
+fn process_batch(db: &mut Database, batch: Vec<DbEntry>) {
+    let wrote = !batch.is_empty();
+    METRICS.entries_written.add(batch.len() as u64);
+
+    for entry in batch {
         match entry {
             DbEntry::Shell {
                 content,
@@ -161,18 +300,33 @@ fn process_batch(db: &mut Database, batch: &mut Vec<DbEntry>) {
                 host,
                 app_name,
                 window_title,
+                run,
             } => {
-                if let Err(e) = process_shell_entry(
-                    db,
-                    &content,
+                let Some(stored) = run_store_hook(EntryContext {
+                    entry_type: "shell".to_string(),
+                    content,
                     timestamp,
-                    working_dir.as_deref(),
-                    user.as_deref(),
-                    host.as_deref(),
-                    &app_name,
-                    &window_title,
-                ) {
-                    eprintln!("Failed to insert shell entry: {}", e);
+                    working_dir: working_dir.clone(),
+                    app_name: app_name.clone(),
+                    window_title: window_title.clone(),
+                    tags: Vec::new(),
+                }) else {
+                    continue;
+                };
+
+                let row = ShellRow {
+                    content: &stored.content,
+                    timestamp,
+                    working_dir: working_dir.as_deref(),
+                    user: user.as_deref(),
+                    host: host.as_deref(),
+                    app_name: &app_name,
+                    window_title: &window_title,
+                    run: &run,
+                };
+                match process_shell_entry(db, &row) {
+                    Ok(id) => add_plugin_tags(db, id, &stored.tags),
+                    Err(e) => error!("Failed to insert shell entry: {}", e),
                 }
             }
             DbEntry::Clipboard {
@@ -181,109 +335,117 @@ fn process_batch(db: &mut Database, batch: &mut Vec<DbEntry>) {
                 app_name,
                 window_title,
             } => {
-                if let Err(e) = process_clipboard_entry(
+                let Some(stored) = run_store_hook(EntryContext {
+                    entry_type: "clipboard".to_string(),
+                    content,
+                    timestamp,
+                    working_dir: None,
+                    app_name: app_name.clone(),
+                    window_title: window_title.clone(),
+                    tags: Vec::new(),
+                }) else {
+                    continue;
+                };
+
+                match process_clipboard_entry(
                     db,
-                    &content,
+                    &stored.content,
                     timestamp,
                     &app_name,
                     &window_title,
                 ) {
-                    eprintln!("Failed to insert clipboard entry: {}", e);
+                    Ok(id) => add_plugin_tags(db, id, &stored.tags),
+                    Err(e) => error!("Failed to insert clipboard entry: {}", e),
+                }
+            }
+            DbEntry::IncrementShell { id } => {
+                if let Err(e) = db.increment_shell_command(id) {
+                    error!("Failed to update shell entry {}: {}", id, e);
+                }
+            }
+            DbEntry::Import { commands, done } => {
+                let result = retry_with_backoff("database writer", MAX_WRITE_ATTEMPTS, || {
+                    db.import_shell(&commands)
+                })
+                .map_err(|e| e.to_string());
+
+                match done {
+                    Some(done) => {
+                        let _ = done.send(result);
+                    }
+                    None => {
+                        if let Err(e) = result {
+                            error!("Failed to import shell history: {}", e);
+                        }
+                    }
                 }
             }
         }
     }
+
+    if wrote {
+        note_write();
+    }
+}
+
+/// Let plugins rewrite, tag or drop an entry right before it is written. Returns
+/// None when a plugin dropped it.
+fn run_store_hook(mut entry: EntryContext) -> Option<EntryContext> {
+    lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER)
+        .trigger_entry_store(&mut entry)
+        .then_some(entry)
 }
 
-/// Process a single shell entry with retry logic
+/// Put the tags plugins asked for on a stored entry; invalid ones are skipped
+fn add_plugin_tags(db: &Database, id: i64, tags: &[String]) {
+    for tag in tags {
+        let added =
+            normalize_tag(tag).and_then(|tag| db.add_tag(id, &tag).map_err(|e| e.to_string()));
+        if let Err(e) = added {
+            warn!("Plugin tag '{}' not added to entry {}: {}", tag, id, e);
+        }
+    }
+}
+
+/// Process a single shell entry with retry logic. Returns the entry's id.
 fn process_shell_entry(
     db: &mut Database,
-    content: &str,
-    timestamp: u64,
-    working_dir: Option<&str>,
-    user: Option<&str>,
-    host: Option<&str>,
-    app_name: &str,
-    window_title: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+    row: &ShellRow,
+) -> Result<i64, Box<dyn std::error::Error>> {
     // Generate embedding (can fail gracefully)
-    let embedding = match generate_embedding(content) {
+    let embedding = match generate_embedding(row.content) {
         Ok(emb) => Some(emb),
         Err(e) => {
-            eprintln!("Failed to generate embedding: {}", e);
+            error!("Failed to generate embedding: {}", e);
             None
         }
     };
     
-    // Retry logic for DB lock
-    let max_retries = 3;
-    let mut attempt = 0;
-    
-    loop {
-        match db.insert_shell(
-            content,
-            timestamp,
-            working_dir,
-            user,
-            host,
-            app_name,
-            window_title,
-            embedding.clone(),
-        ) {
-            Ok(_) => return Ok(()),
-            Err(e) => {
-                attempt += 1;
-                if attempt >= max_retries {
-                    return Err(format!("Failed after {} retries: {}", max_retries, e).into());
-                }
-                
-                // Wait before retry (exponential backoff)
-                let wait_time = Duration::from_millis(100 * (2_u64.pow(attempt - 1)));
-                thread::sleep(wait_time);
-            }
-        }
-    }
+    let id = retry_with_backoff("database writer", MAX_WRITE_ATTEMPTS, || {
+        db.insert_shell(row, embedding.clone())
+    })?;
+    Ok(id)
 }
 
-/// Process a single clipboard entry with retry logic
+/// Process a single clipboard entry with retry logic. Returns the entry's id.
 fn process_clipboard_entry(
     db: &mut Database,
     content: &str,
     timestamp: u64,
     app_name: &str,
     window_title: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<i64, Box<dyn std::error::Error>> {
     // Generate embedding
     let embedding = match generate_embedding(content) {
         Ok(emb) => Some(emb),
         Err(e) => {
-            eprintln!("Failed to generate embedding: {}", e);
+            error!("Failed to generate embedding: {}", e);
             None
         }
     };
     
-    // Retry logic
-    let max_retries = 3;
-    let mut attempt = 0;
-    
-    loop {
-        match db.insert_clipboard(
-            content,
-            timestamp,
-            app_name,
-            window_title,
-            embedding.clone(),
-        ) {
-            Ok(_) => return Ok(()),
-            Err(e) => {
-                attempt += 1;
-                if attempt >= max_retries {
-                    return Err(format!("Failed after {} retries: {}", max_retries, e).into());
-                }
-                
-                let wait_time = Duration::from_millis(100 * (2_u64.pow(attempt - 1)));
-                thread::sleep(wait_time);
-            }
-        }
-    }
+    let id = retry_with_backoff("database writer", MAX_WRITE_ATTEMPTS, || {
+        db.insert_clipboard(content, timestamp, app_name, window_title, embedding.clone())
+    })?;
+    Ok(id)
 }
\ No newline at end of file
@@ -0,0 +1,189 @@
+use byteorder::{ByteOrder, LittleEndian};
+use rusqlite::{OptionalExtension, Result, params};
+
+use super::Database;
+use crate::config::{EmbeddingsConfig, GLOBAL_CONFIG};
+
+/// `embeddings.dimension`: floats in every embedding the index accepts
+pub fn embedding_dimension() -> usize {
+    GLOBAL_CONFIG
+        .read()
+        .map(|config| config.embeddings.dimension)
+        .unwrap_or_else(|_| EmbeddingsConfig::default().dimension)
+}
+
+/// Stored embeddings are little-endian f32s
+fn embedding_bytes() -> i64 {
+    embedding_dimension() as i64 * 4
+}
+
+/// Entries `jotx reindex` should embed: those without an embedding of the configured
+/// dimension, or all of them with `all`. Vaulted entries and hashed commands have no
+/// text worth embedding.
+const TO_EMBED: &str = "FROM entries
+     WHERE (?1 OR embedding IS NULL OR length(embedding) != ?2)
+       AND id NOT IN (SELECT entry_id FROM vault_entries)
+       AND content NOT LIKE '#hashed:%'";
+
+impl Database {
+    /// How many entries `entries_to_embed` will walk through
+    pub fn count_entries_to_embed(&self, all: bool) -> Result<usize> {
+        self.conn
+            .query_row(
+                &format!("SELECT COUNT(*) {}", TO_EMBED),
+                params![all, embedding_bytes()],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|n| n as usize)
+    }
+
+    /// The next `limit` entries to embed after `after_id`, as (id, content)
+    pub fn entries_to_embed(
+        &self,
+        all: bool,
+        after_id: i64,
+        limit: usize,
+    ) -> Result<Vec<(i64, String)>> {
+        self.conn
+            .prepare(&format!(
+                "SELECT id, content {} AND id > ?3 ORDER BY id LIMIT ?4",
+                TO_EMBED
+            ))?
+            .query_map(
+                params![all, embedding_bytes(), after_id, limit as i64],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?
+            .collect()
+    }
+
+    /// Store new embeddings; the vec_entries triggers index them
+    pub fn set_embeddings(&self, embeddings: &[(i64, Vec<f32>)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut update =
+                tx.prepare_cached("UPDATE entries SET embedding = ?2 WHERE id = ?1")?;
+            for (id, embedding) in embeddings {
+                let mut blob = vec![0u8; embedding.len() * 4];
+                LittleEndian::write_f32_into(embedding, &mut blob);
+                update.execute(params![id, blob])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// How many stored embeddings there are of each dimension, most common first.
+    /// Anything but `embedding_dimension()` came from another model.
+    pub fn embedding_dimensions(&self) -> Result<Vec<(usize, usize)>> {
+        self.conn
+            .prepare(
+                "SELECT length(embedding) / 4, COUNT(*) FROM entries
+                 WHERE embedding IS NOT NULL
+                 GROUP BY 1 ORDER BY 2 DESC",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as usize,
+                    row.get::<_, i64>(1)? as usize,
+                ))
+            })?
+            .collect()
+    }
+
+    /// Dimension `vec_entries` was created with, if it exists
+    pub(super) fn vec_index_dimension(&self) -> Result<Option<usize>> {
+        let sql: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE name = 'vec_entries'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(sql.and_then(|sql| parse_vec_dimension(&sql)))
+    }
+
+    pub(super) fn drop_vec_index(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "DROP TRIGGER IF EXISTS vec_entries_ai;
+             DROP TRIGGER IF EXISTS vec_entries_au;
+             DROP TRIGGER IF EXISTS vec_entries_ad;
+             DROP TABLE IF EXISTS vec_entries;",
+        )
+    }
+
+    /// Refill `vec_entries` from the stored embeddings. Returns how many were indexed,
+    /// or None when sqlite-vec isn't available.
+    pub fn rebuild_vec_index(&self) -> Result<Option<usize>> {
+        let has_vec_table: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'vec_entries')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_vec_table {
+            return Ok(None);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM vec_entries", [])?;
+        let indexed = tx.execute(
+            "INSERT INTO vec_entries(entry_id, embedding)
+             SELECT id, embedding FROM entries WHERE length(embedding) = ?1",
+            [embedding_bytes()],
+        )?;
+        tx.commit()?;
+        Ok(Some(indexed))
+    }
+}
+
+/// The N of `embedding FLOAT[N]` in the vec0 table definition
+fn parse_vec_dimension(sql: &str) -> Option<usize> {
+    let sql = sql.to_lowercase();
+    let start = sql.find("float[")? + "float[".len();
+    let end = start + sql[start..].find(']')?;
+    sql[start..end].trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_entries_to_embed() {
+        let db = TempDb::new("reindex");
+        for (content, embedding) in [
+            ("git status", Some(vec![0.1; 384])),
+            ("cargo test", None),
+            ("ls -la", Some(vec![0.1; 8])),
+        ] {
+            db.insert_shell(&ShellRow::new(content, 1), embedding)
+                .unwrap();
+        }
+
+        let pending = db.entries_to_embed(false, 0, 10).unwrap();
+        let contents: Vec<&str> = pending.iter().map(|(_, c)| c.as_str()).collect();
+        assert_eq!(contents, vec!["cargo test", "ls -la"]);
+        assert_eq!(db.count_entries_to_embed(true).unwrap(), 3);
+
+        let embeddings: Vec<(i64, Vec<f32>)> = pending
+            .iter()
+            .map(|(id, _)| (*id, vec![0.2; 384]))
+            .collect();
+        db.set_embeddings(&embeddings).unwrap();
+        assert_eq!(db.count_entries_to_embed(false).unwrap(), 0);
+        assert_eq!(db.rebuild_vec_index().unwrap(), Some(3));
+        assert_eq!(db.embedding_dimensions().unwrap(), vec![(384, 3)]);
+        assert_eq!(db.vec_index_dimension().unwrap(), Some(384));
+    }
+
+    #[test]
+    fn test_parse_vec_dimension() {
+        let sql = "CREATE VIRTUAL TABLE vec_entries USING vec0(
+            entry_id INTEGER PRIMARY KEY,
+            embedding FLOAT[768]
+        )";
+        assert_eq!(parse_vec_dimension(sql), Some(768));
+        assert_eq!(parse_vec_dimension("CREATE TABLE t (x)"), None);
+    }
+}
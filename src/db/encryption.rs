@@ -0,0 +1,238 @@
+//! Optional encryption of jotx.db at rest with SQLCipher (`storage.encrypt`, needs a
+//! build with the `encryption` feature). The key is a random 256-bit raw key kept in
+//! the OS keyring, or read from `JOTX_DB_KEY` on machines without one. Every
+//! connection to jotx.db is opened through `open_db`, which unlocks it.
+//!
+//! `jotx encrypt` copies an existing plaintext database into an encrypted one with
+//! `sqlcipher_export`, and `jotx encrypt --decrypt` goes back.
+
+use once_cell::sync::OnceCell;
+use rusqlite::{Connection, OpenFlags, Result};
+use std::path::Path;
+
+use crate::config::GLOBAL_CONFIG;
+
+/// Overrides the keyring, for servers and containers that have none
+const KEY_ENV: &str = "JOTX_DB_KEY";
+/// Hex digits of a raw 256-bit key
+const KEY_HEX_LEN: usize = 64;
+
+/// The key, looked up once per process rather than for every pooled connection
+static DATABASE_KEY: OnceCell<String> = OnceCell::new();
+
+/// Whether `storage.encrypt` is on
+pub fn encryption_enabled() -> bool {
+    GLOBAL_CONFIG
+        .read()
+        .map(|config| config.storage.encrypt)
+        .unwrap_or(false)
+}
+
+/// Open jotx.db at `path`, unlocking it when encryption is on
+pub fn open_db(path: &Path, flags: OpenFlags) -> Result<Connection> {
+    let conn = Connection::open_with_flags(path, flags)?;
+    if encryption_enabled() {
+        let key = DATABASE_KEY
+            .get_or_try_init(|| load_key().and_then(|key| validate_key(&key)))
+            .map_err(key_error)?;
+        unlock(&conn, key)?;
+    }
+    Ok(conn)
+}
+
+/// Apply the key and check it by reading the schema; SQLCipher only notices a wrong
+/// key on the first read
+fn unlock(conn: &Connection, key: &str) -> Result<()> {
+    conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key))?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|_| {
+        key_error(
+            "Could not decrypt jotx.db: wrong key, or the database is not encrypted".to_string(),
+        )
+    })?;
+    Ok(())
+}
+
+fn key_error(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_AUTH),
+        Some(message),
+    )
+}
+
+/// Keys are stored and passed as 64 lowercase hex digits
+fn validate_key(key: &str) -> Result<String, String> {
+    let key = key.trim().to_lowercase();
+    if key.len() != KEY_HEX_LEN || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Database key must be {} hex digits (32 random bytes)",
+            KEY_HEX_LEN
+        ));
+    }
+    Ok(key)
+}
+
+#[cfg(feature = "encryption")]
+use sqlcipher::load_key;
+#[cfg(feature = "encryption")]
+pub use sqlcipher::{decrypt_database, encrypt_database};
+
+#[cfg(feature = "encryption")]
+mod sqlcipher {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::db::load_vec_extension;
+    use crate::permissions::restrict_db;
+
+    const KEYRING_SERVICE: &str = "jotx";
+    const KEYRING_USER: &str = "database";
+
+    pub(super) fn load_key() -> Result<String, String> {
+        if let Ok(key) = std::env::var(KEY_ENV) {
+            return Ok(key);
+        }
+        keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .and_then(|entry| entry.get_password())
+            .map_err(|e| {
+                format!(
+                    "No database key in the OS keyring ({}); set {} or run jotx encrypt --decrypt",
+                    e, KEY_ENV
+                )
+            })
+    }
+
+    /// A new key, or the one from `JOTX_DB_KEY`, saved to the keyring
+    fn create_key() -> Result<String, String> {
+        if let Ok(key) = std::env::var(KEY_ENV) {
+            return validate_key(&key);
+        }
+        let key: String = rand::random::<[u8; 32]>()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .and_then(|entry| entry.set_password(&key))
+            .map_err(|e| format!("Failed to store the key in the OS keyring: {}", e))?;
+        Ok(key)
+    }
+
+    fn delete_key() {
+        if let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER) {
+            let _ = entry.delete_credential();
+        }
+    }
+
+    /// Rewrite the plaintext database at `path` encrypted under a new key. The daemon
+    /// must be stopped; `storage.encrypt` is switched on by the caller afterwards.
+    pub fn encrypt_database(path: &Path) -> Result<(), String> {
+        let key = create_key()?;
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        convert(conn, path, &format!("x'{}'", key))?;
+
+        // The converted file must open with the key before it replaces the original
+        let check = Connection::open(converted_path(path)).map_err(|e| e.to_string())?;
+        unlock(&check, &key).map_err(|e| e.to_string())?;
+        drop(check);
+
+        replace(path)
+    }
+
+    /// Rewrite the encrypted database at `path` as plaintext and forget its key
+    pub fn decrypt_database(path: &Path) -> Result<(), String> {
+        let key = validate_key(&load_key()?)?;
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        unlock(&conn, &key).map_err(|e| e.to_string())?;
+        convert(conn, path, "")?;
+        replace(path)?;
+        delete_key();
+        Ok(())
+    }
+
+    /// Copy everything in `conn` into `<path>.converting`, keyed with `key` (empty for
+    /// plaintext). The schema version isn't part of the export, so it is copied too.
+    fn convert(conn: Connection, path: &Path, key: &str) -> Result<(), String> {
+        let target = converted_path(path);
+        let _ = fs::remove_file(&target);
+
+        let export = || -> Result<()> {
+            // vec_entries is a virtual table; exporting it needs the extension
+            let _ = load_vec_extension(&conn);
+            conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")?;
+            let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+            conn.execute(
+                &format!("ATTACH DATABASE ?1 AS converted KEY \"{}\"", key),
+                [target.to_string_lossy()],
+            )?;
+            conn.query_row("SELECT sqlcipher_export('converted')", [], |_| Ok(()))?;
+            conn.execute_batch(&format!(
+                "PRAGMA converted.user_version = {}; DETACH DATABASE converted;",
+                version
+            ))
+        };
+
+        export().map_err(|e| {
+            let _ = fs::remove_file(&target);
+            format!("Failed to convert {}: {}", path.display(), e)
+        })
+    }
+
+    /// Move the converted file over the original, dropping the original's WAL files
+    fn replace(path: &Path) -> Result<(), String> {
+        fs::rename(converted_path(path), path)
+            .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+        for suffix in ["-wal", "-shm"] {
+            let mut side = path.as_os_str().to_owned();
+            side.push(suffix);
+            let _ = fs::remove_file(PathBuf::from(side));
+        }
+        restrict_db(path).map_err(|e| format!("Failed to restrict {}: {}", path.display(), e))
+    }
+
+    fn converted_path(path: &Path) -> PathBuf {
+        let mut converted = path.as_os_str().to_owned();
+        converted.push(".converting");
+        PathBuf::from(converted)
+    }
+}
+
+#[cfg(not(feature = "encryption"))]
+fn load_key() -> Result<String, String> {
+    Err(format!(
+        "storage.encrypt is on, but this jotx was built without the encryption feature \
+         (cargo install jotx --features encryption) and can't use {} or the keyring",
+        KEY_ENV
+    ))
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn encrypt_database(_path: &Path) -> Result<(), String> {
+    Err("This jotx was built without the encryption feature \
+         (cargo install jotx --features encryption)"
+        .to_string())
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn decrypt_database(path: &Path) -> Result<(), String> {
+    encrypt_database(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        let key = "AB".repeat(32);
+        assert_eq!(
+            validate_key(&format!(" {}\n", key)).unwrap(),
+            "ab".repeat(32)
+        );
+        assert!(validate_key("abcd").is_err());
+        assert!(validate_key(&"zz".repeat(32)).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use rusqlite::{Connection, ErrorCode, Result};
+
+use super::Database;
+use crate::config::FtsTokenizer;
+
+/// SQL for the full-text index over `entries`, using `tokenizer`
+fn create_fts_sql(tokenizer: FtsTokenizer) -> String {
+    format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS entries_fts USING fts5(
+            content,
+            app_name,
+            window_title,
+            working_dir,
+            content='entries',
+            content_rowid='id',
+            tokenize='{}'
+        )",
+        tokenizer.as_sql()
+    )
+}
+
+/// Tokenizer the existing `entries_fts` table was built with
+pub fn fts_tokenizer(conn: &Connection) -> Result<FtsTokenizer> {
+    let sql: String = conn
+        .prepare_cached("SELECT sql FROM sqlite_master WHERE name = 'entries_fts'")?
+        .query_row([], |row| row.get(0))?;
+
+    if sql.to_lowercase().contains("trigram") {
+        Ok(FtsTokenizer::Trigram)
+    } else {
+        Ok(FtsTokenizer::Unicode61)
+    }
+}
+
+/// How well `entries_fts` covers `entries`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FtsCheck {
+    pub entries: usize,
+    /// Entries with no row in the index
+    pub missing: usize,
+    /// Index rows whose entry no longer exists
+    pub orphaned: usize,
+    /// FTS5's own integrity-check found indexed terms that don't match the content
+    pub corrupt: bool,
+}
+
+impl FtsCheck {
+    pub fn is_healthy(&self) -> bool {
+        self.missing == 0 && self.orphaned == 0 && !self.corrupt
+    }
+}
+
+/// Compare the index against `entries`. Inserts arrive from several connections
+/// through triggers, so the two can drift apart.
+fn check_fts(conn: &Connection) -> Result<FtsCheck> {
+    let count = |sql: &str| -> Result<usize> {
+        conn.query_row(sql, [], |row| row.get::<_, i64>(0))
+            .map(|n| n as usize)
+    };
+
+    let entries = count("SELECT COUNT(*) FROM entries")?;
+    let missing = count(
+        "SELECT COUNT(*) FROM entries WHERE id NOT IN (SELECT id FROM entries_fts_docsize)",
+    )?;
+    let orphaned = count(
+        "SELECT COUNT(*) FROM entries_fts_docsize WHERE id NOT IN (SELECT id FROM entries)",
+    )?;
+
+    // rank = 1 also checks the index against the external content table
+    let corrupt = match conn.execute(
+        "INSERT INTO entries_fts(entries_fts, rank) VALUES('integrity-check', 1)",
+        [],
+    ) {
+        Ok(_) => false,
+        Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == ErrorCode::DatabaseCorrupt => true,
+        Err(e) => return Err(e),
+    };
+
+    Ok(FtsCheck {
+        entries,
+        missing,
+        orphaned,
+        corrupt,
+    })
+}
+
+impl Database {
+    /// Create the FTS table if missing. An existing table keeps its tokenizer until
+    /// `rebuild_fts` is run, as rebuilding re-indexes every entry.
+    pub(super) fn create_fts(&self, tokenizer: FtsTokenizer) -> Result<()> {
+        self.conn.execute(&create_fts_sql(tokenizer), [])?;
+        Ok(())
+    }
+
+    /// Drop and rebuild `entries_fts` with `tokenizer`, re-indexing every entry, and
+    /// check the result. The sync triggers refer to the table by name and keep working.
+    pub fn rebuild_fts(&mut self, tokenizer: FtsTokenizer) -> Result<FtsCheck> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DROP TABLE IF EXISTS entries_fts", [])?;
+        tx.execute(&create_fts_sql(tokenizer), [])?;
+        tx.execute("INSERT INTO entries_fts(entries_fts) VALUES('rebuild')", [])?;
+        tx.commit()?;
+
+        self.check_fts()
+    }
+
+    pub fn check_fts(&self) -> Result<FtsCheck> {
+        check_fts(&self.conn)
+    }
+
+    /// Check the index and re-index in place (keeping the tokenizer) if it drifted.
+    /// Returns the check from before the repair.
+    pub fn repair_fts(&self) -> Result<FtsCheck> {
+        let check = self.check_fts()?;
+        if !check.is_healthy() {
+            self.conn
+                .execute("INSERT INTO entries_fts(entries_fts) VALUES('rebuild')", [])?;
+        }
+        Ok(check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigram_matches_substrings() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE entries (id INTEGER PRIMARY KEY, content TEXT, app_name TEXT,
+                                   window_title TEXT, working_dir TEXT);
+             INSERT INTO entries (content) VALUES ('pip install requests');",
+        )
+        .unwrap();
+
+        let count = |conn: &Connection, query: &str| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM entries_fts WHERE entries_fts MATCH ?1",
+                [query],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        conn.execute(&create_fts_sql(FtsTokenizer::Unicode61), []).unwrap();
+        conn.execute("INSERT INTO entries_fts(entries_fts) VALUES('rebuild')", []).unwrap();
+        assert_eq!(fts_tokenizer(&conn).unwrap(), FtsTokenizer::Unicode61);
+        assert_eq!(count(&conn, "stal"), 0);
+
+        conn.execute("DROP TABLE entries_fts", []).unwrap();
+        conn.execute(&create_fts_sql(FtsTokenizer::Trigram), []).unwrap();
+        conn.execute("INSERT INTO entries_fts(entries_fts) VALUES('rebuild')", []).unwrap();
+        assert_eq!(fts_tokenizer(&conn).unwrap(), FtsTokenizer::Trigram);
+        assert_eq!(count(&conn, "stal"), 1);
+    }
+
+    #[test]
+    fn test_check_finds_drift() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE entries (id INTEGER PRIMARY KEY, content TEXT, app_name TEXT,
+                                   window_title TEXT, working_dir TEXT);
+             INSERT INTO entries (content) VALUES ('git status');",
+        )
+        .unwrap();
+        conn.execute(&create_fts_sql(FtsTokenizer::Unicode61), []).unwrap();
+
+        // No triggers here, so the index misses the row until rebuilt
+        assert_eq!(check_fts(&conn).unwrap().missing, 1);
+        conn.execute("INSERT INTO entries_fts(entries_fts) VALUES('rebuild')", []).unwrap();
+        assert!(check_fts(&conn).unwrap().is_healthy());
+
+        conn.execute("DELETE FROM entries", []).unwrap();
+        let check = check_fts(&conn).unwrap();
+        assert_eq!(check.orphaned, 1);
+        assert!(!check.is_healthy());
+    }
+}
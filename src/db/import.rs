@@ -0,0 +1,129 @@
+use rusqlite::{OptionalExtension, Result, params};
+
+use super::Database;
+use crate::types::{EntryType, ImportedCommand};
+
+impl Database {
+    /// Store commands from `jotx import` in one transaction. A command already in
+    /// history for the same directory keeps its row, taking the higher run count and
+    /// the newer run, so importing the same file twice changes nothing.
+    /// Returns how many new entries were added.
+    pub fn import_shell(&self, commands: &[ImportedCommand]) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut added = 0;
+        {
+            let mut find = tx.prepare_cached(
+                "SELECT id FROM entries
+                 WHERE entry_type = ?1 AND content = ?2 AND working_dir IS ?3
+                 ORDER BY timestamp DESC
+                 LIMIT 1",
+            )?;
+            // SET expressions all see the row as it was, so the CASEs compare
+            // against the old timestamp
+            let mut update = tx.prepare_cached(
+                "UPDATE entries
+                 SET times_run = MAX(times_run, ?2),
+                     exit_code = CASE WHEN ?3 > timestamp THEN ?4 ELSE exit_code END,
+                     duration_ms = CASE WHEN ?3 > timestamp THEN ?5 ELSE duration_ms END,
+                     timestamp = MAX(timestamp, ?3),
+                     user = COALESCE(user, ?6),
+                     host = COALESCE(host, ?7),
+                     updated_at = strftime('%s', 'now')
+                 WHERE id = ?1",
+            )?;
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO entries (entry_type, content, timestamp, times_run, working_dir,
+                                      user, host, app_name, window_title, exit_code, duration_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'Terminal', 'import', ?8, ?9)",
+            )?;
+
+            for command in commands {
+                let existing: Option<i64> = find
+                    .query_row(
+                        params![EntryType::Shell, command.content, command.working_dir],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                let duration_ms = command.duration_ms.map(|ms| ms as i64);
+
+                match existing {
+                    Some(id) => {
+                        update.execute(params![
+                            id,
+                            command.times_run,
+                            command.timestamp as i64,
+                            command.exit_code,
+                            duration_ms,
+                            command.user,
+                            command.host,
+                        ])?;
+                    }
+                    None => {
+                        insert.execute(params![
+                            EntryType::Shell,
+                            command.content,
+                            command.timestamp as i64,
+                            command.times_run,
+                            command.working_dir,
+                            command.user,
+                            command.host,
+                            command.exit_code,
+                            duration_ms,
+                        ])?;
+                        added += 1;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_import_is_idempotent() {
+        let db = TempDb::new("import");
+        db.shell(ShellRow::new("git status", 500).in_dir("/src"));
+
+        let commands = vec![
+            ImportedCommand {
+                content: "git status".to_string(),
+                timestamp: 1000,
+                working_dir: Some("/src".to_string()),
+                exit_code: Some(0),
+                times_run: 4,
+                ..Default::default()
+            },
+            ImportedCommand {
+                content: "cargo build".to_string(),
+                timestamp: 900,
+                times_run: 2,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(db.import_shell(&commands).unwrap(), 1);
+        assert_eq!(db.import_shell(&commands).unwrap(), 0);
+
+        let rows: Vec<(String, i64, i64)> = db
+            .conn
+            .prepare("SELECT content, times_run, timestamp FROM entries ORDER BY content")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("cargo build".to_string(), 2, 900),
+                ("git status".to_string(), 4, 1000),
+            ]
+        );
+    }
+}
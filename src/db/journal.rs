@@ -0,0 +1,192 @@
+//! Append-only journal of entries queued for the DB writer. Every queued entry is
+//! written here before it enters the in-memory queue and acknowledged once the
+//! writer has stored it, so captures survive the process being killed with a
+//! non-empty queue. Each process journals to its own file; the daemon replays
+//! the files of processes that are no longer running.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::error;
+
+use super::db_writer::DbEntry;
+use crate::permissions::{create_private_dir, open_private_append};
+use crate::pid_controller::pid_alive;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Record {
+    Entry {
+        seq: u64,
+        entry: Box<DbEntry>,
+    },
+    /// Everything up to and including `seq` is in the database
+    Ack {
+        seq: u64,
+    },
+}
+
+pub(crate) struct Journal {
+    state: Mutex<JournalState>,
+}
+
+struct JournalState {
+    file: File,
+    next_seq: u64,
+}
+
+fn journal_dir() -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".jotx").join("journal")
+}
+
+impl Journal {
+    /// Open this process's journal
+    pub(crate) fn open() -> io::Result<Self> {
+        let dir = journal_dir();
+        create_private_dir(&dir)?;
+        let file = open_private_append(&dir.join(format!("{}.jsonl", std::process::id())))?;
+
+        Ok(Self {
+            state: Mutex::new(JournalState { file, next_seq: 1 }),
+        })
+    }
+
+    /// Journal `entry`, then hand its sequence number to `enqueue`. Both happen under
+    /// the journal lock so an `ack` can't truncate between the two.
+    pub(crate) fn append_then<T>(
+        &self,
+        entry: &DbEntry,
+        enqueue: impl FnOnce(u64) -> T,
+    ) -> io::Result<T> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let seq = state.next_seq;
+        state.next_seq += 1;
+
+        let record = Record::Entry {
+            seq,
+            entry: Box::new(entry.clone()),
+        };
+        write_record(&mut state.file, &record)?;
+        state.file.sync_data()?;
+
+        Ok(enqueue(seq))
+    }
+
+    /// Mark everything up to `seq` as stored. When the queue has `drained`, nothing
+    /// is outstanding and the journal is emptied instead.
+    pub(crate) fn ack(&self, seq: u64, drained: impl FnOnce() -> bool) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if drained() {
+            state.file.set_len(0)
+        } else {
+            write_record(&mut state.file, &Record::Ack { seq })
+        }
+    }
+}
+
+fn write_record(file: &mut File, record: &Record) -> io::Result<()> {
+    let mut line = serde_json::to_string(record).map_err(io::Error::other)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())
+}
+
+/// Take the unwritten entries left by processes that exited or were killed,
+/// removing their journals. Entries come back oldest first.
+pub(crate) fn take_orphaned() -> Vec<DbEntry> {
+    let Ok(dir) = std::fs::read_dir(journal_dir()) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    for file in dir.flatten() {
+        let path = file.path();
+        let Some(pid) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid == std::process::id() || pid_alive(pid) {
+            continue;
+        }
+
+        match read_unacked(&path) {
+            Ok(unacked) => entries.extend(unacked),
+            Err(e) => error!("Failed to read journal {}: {}", path.display(), e),
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+    entries
+}
+
+fn read_unacked(path: &std::path::Path) -> io::Result<Vec<DbEntry>> {
+    let mut entries = Vec::new();
+    let mut acked = 0;
+
+    for line in BufReader::new(File::open(path)?).lines() {
+        // A torn last line (killed mid-write) is skipped
+        match serde_json::from_str::<Record>(&line?) {
+            Ok(Record::Entry { seq, entry }) => entries.push((seq, *entry)),
+            Ok(Record::Ack { seq }) => acked = acked.max(seq),
+            Err(_) => continue,
+        }
+    }
+
+    Ok(entries
+        .into_iter()
+        .filter(|(seq, _)| *seq > acked)
+        .map(|(_, entry)| entry)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommandRun;
+
+    #[test]
+    fn test_unacked_entries_survive() {
+        let path = std::env::temp_dir().join(format!("jotx-journal-test-{}", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+
+        for seq in 1..=3 {
+            let entry = Box::new(DbEntry::IncrementShell { id: seq as i64 });
+            write_record(&mut file, &Record::Entry { seq, entry }).unwrap();
+        }
+        write_record(&mut file, &Record::Ack { seq: 2 }).unwrap();
+        file.write_all(b"{\"kind\":\"entry\",\"se").unwrap();
+
+        let unacked = read_unacked(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(unacked.len(), 1);
+        assert!(matches!(unacked[0], DbEntry::IncrementShell { id: 3 }));
+    }
+
+    #[test]
+    fn test_shell_entries_from_older_journals() {
+        // Written before git context and exit codes were recorded
+        let old = r#"{"kind":"entry","seq":1,"entry":{"Shell":{"content":"ls","timestamp":1,"working_dir":null,"user":null,"host":null,"app_name":"Terminal","window_title":"unknown"}}}"#;
+        let Record::Entry { entry, .. } = serde_json::from_str(old).unwrap() else {
+            panic!("not an entry");
+        };
+        let DbEntry::Shell { run, .. } = *entry else {
+            panic!("not a shell entry");
+        };
+        assert_eq!(run, CommandRun::default());
+
+        let current = r#"{"kind":"entry","seq":2,"entry":{"Shell":{"content":"ls","timestamp":1,"working_dir":"/src","user":null,"host":null,"app_name":"Terminal","window_title":"unknown","exit_code":1,"duration_ms":20,"git_repo":"/src","git_branch":"main"}}}"#;
+        let Record::Entry { entry, .. } = serde_json::from_str(current).unwrap() else {
+            panic!("not an entry");
+        };
+        let DbEntry::Shell { run, .. } = *entry else {
+            panic!("not a shell entry");
+        };
+        assert_eq!(run.exit_code, Some(1));
+        assert_eq!(run.git_branch.as_deref(), Some("main"));
+    }
+}
@@ -0,0 +1,176 @@
+use rusqlite::{Result, params};
+
+use super::{
+    Database, ENTRIES_DELETE_TRIGGER, ENTRIES_UPDATE_TRIGGER, embedding_dimension, vec_triggers,
+};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::normalize::{fold, normalize};
+use crate::settings::GLOBAL_SETTINGS;
+
+/// Bumped whenever a migration is added; stored in `PRAGMA user_version`
+const SCHEMA_VERSION: i64 = 6;
+
+impl Database {
+    /// Bring existing rows up to date with the current storage format
+    pub(super) fn migrate(&mut self) -> Result<()> {
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        if version < 1 {
+            self.normalize_existing_content()?;
+        }
+
+        if version < 2 {
+            self.add_command_outcome_columns()?;
+        }
+
+        if version < 3 {
+            self.fix_fts_delete_trigger()?;
+        }
+
+        if version < 4 {
+            self.backfill_vec_entries()?;
+        }
+
+        if version < 5 {
+            self.add_entry_columns(&[("pinned", "INTEGER NOT NULL DEFAULT 0")])?;
+        }
+
+        if version < 6 {
+            self.fix_fts_update_trigger()?;
+        }
+
+        if version < SCHEMA_VERSION {
+            self.conn
+                .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        Ok(())
+    }
+
+    /// v2: exit code and duration of shell commands
+    fn add_command_outcome_columns(&mut self) -> Result<()> {
+        self.add_entry_columns(&[("exit_code", "INTEGER"), ("duration_ms", "INTEGER")])
+    }
+
+    /// Add columns to `entries`. New databases already have them from `init_schema`,
+    /// so only missing ones are added.
+    fn add_entry_columns(&mut self, new_columns: &[(&str, &str)]) -> Result<()> {
+        let columns: Vec<String> = self
+            .conn
+            .prepare("SELECT name FROM pragma_table_info('entries')")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_>>()?;
+
+        for &(column, sql_type) in new_columns {
+            if !columns.iter().any(|c| c == column) {
+                self.conn.execute(
+                    &format!("ALTER TABLE entries ADD COLUMN {} {}", column, sql_type),
+                    [],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// v3: the old delete trigger left the text of deleted entries in `entries_fts`.
+    /// Replace it, then rebuild the index so text deleted before is gone too.
+    fn fix_fts_delete_trigger(&mut self) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DROP TRIGGER IF EXISTS entries_ad", [])?;
+        tx.execute(ENTRIES_DELETE_TRIGGER, [])?;
+        tx.execute("INSERT INTO entries_fts(entries_fts) VALUES('rebuild')", [])?;
+        tx.commit()
+    }
+
+    /// v6: the old update trigger re-indexed changed entries from the new row, so the
+    /// old text stayed searchable. Replace it and rebuild the index.
+    fn fix_fts_update_trigger(&mut self) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DROP TRIGGER IF EXISTS entries_au", [])?;
+        tx.execute(ENTRIES_UPDATE_TRIGGER, [])?;
+        tx.execute("INSERT INTO entries_fts(entries_fts) VALUES('rebuild')", [])?;
+        tx.commit()
+    }
+
+    /// v4: sqlite-vec used to be an optional download, so `vec_entries` may be missing
+    /// rows or have the old update trigger, which failed on every re-run command.
+    /// Replace the triggers and index every stored embedding that isn't there yet.
+    fn backfill_vec_entries(&mut self) -> Result<()> {
+        let has_vec_table: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'vec_entries')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_vec_table {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction()?;
+        for trigger in ["vec_entries_ai", "vec_entries_au", "vec_entries_ad"] {
+            tx.execute(&format!("DROP TRIGGER IF EXISTS {}", trigger), [])?;
+        }
+        let dimension = embedding_dimension();
+        for trigger in vec_triggers(dimension) {
+            tx.execute(&trigger, [])?;
+        }
+
+        let added = tx.execute(
+            "INSERT INTO vec_entries(entry_id, embedding)
+             SELECT id, embedding FROM entries
+             WHERE length(embedding) = ?1
+               AND id NOT IN (SELECT entry_id FROM vec_entries)",
+            [dimension * 4],
+        )?;
+        if added > 0 {
+            println!("Indexed {} existing embeddings for vector search", added);
+        }
+
+        tx.commit()
+    }
+
+    /// v1: store content as NFC, case-folded where capture is case-insensitive, so
+    /// rows written before normalization match the same queries as new ones
+    fn normalize_existing_content(&mut self) -> Result<()> {
+        let (shell_case_sensitive, clipboard_case_sensitive) = {
+            let settings = lock_or_recover("settings", &GLOBAL_SETTINGS);
+            (settings.shell_case_sensitive, settings.clipboard_case_sensitive)
+        };
+
+        let tx = self.conn.transaction()?;
+        let rows: Vec<(i64, String, String)> = tx
+            .prepare("SELECT id, entry_type, content FROM entries")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<_>>()?;
+
+        let mut changed = 0;
+        {
+            let mut update = tx.prepare("UPDATE entries SET content = ?2 WHERE id = ?1")?;
+            for (id, entry_type, content) in rows {
+                let case_sensitive = match entry_type.as_str() {
+                    "shell" => shell_case_sensitive,
+                    "clipboard" => clipboard_case_sensitive,
+                    _ => true,
+                };
+                let normalized = if case_sensitive {
+                    normalize(&content)
+                } else {
+                    fold(&content)
+                };
+
+                if normalized != content {
+                    update.execute(params![id, normalized])?;
+                    changed += 1;
+                }
+            }
+        }
+
+        // Rebuild rather than trust per-row updates of the external-content index
+        if changed > 0 {
+            tx.execute("INSERT INTO entries_fts(entries_fts) VALUES('rebuild')", [])?;
+            println!("Normalized {} existing entries", changed);
+        }
+
+        tx.commit()
+    }
+}
@@ -1,6 +1,6 @@
 use byteorder::{ByteOrder, LittleEndian};
-use rusqlite::{Connection, Result, params};
-use std::path::PathBuf;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Result, params};
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use once_cell::sync::Lazy;
@@ -8,19 +8,126 @@ use std::sync::Mutex;
 
 mod cache;
 mod db_writer;
+mod embeddings;
+mod encryption;
+mod fts;
+mod import;
+mod journal;
+mod migrations;
+mod pins;
+mod read_pool;
 mod sample_generator;
-
-pub use db_writer::DB_WRITER;
+mod snippets;
+mod stats;
+mod tags;
+#[cfg(test)]
+pub(crate) mod test_db;
+mod vault;
+
+pub use db_writer::{DB_WRITER, write_generation};
+pub use embeddings::embedding_dimension;
+pub use encryption::{decrypt_database, encrypt_database, encryption_enabled, open_db};
+pub use fts::{FtsCheck, fts_tokenizer};
+pub use read_pool::{READ_POOL, ReadConn, open_quick_reader};
+pub use pins::pinned_in;
 pub use sample_generator::{Sample, SampleSelector, SampleStrategy};
+pub use tags::{entry_tags_in, normalize_tag};
+pub use vault::vault_placeholder;
 
 use cache::FingerprintCache;
 
-use crate::types::EntryType;
+use crate::config::{Config, FtsTokenizer, GLOBAL_CONFIG};
+use crate::normalize::{fold, normalize};
+use crate::permissions::{create_private_dir, restrict_db, write_private};
+use crate::types::{CommandRun, Entry, EntryType, QueryParams, RelatedCommand};
 
-const ASSOCIATION_DEPTH: i64 = 3;
 const CLEAN_SESSIONS_DAYS: i64 = 90;
+const DELETE_BATCH_SIZE: usize = 500; // Stay well under SQLite's bound-variable limit
 const CLEAN_OLD_ASSOCIATIONS_DAYS: i64 = 30;
 
+/// Removes a deleted entry's terms from the external-content FTS index. The index
+/// can't look them up once the row is gone, so they are passed with the 'delete' command.
+pub(super) const ENTRIES_DELETE_TRIGGER: &str =
+    "CREATE TRIGGER IF NOT EXISTS entries_ad AFTER DELETE ON entries BEGIN
+        INSERT INTO entries_fts(entries_fts, rowid, content, app_name, window_title, working_dir)
+        VALUES ('delete', old.id, old.content, old.app_name, old.window_title, old.working_dir);
+    END";
+
+/// Re-indexes an updated entry. Like a delete, the old terms have to be passed in
+/// explicitly; an UPDATE of `entries_fts` would look them up in the already-changed row.
+pub(super) const ENTRIES_UPDATE_TRIGGER: &str =
+    "CREATE TRIGGER IF NOT EXISTS entries_au AFTER UPDATE ON entries BEGIN
+        INSERT INTO entries_fts(entries_fts, rowid, content, app_name, window_title, working_dir)
+        VALUES ('delete', old.id, old.content, old.app_name, old.window_title, old.working_dir);
+        INSERT INTO entries_fts(rowid, content, app_name, window_title, working_dir)
+        VALUES (new.id, new.content, new.app_name, new.window_title, new.working_dir);
+    END";
+
+/// Keep `vec_entries` in step with the embeddings in `entries`. vec0 can't replace a
+/// row, so a changed embedding is deleted and inserted again; embeddings of another
+/// dimension are left out rather than failing the write to `entries`.
+pub(super) fn vec_triggers(dimension: usize) -> [String; 3] {
+    let bytes = dimension * 4;
+    [
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS vec_entries_ai AFTER INSERT ON entries
+                WHEN length(new.embedding) = {bytes}
+            BEGIN
+                INSERT INTO vec_entries(entry_id, embedding) VALUES (new.id, new.embedding);
+            END"
+        ),
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS vec_entries_au AFTER UPDATE OF embedding ON entries BEGIN
+                DELETE FROM vec_entries WHERE entry_id = old.id;
+                INSERT INTO vec_entries(entry_id, embedding)
+                SELECT new.id, new.embedding WHERE length(new.embedding) = {bytes};
+            END"
+        ),
+        "CREATE TRIGGER IF NOT EXISTS vec_entries_ad AFTER DELETE ON entries BEGIN
+            DELETE FROM vec_entries WHERE entry_id = old.id;
+        END"
+        .to_string(),
+    ]
+}
+
+/// Room for every hot query (capture, associations, search) to stay prepared
+pub(crate) const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// A captured shell command, as `Database::insert_shell` stores it
+#[derive(Debug, Clone, Copy)]
+pub struct ShellRow<'a> {
+    pub content: &'a str,
+    pub timestamp: u64,
+    pub working_dir: Option<&'a str>,
+    pub user: Option<&'a str>,
+    pub host: Option<&'a str>,
+    pub app_name: &'a str,
+    pub window_title: &'a str,
+    pub run: &'a CommandRun,
+}
+
+impl<'a> ShellRow<'a> {
+    /// A command run at `timestamp` with nothing else known about it
+    pub fn new(content: &'a str, timestamp: u64) -> Self {
+        const UNKNOWN_RUN: &CommandRun = &CommandRun {
+            exit_code: None,
+            duration_ms: None,
+            git_repo: None,
+            git_branch: None,
+        };
+        Self {
+            content,
+            timestamp,
+            working_dir: None,
+            user: None,
+            host: None,
+            app_name: "terminal",
+            window_title: "terminal",
+            run: UNKNOWN_RUN,
+        }
+    }
+}
+
 pub struct Database {
     pub conn: Connection,
     pub cache: FingerprintCache,
@@ -28,17 +135,23 @@ pub struct Database {
 
 impl Database {
     pub fn new() -> Result<Self> {
-        let db_path = Self::get_db_path();
-        let cache_path = Self::get_cache_path();
+        Self::open(&Self::get_db_path(), &Self::get_cache_path())
+    }
+
+    /// Open a database kept in `dir` instead of ~/.jotx (used by `jotx bench`)
+    pub fn open_in(dir: &Path) -> Result<Self> {
+        Self::open(&dir.join("jotx.db"), &dir.join("fingerprint_cache.db"))
+    }
 
+    fn open(db_path: &Path, cache_path: &Path) -> Result<Self> {
         if let Some(parent) = db_path.parent() {
-            std::fs::create_dir_all(parent).ok();
+            create_private_dir(parent).ok();
         }
 
-        let conn = Connection::open(db_path)?;
+        let conn = open_db(db_path, OpenFlags::default())?;
         let cache;
 
-        match FingerprintCache::new(cache_path) {
+        match FingerprintCache::new(cache_path.to_path_buf()) {
             Ok(c) => cache = c,
             Err(e) => {
                 println!("Failed to create cache: {}", e);
@@ -50,10 +163,19 @@ impl Database {
 
         conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.pragma_update(None, "synchronous", "NORMAL")?;
+        // Zero freed pages so deleted and vaulted text doesn't linger in the file
+        conn.pragma_update(None, "secure_delete", true)?;
         conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+        // History must not be readable by other users, whatever the umask
+        if let Err(e) = restrict_db(db_path).and_then(|_| restrict_db(cache_path)) {
+            eprintln!("⚠️  Failed to restrict database permissions: {}", e);
+        }
 
-        let db = Database { conn, cache };
+        let mut db = Database { conn, cache };
         db.init_schema()?;
+        db.migrate()?;
         Ok(db)
     }
 
@@ -70,46 +192,9 @@ impl Database {
     }
 
     fn init_schema(&self) -> Result<()> {
-        unsafe {
-            self.conn.load_extension_enable()?;
-
-            // Build list of paths to try
-            let mut extension_paths = Vec::new();
-
-            if cfg!(target_os = "macos") {
-                if let Ok(home) = std::env::var("HOME") {
-                    extension_paths.push(format!("{}/.local/lib/vec0", home));
-                }
-                extension_paths.push("/usr/local/lib/vec0".to_string());
-            } else if cfg!(target_os = "linux") {
-                if let Ok(home) = std::env::var("HOME") {
-                    extension_paths.push(format!("{}/.local/lib/vec0", home));
-                }
-                extension_paths.push("/usr/local/lib/vec0".to_string());
-            } else if cfg!(target_os = "windows") {
-                if let Ok(localappdata) = std::env::var("LOCALAPPDATA") {
-                    extension_paths.push(format!("{}\\sqlite-vec\\vec0", localappdata));
-                }
-            }
-
-            // Always try bare "vec0" as last resort
-            extension_paths.push("vec0".to_string());
-
-            let mut loaded = false;
-            for path in &extension_paths {
-                match self.conn.load_extension(path, None::<&str>) {
-                    Ok(_) => {
-                        loaded = true;
-                        break;
-                    }
-                    Err(_) => continue,
-                }
-            }
-
-            if !loaded {
-                eprintln!("⚠ sqlite-vec not available. Vector search will use fallback.");
-                eprintln!("  Install from: https://github.com/asg017/sqlite-vec");
-            }
+        let vec_loaded = load_vec_extension(&self.conn)?;
+        if !vec_loaded {
+            eprintln!("⚠ sqlite-vec not available. Vector search will use fallback.");
         }
 
         // Main entries table
@@ -130,6 +215,13 @@ impl Database {
                 app_name TEXT,
                 window_title TEXT,
                 
+                -- Last run of a shell command, when the hook reported it
+                exit_code INTEGER,
+                duration_ms INTEGER,
+                
+                -- Pinned entries are kept when old history is trimmed
+                pinned INTEGER NOT NULL DEFAULT 0,
+                
                 quality_score INTEGER DEFAULT 0,
                 embedding BLOB,
                 
@@ -165,45 +257,36 @@ impl Database {
             [],
         )?;
 
+        // A new embeddings.dimension needs a new index; the old vectors can't go in it
+        let dimension = embedding_dimension();
+        let previous = self.vec_index_dimension()?;
+        if vec_loaded && let Some(previous) = previous.filter(|&previous| previous != dimension) {
+            eprintln!(
+                "⚠ Embeddings are now {} floats instead of {}; rebuilding the vector index.",
+                dimension, previous
+            );
+            eprintln!("  Run `jotx reindex` to re-embed existing entries with the new model.");
+            self.drop_vec_index()?;
+        }
+
         match self.conn.execute(
-            "CREATE VIRTUAL TABLE IF NOT EXISTS vec_entries USING vec0(
-            entry_id INTEGER PRIMARY KEY,
-            embedding FLOAT[384]
-        )",
+            &format!(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS vec_entries USING vec0(
+                    entry_id INTEGER PRIMARY KEY,
+                    embedding FLOAT[{}]
+                )",
+                dimension
+            ),
             [],
         ) {
             Ok(_) => {
-                // Optional: Create triggers to keep vec_entries in sync with entries
-                self.conn.execute(
-                    "CREATE TRIGGER IF NOT EXISTS vec_entries_ai 
-                 AFTER INSERT ON entries 
-                 WHEN new.embedding IS NOT NULL
-                 BEGIN
-                    INSERT INTO vec_entries(entry_id, embedding)
-                    VALUES (new.id, new.embedding);
-                 END",
-                    [],
-                )?;
-
-                self.conn.execute(
-                    "CREATE TRIGGER IF NOT EXISTS vec_entries_au 
-                 AFTER UPDATE ON entries 
-                 WHEN new.embedding IS NOT NULL
-                 BEGIN
-                    INSERT OR REPLACE INTO vec_entries(entry_id, embedding)
-                    VALUES (new.id, new.embedding);
-                 END",
-                    [],
-                )?;
-
-                self.conn.execute(
-                    "CREATE TRIGGER IF NOT EXISTS vec_entries_ad 
-                 AFTER DELETE ON entries 
-                 BEGIN
-                    DELETE FROM vec_entries WHERE entry_id = old.id;
-                 END",
-                    [],
-                )?;
+                for trigger in vec_triggers(dimension) {
+                    self.conn.execute(&trigger, [])?;
+                }
+                if previous.is_some_and(|previous| previous != dimension) {
+                    // Entries already embedded at the new dimension go straight back in
+                    self.rebuild_vec_index()?;
+                }
             }
             Err(e) => {
                 eprintln!(
@@ -214,17 +297,11 @@ impl Database {
         }
 
         // FTS5 table
-        self.conn.execute(
-            "CREATE VIRTUAL TABLE IF NOT EXISTS entries_fts USING fts5(
-                content,
-                app_name,
-                window_title,
-                working_dir,
-                content='entries',
-                content_rowid='id'
-            )",
-            [],
-        )?;
+        let tokenizer = match GLOBAL_CONFIG.read() {
+            Ok(config) => config.search.fts_tokenizer,
+            Err(_) => FtsTokenizer::default(),
+        };
+        self.create_fts(tokenizer)?;
 
         // Triggers
         self.conn.execute(
@@ -235,24 +312,9 @@ impl Database {
             [],
         )?;
 
-        self.conn.execute(
-            "CREATE TRIGGER IF NOT EXISTS entries_ad AFTER DELETE ON entries BEGIN
-                DELETE FROM entries_fts WHERE rowid = old.id;
-            END",
-            [],
-        )?;
+        self.conn.execute(ENTRIES_DELETE_TRIGGER, [])?;
 
-        self.conn.execute(
-            "CREATE TRIGGER IF NOT EXISTS entries_au AFTER UPDATE ON entries BEGIN
-                UPDATE entries_fts 
-                SET content = new.content,
-                    app_name = new.app_name,
-                    window_title = new.window_title,
-                    working_dir = new.working_dir
-                WHERE rowid = new.id;
-            END",
-            [],
-        )?;
+        self.conn.execute(ENTRIES_UPDATE_TRIGGER, [])?;
 
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS command_associations (
@@ -301,6 +363,45 @@ impl Database {
             [],
         )?;
 
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS entry_tags (
+                entry_id INTEGER NOT NULL,
+                tag TEXT NOT NULL,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                PRIMARY KEY (entry_id, tag),
+                FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_entry_tags_tag ON entry_tags(tag)",
+            [],
+        )?;
+
+        // Encrypted content of vaulted entries (entries.content holds a placeholder)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS vault_entries (
+                entry_id INTEGER PRIMARY KEY,
+                ciphertext BLOB NOT NULL,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        // Reusable commands saved from history; placeholders is a JSON array
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS snippets (
+                name TEXT PRIMARY KEY,
+                template TEXT NOT NULL,
+                placeholders TEXT NOT NULL,
+                source_entry_id INTEGER,
+                created_at INTEGER DEFAULT (strftime('%s', 'now'))
+            )",
+            [],
+        )?;
+
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS prompt_examples (
                 query TEXT PRIMARY KEY,
@@ -324,7 +425,7 @@ impl Database {
         app_name: &str,
         window_title: &str,
         embedding: Option<Vec<f32>>,
-    ) -> Result<()> {
+    ) -> Result<i64> {
         let embedding_blob: Option<Vec<u8>> = embedding.map(|vec| {
             let mut blob = vec![0u8; vec.len() * 4];
             LittleEndian::write_f32_into(&vec, &mut blob);
@@ -343,22 +444,53 @@ impl Database {
                 embedding_blob
             ),
         )?;
-        Ok(())
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Store a note written with `jotx note`. Notes are never trimmed by cleanup.
+    pub fn insert_note(
+        &self,
+        content: &str,
+        timestamp: u64,
+        working_dir: Option<&str>,
+        user: Option<&str>,
+        host: Option<&str>,
+        embedding: Option<Vec<f32>>,
+    ) -> Result<i64> {
+        let embedding_blob: Option<Vec<u8>> = embedding.map(|vec| {
+            let mut blob = vec![0u8; vec.len() * 4];
+            LittleEndian::write_f32_into(&vec, &mut blob);
+            blob
+        });
+
+        self.conn.execute(
+            "INSERT INTO entries (entry_type, content, timestamp, working_dir, user, host, embedding)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                EntryType::Note.to_string(),
+                content,
+                timestamp as i64,
+                working_dir,
+                user,
+                host,
+                embedding_blob,
+            ],
+        )?;
+        db_writer::note_write();
+        Ok(self.conn.last_insert_rowid())
     }
 
     // Check if shell command exists and return its ID
     pub fn get_shell_command_id(&self, content: &str) -> Result<Option<i64>> {
-        let result = self.conn.query_row(
-            "SELECT id FROM entries WHERE entry_type = ?1 AND content = ?2",
-            (EntryType::Shell, content),
-            |row| row.get(0),
-        );
+        shell_command_id(&self.conn, content)
+    }
 
-        match result {
-            Ok(id) => Ok(Some(id)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }
+    /// Record the repository and branch a shell entry last ran in
+    pub fn set_git_context(&self, id: i64, repo: &str, branch: Option<&str>) -> Result<()> {
+        self.conn
+            .prepare_cached("UPDATE entries SET git_repo = ?2, git_branch = ?3 WHERE id = ?1")?
+            .execute(rusqlite::params![id, repo, branch])?;
+        Ok(())
     }
 
     // Increment times_run for existing entry
@@ -371,17 +503,9 @@ impl Database {
         Ok(())
     }
 
-    pub fn insert_shell(
-        &self,
-        content: &str,
-        timestamp: u64,
-        working_dir: Option<&str>,
-        user: Option<&str>,
-        host: Option<&str>,
-        app_name: &str,
-        window_title: &str,
-        embedding: Option<Vec<f32>>,
-    ) -> Result<()> {
+    /// Store a captured command, or fold it into the latest entry with the same
+    /// content. Returns the entry's id.
+    pub fn insert_shell(&self, row: &ShellRow, embedding: Option<Vec<f32>>) -> Result<i64> {
         let embedding_blob: Option<Vec<u8>> = embedding.map(|vec| {
             let mut blob = vec![0u8; vec.len() * 4];
             LittleEndian::write_f32_into(&vec, &mut blob);
@@ -391,103 +515,116 @@ impl Database {
         // Check if command exists with same content
         let existing: Option<(i64, Option<String>)> = self
             .conn
-            .query_row(
+            .prepare_cached(
                 "SELECT id, working_dir FROM entries 
              WHERE entry_type = 'shell' 
              AND content = ?1
              ORDER BY timestamp DESC
              LIMIT 1",
-                [content],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
+            )?
+            .query_row([row.content], |r| Ok((r.get(0)?, r.get(1)?)))
             .ok();
 
-        let entry_id = if let Some((id, existing_working_dir)) = existing {
-            // Check if existing working dir is null/empty
-            let existing_dir_empty = existing_working_dir
-                .as_ref()
-                .map(|h| h.trim().is_empty())
-                .unwrap_or(true);
-
-            if existing_dir_empty {
-                // Old entry has no working dir - update with new working dir info, DON'T increment times_run
-                self.conn.execute(
-                    "UPDATE entries 
-                    SET host = ?2,
-                     working_dir = ?3,
-                     user = ?4,
-                     app_name = ?5,
-                     window_title = ?6,
-                     timestamp = ?7,
-                     updated_at = strftime('%s', 'now')
-                 WHERE id = ?1",
-                    rusqlite::params![
-                        id,
-                        host,
-                        working_dir,
-                        user,
-                        app_name,
-                        window_title,
-                        timestamp as i64
-                    ],
-                )?;
-                id
-            } else if existing_working_dir == working_dir.map(|h| h.to_string()) {
-                // Same command + same working dir: increment times_run
-                self.conn.execute(
-                    "UPDATE entries 
-                 SET times_run = times_run + 1, 
-                     updated_at = strftime('%s', 'now'),
-                     timestamp = ?2
-                 WHERE id = ?1",
-                    rusqlite::params![id, timestamp as i64],
-                )?;
-                id
-            } else {
-                // Different working dir: insert as new entry
-                self.conn.execute(
-                "INSERT INTO entries (entry_type, content, timestamp, working_dir, user, host, app_name, window_title, embedding)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-                rusqlite::params![
-                    EntryType::Shell.to_string(),
-                    content,
-                    timestamp as i64,
-                    working_dir,
-                    user,
-                    host,
-                    app_name,
-                    window_title,
-                    embedding_blob,
-                ],
-            )?;
-                self.conn.last_insert_rowid()
+        // The existing entry to update, if any
+        let updated = match existing {
+            Some((id, existing_working_dir)) => {
+                // Check if existing working dir is null/empty
+                let existing_dir_empty = existing_working_dir
+                    .as_ref()
+                    .map(|h| h.trim().is_empty())
+                    .unwrap_or(true);
+
+                if existing_dir_empty {
+                    // Old entry has no working dir - update with new working dir info, DON'T increment times_run
+                    self.conn.prepare_cached(
+                        "UPDATE entries 
+                        SET host = ?2,
+                         working_dir = ?3,
+                         user = ?4,
+                         app_name = ?5,
+                         window_title = ?6,
+                         timestamp = ?7,
+                         exit_code = ?8,
+                         duration_ms = ?9,
+                         updated_at = strftime('%s', 'now')
+                     WHERE id = ?1",
+                    )?.execute(
+                        rusqlite::params![
+                            id,
+                            row.host,
+                            row.working_dir,
+                            row.user,
+                            row.app_name,
+                            row.window_title,
+                            row.timestamp as i64,
+                            row.run.exit_code,
+                            row.run.duration_ms.map(|ms| ms as i64)
+                        ],
+                    )?;
+                    Some(id)
+                } else if existing_working_dir.as_deref() == row.working_dir {
+                    // Same command + same working dir: increment times_run, keep the latest outcome
+                    self.conn
+                        .prepare_cached(
+                            "UPDATE entries 
+                     SET times_run = times_run + 1, 
+                         updated_at = strftime('%s', 'now'),
+                         timestamp = ?2,
+                         exit_code = ?3,
+                         duration_ms = ?4
+                     WHERE id = ?1",
+                        )?
+                        .execute(rusqlite::params![
+                            id,
+                            row.timestamp as i64,
+                            row.run.exit_code,
+                            row.run.duration_ms.map(|ms| ms as i64)
+                        ])?;
+                    Some(id)
+                } else {
+                    // Different working dir: insert as new entry
+                    None
+                }
             }
-        } else {
             // New command: insert
-            self.conn.execute(
-            "INSERT INTO entries (entry_type, content, timestamp, working_dir, user, host, app_name, window_title, embedding)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            rusqlite::params![
-                EntryType::Shell.to_string(),
-                content,
-                timestamp as i64,
-                working_dir,
-                user,
-                host,
-                app_name,
-                window_title,
-                embedding_blob,
-            ],
-        )?;
-            self.conn.last_insert_rowid()
+            None => None,
+        };
+
+        let entry_id = match updated {
+            Some(id) => id,
+            None => {
+                self.conn
+                    .prepare_cached(
+                        "INSERT INTO entries (entry_type, content, timestamp, working_dir, user, host, app_name, window_title, exit_code, duration_ms, embedding)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    )?
+                    .execute(rusqlite::params![
+                        EntryType::Shell.to_string(),
+                        row.content,
+                        row.timestamp as i64,
+                        row.working_dir,
+                        row.user,
+                        row.host,
+                        row.app_name,
+                        row.window_title,
+                        row.run.exit_code,
+                        row.run.duration_ms.map(|ms| ms as i64),
+                        embedding_blob,
+                    ])?;
+                self.conn.last_insert_rowid()
+            }
         };
 
+        if let Some(repo) = &row.run.git_repo {
+            self.set_git_context(entry_id, repo, row.run.git_branch.as_deref())?;
+        }
         self.track_associations_only(entry_id)?;
-        Ok(())
+        Ok(entry_id)
     }
 
     pub fn cleanup_old_entries(&self, clipboard_limit: usize, shell_limit: usize) -> Result<()> {
-        // Clean up old clipboard entries
+        // Clean up old clipboard entries (pinned and vaulted entries are kept until
+        // deleted explicitly)
         self.conn.execute(
             "DELETE FROM entries 
              WHERE entry_type = ?1 
@@ -496,7 +633,9 @@ impl Database {
                  WHERE entry_type = ?1 
                  ORDER BY timestamp DESC 
                  LIMIT ?2
-             )",
+             )
+             AND pinned = 0
+             AND id NOT IN (SELECT entry_id FROM vault_entries)",
             (EntryType::Clipboard, clipboard_limit),
         )?;
 
@@ -509,13 +648,71 @@ impl Database {
                  WHERE entry_type = ?1 
                  ORDER BY timestamp DESC 
                  LIMIT ?2
-             )",
+             )
+             AND pinned = 0
+             AND id NOT IN (SELECT entry_id FROM vault_entries)",
             (EntryType::Shell, shell_limit),
         )?;
+        db_writer::note_write();
+
+        // Foreign keys are not enforced, so drop tags of trimmed entries by hand
+        self.conn.execute(
+            "DELETE FROM entry_tags WHERE entry_id NOT IN (SELECT id FROM entries)",
+            [],
+        )?;
 
         Ok(())
     }
 
+    /// Delete entries by id together with their session, tag, vault and association
+    /// rows, in one transaction; triggers drop them from the FTS and vector indexes.
+    /// Returns the number of entries deleted.
+    pub fn delete_entries(&self, ids: &[i64]) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut deleted = 0;
+
+        for batch in ids.chunks(DELETE_BATCH_SIZE) {
+            let placeholders = (1..=batch.len())
+                .map(|i| format!("?{}", i))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            // Foreign keys are not enforced, so cascade by hand
+            tx.execute(
+                &format!("DELETE FROM command_sessions WHERE entry_id IN ({})", placeholders),
+                rusqlite::params_from_iter(batch),
+            )?;
+
+            tx.execute(
+                &format!("DELETE FROM entry_tags WHERE entry_id IN ({})", placeholders),
+                rusqlite::params_from_iter(batch),
+            )?;
+
+            tx.execute(
+                &format!("DELETE FROM vault_entries WHERE entry_id IN ({})", placeholders),
+                rusqlite::params_from_iter(batch),
+            )?;
+
+            tx.execute(
+                &format!(
+                    "DELETE FROM command_associations
+                     WHERE command_a_id IN ({0}) OR command_b_id IN ({0})",
+                    placeholders
+                ),
+                rusqlite::params_from_iter(batch),
+            )?;
+
+            deleted += tx.execute(
+                &format!("DELETE FROM entries WHERE id IN ({})", placeholders),
+                rusqlite::params_from_iter(batch),
+            )?;
+        }
+
+        tx.commit()?;
+        db_writer::note_write();
+        Ok(deleted)
+    }
+
     /// Remove weak associations that haven't been reinforced
     /// Deletes associations with strength < 2 that are older than 30 days
     pub fn cleanup_weak_associations(&self) -> Result<usize> {
@@ -563,6 +760,7 @@ impl Database {
     pub fn run_maintenance(&self) -> Result<()> {
         let associations_deleted = self.cleanup_weak_associations()?;
         let sessions_deleted = self.cleanup_old_sessions()?;
+        let fts = self.repair_fts()?;
 
         // Also vacuum to reclaim disk space
         self.conn.execute("VACUUM", [])?;
@@ -570,6 +768,12 @@ impl Database {
         println!("🧹 Maintenance complete:");
         println!("  - Removed {} weak associations", associations_deleted);
         println!("  - Removed {} old sessions", sessions_deleted);
+        if !fts.is_healthy() {
+            println!(
+                "  - Re-indexed search ({} missing, {} stale rows)",
+                fts.missing, fts.orphaned
+            );
+        }
 
         Ok(())
     }
@@ -607,10 +811,10 @@ impl Database {
 
         // Ensure directory exists
         if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
+            create_private_dir(parent)?;
         }
 
-        std::fs::write(path, now.to_string())
+        write_private(&path, now.to_string())
     }
 
     fn get_maintenance_file_path(&self) -> PathBuf {
@@ -619,7 +823,10 @@ impl Database {
     }
 
     pub fn get_or_create_session_id(&self) -> Result<String> {
-        const SESSION_TIMEOUT: i64 = 300; // 5 minutes in seconds
+        let session_timeout = match GLOBAL_CONFIG.read() {
+            Ok(config) => config.storage.session_timeout_secs,
+            Err(_) => Config::default().storage.session_timeout_secs,
+        };
 
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -629,20 +836,19 @@ impl Database {
         // Try to get the most recent session
         let last_session: Option<(String, i64)> = self
             .conn
-            .query_row(
+            .prepare_cached(
                 "SELECT session_id, MAX(timestamp) as last_time
              FROM command_sessions
              GROUP BY session_id
              ORDER BY last_time DESC
              LIMIT 1",
-                [],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
+            )?
+            .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))
             .ok();
 
         // If last command was within timeout, reuse session
         if let Some((session_id, last_time)) = last_session {
-            if now - last_time < SESSION_TIMEOUT {
+            if now - last_time < session_timeout {
                 return Ok(session_id);
             }
         }
@@ -657,28 +863,35 @@ impl Database {
             .unwrap_or_default()
             .as_secs() as i64;
 
+        let association_depth = match GLOBAL_CONFIG.read() {
+            Ok(config) => config.storage.association_depth,
+            Err(_) => Config::default().storage.association_depth,
+        };
+
         // Get or create session
         let session_id = self.get_or_create_session_id()?;
 
         // Get position in this session
-        let position: i64 = self.conn.query_row(
-            "SELECT COALESCE(MAX(position), -1) + 1 
+        let position: i64 = self
+            .conn
+            .prepare_cached(
+                "SELECT COALESCE(MAX(position), -1) + 1 
          FROM command_sessions WHERE session_id = ?1",
-            params![session_id],
-            |row| row.get(0),
-        )?;
+            )?
+            .query_row(params![session_id], |row| row.get(0))?;
 
         // Add to current session
-        self.conn.execute(
-            "INSERT INTO command_sessions (entry_id, session_id, position, timestamp)
+        self.conn
+            .prepare_cached(
+                "INSERT INTO command_sessions (entry_id, session_id, position, timestamp)
          VALUES (?1, ?2, ?3, ?4)",
-            params![entry_id, session_id, position, now],
-        )?;
+            )?
+            .execute(params![entry_id, session_id, position, now])?;
 
         // Update associations with recent commands in this session
         let recent_commands: Vec<i64> = self
             .conn
-            .prepare(
+            .prepare_cached(
                 "SELECT entry_id FROM command_sessions 
              WHERE session_id = ?1 AND position < ?2 AND position >= ?3
              ORDER BY position DESC",
@@ -687,55 +900,255 @@ impl Database {
                 params![
                     session_id,
                     position,
-                    position.saturating_sub(ASSOCIATION_DEPTH)
+                    position.saturating_sub(association_depth)
                 ],
                 |row| row.get(0),
             )?
             .collect::<Result<Vec<_>, _>>()?;
 
-        for (idx, prev_command_id) in recent_commands.iter().enumerate() {
-            let sequence_order = (recent_commands.len() - idx) as i64;
-
-            self.conn.execute(
-                "INSERT INTO command_associations 
+        let mut upsert = self.conn.prepare_cached(
+            "INSERT INTO command_associations 
              (command_a_id, command_b_id, sequence_order, strength, last_seen)
              VALUES (?1, ?2, ?3, 1, ?4)
              ON CONFLICT(command_a_id, command_b_id, sequence_order) 
              DO UPDATE SET 
                 strength = strength + 1,
                 last_seen = ?4",
-                params![prev_command_id, entry_id, sequence_order, now],
-            )?;
+        )?;
+        // recent_commands is newest first, so the command just before is 1 step back
+        for (idx, prev_command_id) in recent_commands.iter().enumerate() {
+            let sequence_order = idx as i64 + 1;
+            upsert.execute(params![prev_command_id, entry_id, sequence_order, now])?;
         }
 
         Ok(())
     }
 
-    // // Get related commands for a given command
-    // pub fn get_related_commands(&self, entry_id: i64, limit: usize) -> Result<Vec<RelatedCommand>> {
-    //     let mut stmt = self.conn.prepare(
-    //         "SELECT e.id, e.content, a.strength, a.sequence_order, a.last_seen
-    //      FROM command_associations a
-    //      JOIN entries e ON e.id = a.command_b_id
-    //      WHERE a.command_a_id = ?1
-    //      ORDER BY a.strength DESC, a.last_seen DESC
-    //      LIMIT ?2",
-    //     )?;
+    /// Load entries matching the given filters, newest first.
+    /// Embeddings are not loaded.
+    pub fn query_entries(&self, params: &QueryParams) -> Result<Vec<Entry>> {
+        let (where_clause, mut bind_params) = Self::build_entry_filters(params);
 
-    //     let results = stmt
-    //         .query_map(params![entry_id, limit], |row| {
-    //             Ok(RelatedCommand {
-    //                 id: row.get(0)?,
-    //                 content: row.get(1)?,
-    //                 strength: row.get(2)?,
-    //                 sequence_order: row.get(3)?,
-    //                 last_seen: row.get(4)?,
-    //             })
-    //         })?
-    //         .collect::<Result<Vec<_>, _>>()?;
-
-    //     Ok(results)
-    // }
+        let sql = format!(
+            "SELECT id, entry_type, content, timestamp, times_run, working_dir, git_repo,
+                    git_branch, user, host, app_name, window_title, exit_code, duration_ms
+             FROM entries
+             WHERE {}
+             ORDER BY timestamp DESC
+             LIMIT ?{} OFFSET ?{}",
+            where_clause,
+            bind_params.len() + 1,
+            bind_params.len() + 2
+        );
+
+        // SQLite treats a negative LIMIT as "no limit"
+        let limit = params.limit.map(|l| l as i64).unwrap_or(-1);
+        bind_params.push(Box::new(limit));
+        bind_params.push(Box::new(params.offset.unwrap_or(0) as i64));
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            bind_params.iter().map(|b| b.as_ref()).collect();
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let entries = stmt
+            .query_map(params_refs.as_slice(), Self::entry_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    /// Load a single entry by id (without its embedding)
+    pub fn get_entry(&self, id: i64) -> Result<Option<Entry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, entry_type, content, timestamp, times_run, working_dir, git_repo,
+                    git_branch, user, host, app_name, window_title, exit_code, duration_ms
+             FROM entries
+             WHERE id = ?1",
+        )?;
+
+        let mut rows = stmt.query_map([id], Self::entry_from_row)?;
+        rows.next().transpose()
+    }
+
+    /// Map a row selected with the column list used by `query_entries`/`get_entry`
+    fn entry_from_row(row: &rusqlite::Row) -> Result<Entry> {
+        Ok(Entry {
+            id: row.get(0)?,
+            entry_type: row.get(1)?,
+            content: row.get(2)?,
+            timestamp: row.get(3)?,
+            times_run: row.get(4)?,
+            working_dir: row.get(5)?,
+            git_repo: row.get(6)?,
+            git_branch: row.get(7)?,
+            user: row.get(8)?,
+            host: row.get(9)?,
+            app_name: row.get(10)?,
+            window_title: row.get(11)?,
+            exit_code: row.get(12)?,
+            duration_ms: row.get(13)?,
+            embedding: None,
+        })
+    }
+
+    /// Count entries matching the given filters (limit/offset are ignored)
+    pub fn count_entries(&self, params: &QueryParams) -> Result<usize> {
+        let (where_clause, bind_params) = Self::build_entry_filters(params);
+        let sql = format!("SELECT COUNT(*) FROM entries WHERE {}", where_clause);
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            bind_params.iter().map(|b| b.as_ref()).collect();
+
+        let count: i64 = self
+            .conn
+            .query_row(&sql, params_refs.as_slice(), |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    fn build_entry_filters(params: &QueryParams) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+        let mut where_clauses = vec!["1 = 1".to_string()];
+        let mut bind_params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(ref entry_type) = params.entry_type
+            && *entry_type != EntryType::Any
+        {
+            bind_params.push(Box::new(entry_type.to_string()));
+            where_clauses.push(format!("entry_type = ?{}", bind_params.len()));
+        }
+
+        if let Some(ref content) = params.content_search {
+            bind_params.push(Box::new(format!("%{}%", content)));
+            where_clauses.push(format!("content LIKE ?{}", bind_params.len()));
+        }
+
+        let exact_filters = [
+            ("working_dir", &params.working_dir),
+            ("app_name", &params.app_name),
+            ("user", &params.user),
+            ("host", &params.host),
+        ];
+
+        for (column, value) in exact_filters {
+            if let Some(value) = value {
+                bind_params.push(Box::new(value.clone()));
+                where_clauses.push(format!("{} = ?{}", column, bind_params.len()));
+            }
+        }
+
+        if let Some(ref tag) = params.tag {
+            bind_params.push(Box::new(tag.clone()));
+            where_clauses.push(format!(
+                "id IN (SELECT entry_id FROM entry_tags WHERE tag = ?{})",
+                bind_params.len()
+            ));
+        }
+
+        if let Some(since) = params.since {
+            bind_params.push(Box::new(since));
+            where_clauses.push(format!("timestamp >= ?{}", bind_params.len()));
+        }
+
+        if let Some(until) = params.until {
+            bind_params.push(Box::new(until));
+            where_clauses.push(format!("timestamp < ?{}", bind_params.len()));
+        }
+
+        (where_clauses.join(" AND "), bind_params)
+    }
+
+    /// Commands usually run after the shell command `content` (stored in any
+    /// directory), strongest first. Association strength is weighted by how close
+    /// behind the command came, and fades with `search.recency_half_life_hours`.
+    pub fn get_related_commands(&self, content: &str, limit: usize) -> Result<Vec<RelatedCommand>> {
+        let half_life_hours = match GLOBAL_CONFIG.read() {
+            Ok(config) => config.search.recency_half_life_hours,
+            Err(_) => Config::default().search.recency_half_life_hours,
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        // Capture stores content normalized, and folded when shell case sensitivity is off
+        let mut stmt = self.conn.prepare(
+            "SELECT MAX(e.id), e.content, SUM(a.strength), MIN(a.sequence_order),
+                    MAX(a.last_seen), SUM(a.strength * 1.0 / MAX(a.sequence_order, 1))
+             FROM command_associations a
+             JOIN entries src ON src.id = a.command_a_id
+             JOIN entries e ON e.id = a.command_b_id
+             WHERE src.entry_type = ?1 AND src.content IN (?2, ?3) AND e.content != src.content
+             GROUP BY e.content",
+        )?;
+        let mut related = stmt
+            .query_map(
+                params![EntryType::Shell, normalize(content), fold(content)],
+                |row| {
+                    let weight: f64 = row.get(5)?;
+                    let last_seen: i64 = row.get(4)?;
+                    let recency = if half_life_hours > 0.0 {
+                        0.5f64.powf((now - last_seen).max(0) as f64 / 3600.0 / half_life_hours)
+                    } else {
+                        0.0
+                    };
+                    Ok(RelatedCommand {
+                        id: row.get(0)?,
+                        content: row.get(1)?,
+                        strength: row.get(2)?,
+                        sequence_order: row.get(3)?,
+                        last_seen,
+                        score: weight * (1.0 + recency),
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        related.sort_by(|a, b| b.score.total_cmp(&a.score));
+        related.truncate(limit);
+        Ok(related)
+    }
+
+    /// The most recently captured shell command
+    pub fn last_shell_command(&self) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT content FROM entries WHERE entry_type = ?1 ORDER BY timestamp DESC, id DESC LIMIT 1",
+                [EntryType::Shell],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// The shell commands of every session active since `since`, in the order they
+    /// were run. Vaulted entries and hashed commands are left out.
+    pub fn session_commands(&self, since: i64) -> Result<Vec<Vec<String>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.session_id, e.content
+             FROM command_sessions s
+             JOIN entries e ON e.id = s.entry_id
+             WHERE s.timestamp >= ?1
+               AND e.entry_type = ?2
+               AND e.content NOT LIKE '#hashed:%'
+               AND e.id NOT IN (SELECT entry_id FROM vault_entries)
+             ORDER BY s.session_id, s.position",
+        )?;
+        let rows = stmt.query_map(params![since, EntryType::Shell], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut sessions: Vec<Vec<String>> = Vec::new();
+        let mut current: Option<String> = None;
+        for row in rows {
+            let (session_id, content) = row?;
+            if current.as_deref() != Some(session_id.as_str()) {
+                sessions.push(Vec::new());
+                current = Some(session_id);
+            }
+            if let Some(session) = sessions.last_mut() {
+                session.push(content);
+            }
+        }
+        Ok(sessions)
+    }
 
     // Get count of entries by type
     // pub fn get_entry_count(&self, entry_type: EntryType) -> Result<usize> {
@@ -748,15 +1161,46 @@ impl Database {
     // }
 }
 
+/// Register sqlite-vec, which is compiled into jotx, on `conn`. Returns whether its
+/// functions and the vec0 module are available afterwards.
+pub(crate) fn load_vec_extension(conn: &Connection) -> Result<bool> {
+    type ExtensionInit = unsafe extern "C" fn(
+        *mut rusqlite::ffi::sqlite3,
+        *mut *mut std::os::raw::c_char,
+        *const rusqlite::ffi::sqlite3_api_routines,
+    ) -> std::os::raw::c_int;
+
+    // The crate declares the entry point without arguments; it has the usual
+    // extension signature. Built with SQLITE_CORE, it doesn't need the API routines.
+    let rc = unsafe {
+        let init: ExtensionInit = std::mem::transmute(sqlite_vec::sqlite3_vec_init as *const ());
+        init(conn.handle(), std::ptr::null_mut(), std::ptr::null())
+    };
+    if rc != rusqlite::ffi::SQLITE_OK {
+        return Ok(false);
+    }
+
+    Ok(conn
+        .query_row("SELECT vec_version()", [], |row| row.get::<_, String>(0))
+        .is_ok())
+}
+
 pub static USER_DB: Lazy<Mutex<Database>> =
     Lazy::new(|| Mutex::new(Database::new().expect("Failed to initialize user database")));
 
-pub static CLIPBOARD_DB: Lazy<Mutex<Database>> =
-    Lazy::new(|| Mutex::new(Database::new().expect("Failed to init clipboard DB")));
-
-// Shell monitor thread
-pub static SHELL_DB: Lazy<Mutex<Database>> =
-    Lazy::new(|| Mutex::new(Database::new().expect("Failed to init shell DB")));
+/// Id of the shell entry with exactly this content, on any connection (including
+/// read-only ones from `READ_POOL`)
+pub fn shell_command_id(conn: &Connection, content: &str) -> Result<Option<i64>> {
+    let result = conn
+        .prepare_cached("SELECT id FROM entries WHERE entry_type = ?1 AND content = ?2")?
+        .query_row((EntryType::Shell, content), |row| row.get(0));
+
+    match result {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
 
 pub fn get_db_path() -> PathBuf {
     let home = std::env::var("HOME").expect("HOME not set");
@@ -766,6 +1210,7 @@ pub fn get_db_path() -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_db::TempDb;
 
     #[tokio::test]
     async fn test_db() {
@@ -775,4 +1220,106 @@ mod tests {
             Err(e) => println!("Failed to initialize schema: {}", e),
         }
     }
+
+    #[test]
+    fn test_delete_entries_clears_fts() {
+        let db = TempDb::new("delete");
+        for content in ["export TOKEN=hunter2", "git status"] {
+            db.shell(ShellRow::new(content, 1));
+        }
+        let id = db.get_shell_command_id("export TOKEN=hunter2").unwrap().unwrap();
+
+        assert_eq!(db.delete_entries(&[id]).unwrap(), 1);
+        let matches: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM entries_fts WHERE entries_fts MATCH 'hunter2'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(matches, 0);
+        assert!(db.check_fts().unwrap().is_healthy());
+    }
+
+    #[test]
+    fn test_update_entries_reindexes_fts() {
+        let db = TempDb::new("update");
+        let id = db.shell(ShellRow::new("export TOKEN=hunter2", 1));
+        db.shell(ShellRow::new("git status", 2));
+
+        db.conn
+            .execute(
+                "UPDATE entries SET content = 'export TOKEN' WHERE id = ?1",
+                [id],
+            )
+            .unwrap();
+        let count = |query: &str| -> i64 {
+            db.conn
+                .query_row(
+                    "SELECT COUNT(*) FROM entries_fts WHERE entries_fts MATCH ?1",
+                    [query],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(count("hunter2"), 0);
+        assert_eq!(count("token"), 1);
+        assert!(db.check_fts().unwrap().is_healthy());
+    }
+
+    #[test]
+    fn test_notes_outlive_cleanup() {
+        let db = TempDb::new("note");
+        let id = db
+            .insert_note("staging db password rotates on fridays", 1, Some("/srv/app"), None, None, None)
+            .unwrap();
+        db.shell(ShellRow::new("ls", 2));
+
+        db.cleanup_old_entries(0, 0).unwrap();
+        let note = db.get_entry(id).unwrap().unwrap();
+        assert_eq!(note.entry_type, EntryType::Note);
+        assert_eq!(note.working_dir.as_deref(), Some("/srv/app"));
+        assert!(db.get_shell_command_id("ls").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_related_commands_follow_sessions() {
+        let db = TempDb::new("next");
+        for content in ["git add .", "git commit", "git push"] {
+            db.shell(ShellRow::new(content, 1));
+        }
+
+        let related = db.get_related_commands("git add .", 5).unwrap();
+        let contents: Vec<&str> = related.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, vec!["git commit", "git push"]);
+        assert_eq!(related[0].sequence_order, 1);
+        assert_eq!(related[1].sequence_order, 2);
+        assert_eq!(db.last_shell_command().unwrap().as_deref(), Some("git push"));
+        assert_eq!(
+            db.session_commands(0).unwrap(),
+            vec![vec!["git add .", "git commit", "git push"]]
+        );
+    }
+
+    #[test]
+    fn test_vec_entries_follow_entries() {
+        let db = TempDb::new("vec");
+        let vec_rows = |db: &Database| -> i64 {
+            db.conn
+                .query_row("SELECT COUNT(*) FROM vec_entries", [], |row| row.get(0))
+                .unwrap()
+        };
+
+        // Running a command again updates the row, which must not trip the vec0 table
+        for _ in 0..2 {
+            db.insert_shell(&ShellRow::new("cargo build", 1), Some(vec![0.1; 384]))
+                .unwrap();
+        }
+        assert_eq!(vec_rows(&db), 1);
+
+        let id = db.get_shell_command_id("cargo build").unwrap().unwrap();
+        db.delete_entries(&[id]).unwrap();
+        assert_eq!(vec_rows(&db), 0);
+    }
 }
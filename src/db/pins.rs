@@ -0,0 +1,72 @@
+use rusqlite::{Connection, Result, params, params_from_iter};
+use std::collections::HashSet;
+
+use super::Database;
+
+/// Which of `ids` are pinned, for marking search results
+pub fn pinned_in(conn: &Connection, ids: &[i64]) -> Result<HashSet<i64>> {
+    if ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id FROM entries WHERE pinned = 1 AND id IN ({})",
+        placeholders
+    ))?;
+    stmt.query_map(params_from_iter(ids), |row| row.get(0))?
+        .collect()
+}
+
+impl Database {
+    /// Pin or unpin an entry; pinned entries are never trimmed by cleanup.
+    /// Returns false if there is no entry with that id.
+    pub fn set_pinned(&self, entry_id: i64, pinned: bool) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE entries SET pinned = ?2 WHERE id = ?1",
+            params![entry_id, pinned],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn is_pinned(&self, entry_id: i64) -> Result<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM entries WHERE id = ?1 AND pinned = 1)",
+            [entry_id],
+            |row| row.get(0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_cleanup_keeps_pinned_entries() {
+        let db = TempDb::new("pins");
+        for (i, content) in ["ssh prod-db-7", "ls", "git status"].iter().enumerate() {
+            db.shell(ShellRow::new(content, i as u64));
+        }
+        let rare = db.get_shell_command_id("ssh prod-db-7").unwrap().unwrap();
+        let ls = db.get_shell_command_id("ls").unwrap().unwrap();
+        assert!(db.set_pinned(rare, true).unwrap());
+        assert!(!db.set_pinned(9999, true).unwrap());
+
+        db.cleanup_old_entries(10, 1).unwrap();
+        assert!(db.get_entry(rare).unwrap().is_some());
+        assert!(db.get_entry(ls).unwrap().is_none());
+        assert!(db.get_shell_command_id("git status").unwrap().is_some());
+        assert_eq!(
+            pinned_in(&db.conn, &[rare, ls]).unwrap(),
+            HashSet::from([rare])
+        );
+
+        assert!(db.set_pinned(rare, false).unwrap());
+        assert!(!db.is_pinned(rare).unwrap());
+        db.cleanup_old_entries(10, 1).unwrap();
+        assert!(db.get_entry(rare).unwrap().is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use once_cell::sync::Lazy;
+use rusqlite::{Connection, OpenFlags, Result};
+use std::ops::Deref;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{STATEMENT_CACHE_CAPACITY, USER_DB, get_db_path, load_vec_extension, open_db};
+
+/// Idle connections kept around for reuse; busier moments just open more
+const MAX_IDLE_READERS: usize = 4;
+
+/// Read-only connections for search. Readers never hold the write lock, so searches
+/// run alongside the `DbWriter` (WAL lets both proceed) instead of queueing on
+/// `USER_DB` or hitting SQLITE_BUSY. All writes go through `DB_WRITER`.
+pub struct ReadPool {
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl ReadPool {
+    fn new() -> Self {
+        ReadPool {
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn open() -> Result<Connection> {
+        // A read-only connection can't create the schema or the WAL index, so make
+        // sure a read-write connection has set them up (and stays open) first
+        Lazy::force(&USER_DB);
+
+        let conn = open_db(
+            &get_db_path(),
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX
+                | OpenFlags::SQLITE_OPEN_URI,
+        )?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+        // Needed for vec_distance_cosine; semantic search falls back without it
+        let _ = load_vec_extension(&conn);
+
+        Ok(conn)
+    }
+
+    /// A read-only connection, returned to the pool when dropped
+    pub fn get(&self) -> Result<ReadConn<'_>> {
+        let idle = self.idle.lock().ok().and_then(|mut idle| idle.pop());
+        let conn = match idle {
+            Some(conn) => conn,
+            None => Self::open()?,
+        };
+
+        Ok(ReadConn {
+            pool: self,
+            conn: Some(conn),
+        })
+    }
+}
+
+pub struct ReadConn<'a> {
+    pool: &'a ReadPool,
+    conn: Option<Connection>,
+}
+
+impl Deref for ReadConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("read connection used after release")
+    }
+}
+
+impl Drop for ReadConn<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take()
+            && let Ok(mut idle) = self.pool.idle.lock()
+            && idle.len() < MAX_IDLE_READERS
+        {
+            idle.push(conn);
+        }
+    }
+}
+
+pub static READ_POOL: Lazy<ReadPool> = Lazy::new(ReadPool::new);
+
+/// A standalone read-only connection for one-shot commands with a latency budget
+/// (`jotx suggest`). Skips opening `USER_DB` and loading extensions, so it fails
+/// rather than creating anything when there is no database yet.
+pub fn open_quick_reader() -> Result<Connection> {
+    let conn = open_db(
+        &get_db_path(),
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.busy_timeout(Duration::from_millis(50))?;
+    Ok(conn)
+}
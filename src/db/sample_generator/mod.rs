@@ -1,6 +1,8 @@
 use rusqlite::params;
 
-use crate::{db::USER_DB, embeds::EMBEDDING_MODEL};
+use crate::db::USER_DB;
+use crate::embeds::{EMBEDDING_MODEL, TopK, cosine_similarity_blob, embedding_norm};
+use crate::managers::lock_manager::lock_or_recover;
 
 #[derive(Debug, Clone, Copy)]
 pub enum SampleStrategy {
@@ -36,9 +38,7 @@ impl SampleSelector {
             Err(_) => return Err("Failed to lock embedding model".into()),
         };
 
-        let db = USER_DB
-            .lock()
-            .map_err(|e| format!("DB lock error: {}", e))?;
+        let db = lock_or_recover("database", &USER_DB);
 
         // Try vector search first (if available)
         match self.get_samples_vector(&db.conn, &query_embedding, k, strategy) {
@@ -112,7 +112,7 @@ impl SampleSelector {
         // Stage 1: Get top candidates by simple heuristics (FAST)
         let candidate_limit = (k * 10).min(1000); // Cap at 1000 to avoid loading too much
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             "SELECT id, content, embedding, times_run 
          FROM entries
          WHERE entry_type = 'command' 
@@ -121,21 +121,31 @@ impl SampleSelector {
          LIMIT ?1",
         )?;
 
-        // Stage 2: Compute similarity only on candidates
-        let all_samples: Vec<(i64, String, f32, f32, i32)> = stmt
-            .query_map([candidate_limit], |row| {
-                let id: i64 = row.get(0)?;
-                let command: String = row.get(1)?;
-                let embedding_blob: Vec<u8> = row.get(2)?;
-                let times_run: i32 = row.get(3)?;
+        // Stage 2: Score candidates straight from the stored blobs, keeping the same
+        // pool the vector search hands to the strategy
+        let pool = match strategy {
+            SampleStrategy::Diverse => k * 3,
+            _ => k * 2,
+        };
+        let query_norm = embedding_norm(query_embedding);
+        let mut top = TopK::new(pool);
+        let mut rows = stmt.query([candidate_limit])?;
+
+        while let Some(row) = rows.next()? {
+            let similarity =
+                cosine_similarity_blob(query_embedding, query_norm, row.get_ref(2)?.as_blob()?);
+            if !top.accepts(similarity) {
+                continue;
+            }
 
-                let embedding = blob_to_vec(&embedding_blob);
-                let similarity = cosine_similarity(query_embedding, &embedding);
-                let quality_score = (times_run as f32).ln().max(1.0);
+            let id: i64 = row.get(0)?;
+            let command: String = row.get(1)?;
+            let times_run: i32 = row.get(3)?;
+            let quality_score = (times_run as f32).ln().max(1.0);
 
-                Ok((id, command, similarity, quality_score, times_run))
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+            top.push(similarity, (id, command, similarity, quality_score, times_run));
+        }
+        let all_samples = top.into_sorted_vec();
 
         // Stage 3: Apply strategy filtering
         let selected = self.select_by_strategy(all_samples, k, strategy);
@@ -143,7 +153,7 @@ impl SampleSelector {
         Ok(selected)
     }
 
-    fn select_by_strategy(
+    pub(crate) fn select_by_strategy(
         &self,
         mut samples: Vec<(i64, String, f32, f32, i32)>,
         k: usize,
@@ -228,18 +238,6 @@ impl SampleSelector {
     }
 }
 
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
-
-    if norm_a == 0.0 || norm_b == 0.0 {
-        0.0
-    } else {
-        dot / (norm_a * norm_b)
-    }
-}
-
 fn jaccard_similarity_str(a: &str, b: &str) -> f32 {
     let words_a: std::collections::HashSet<_> = a.split_whitespace().collect();
     let words_b: std::collections::HashSet<_> = b.split_whitespace().collect();
@@ -258,12 +256,6 @@ fn vec_to_blob(vec: &[f32]) -> Vec<u8> {
     vec.iter().flat_map(|f| f.to_le_bytes()).collect()
 }
 
-fn blob_to_vec(blob: &[u8]) -> Vec<f32> {
-    blob.chunks_exact(4)
-        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
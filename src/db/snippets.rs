@@ -0,0 +1,68 @@
+use rusqlite::{OptionalExtension, Result, params};
+
+use super::Database;
+use crate::types::Snippet;
+
+impl Database {
+    /// Save a snippet, replacing any snippet with the same name
+    pub fn save_snippet(&self, snippet: &Snippet) -> Result<()> {
+        let placeholders = serde_json::to_string(&snippet.placeholders)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO snippets (name, template, placeholders, source_entry_id)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                snippet.name,
+                snippet.template,
+                placeholders,
+                snippet.source_entry_id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_snippet(&self, name: &str) -> Result<Option<Snippet>> {
+        self.conn
+            .query_row(
+                "SELECT name, template, placeholders, source_entry_id FROM snippets WHERE name = ?1",
+                [name],
+                Self::snippet_from_row,
+            )
+            .optional()
+    }
+
+    /// All snippets by name
+    pub fn list_snippets(&self) -> Result<Vec<Snippet>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, template, placeholders, source_entry_id FROM snippets ORDER BY name",
+        )?;
+        let snippets = stmt
+            .query_map([], Self::snippet_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(snippets)
+    }
+
+    /// Returns false if there was no snippet with that name
+    pub fn delete_snippet(&self, name: &str) -> Result<bool> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM snippets WHERE name = ?1", [name])?;
+        Ok(deleted > 0)
+    }
+
+    fn snippet_from_row(row: &rusqlite::Row) -> Result<Snippet> {
+        let placeholders: String = row.get(2)?;
+        Ok(Snippet {
+            name: row.get(0)?,
+            template: row.get(1)?,
+            placeholders: serde_json::from_str(&placeholders).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+            source_entry_id: row.get(3)?,
+        })
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::{DateTime, Datelike, Utc, Weekday};
+use rusqlite::{Result, params};
+
+use super::Database;
+use crate::pid_controller::dropped_captures;
+use crate::timezone::QueryTimezone;
+use crate::types::{
+    CommandCount, DailyCount, PlaceCount, SessionStats, Stats, TypeCount, WeekdayCount,
+};
+
+impl Database {
+    /// Collect usage statistics: counts per type, captures per day and per weekday over
+    /// the last `days` days, the `top_n` most run commands and busiest directories and
+    /// hosts, sessions, DB size, cache hit rate and captures dropped by a full writer queue.
+    pub fn get_stats(&self, days: i64, top_n: usize) -> Result<Stats> {
+        let total_entries: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT entry_type, COUNT(*) FROM entries GROUP BY entry_type ORDER BY COUNT(*) DESC",
+        )?;
+        let counts_by_type = stmt
+            .query_map([], |row| {
+                Ok(TypeCount {
+                    entry_type: row.get(0)?,
+                    count: row.get::<_, i64>(1)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT content, times_run FROM entries
+             WHERE entry_type = 'shell'
+             ORDER BY times_run DESC, timestamp DESC
+             LIMIT ?1",
+        )?;
+        let top_commands = stmt
+            .query_map([top_n as i64], |row| {
+                Ok(CommandCount {
+                    command: row.get(0)?,
+                    times_run: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Stats {
+            total_entries: total_entries as usize,
+            counts_by_type,
+            daily_captures: self.daily_captures(days)?,
+            top_commands,
+            top_directories: self.top_places("working_dir", top_n)?,
+            top_hosts: self.top_places("host", top_n)?,
+            weekday_captures: self.weekday_captures(days)?,
+            sessions: self.session_stats()?,
+            db_size_bytes: self.db_size_bytes()?,
+            cache: self.cache.stats().unwrap_or_default(),
+            dropped_captures: dropped_captures(),
+        })
+    }
+
+    /// Entries captured per day (oldest first), using the configured timezone for day boundaries
+    fn daily_captures(&self, days: i64) -> Result<Vec<DailyCount>> {
+        let tz = QueryTimezone::from_config();
+        let now = Utc::now();
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT COUNT(*) FROM entries WHERE timestamp >= ?1 AND timestamp < ?2")?;
+
+        let mut daily = Vec::new();
+        for days_back in (0..days.max(1)).rev() {
+            let start = tz.day_start(now, days_back);
+            let end = if days_back == 0 {
+                now.timestamp() + 1
+            } else {
+                tz.day_start(now, days_back - 1)
+            };
+
+            let count: i64 = stmt.query_row(params![start, end], |row| row.get(0))?;
+            daily.push(DailyCount {
+                day: tz.format_timestamp(start, "%Y-%m-%d"),
+                count: count as usize,
+            });
+        }
+
+        Ok(daily)
+    }
+
+    /// The `top_n` values of `column` ("working_dir" or "host") with the most shell
+    /// command runs
+    fn top_places(&self, column: &str, top_n: usize) -> Result<Vec<PlaceCount>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {column}, SUM(times_run) FROM entries
+             WHERE entry_type = 'shell' AND {column} IS NOT NULL AND {column} != ''
+             GROUP BY {column}
+             ORDER BY SUM(times_run) DESC
+             LIMIT ?1"
+        ))?;
+        stmt.query_map([top_n as i64], |row| {
+            Ok(PlaceCount {
+                name: row.get(0)?,
+                times_run: row.get(1)?,
+            })
+        })?
+        .collect()
+    }
+
+    /// Entries captured on each day of the week over the last `days` days, Monday first
+    fn weekday_captures(&self, days: i64) -> Result<Vec<WeekdayCount>> {
+        let tz = QueryTimezone::from_config();
+        let since = tz.day_start(Utc::now(), days.max(1) - 1);
+
+        let mut counts = [0usize; 7];
+        let mut stmt = self
+            .conn
+            .prepare("SELECT timestamp FROM entries WHERE timestamp >= ?1")?;
+        let mut rows = stmt.query([since])?;
+        while let Some(row) = rows.next()? {
+            if let Some(utc) = DateTime::<Utc>::from_timestamp(row.get(0)?, 0) {
+                counts[tz.local_time(utc).weekday().num_days_from_monday() as usize] += 1;
+            }
+        }
+
+        let mut weekday = Weekday::Mon;
+        Ok(counts
+            .into_iter()
+            .map(|count| {
+                let day = WeekdayCount {
+                    weekday: weekday.to_string(),
+                    count,
+                };
+                weekday = weekday.succ();
+                day
+            })
+            .collect())
+    }
+
+    fn session_stats(&self) -> Result<SessionStats> {
+        let (sessions, commands): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(DISTINCT session_id), COUNT(*) FROM command_sessions",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let associations: i64 =
+            self.conn
+                .query_row("SELECT COUNT(*) FROM command_associations", [], |row| {
+                    row.get(0)
+                })?;
+
+        Ok(SessionStats {
+            sessions: sessions as usize,
+            average_length: if sessions > 0 {
+                commands as f64 / sessions as f64
+            } else {
+                0.0
+            },
+            associations: associations as usize,
+        })
+    }
+
+    fn db_size_bytes(&self) -> Result<u64> {
+        let page_count: i64 = self
+            .conn
+            .query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: i64 = self
+            .conn
+            .query_row("PRAGMA page_size", [], |row| row.get(0))?;
+        Ok((page_count * page_size) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_stats_places_and_weekdays() {
+        let db = TempDb::new("stats");
+        let now = Utc::now().timestamp() as u64;
+        for (content, working_dir, host) in [
+            ("cargo build", "/src/jotx", "laptop"),
+            ("cargo test", "/src/jotx", "laptop"),
+            ("ls", "/tmp", "server"),
+        ] {
+            db.shell(
+                ShellRow::new(content, now)
+                    .in_dir(working_dir)
+                    .on_host(host),
+            );
+        }
+
+        let stats = db.get_stats(7, 10).unwrap();
+        assert_eq!(stats.top_directories[0].name, "/src/jotx");
+        assert_eq!(stats.top_directories[0].times_run, 2);
+        assert_eq!(stats.top_hosts.len(), 2);
+        assert_eq!(stats.weekday_captures.len(), 7);
+        assert_eq!(stats.weekday_captures[0].weekday, "Mon");
+        assert_eq!(
+            stats.weekday_captures.iter().map(|day| day.count).sum::<usize>(),
+            3
+        );
+    }
+}
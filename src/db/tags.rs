@@ -0,0 +1,105 @@
+use rusqlite::{Connection, Result, params, params_from_iter};
+use std::collections::HashMap;
+
+use super::Database;
+use crate::types::TagCount;
+
+/// Tags are stored lowercase without surrounding whitespace, e.g. "deploy" or "incident-2024"
+pub fn normalize_tag(tag: &str) -> std::result::Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    if tag.chars().any(char::is_whitespace) {
+        return Err(format!("Tag '{}' cannot contain spaces", tag));
+    }
+
+    Ok(tag)
+}
+
+/// Tags of each of `ids` that has any, for labelling search results
+pub fn entry_tags_in(conn: &Connection, ids: &[i64]) -> Result<HashMap<i64, Vec<String>>> {
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    if ids.is_empty() {
+        return Ok(tags);
+    }
+
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT entry_id, tag FROM entry_tags WHERE entry_id IN ({}) ORDER BY tag",
+        placeholders
+    ))?;
+    let rows = stmt.query_map(params_from_iter(ids), |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (id, tag) = row?;
+        tags.entry(id).or_default().push(tag);
+    }
+
+    Ok(tags)
+}
+
+impl Database {
+    /// Tag an entry. Returns false if the entry already had the tag.
+    pub fn add_tag(&self, entry_id: i64, tag: &str) -> Result<bool> {
+        let added = self.conn.execute(
+            "INSERT OR IGNORE INTO entry_tags (entry_id, tag) VALUES (?1, ?2)",
+            params![entry_id, tag],
+        )?;
+        Ok(added > 0)
+    }
+
+    /// Remove a tag from an entry. Returns false if the entry did not have it.
+    pub fn remove_tag(&self, entry_id: i64, tag: &str) -> Result<bool> {
+        let removed = self.conn.execute(
+            "DELETE FROM entry_tags WHERE entry_id = ?1 AND tag = ?2",
+            params![entry_id, tag],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// All tags with the number of entries carrying each, most used first
+    pub fn list_tags(&self) -> Result<Vec<TagCount>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tag, COUNT(*) FROM entry_tags GROUP BY tag ORDER BY COUNT(*) DESC, tag",
+        )?;
+
+        let tags = stmt
+            .query_map([], |row| {
+                Ok(TagCount {
+                    tag: row.get(0)?,
+                    count: row.get::<_, i64>(1)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tags)
+    }
+
+    pub fn tags_for_entry(&self, entry_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM entry_tags WHERE entry_id = ?1 ORDER BY tag")?;
+
+        let tags = stmt
+            .query_map([entry_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("  Deploy ").unwrap(), "deploy");
+        assert_eq!(normalize_tag("incident-2024").unwrap(), "incident-2024");
+        assert!(normalize_tag("   ").is_err());
+        assert!(normalize_tag("two words").is_err());
+    }
+}
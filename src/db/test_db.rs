@@ -0,0 +1,75 @@
+//! Throwaway databases for tests. The directory is removed when the `TempDb` is
+//! dropped, so a failed assert doesn't leave it behind.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{Database, ShellRow};
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) struct TempDb {
+    dir: PathBuf,
+    db: Option<Database>,
+}
+
+impl TempDb {
+    /// A fresh database in its own directory under the temp dir
+    pub(crate) fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "jotx-{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::remove_dir_all(&dir).ok();
+        let db = Database::open_in(&dir).unwrap();
+        Self { dir, db: Some(db) }
+    }
+
+    pub(crate) fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Close the connection, keeping the files until drop
+    pub(crate) fn close(&mut self) {
+        self.db = None;
+    }
+
+    /// Store a shell command without an embedding and return its id
+    pub(crate) fn shell(&self, row: ShellRow) -> i64 {
+        self.insert_shell(&row, None).unwrap()
+    }
+}
+
+impl Deref for TempDb {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        self.db.as_ref().expect("TempDb used after close")
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        self.db = None;
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+impl<'a> ShellRow<'a> {
+    pub(crate) fn in_dir(self, working_dir: &'a str) -> Self {
+        Self {
+            working_dir: Some(working_dir),
+            ..self
+        }
+    }
+
+    pub(crate) fn on_host(self, host: &'a str) -> Self {
+        Self {
+            host: Some(host),
+            ..self
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use rusqlite::{OptionalExtension, Result, params};
+
+use super::Database;
+
+/// Content stored in `entries` in place of a vaulted entry's plaintext
+pub fn vault_placeholder(entry_id: i64) -> String {
+    format!("🔒 vault #{}", entry_id)
+}
+
+impl Database {
+    /// Move an entry into the vault: the ciphertext is stored separately and the entry's
+    /// content and embedding are cleared so nothing searchable is left in plaintext.
+    /// Returns false if the entry does not exist or is already vaulted.
+    pub fn vault_entry(&self, entry_id: i64, ciphertext: &[u8]) -> Result<bool> {
+        if self.is_vaulted(entry_id)? {
+            return Ok(false);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        let updated = tx.execute(
+            "UPDATE entries SET content = ?2, embedding = NULL, updated_at = strftime('%s', 'now')
+             WHERE id = ?1",
+            params![entry_id, vault_placeholder(entry_id)],
+        )?;
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        tx.execute(
+            "INSERT INTO vault_entries (entry_id, ciphertext) VALUES (?1, ?2)",
+            params![entry_id, ciphertext],
+        )?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Put decrypted content back into the entry and drop its vault row
+    pub fn restore_from_vault(&self, entry_id: i64, content: &str) -> Result<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let removed = tx.execute("DELETE FROM vault_entries WHERE entry_id = ?1", [entry_id])?;
+        if removed == 0 {
+            return Ok(false);
+        }
+
+        tx.execute(
+            "UPDATE entries SET content = ?2, updated_at = strftime('%s', 'now') WHERE id = ?1",
+            params![entry_id, content],
+        )?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    pub fn is_vaulted(&self, entry_id: i64) -> Result<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM vault_entries WHERE entry_id = ?1)",
+            [entry_id],
+            |row| row.get(0),
+        )
+    }
+
+    pub fn vault_ciphertext(&self, entry_id: i64) -> Result<Option<Vec<u8>>> {
+        self.conn
+            .query_row(
+                "SELECT ciphertext FROM vault_entries WHERE entry_id = ?1",
+                [entry_id],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Ids of all vaulted entries, newest first
+    pub fn vault_ids(&self) -> Result<Vec<i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT v.entry_id FROM vault_entries v
+             JOIN entries e ON e.id = v.entry_id
+             ORDER BY e.timestamp DESC",
+        )?;
+
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_vault_clears_fts() {
+        let db = TempDb::new("vault");
+        let id = db.shell(ShellRow::new("export TOKEN=hunter2", 1));
+        db.shell(ShellRow::new("git status", 2));
+
+        assert!(db.vault_entry(id, b"ciphertext").unwrap());
+        assert!(!db.vault_entry(id, b"ciphertext").unwrap());
+        let matches: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM entries_fts WHERE entries_fts MATCH 'hunter2'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(matches, 0);
+        assert!(db.check_fts().unwrap().is_healthy());
+        assert_eq!(db.vault_ids().unwrap(), vec![id]);
+    }
+}
@@ -1,30 +1,64 @@
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use fastembed::{EmbeddingModel, InitOptions, ModelInfo, TextEmbedding};
 use once_cell::sync::Lazy;
-use std::{path::PathBuf, sync::Mutex, env::var};
+use std::{env::var, path::PathBuf, sync::Mutex};
+
+use crate::config::{EmbeddingsConfig, GLOBAL_CONFIG};
+use crate::metrics::METRICS;
+use crate::notes::expand_home;
+
+mod top_k;
+
+pub use top_k::TopK;
 
 pub static EMBEDDING_MODEL: Lazy<Mutex<SentenceEmbeddingsModel>> =
     Lazy::new(|| Mutex::new(SentenceEmbeddingsModel::new()));
 
 pub struct SentenceEmbeddingsModel {
     model: TextEmbedding,
+    /// `embeddings.dimension`; vectors of any other size are refused
+    dimension: usize,
 }
 
 impl SentenceEmbeddingsModel {
     pub fn new() -> Self {
+        let config = GLOBAL_CONFIG
+            .read()
+            .map(|config| config.embeddings.clone())
+            .unwrap_or_default();
+
+        let model = match resolve_model(&config.model) {
+            Some((model, _)) => model,
+            None => {
+                eprintln!(
+                    "⚠️  Unknown embeddings.model '{}', using {}",
+                    config.model,
+                    EmbeddingsConfig::default().model
+                );
+                EmbeddingModel::AllMiniLML6V2
+            }
+        };
+
         // Set cache directory to ~/.jotx/models instead of current directory
-        let cache_dir = Self::get_cache_dir();
+        let cache_dir = config
+            .cache_dir
+            .as_deref()
+            .map(expand_home)
+            .unwrap_or_else(Self::get_cache_dir);
 
         // Create cache directory if it doesn't exist
         std::fs::create_dir_all(&cache_dir).expect("Failed to create embedding cache directory");
 
         let model = TextEmbedding::try_new(
-            InitOptions::new(EmbeddingModel::AllMiniLML6V2)
+            InitOptions::new(model)
                 .with_show_download_progress(true)
                 .with_cache_dir(cache_dir), // ← THIS IS THE KEY!
         )
         .expect("Failed to create embedding model");
 
-        Self { model }
+        Self {
+            model,
+            dimension: config.dimension,
+        }
     }
 
     /// Get the global cache directory for embedding models
@@ -36,18 +70,92 @@ impl SentenceEmbeddingsModel {
     pub fn embed(&mut self, text: &str) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
         let embeddings = self.model.embed(vec![text], None)?;
 
-        if embeddings.is_empty() {
-            return Err("Failed to generate embedding".into());
+        match embeddings.into_iter().next() {
+            Some(embedding) => self.checked(embedding),
+            None => Err("Failed to generate embedding".into()),
         }
+    }
+
+    pub fn embed_batch(
+        &mut self,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error>> {
+        let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
+        let embeddings = self.model.embed(text_refs, None)?;
+        embeddings
+            .into_iter()
+            .map(|embedding| self.checked(embedding))
+            .collect()
+    }
 
-        Ok(embeddings[0].clone())
+    /// Refuse vectors that don't fit the index rather than store them
+    fn checked(&self, embedding: Vec<f32>) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+        if embedding.len() != self.dimension {
+            return Err(format!(
+                "The embedding model returns {} floats but embeddings.dimension is {}",
+                embedding.len(),
+                self.dimension
+            )
+            .into());
+        }
+        Ok(embedding)
     }
+}
+
+/// The fastembed model called `name`, with its dimension. Accepts the model code
+/// ("Qdrant/all-MiniLM-L6-v2-onnx"), the name without the organisation and "-onnx"
+/// ("all-MiniLM-L6-v2"), or the enum variant ("AllMiniLML6V2"), in any case.
+pub fn resolve_model(name: &str) -> Option<(EmbeddingModel, usize)> {
+    let name = name.trim().to_lowercase();
+    let models = TextEmbedding::list_supported_models();
 
-    // pub fn embed_batch(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error>> {
-    //     let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
-    //     let embeddings = self.model.embed(text_refs, None)?;
-    //     Ok(embeddings)
-    // }
+    // Several models can share a code or a short name, and the list comes from a
+    // HashMap. Prefer the full-precision model, then Qdrant's export, so the pick is
+    // the same on every run.
+    let preference = |info: &&ModelInfo<EmbeddingModel>| {
+        let variant = format!("{:?}", info.model);
+        (
+            variant.ends_with('Q'),
+            !info.model_code.starts_with("Qdrant/"),
+            variant,
+        )
+    };
+
+    // Exact matches win over short names
+    let exact = models
+        .iter()
+        .filter(|info| {
+            info.model_code.to_lowercase() == name
+                || format!("{:?}", info.model).to_lowercase() == name
+        })
+        .min_by_key(preference);
+    let short = || {
+        models
+            .iter()
+            .filter(|info| {
+                let code = info.model_code.to_lowercase();
+                let short = code.rsplit('/').next().unwrap_or(&code);
+                short.trim_end_matches("-onnx") == name
+            })
+            .min_by_key(preference)
+    };
+
+    exact
+        .or_else(short)
+        .map(|info| (info.model.clone(), info.dim))
+}
+
+/// Whether `config` names a known model with its real dimension
+pub fn check_config(config: &EmbeddingsConfig) -> Result<(), String> {
+    let (_, dimension) = resolve_model(&config.model)
+        .ok_or_else(|| format!("Unknown embedding model '{}'", config.model))?;
+    if dimension != config.dimension {
+        return Err(format!(
+            "{} produces {} floats, but dimension is {}",
+            config.model, dimension, config.dimension
+        ));
+    }
+    Ok(())
 }
 
 // Helper function for easy access
@@ -55,11 +163,24 @@ pub fn generate_embedding(text: &str) -> Result<Vec<f32>, Box<dyn std::error::Er
     let mut model = EMBEDDING_MODEL
         .lock()
         .map_err(|e| format!("Failed to lock embedding model: {}", e))?;
-    model.embed(text)
+    METRICS.embeddings.time(|| model.embed(text))
+}
+
+/// Embed several texts in one pass, in order
+pub fn generate_embeddings(texts: &[String]) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error>> {
+    let mut model = EMBEDDING_MODEL
+        .lock()
+        .map_err(|e| format!("Failed to lock embedding model: {}", e))?;
+    METRICS.embeddings.time(|| model.embed_batch(texts))
 }
 
 // Calculate cosine similarity between two embeddings
 pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    // Vectors from different models can't be compared
+    if a.len() != b.len() {
+        return 0.0;
+    }
+
     let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
     let magnitude_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
     let magnitude_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
@@ -70,3 +191,70 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
 
     dot_product / (magnitude_a * magnitude_b)
 }
+
+/// Euclidean norm of an embedding, for `cosine_similarity_blob`
+pub fn embedding_norm(v: &[f32]) -> f32 {
+    v.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+/// Cosine similarity against a stored little-endian f32 blob, read in place so
+/// scans don't decode every row into a Vec. `query_norm` is `embedding_norm(query)`,
+/// computed once per scan. Blobs from a model of another dimension score 0.
+pub fn cosine_similarity_blob(query: &[f32], query_norm: f32, blob: &[u8]) -> f32 {
+    if blob.len() != query.len() * 4 {
+        return 0.0;
+    }
+
+    let mut dot_product = 0.0f32;
+    let mut magnitude_b = 0.0f32;
+    for (x, chunk) in query.iter().zip(blob.chunks_exact(4)) {
+        let y = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        dot_product += x * y;
+        magnitude_b += y * y;
+    }
+    let magnitude_b = magnitude_b.sqrt();
+
+    if query_norm == 0.0 || magnitude_b == 0.0 {
+        return 0.0;
+    }
+
+    dot_product / (query_norm * magnitude_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_model() {
+        for name in [
+            "all-MiniLM-L6-v2",
+            "Qdrant/all-MiniLM-L6-v2-onnx",
+            "allminilml6v2",
+        ] {
+            let (model, dimension) = resolve_model(name).unwrap();
+            assert_eq!(model, EmbeddingModel::AllMiniLML6V2);
+            assert_eq!(dimension, 384);
+        }
+        // A code shared with the quantized model resolves to the full-precision one
+        for name in ["Alibaba-NLP/gte-base-en-v1.5", "gte-base-en-v1.5"] {
+            assert_eq!(resolve_model(name).unwrap().0, EmbeddingModel::GTEBaseENV15);
+        }
+        assert!(resolve_model("no-such-model").is_none());
+
+        let mut config = EmbeddingsConfig::default();
+        assert!(check_config(&config).is_ok());
+        config.dimension = 768;
+        assert!(check_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_blob_of_other_dimension_scores_zero() {
+        let query = vec![1.0f32; 4];
+        let blob: Vec<u8> = [1.0f32; 8].iter().flat_map(|f| f.to_le_bytes()).collect();
+        assert_eq!(
+            cosine_similarity_blob(&query, embedding_norm(&query), &blob),
+            0.0
+        );
+    }
+}
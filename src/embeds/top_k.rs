@@ -0,0 +1,122 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Keeps the `k` highest-scoring items seen so far, so a scan only ever holds
+/// `k` rows no matter how many it visits. Ties go to the item pushed first,
+/// matching a stable sort over the same rows.
+pub struct TopK<T> {
+    capacity: usize,
+    seen: u64,
+    heap: BinaryHeap<Reverse<Scored<T>>>,
+}
+
+struct Scored<T> {
+    score: f32,
+    seq: u64,
+    item: T,
+}
+
+impl<T> Ord for Scored<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Earlier items rank higher on equal scores
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl<T> PartialOrd for Scored<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Scored<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Scored<T> {}
+
+impl<T> TopK<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            heap: BinaryHeap::with_capacity(capacity + 1),
+        }
+    }
+
+    /// Whether an item with this score would be kept. Check before building the
+    /// item to skip allocating rows that can't make the cut.
+    pub fn accepts(&self, score: f32) -> bool {
+        if self.heap.len() < self.capacity {
+            return true;
+        }
+        // A later push never wins a tie, so it has to beat the current minimum
+        match self.heap.peek() {
+            Some(Reverse(min)) => score.total_cmp(&min.score) == Ordering::Greater,
+            None => false,
+        }
+    }
+
+    pub fn push(&mut self, score: f32, item: T) {
+        let seq = self.seen;
+        self.seen += 1;
+
+        if !self.accepts(score) {
+            return;
+        }
+
+        self.heap.push(Reverse(Scored { score, seq, item }));
+        if self.heap.len() > self.capacity {
+            self.heap.pop();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Kept items, highest score first
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        // Ascending order of Reverse is descending order of score
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(scored)| scored.item)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_highest_scores_in_order() {
+        let mut top = TopK::new(3);
+        for (score, name) in [(0.2, "a"), (0.9, "b"), (0.5, "c"), (0.1, "d"), (0.7, "e")] {
+            top.push(score, name);
+        }
+        assert_eq!(top.into_sorted_vec(), vec!["b", "e", "c"]);
+    }
+
+    #[test]
+    fn test_ties_keep_first_pushed() {
+        let mut top = TopK::new(2);
+        for name in ["a", "b", "c", "d"] {
+            top.push(0.5, name);
+        }
+        assert!(!top.accepts(0.5));
+        assert_eq!(top.into_sorted_vec(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_zero_capacity_keeps_nothing() {
+        let mut top = TopK::new(0);
+        top.push(1.0, "a");
+        assert!(top.is_empty());
+    }
+}
@@ -0,0 +1,261 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::timezone::QueryTimezone;
+use crate::types::{EntryType, HistoryEntry};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+    /// zsh extended history, which `atuin import zsh` reads
+    Atuin,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Atuin => "zsh_history",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "atuin" => Ok(ExportFormat::Atuin),
+            _ => Err(format!("Unknown export format: {} (expected json, csv, md or atuin)", s)),
+        }
+    }
+}
+
+/// Write entries to `out` in the given format, one entry at a time so large
+/// histories can be streamed straight to a file or stdout.
+/// Returns the number of entries written.
+pub fn export_entries<I, W>(
+    entries: I,
+    format: ExportFormat,
+    out: &mut W,
+) -> Result<usize, Box<dyn std::error::Error>>
+where
+    I: IntoIterator<Item = HistoryEntry>,
+    W: Write,
+{
+    let tz = QueryTimezone::from_config();
+    let mut count = 0;
+
+    match format {
+        ExportFormat::Json => {
+            writeln!(out, "[")?;
+            for entry in entries {
+                if count > 0 {
+                    writeln!(out, ",")?;
+                }
+                write!(out, "  {}", serde_json::to_string(&entry)?)?;
+                count += 1;
+            }
+            if count > 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "]")?;
+        }
+        ExportFormat::Csv => {
+            writeln!(
+                out,
+                "id,entry_type,timestamp,time,content,times_run,working_dir,user,host,app_name,window_title"
+            )?;
+            for entry in entries {
+                let row = [
+                    entry.id.to_string(),
+                    entry.entry_type.clone(),
+                    entry.timestamp.to_string(),
+                    tz.format_timestamp(entry.timestamp, TIME_FORMAT),
+                    entry.content.clone(),
+                    entry.times_run.to_string(),
+                    entry.working_dir.clone().unwrap_or_default(),
+                    entry.user.clone().unwrap_or_default(),
+                    entry.host.clone().unwrap_or_default(),
+                    entry.app_name.clone().unwrap_or_default(),
+                    entry.window_title.clone().unwrap_or_default(),
+                ];
+                let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+                writeln!(out, "{}", fields.join(","))?;
+                count += 1;
+            }
+        }
+        ExportFormat::Markdown => {
+            writeln!(out, "# Jotx history export")?;
+            for entry in entries {
+                writeln!(out)?;
+                writeln!(
+                    out,
+                    "### {} · {}",
+                    tz.format_timestamp(entry.timestamp, TIME_FORMAT),
+                    entry.entry_type
+                )?;
+                writeln!(out)?;
+
+                let fence = code_fence(&entry.content);
+                writeln!(out, "{}", fence)?;
+                writeln!(out, "{}", entry.content)?;
+                writeln!(out, "{}", fence)?;
+
+                if let Some(dir) = entry.working_dir.as_deref().filter(|d| !d.is_empty()) {
+                    writeln!(out)?;
+                    writeln!(out, "_Directory:_ `{}`", dir)?;
+                }
+                if let Some(app) = entry.app_name.as_deref().filter(|a| !a.is_empty()) {
+                    writeln!(out)?;
+                    writeln!(out, "_App:_ {}", app)?;
+                }
+                count += 1;
+            }
+        }
+        ExportFormat::Atuin => {
+            let shell = EntryType::Shell.to_string();
+            for entry in entries {
+                // Atuin only keeps shell history
+                if entry.entry_type != shell {
+                    continue;
+                }
+                writeln!(out, "{}", zsh_history_line(entry.timestamp, &entry.content))?;
+                count += 1;
+            }
+        }
+    }
+
+    out.flush()?;
+    Ok(count)
+}
+
+/// One zsh extended history record: `: <start>:<duration>;<command>`. jotx doesn't
+/// record durations, so it's always 0. Multi-line commands continue with a trailing
+/// backslash, as zsh writes them.
+pub(crate) fn zsh_history_line(timestamp: i64, command: &str) -> String {
+    format!(": {}:0;{}", timestamp, command.replace('\n', "\\\n"))
+}
+
+/// Read zsh history back into (start time, command) pairs, the inverse of
+/// `zsh_history_line`. Plain lines without the extended header have no time.
+pub(crate) fn parse_zsh_history(contents: &str) -> Vec<(Option<i64>, String)> {
+    let mut records = Vec::new();
+    let mut pending: Option<(Option<i64>, String)> = None;
+
+    for line in contents.lines() {
+        let (timestamp, command) = match pending.take() {
+            Some((timestamp, mut command)) => {
+                command.push('\n');
+                command.push_str(line);
+                (timestamp, command)
+            }
+            None => parse_zsh_header(line),
+        };
+
+        match command.strip_suffix('\\') {
+            Some(continued) => pending = Some((timestamp, continued.to_string())),
+            None => records.push((timestamp, command)),
+        }
+    }
+    records.extend(pending);
+
+    records
+}
+
+fn parse_zsh_header(line: &str) -> (Option<i64>, String) {
+    let parsed = line.strip_prefix(": ").and_then(|rest| {
+        let (header, command) = rest.split_once(';')?;
+        let (start, _duration) = header.split_once(':')?;
+        Some((start.parse().ok()?, command))
+    });
+
+    match parsed {
+        Some((timestamp, command)) => (Some(timestamp), command.to_string()),
+        None => (None, line.to_string()),
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A backtick fence longer than any backtick run inside the content
+fn code_fence(content: &str) -> String {
+    let longest_run = content
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+    "`".repeat((longest_run + 1).max(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("ls -la"), "ls -la");
+        assert_eq!(csv_field("echo a,b"), "\"echo a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line1\nline2"), "\"line1\nline2\"");
+    }
+
+    #[test]
+    fn test_code_fence_outgrows_content() {
+        assert_eq!(code_fence("ls"), "```");
+        assert_eq!(code_fence("echo ````"), "`````");
+    }
+
+    #[test]
+    fn test_parse_export_format() {
+        assert_eq!(ExportFormat::from_str("JSON").unwrap(), ExportFormat::Json);
+        assert_eq!(ExportFormat::from_str("markdown").unwrap(), ExportFormat::Markdown);
+        assert_eq!(ExportFormat::from_str("atuin").unwrap(), ExportFormat::Atuin);
+        assert!(ExportFormat::from_str("xml").is_err());
+    }
+
+    #[test]
+    fn test_zsh_history_round_trip() {
+        let commands = [
+            (1700000000, "git status"),
+            (1700000060, "cd src; ls"),
+            (1700000120, "for f in *; do\n  echo $f\ndone"),
+        ];
+        let contents: String = commands
+            .iter()
+            .map(|(timestamp, command)| zsh_history_line(*timestamp, command) + "\n")
+            .collect();
+
+        assert!(contents.starts_with(": 1700000000:0;git status\n"));
+        let parsed = parse_zsh_history(&contents);
+        let expected: Vec<_> = commands
+            .iter()
+            .map(|(timestamp, command)| (Some(*timestamp), command.to_string()))
+            .collect();
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn test_parse_plain_zsh_history() {
+        assert_eq!(
+            parse_zsh_history("ls -la\n: 1700000000:3;make\n"),
+            vec![(None, "ls -la".to_string()), (Some(1700000000), "make".to_string())]
+        );
+    }
+}
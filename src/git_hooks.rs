@@ -0,0 +1,201 @@
+//! `jotx git-hooks`: post-commit and post-checkout hooks that record commits and
+//! branch switches as shell entries, so they land in the same command session as
+//! the commands around them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+/// Trailing comment identifying the line jotx adds to a hook
+const HOOK_MARKER: &str = "# added by jotx git-hooks";
+
+/// Hooks and the `jotx git-event` they run. Backgrounded so git never waits on jotx.
+const HOOKS: &[(&str, &str)] = &[
+    ("post-commit", "jotx git-event commit"),
+    (
+        "post-checkout",
+        "jotx git-event checkout \"$1\" \"$2\" \"$3\"",
+    ),
+];
+
+fn hook_line(event: &str) -> String {
+    format!(
+        "command -v jotx >/dev/null 2>&1 && ({} >/dev/null 2>&1 &) {}",
+        event, HOOK_MARKER
+    )
+}
+
+/// Hooks directory of the repository containing `dir`, honouring core.hooksPath
+fn hooks_dir(dir: &Path) -> Result<PathBuf, String> {
+    let path = git(dir, &["rev-parse", "--git-path", "hooks"])
+        .ok_or_else(|| format!("{} is not inside a git repository", dir.display()))?;
+    Ok(dir.join(path))
+}
+
+/// Add the jotx line to each hook of the repository containing `dir`, creating hooks
+/// that don't exist and keeping whatever an existing hook already does.
+/// Returns the hooks that were changed.
+pub fn install_git_hooks(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let hooks = hooks_dir(dir)?;
+    fs::create_dir_all(&hooks)
+        .map_err(|e| format!("Failed to create {}: {}", hooks.display(), e))?;
+
+    let mut changed = Vec::new();
+    for (name, event) in HOOKS {
+        let path = hooks.join(name);
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        if existing.contains(HOOK_MARKER) {
+            continue;
+        }
+
+        let mut script = if existing.trim().is_empty() {
+            "#!/bin/sh\n".to_string()
+        } else {
+            existing
+        };
+        if !script.ends_with('\n') {
+            script.push('\n');
+        }
+        script.push_str(&hook_line(event));
+        script.push('\n');
+
+        fs::write(&path, script)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        #[cfg(unix)]
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make {} executable: {}", path.display(), e))?;
+        changed.push(path);
+    }
+
+    Ok(changed)
+}
+
+/// Remove the jotx lines again, deleting hooks that contained nothing else
+pub fn uninstall_git_hooks(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let hooks = hooks_dir(dir)?;
+
+    let mut changed = Vec::new();
+    for (name, _) in HOOKS {
+        let path = hooks.join(name);
+        let Ok(existing) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if !existing.contains(HOOK_MARKER) {
+            continue;
+        }
+
+        let kept: Vec<&str> = existing
+            .lines()
+            .filter(|line| !line.contains(HOOK_MARKER))
+            .collect();
+        let only_shebang = kept
+            .iter()
+            .all(|line| line.trim().is_empty() || line.starts_with("#!"));
+
+        let result = if only_shebang {
+            fs::remove_file(&path)
+        } else {
+            fs::write(&path, kept.join("\n") + "\n")
+        };
+        result.map_err(|e| format!("Failed to update {}: {}", path.display(), e))?;
+        changed.push(path);
+    }
+
+    Ok(changed)
+}
+
+/// Describe a hook invocation as a history entry: (content, repository root).
+/// `None` for checkouts of files rather than branches.
+pub fn git_event_entry(
+    dir: &Path,
+    event: &str,
+    args: &[String],
+) -> Result<Option<(String, String)>, String> {
+    let root = git(dir, &["rev-parse", "--show-toplevel"])
+        .ok_or_else(|| format!("{} is not inside a git repository", dir.display()))?;
+    let branch = git(dir, &["rev-parse", "--abbrev-ref", "HEAD"]).unwrap_or_default();
+
+    let content = match event {
+        "commit" => {
+            let commit = git(dir, &["log", "-1", "--format=%h%x1f%s"])
+                .ok_or("Could not read the new commit")?;
+            let (hash, subject) = commit.split_once('\x1f').unwrap_or((commit.as_str(), ""));
+            format!("git commit {} on {}: {}", hash, branch, subject)
+        }
+        "checkout" => {
+            // post-checkout gets <previous HEAD> <new HEAD> <1 for a branch checkout>
+            if args.get(2).map(String::as_str) != Some("1") {
+                return Ok(None);
+            }
+            if branch == "HEAD" {
+                let head = args
+                    .get(1)
+                    .map(|h| &h[..h.len().min(7)])
+                    .unwrap_or_default();
+                format!("git checkout {} (detached)", head)
+            } else {
+                format!("git checkout {}", branch)
+            }
+        }
+        other => {
+            return Err(format!(
+                "Unknown git event: {} (expected commit or checkout)",
+                other
+            ));
+        }
+    };
+
+    Ok(Some((content, root)))
+}
+
+/// Trimmed stdout of a successful git command run in `dir`
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_keeps_existing_hooks() {
+        let repo = std::env::temp_dir().join(format!("jotx-git-hooks-{}", std::process::id()));
+        fs::remove_dir_all(&repo).ok();
+        fs::create_dir_all(&repo).unwrap();
+        if git(&repo, &["init", "-q"]).is_none() {
+            return; // git not installed
+        }
+
+        let hooks = hooks_dir(&repo).unwrap();
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(hooks.join("post-commit"), "#!/bin/sh\nmake lint").unwrap();
+
+        assert_eq!(install_git_hooks(&repo).unwrap().len(), 2);
+        assert!(install_git_hooks(&repo).unwrap().is_empty());
+
+        let post_commit = fs::read_to_string(hooks.join("post-commit")).unwrap();
+        assert!(post_commit.starts_with("#!/bin/sh\nmake lint\n"));
+        assert!(post_commit.contains("jotx git-event commit"));
+
+        assert_eq!(uninstall_git_hooks(&repo).unwrap().len(), 2);
+        assert_eq!(
+            fs::read_to_string(hooks.join("post-commit")).unwrap(),
+            "#!/bin/sh\nmake lint\n"
+        );
+        assert!(!hooks.join("post-checkout").exists());
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
@@ -0,0 +1,244 @@
+//! `jotx heatmap`: a GitHub-style contributions grid of the commands run in a year,
+//! printed to the terminal or exported as SVG or JSON.
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::db::READ_POOL;
+use crate::timezone::QueryTimezone;
+use crate::types::EntryType;
+
+/// Terminal cells from no activity to the busiest days
+const CELLS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+/// GitHub's contribution greens, same levels as `CELLS`
+const COLORS: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+const SVG_CELL: usize = 11;
+const SVG_GAP: usize = 2;
+/// Room for the weekday labels left of the grid and the month labels above it
+const SVG_LEFT: usize = 28;
+const SVG_TOP: usize = 16;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DayCount {
+    pub date: String,
+    pub count: usize,
+}
+
+/// Commands run on each day of `year` that had any, in the configured timezone
+pub fn heatmap_counts(year: i32) -> Result<BTreeMap<NaiveDate, usize>, Box<dyn std::error::Error>> {
+    let conn = READ_POOL.get()?;
+    Ok(heatmap_counts_in(
+        &conn,
+        year,
+        &QueryTimezone::from_config(),
+    )?)
+}
+
+pub(crate) fn heatmap_counts_in(
+    conn: &Connection,
+    year: i32,
+    tz: &QueryTimezone,
+) -> rusqlite::Result<BTreeMap<NaiveDate, usize>> {
+    // A day of slack on both ends covers any UTC offset; days outside the year are dropped below
+    let start = year_start_utc(year) - 86400;
+    let end = year_start_utc(year + 1) + 86400;
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT timestamp FROM entries
+         WHERE entry_type = ?1 AND timestamp >= ?2 AND timestamp < ?3",
+    )?;
+    let mut rows = stmt.query(rusqlite::params![EntryType::Shell.to_string(), start, end])?;
+
+    let mut counts = BTreeMap::new();
+    while let Some(row) = rows.next()? {
+        let Some(utc) = DateTime::<Utc>::from_timestamp(row.get(0)?, 0) else {
+            continue;
+        };
+        let date = tz.local_time(utc).date();
+        if date.year() == year {
+            *counts.entry(date).or_insert(0) += 1;
+        }
+    }
+    Ok(counts)
+}
+
+fn year_start_utc(year: i32) -> i64 {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+        .unwrap_or_default()
+}
+
+/// Every day of the year in order, zero for days without commands
+pub fn day_counts(year: i32, counts: &BTreeMap<NaiveDate, usize>) -> Vec<DayCount> {
+    days_of(year)
+        .map(|date| DayCount {
+            date: date.format("%Y-%m-%d").to_string(),
+            count: counts.get(&date).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+fn days_of(year: i32) -> impl Iterator<Item = NaiveDate> {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .into_iter()
+        .flat_map(|first| first.iter_days())
+        .take_while(move |date| date.year() == year)
+}
+
+/// Grid position: weeks run left to right starting on Sunday, as on GitHub
+fn cell(date: NaiveDate) -> (usize, usize) {
+    let jan1 = NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date);
+    let offset = jan1.weekday().num_days_from_sunday() as usize;
+    let column = (date.ordinal0() as usize + offset) / 7;
+    (column, date.weekday().num_days_from_sunday() as usize)
+}
+
+/// 0 for no commands, otherwise 1-4 by share of the busiest day
+fn level(count: usize, max: usize) -> usize {
+    if count == 0 || max == 0 {
+        0
+    } else {
+        (count * 4).div_ceil(max).clamp(1, 4)
+    }
+}
+
+/// The grid for a terminal, with month and weekday labels and a summary line
+pub fn render_text(year: i32, counts: &BTreeMap<NaiveDate, usize>) -> String {
+    let max = counts.values().copied().max().unwrap_or(0);
+    let columns = cell(NaiveDate::from_ymd_opt(year, 12, 31).unwrap_or_default()).0 + 1;
+    let mut grid = vec![vec![" "; columns]; 7];
+    let mut months = vec![' '; columns + 3];
+
+    for date in days_of(year) {
+        let (column, row) = cell(date);
+        grid[row][column] = CELLS[level(counts.get(&date).copied().unwrap_or(0), max)];
+        if date.day() == 1 {
+            let label = date.format("%b").to_string();
+            for (i, c) in label.chars().enumerate() {
+                months[column + i] = c;
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "    {}", months.iter().collect::<String>().trim_end());
+    for (row, cells) in grid.iter().enumerate() {
+        let label = match row {
+            1 => "Mon",
+            3 => "Wed",
+            5 => "Fri",
+            _ => "",
+        };
+        let _ = writeln!(out, "{:<4}{}", label, cells.concat().trim_end());
+    }
+
+    let total: usize = counts.values().sum();
+    let _ = write!(out, "\n{} commands in {}", total, year);
+    if let Some((date, count)) = counts
+        .iter()
+        .max_by_key(|(date, count)| (**count, std::cmp::Reverse(**date)))
+    {
+        let _ = write!(out, ", busiest {} ({})", date.format("%b %-d"), count);
+    }
+    let _ = writeln!(out, " · less {} more", CELLS.concat());
+    out
+}
+
+/// A standalone SVG of the grid
+pub fn render_svg(year: i32, counts: &BTreeMap<NaiveDate, usize>) -> String {
+    let max = counts.values().copied().max().unwrap_or(0);
+    let step = SVG_CELL + SVG_GAP;
+    let columns = cell(NaiveDate::from_ymd_opt(year, 12, 31).unwrap_or_default()).0 + 1;
+    let width = SVG_LEFT + columns * step;
+    let height = SVG_TOP + 7 * step;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="9" fill="#767676">"##,
+        width, height
+    );
+    for (row, label) in [(1, "Mon"), (3, "Wed"), (5, "Fri")] {
+        let _ = writeln!(
+            out,
+            r#"  <text x="0" y="{}">{}</text>"#,
+            SVG_TOP + row * step + SVG_CELL - 2,
+            label
+        );
+    }
+
+    for date in days_of(year) {
+        let (column, row) = cell(date);
+        let x = SVG_LEFT + column * step;
+        if date.day() == 1 {
+            let _ = writeln!(
+                out,
+                r#"  <text x="{}" y="{}">{}</text>"#,
+                x,
+                SVG_TOP - 6,
+                date.format("%b")
+            );
+        }
+
+        let count = counts.get(&date).copied().unwrap_or(0);
+        let _ = writeln!(
+            out,
+            r#"  <rect x="{}" y="{}" width="{}" height="{}" rx="2" fill="{}"><title>{}: {} commands</title></rect>"#,
+            x,
+            SVG_TOP + row * step,
+            SVG_CELL,
+            SVG_CELL,
+            COLORS[level(count, max)],
+            date.format("%Y-%m-%d"),
+            count
+        );
+    }
+    let _ = writeln!(out, "</svg>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ShellRow;
+    use crate::db::test_db::TempDb;
+
+    #[test]
+    fn test_heatmap_counts_and_grid() {
+        let db = TempDb::new("heatmap");
+        let at = |m: u32, d: u32, h: u32| {
+            NaiveDate::from_ymd_opt(2024, m, d)
+                .unwrap()
+                .and_hms_opt(h, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp() as u64
+        };
+        for (i, ts) in [at(3, 11, 9), at(3, 11, 10), at(3, 12, 9), at(1, 1, 0)]
+            .into_iter()
+            .enumerate()
+        {
+            db.shell(ShellRow::new(&format!("cmd {}", i), ts));
+        }
+        db.shell(ShellRow::new("old", at(1, 1, 0) - 3600));
+
+        let counts = heatmap_counts_in(&db.conn, 2024, &QueryTimezone::Utc).unwrap();
+        assert_eq!(counts.values().sum::<usize>(), 4);
+        assert_eq!(counts[&NaiveDate::from_ymd_opt(2024, 3, 11).unwrap()], 2);
+
+        // 2024-01-01 was a Monday: second row of the first week
+        assert_eq!(cell(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()), (0, 1));
+        assert_eq!(day_counts(2024, &counts).len(), 366);
+
+        let text = render_text(2024, &counts);
+        assert!(text.starts_with("    Jan"));
+        assert!(text.contains("4 commands in 2024, busiest Mar 11 (2)"));
+        let svg = render_svg(2024, &counts);
+        assert_eq!(svg.matches("<rect").count(), 366);
+        assert!(svg.contains("<title>2024-03-11: 2 commands</title>"));
+    }
+}
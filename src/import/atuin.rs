@@ -0,0 +1,103 @@
+//! Atuin's SQLite history: a row per run, with times and durations in nanoseconds
+
+use rusqlite::{Connection, OpenFlags};
+use std::path::Path;
+
+use crate::types::ImportedCommand;
+
+const NANOS_PER_MILLI: i64 = 1_000_000;
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+pub(super) fn read(path: &Path) -> Result<Vec<ImportedCommand>, Box<dyn std::error::Error>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    // Older versions deleted rows instead of marking them
+    let soft_deletes: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info('history') WHERE name = 'deleted_at')",
+        [],
+        |row| row.get(0),
+    )?;
+    let live = if soft_deletes {
+        "WHERE deleted_at IS NULL"
+    } else {
+        ""
+    };
+    let sql = format!(
+        "SELECT command, timestamp, duration, exit, cwd, hostname FROM history {} ORDER BY timestamp",
+        live
+    );
+
+    let runs = conn
+        .prepare(&sql)?
+        .query_map([], |row| {
+            let timestamp: i64 = row.get(1)?;
+            let duration: i64 = row.get(2)?;
+            let exit: i64 = row.get(3)?;
+            let cwd: Option<String> = row.get(4)?;
+            let hostname: Option<String> = row.get(5)?;
+            let (host, user) = split_hostname(hostname.as_deref());
+
+            Ok(ImportedCommand {
+                content: row.get(0)?,
+                timestamp: (timestamp / NANOS_PER_SEC).max(0) as u64,
+                // Atuin's own imports record "unknown" for these
+                working_dir: cwd.filter(|cwd| !cwd.is_empty() && cwd != "unknown"),
+                user,
+                host,
+                exit_code: (exit >= 0).then_some(exit as i32),
+                duration_ms: (duration >= 0).then_some((duration / NANOS_PER_MILLI) as u64),
+                times_run: 1,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(runs)
+}
+
+/// Atuin stores `host:user`
+fn split_hostname(hostname: Option<&str>) -> (Option<String>, Option<String>) {
+    let Some(hostname) = hostname.filter(|h| !h.is_empty()) else {
+        return (None, None);
+    };
+    match hostname.split_once(':') {
+        Some((host, user)) => (Some(host.to_string()), Some(user.to_string())),
+        None => (Some(hostname.to_string()), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_atuin_history() {
+        let path = std::env::temp_dir().join(format!("jotx-atuin-{}.db", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE history (
+                id TEXT PRIMARY KEY, timestamp INTEGER NOT NULL, duration INTEGER NOT NULL,
+                exit INTEGER NOT NULL, command TEXT NOT NULL, cwd TEXT NOT NULL,
+                session TEXT NOT NULL, hostname TEXT NOT NULL, deleted_at INTEGER
+            );
+            INSERT INTO history VALUES
+                ('a', 1700000000000000000, 1500000000, 0, 'cargo test', '/src', 's', 'box:ann', NULL),
+                ('b', 1700000100000000000, -1, -1, 'ls', 'unknown', 's', 'box:ann', NULL),
+                ('c', 1700000200000000000, 0, 0, 'rm secret', '/src', 's', 'box:ann', 1700000300);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let runs = read(&path).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].content, "cargo test");
+        assert_eq!(runs[0].timestamp, 1_700_000_000);
+        assert_eq!(runs[0].duration_ms, Some(1500));
+        assert_eq!(runs[0].working_dir.as_deref(), Some("/src"));
+        assert_eq!(runs[0].host.as_deref(), Some("box"));
+        assert_eq!(runs[0].user.as_deref(), Some("ann"));
+        assert_eq!(runs[1].working_dir, None);
+        assert_eq!(runs[1].exit_code, None);
+        assert_eq!(runs[1].duration_ms, None);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,71 @@
+//! .bash_history: a command per line, or with HISTTIMEFORMAT set, a `#<unix time>`
+//! line before each command. Multi-line commands saved with `lithist` then run up to
+//! the next time line.
+
+use crate::types::ImportedCommand;
+
+pub(super) fn parse(contents: &str) -> Vec<ImportedCommand> {
+    let mut runs: Vec<ImportedCommand> = Vec::new();
+    // The command started by the last time line, which later lines may continue
+    let mut timed: Option<ImportedCommand> = None;
+    let mut timestamp: Option<u64> = None;
+
+    for line in contents.lines() {
+        if let Some(time) = time_line(line) {
+            runs.extend(timed.take());
+            timestamp = Some(time);
+            continue;
+        }
+
+        if let Some(run) = timed.as_mut() {
+            run.content.push('\n');
+            run.content.push_str(line);
+            continue;
+        }
+
+        let run = ImportedCommand {
+            content: line.to_string(),
+            timestamp: timestamp.take().unwrap_or_default(),
+            times_run: 1,
+            ..Default::default()
+        };
+        if run.timestamp > 0 {
+            timed = Some(run);
+        } else {
+            runs.push(run);
+        }
+    }
+    runs.extend(timed);
+    runs
+}
+
+fn time_line(line: &str) -> Option<u64> {
+    let digits = line.strip_prefix('#')?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_bash_history() {
+        let runs = parse("ls -la\n# a comment\nmake\n");
+        let contents: Vec<&str> = runs.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, vec!["ls -la", "# a comment", "make"]);
+        assert!(runs.iter().all(|r| r.timestamp == 0));
+    }
+
+    #[test]
+    fn test_parse_timestamped_bash_history() {
+        let runs = parse("#1700000000\ngit pull\n#1700000100\nfor f in *; do\n  echo $f\ndone\n");
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].content, "git pull");
+        assert_eq!(runs[0].timestamp, 1_700_000_000);
+        assert_eq!(runs[1].content, "for f in *; do\n  echo $f\ndone");
+        assert_eq!(runs[1].timestamp, 1_700_000_100);
+    }
+}
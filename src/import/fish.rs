@@ -0,0 +1,65 @@
+//! fish_history: YAML-like records of `- cmd: <command>` followed by `  when: <time>`
+
+use crate::types::ImportedCommand;
+
+pub(super) fn parse(contents: &str) -> Vec<ImportedCommand> {
+    let mut runs: Vec<ImportedCommand> = Vec::new();
+
+    for line in contents.lines() {
+        if let Some(cmd) = line.strip_prefix("- cmd: ") {
+            runs.push(ImportedCommand {
+                content: unescape(cmd),
+                times_run: 1,
+                ..Default::default()
+            });
+        } else if let Some(when) = line.trim_start().strip_prefix("when: ")
+            && let Some(run) = runs.last_mut()
+        {
+            run.timestamp = when.trim().parse().unwrap_or_default();
+        }
+    }
+    runs
+}
+
+/// fish writes a newline in a command as `\n` and a backslash as `\\`
+fn unescape(cmd: &str) -> String {
+    let mut unescaped = String::with_capacity(cmd.len());
+    let mut chars = cmd.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fish_history() {
+        let contents = "- cmd: git status\n  when: 1700000000\n  paths:\n    - src\n\
+                        - cmd: for f in *\\n  echo $f\\nend\n  when: 1700000060\n\
+                        - cmd: echo a\\\\b\n";
+        let runs = parse(contents);
+
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].content, "git status");
+        assert_eq!(runs[0].timestamp, 1_700_000_000);
+        assert_eq!(runs[1].content, "for f in *\n  echo $f\nend");
+        assert_eq!(runs[1].timestamp, 1_700_000_060);
+        assert_eq!(runs[2].content, "echo a\\b");
+        assert_eq!(runs[2].timestamp, 0);
+    }
+}
@@ -0,0 +1,80 @@
+//! zsh-histdb's SQLite history: runs in `history`, with the command text in
+//! `commands` and the host and directory in `places`. Times are in seconds.
+
+use rusqlite::{Connection, OpenFlags};
+use std::path::Path;
+
+use crate::types::ImportedCommand;
+
+pub(super) fn read(path: &Path) -> Result<Vec<ImportedCommand>, Box<dyn std::error::Error>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let runs = conn
+        .prepare(
+            "SELECT c.argv, h.start_time, h.duration, h.exit_status, p.dir, p.host
+             FROM history h
+             JOIN commands c ON c.id = h.command_id
+             LEFT JOIN places p ON p.id = h.place_id
+             ORDER BY h.start_time",
+        )?
+        .query_map([], |row| {
+            let start_time: Option<i64> = row.get(1)?;
+            let duration: Option<i64> = row.get(2)?;
+            let dir: Option<String> = row.get(4)?;
+            let host: Option<String> = row.get(5)?;
+
+            Ok(ImportedCommand {
+                content: row.get(0)?,
+                timestamp: start_time.unwrap_or_default().max(0) as u64,
+                working_dir: dir.filter(|dir| !dir.is_empty()),
+                user: None,
+                host: host.filter(|host| !host.is_empty()),
+                // Both are NULL while the command is still running
+                exit_code: row.get(3)?,
+                duration_ms: duration
+                    .filter(|secs| *secs >= 0)
+                    .map(|secs| secs as u64 * 1000),
+                times_run: 1,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_histdb_history() {
+        let path = std::env::temp_dir().join(format!("jotx-histdb-{}.db", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE commands (id INTEGER PRIMARY KEY AUTOINCREMENT, argv TEXT);
+            CREATE TABLE places (id INTEGER PRIMARY KEY AUTOINCREMENT, host TEXT, dir TEXT);
+            CREATE TABLE history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, session INT, command_id INT,
+                place_id INT, exit_status INT, start_time INT, duration INT
+            );
+            INSERT INTO commands (argv) VALUES ('make test'), ('vim');
+            INSERT INTO places (host, dir) VALUES ('box', '/src');
+            INSERT INTO history (session, command_id, place_id, exit_status, start_time, duration)
+            VALUES (1, 1, 1, 2, 1700000000, 12), (1, 2, 1, NULL, 1700000050, NULL);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let runs = read(&path).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].content, "make test");
+        assert_eq!(runs[0].timestamp, 1_700_000_000);
+        assert_eq!(runs[0].exit_code, Some(2));
+        assert_eq!(runs[0].duration_ms, Some(12_000));
+        assert_eq!(runs[0].working_dir.as_deref(), Some("/src"));
+        assert_eq!(runs[0].host.as_deref(), Some("box"));
+        assert_eq!(runs[1].exit_code, None);
+        assert_eq!(runs[1].duration_ms, None);
+        std::fs::remove_file(&path).ok();
+    }
+}
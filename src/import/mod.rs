@@ -0,0 +1,166 @@
+//! `jotx import`: history recorded by other tools (Atuin, zsh-histdb, fish, bash),
+//! read with whatever each one keeps (times, directories, durations, exit codes) and
+//! merged into jotx's one row per command and directory.
+
+mod atuin;
+mod bash;
+mod fish;
+mod histdb;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+use crate::types::ImportedCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportSource {
+    Atuin,
+    /// zsh-histdb
+    Histdb,
+    Fish,
+    Bash,
+}
+
+impl ImportSource {
+    /// Where the tool keeps its history unless configured otherwise
+    pub fn default_path(&self) -> PathBuf {
+        let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+        let data_dir = std::env::var("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home.join(".local/share"));
+
+        match self {
+            ImportSource::Atuin => data_dir.join("atuin/history.db"),
+            ImportSource::Histdb => std::env::var("HISTDB_FILE")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| home.join(".histdb/zsh-history.db")),
+            ImportSource::Fish => data_dir.join("fish/fish_history"),
+            ImportSource::Bash => std::env::var("HISTFILE")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| home.join(".bash_history")),
+        }
+    }
+}
+
+impl FromStr for ImportSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "atuin" => Ok(ImportSource::Atuin),
+            "histdb" | "zsh-histdb" => Ok(ImportSource::Histdb),
+            "fish" => Ok(ImportSource::Fish),
+            "bash" => Ok(ImportSource::Bash),
+            _ => Err(format!(
+                "Unknown history source: {} (expected atuin, histdb, fish or bash)",
+                s
+            )),
+        }
+    }
+}
+
+/// Read every run recorded at `path`, oldest first. Runs the file doesn't date get
+/// the file's modification time.
+pub fn read_history(
+    source: ImportSource,
+    path: &Path,
+) -> Result<Vec<ImportedCommand>, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Err(format!("{} not found", path.display()).into());
+    }
+
+    let mut runs = match source {
+        ImportSource::Atuin => atuin::read(path)?,
+        ImportSource::Histdb => histdb::read(path)?,
+        ImportSource::Fish => fish::parse(&fs::read_to_string(path)?),
+        // bash writes whatever bytes were typed
+        ImportSource::Bash => bash::parse(&String::from_utf8_lossy(&fs::read(path)?)),
+    };
+
+    let modified = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_secs())
+        .unwrap_or_default();
+    for run in runs.iter_mut().filter(|run| run.timestamp == 0) {
+        run.timestamp = modified;
+    }
+    runs.retain(|run| !run.content.trim().is_empty());
+    Ok(runs)
+}
+
+/// Merge runs of the same command in the same directory. The latest run's time,
+/// outcome and host win; the order is that of first appearance.
+pub fn merge_runs(runs: Vec<ImportedCommand>) -> Vec<ImportedCommand> {
+    let mut merged: Vec<ImportedCommand> = Vec::new();
+    let mut index: HashMap<(String, Option<String>), usize> = HashMap::new();
+
+    for run in runs {
+        let key = (run.content.clone(), run.working_dir.clone());
+        let Some(&i) = index.get(&key) else {
+            index.insert(key, merged.len());
+            merged.push(run);
+            continue;
+        };
+
+        let existing = &mut merged[i];
+        existing.times_run += run.times_run;
+        if run.timestamp >= existing.timestamp {
+            existing.timestamp = run.timestamp;
+            existing.exit_code = run.exit_code;
+            existing.duration_ms = run.duration_ms;
+            existing.user = run.user.or(existing.user.take());
+            existing.host = run.host.or(existing.host.take());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(content: &str, dir: Option<&str>, timestamp: u64, exit_code: i32) -> ImportedCommand {
+        ImportedCommand {
+            content: content.to_string(),
+            timestamp,
+            working_dir: dir.map(str::to_string),
+            exit_code: Some(exit_code),
+            times_run: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_runs() {
+        let merged = merge_runs(vec![
+            run("make", Some("/a"), 300, 2),
+            run("ls", None, 100, 0),
+            run("make", Some("/a"), 200, 0),
+            run("make", Some("/b"), 400, 0),
+            run("make", Some("/a"), 500, 0),
+        ]);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].content, "make");
+        assert_eq!(merged[0].times_run, 3);
+        assert_eq!(merged[0].timestamp, 500);
+        assert_eq!(merged[0].exit_code, Some(0));
+        assert_eq!(merged[1].content, "ls");
+        assert_eq!(merged[2].working_dir.as_deref(), Some("/b"));
+    }
+
+    #[test]
+    fn test_parse_import_source() {
+        assert_eq!(ImportSource::from_str("Atuin"), Ok(ImportSource::Atuin));
+        assert_eq!(
+            ImportSource::from_str("zsh-histdb"),
+            Ok(ImportSource::Histdb)
+        );
+        assert!(ImportSource::from_str("zsh").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use std::io::Write;
+use std::net::SocketAddr;
+use std::process::Command;
+use std::str::FromStr;
+
+use clap::CommandFactory;
+use dialoguer::Input;
+
+use crate::ask::search;
+use crate::commands::get_working_directory;
+use crate::config::{GLOBAL_CONFIG, RemoteConfig};
+use crate::types::{Cli, EntryType};
+
+const FZF_BASH: &str = include_str!("scripts/fzf.bash");
+const FZF_ZSH: &str = include_str!("scripts/fzf.zsh");
+pub const TMUX_CONF: &str = include_str!("scripts/tmux.conf");
+
+/// PROMPT_COMMAND for bash inside a container: POSTs the last command to the daemon's
+/// `/v1/capture` in the background, using JOTX_URL, JOTX_TOKEN and JOTX_CONTAINER
+const CONTAINER_HOOK: &str = r#"ec=$?; cmd=$(HISTTIMEFORMAT= history 1 | sed "s/^ *[0-9]* *//"); [ -n "$cmd" ] && (curl -fsS -m 2 ${JOTX_TOKEN:+-H} ${JOTX_TOKEN:+"Authorization: Bearer $JOTX_TOKEN"} --data-urlencode "cmd=$cmd" --data-urlencode "exit_code=$ec" --data-urlencode "pwd=$PWD" --data-urlencode "user=${USER:-$(id -un)}" --data-urlencode "container=$JOTX_CONTAINER" "$JOTX_URL/v1/capture" >/dev/null 2>&1 &)"#;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Integration {
+    Fzf,
+    Tmux,
+}
+
+impl FromStr for Integration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fzf" => Ok(Integration::Fzf),
+            "tmux" => Ok(Integration::Tmux),
+            _ => Err(format!("Unknown integration: {} (expected fzf or tmux)", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+}
+
+impl Shell {
+    /// The login shell from $SHELL, as the hook setup uses
+    pub fn detect() -> Result<Self, String> {
+        let shell = std::env::var("SHELL").map_err(|_| "SHELL not set; pass --shell")?;
+        let name = shell.rsplit('/').next().unwrap_or_default();
+        Self::from_str(name)
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            _ => Err(format!("Unsupported shell: {} (expected bash or zsh)", s)),
+        }
+    }
+}
+
+/// `jotx integrations <tool>`: the snippet to add to the shell's rc file (or to
+/// ~/.tmux.conf for tmux)
+pub fn integration_snippet(tool: &str, shell: Option<&str>) -> Result<&'static str, String> {
+    let pick_shell = || match shell {
+        Some(shell) => Shell::from_str(shell),
+        None => Shell::detect(),
+    };
+
+    Ok(match Integration::from_str(tool)? {
+        Integration::Fzf => match pick_shell()? {
+            Shell::Bash => FZF_BASH,
+            Shell::Zsh => FZF_ZSH,
+        },
+        Integration::Tmux => TMUX_CONF,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContainerShim {
+    Docker,
+    Devcontainer,
+}
+
+impl FromStr for ContainerShim {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "docker" => Ok(ContainerShim::Docker),
+            "devcontainer" | "devcontainers" => Ok(ContainerShim::Devcontainer),
+            _ => Err(format!(
+                "Unknown shim: {} (expected docker or devcontainer)",
+                s
+            )),
+        }
+    }
+}
+
+/// `jotx shim <docker|devcontainer>`: a shell function wrapping `docker exec`, or the
+/// devcontainer.json settings, that report commands run in the container to the
+/// daemon's HTTP API
+pub fn container_shim(kind: &str) -> Result<String, String> {
+    let remote = GLOBAL_CONFIG
+        .read()
+        .map(|config| config.remote.clone())
+        .map_err(|_| "Failed to read config".to_string())?;
+    container_shim_for(ContainerShim::from_str(kind)?, &remote)
+}
+
+pub(crate) fn container_shim_for(
+    kind: ContainerShim,
+    remote: &RemoteConfig,
+) -> Result<String, String> {
+    let listen = remote.api_listen.as_deref().ok_or(
+        "Containers report through the HTTP API: set remote.api_listen in config.toml first",
+    )?;
+    let addr: SocketAddr = listen
+        .parse()
+        .map_err(|e| format!("Invalid remote.api_listen {}: {}", listen, e))?;
+    let token = remote.token.clone().ok_or(
+        "Containers report through a POST route, which needs a token: set remote.token in config.toml first",
+    )?;
+
+    // Only Docker Desktop routes host.docker.internal to the host's localhost; on Linux
+    // the API has to listen on the bridge address (e.g. 172.17.0.1)
+    let (url, note) = if addr.ip().is_loopback() || addr.ip().is_unspecified() {
+        (
+            format!("http://host.docker.internal:{}", addr.port()),
+            "API is on localhost, which only Docker Desktop forwards. On Linux set remote.api_listen to the docker0 address (e.g. 172.17.0.1:7311) and list it in remote.allowed_interfaces",
+        )
+    } else {
+        (format!("http://{}", addr), "")
+    };
+
+    let mut out = String::new();
+    match kind {
+        ContainerShim::Docker => {
+            if !note.is_empty() {
+                out.push_str(&format!("# {}\n", note));
+            }
+            out.push_str(&format!(
+                r#"# jotx_exec <container> [command]: docker exec a shell whose commands jotx records
+# with host container:<container> (the container needs bash and curl)
+jotx_exec() {{
+    local container="$1"
+    shift
+    docker exec -it \
+        -e JOTX_URL={} \
+        -e JOTX_TOKEN={} \
+        -e JOTX_CONTAINER="$container" \
+        -e PROMPT_COMMAND={} \
+        "$container" "${{@:-bash}}"
+}}
+"#,
+                shell_quote(&url),
+                shell_quote(&token),
+                shell_quote(CONTAINER_HOOK)
+            ));
+        }
+        ContainerShim::Devcontainer => {
+            if !note.is_empty() {
+                out.push_str(&format!("// {}\n", note));
+            }
+            let settings = serde_json::json!({
+                "runArgs": ["--add-host=host.docker.internal:host-gateway"],
+                "remoteEnv": {
+                    "JOTX_URL": url,
+                    "JOTX_TOKEN": token,
+                    "JOTX_CONTAINER": "${localWorkspaceFolderBasename}",
+                    "PROMPT_COMMAND": CONTAINER_HOOK,
+                },
+            });
+            out.push_str(
+                "// Merge into .devcontainer/devcontainer.json (the image needs bash and curl)\n",
+            );
+            out.push_str(&serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// Single-quote for sh
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `jotx completions <shell>`: the tab completion script for every subcommand and flag
+pub fn write_completions<W: Write>(shell: clap_complete::Shell, out: &mut W) {
+    clap_complete::generate(shell, &mut Cli::command(), "jotx", out);
+}
+
+/// `jotx tmux`: search in a tmux popup and type the pick into the pane it was opened
+/// from, without running it. Without `pane` this opens the popup, which runs
+/// `jotx tmux --pane <origin>`; the TMUX_CONF binding starts there directly.
+pub fn tmux_search(pane: Option<&str>, clipboard: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(pane) = pane else {
+        let origin = std::env::var("TMUX_PANE").map_err(|_| "Not running inside tmux")?;
+        let mut popup = format!("jotx tmux --pane '{}'", origin);
+        if clipboard {
+            popup.push_str(" --clipboard");
+        }
+
+        let status = Command::new("tmux")
+            .args(["display-popup", "-E", "-w", "80%", "-h", "60%", "-d"])
+            .arg(get_working_directory())
+            .arg(popup)
+            .status()?;
+        if !status.success() {
+            return Err("tmux display-popup failed (needs tmux 3.2+)".into());
+        }
+        return Ok(());
+    };
+
+    let query: String = Input::new().with_prompt("🔍 Search").interact_text()?;
+
+    // The popup starts in the origin pane's directory
+    let Some(result) = search(
+        &query,
+        EntryType::from_clipboard_flag(clipboard),
+        &get_working_directory(),
+        None,
+        true,
+    ) else {
+        return Ok(());
+    };
+
+    // -l sends the text literally, so nothing is run until the user presses Enter
+    let status = Command::new("tmux")
+        .args(["send-keys", "-t", pane, "-l"])
+        .arg(&result)
+        .status()?;
+    if !status.success() {
+        return Err(format!("Failed to send the selection to pane {}", pane).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_matches_shell() {
+        assert!(
+            integration_snippet("fzf", Some("bash"))
+                .unwrap()
+                .contains("READLINE_LINE")
+        );
+        assert!(
+            integration_snippet("FZF", Some("zsh"))
+                .unwrap()
+                .contains("zle -N")
+        );
+        assert!(integration_snippet("fzf", Some("fish")).is_err());
+        assert!(integration_snippet("skim", Some("zsh")).is_err());
+        assert_eq!(
+            integration_snippet("tmux", Some("fish")).unwrap(),
+            TMUX_CONF
+        );
+    }
+
+    #[test]
+    fn test_container_shim_needs_the_api_and_token() {
+        let mut remote = RemoteConfig::default();
+        assert!(container_shim_for(ContainerShim::Docker, &remote).is_err());
+
+        remote.api_listen = Some("172.17.0.1:7311".to_string());
+        assert!(container_shim_for(ContainerShim::Docker, &remote).is_err());
+        remote.token = Some("it's-a-token".to_string());
+        let docker = container_shim_for(ContainerShim::Docker, &remote).unwrap();
+        assert!(docker.starts_with("# jotx_exec"));
+        assert!(docker.contains("-e JOTX_URL='http://172.17.0.1:7311'"));
+        assert!(docker.contains(r"-e JOTX_TOKEN='it'\''s-a-token'"));
+
+        remote.api_listen = Some("127.0.0.1:7311".to_string());
+        let devcontainer = container_shim_for(ContainerShim::Devcontainer, &remote).unwrap();
+        let json: String = devcontainer
+            .lines()
+            .filter(|l| !l.starts_with("//"))
+            .collect();
+        let settings: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            settings["remoteEnv"]["JOTX_URL"],
+            "http://host.docker.internal:7311"
+        );
+        assert_eq!(settings["remoteEnv"]["PROMPT_COMMAND"], CONTAINER_HOOK);
+    }
+
+    #[test]
+    fn test_completions_cover_subcommands() {
+        let mut out = Vec::new();
+        write_completions(clap_complete::Shell::Bash, &mut out);
+        let script = String::from_utf8(out).unwrap();
+        assert!(script.contains("prompt-info") && script.contains("--clipboard"));
+    }
+}
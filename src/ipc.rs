@@ -0,0 +1,352 @@
+//! Control channel between the CLI and the daemon: a Unix socket at
+//! `~/.jotx/daemon.sock`, only accessible to the user. A client connects, writes one
+//! JSON request line and reads one JSON response line:
+//!
+//! `{"command": "status"}` → `{"result": "status", "status": {"running": true, ...}}`
+//!
+//! `{"command": "reload"}` → `{"result": "done", "message": "..."}` after the daemon
+//! re-read config.toml, the settings and the plugins directory (SIGHUP does the same)
+//!
+//! `{"command": "exit"}` → `{"result": "done", ...}`, then the daemon shuts down
+//!
+//! `{"command": "pause"}` / `{"command": "resume"}` stop and restart capture, and
+//! `{"command": "maintenance"}` trims and maintains the database on the daemon's own
+//! connection; all three answer `done`
+//!
+//! `{"command": "copy_entry", "id": 42}` → `done` once the daemon put entry 42 on the
+//! clipboard, where its monitor knows not to capture it again
+//!
+//! Failures come back as `{"result": "error", "message": "..."}`. A socket that
+//! answers is a running daemon, so there is no stale PID file to trip over.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::config::reload_config;
+use crate::llm::GLOBAL_LLM;
+use crate::managers::lock_manager::lock_or_recover;
+use crate::managers::task_manager::worker_health;
+use crate::metrics::{MetricsSnapshot, snapshot};
+use crate::pid_controller::{
+    dropped_captures, is_paused, paused_until, problems, quiet_reason, set_paused,
+};
+use crate::plugin::GLOBAL_PLUGIN_MANAGER;
+use crate::settings::{GLOBAL_SETTINGS, Settings};
+use crate::types::DaemonStatus;
+use crate::utils::{copy_stored_entry, run_maintenance};
+
+/// Requests larger than this are rejected
+const MAX_REQUEST_BYTES: u64 = 4 * 1024;
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maintenance can vacuum a large database before it answers
+const MAINTENANCE_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IpcRequest {
+    Status,
+    Metrics,
+    Reload,
+    Exit,
+    Pause,
+    Resume,
+    Maintenance,
+    CopyEntry { id: i64 },
+}
+
+impl IpcRequest {
+    /// How long a client waits for the answer
+    fn timeout(&self) -> Duration {
+        match self {
+            IpcRequest::Maintenance => MAINTENANCE_TIMEOUT,
+            _ => IO_TIMEOUT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum IpcResponse {
+    Status { status: DaemonStatus },
+    Metrics { metrics: MetricsSnapshot },
+    Done { message: String },
+    Error { message: String },
+}
+
+/// Set when a client sends `exit`; `shutdown_requested` waits on it
+static EXIT_REQUESTED: Lazy<Notify> = Lazy::new(Notify::new);
+static STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+
+pub fn get_socket_path() -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".jotx").join("daemon.sock")
+}
+
+/// Resolves once a client has asked the daemon to exit
+pub async fn exit_requested() {
+    EXIT_REQUESTED.notified().await;
+}
+
+/// Status as the daemon sees it
+pub(crate) fn daemon_status() -> DaemonStatus {
+    DaemonStatus {
+        running: true,
+        paused: is_paused(),
+        paused_until: paused_until().map(|end| end.timestamp()),
+        pid: Some(std::process::id()),
+        quiet: quiet_reason(),
+        problems: problems()
+            .into_iter()
+            .map(|(component, problem)| format!("{}: {}", component, problem))
+            .collect(),
+        dropped_captures: dropped_captures(),
+        uptime_secs: Some(STARTED.elapsed().as_secs()),
+        workers: worker_health(),
+    }
+}
+
+/// The daemon's counters, as `jotx status --metrics` and `/metrics` report them
+pub(crate) fn daemon_metrics() -> MetricsSnapshot {
+    snapshot(STARTED.elapsed())
+}
+
+/// Re-read config.toml, the settings and the plugins directory in the running daemon,
+/// for `reload` on the control socket and SIGHUP. Blocking: call it off the runtime
+/// or inside `block_in_place`.
+pub fn reload_daemon() -> Result<String, String> {
+    reload_config().map_err(|e| format!("Failed to reload config: {}", e))?;
+    *lock_or_recover("settings", &GLOBAL_SETTINGS) = Settings::load();
+    GLOBAL_LLM.blocking_lock().reload_config();
+
+    let mut plugins = lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER);
+    plugins.reload();
+    let message = format!(
+        "Config, settings and plugins reloaded ({} plugins loaded)",
+        plugins.list().len()
+    );
+    info!("{}", message);
+    Ok(message)
+}
+
+fn respond(request: IpcRequest) -> IpcResponse {
+    match request {
+        IpcRequest::Status => IpcResponse::Status {
+            status: daemon_status(),
+        },
+        IpcRequest::Metrics => IpcResponse::Metrics {
+            metrics: daemon_metrics(),
+        },
+        IpcRequest::Reload => match reload_daemon() {
+            Ok(message) => IpcResponse::Done { message },
+            Err(message) => IpcResponse::Error { message },
+        },
+        IpcRequest::Exit => {
+            EXIT_REQUESTED.notify_one();
+            IpcResponse::Done {
+                message: "Daemon is shutting down".to_string(),
+            }
+        }
+        IpcRequest::Pause => pause_response(true, "Capture paused"),
+        IpcRequest::Resume => pause_response(false, "Capture resumed"),
+        IpcRequest::Maintenance => match run_maintenance() {
+            Ok(()) => IpcResponse::Done {
+                message: "Maintenance finished".to_string(),
+            },
+            Err(message) => IpcResponse::Error { message },
+        },
+        IpcRequest::CopyEntry { id } => match copy_stored_entry(id) {
+            Ok(()) => IpcResponse::Done {
+                message: format!("Copied entry {} to the clipboard", id),
+            },
+            Err(message) => IpcResponse::Error { message },
+        },
+    }
+}
+
+fn pause_response(paused: bool, message: &str) -> IpcResponse {
+    match set_paused(paused) {
+        Ok(()) => IpcResponse::Done {
+            message: message.to_string(),
+        },
+        Err(e) => IpcResponse::Error {
+            message: format!("Failed to update pause state: {}", e),
+        },
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{remove_socket, request, spawn_ipc_server};
+
+#[cfg(unix)]
+mod unix {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::thread;
+
+    use super::*;
+
+    /// Listen on the control socket on its own thread. Fails if another daemon
+    /// already answers on it; a socket file left behind by a crash is replaced.
+    pub fn spawn_ipc_server() -> Result<(), String> {
+        let path = get_socket_path();
+        if UnixStream::connect(&path).is_ok() {
+            return Err(format!("Another daemon is listening on {}", path.display()));
+        }
+        let _ = fs::remove_file(&path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+
+        let listener = UnixListener::bind(&path)
+            .map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict {}: {}", path.display(), e))?;
+        Lazy::force(&STARTED);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    // Each connection gets its own thread, so a long `maintenance`
+                    // doesn't hold up `status` or `exit`
+                    Ok(stream) => {
+                        thread::spawn(move || {
+                            if let Err(e) = handle(stream) {
+                                warn!("Control request failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Control socket accept failed: {}", e),
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    fn handle(stream: UnixStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        let mut line = String::new();
+        BufReader::new((&stream).take(MAX_REQUEST_BYTES)).read_line(&mut line)?;
+
+        let response = match serde_json::from_str::<IpcRequest>(line.trim()) {
+            Ok(request) => respond(request),
+            Err(e) => IpcResponse::Error {
+                message: format!("Invalid request: {}", e),
+            },
+        };
+
+        let mut out = serde_json::to_string(&response).map_err(std::io::Error::other)?;
+        out.push('\n');
+        (&stream).write_all(out.as_bytes())
+    }
+
+    /// Send `request` to the running daemon. Errors if no daemon is listening.
+    pub fn request(request: IpcRequest) -> Result<IpcResponse, String> {
+        let path = get_socket_path();
+        let mut stream =
+            UnixStream::connect(&path).map_err(|e| format!("Daemon not reachable: {}", e))?;
+        stream
+            .set_read_timeout(Some(request.timeout()))
+            .and_then(|_| stream.set_write_timeout(Some(IO_TIMEOUT)))
+            .map_err(|e| e.to_string())?;
+
+        let mut line = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+        line.push('\n');
+        stream
+            .write_all(line.as_bytes())
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+
+        let mut response = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut response)
+            .map_err(|e| format!("No response from daemon: {}", e))?;
+        serde_json::from_str(response.trim()).map_err(|e| format!("Invalid response: {}", e))
+    }
+
+    /// Remove the socket file when the daemon stops
+    pub fn remove_socket() {
+        let _ = fs::remove_file(get_socket_path());
+    }
+}
+
+#[cfg(not(unix))]
+pub fn spawn_ipc_server() -> Result<(), String> {
+    Err("the control socket needs a Unix platform".to_string())
+}
+
+#[cfg(not(unix))]
+pub fn request(_request: IpcRequest) -> Result<IpcResponse, String> {
+    Err("the control socket needs a Unix platform".to_string())
+}
+
+#[cfg(not(unix))]
+pub fn remove_socket() {}
+
+/// The daemon's status over the socket, `None` if no daemon answers
+pub fn daemon_status_via_socket() -> Option<DaemonStatus> {
+    match request(IpcRequest::Status) {
+        Ok(IpcResponse::Status { status }) => Some(status),
+        _ => None,
+    }
+}
+
+/// Send a command answered with `done` (pause, resume, maintenance...) to the running
+/// daemon, returning its message
+pub fn send_command(command: IpcRequest) -> Result<String, String> {
+    match request(command)? {
+        IpcResponse::Done { message } => Ok(message),
+        IpcResponse::Error { message } => Err(message),
+        other => Err(format!("Unexpected response: {:?}", other)),
+    }
+}
+
+/// The running daemon's counters, if it answers on the control socket
+pub fn daemon_metrics_via_socket() -> Option<MetricsSnapshot> {
+    match request(IpcRequest::Metrics) {
+        Ok(IpcResponse::Metrics { metrics }) => Some(metrics),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_json() {
+        assert_eq!(
+            serde_json::to_string(&IpcRequest::Status).unwrap(),
+            r#"{"command":"status"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<IpcRequest>(r#"{"command":"exit"}"#).unwrap(),
+            IpcRequest::Exit
+        );
+        assert_eq!(
+            serde_json::from_str::<IpcRequest>(r#"{"command":"maintenance"}"#).unwrap(),
+            IpcRequest::Maintenance
+        );
+        assert_eq!(
+            serde_json::from_str::<IpcRequest>(r#"{"command":"copy_entry","id":42}"#).unwrap(),
+            IpcRequest::CopyEntry { id: 42 }
+        );
+        assert!(serde_json::from_str::<IpcRequest>(r#"{"command":"rm"}"#).is_err());
+
+        let done = IpcResponse::Done {
+            message: "ok".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&done).unwrap(),
+            r#"{"result":"done","message":"ok"}"#
+        );
+    }
+}
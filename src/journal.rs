@@ -0,0 +1,337 @@
+//! `jotx journal`: one day of history rendered as Markdown, split into work sessions,
+//! for pasting into standup notes.
+
+use chrono::{Duration, NaiveDate, Utc};
+use std::fmt::Write;
+
+use crate::db::{USER_DB, vault_placeholder};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::timezone::QueryTimezone;
+use crate::types::{EntryType, HistoryEntry, QueryParams};
+
+/// A gap longer than this between two captures starts a new session
+const SESSION_GAP_SECS: i64 = 30 * 60;
+/// Commands listed per session before the rest are summarised as "… and N more"
+const MAX_COMMANDS_PER_SESSION: usize = 15;
+/// Clipboard items listed per session
+const MAX_CLIPS_PER_SESSION: usize = 5;
+/// Clipboard items shorter than this (a word, a number) are not worth a line
+const MIN_CLIP_CHARS: usize = 12;
+/// Longer commands and clipboard items are cut to this many characters
+const MAX_LINE_CHARS: usize = 100;
+
+/// Captures close enough together to count as one stretch of work
+#[derive(Debug)]
+pub(crate) struct Session {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl Session {
+    pub fn start(&self) -> i64 {
+        self.entries
+            .first()
+            .map(|e| e.timestamp)
+            .unwrap_or_default()
+    }
+
+    pub fn end(&self) -> i64 {
+        self.entries.last().map(|e| e.timestamp).unwrap_or_default()
+    }
+
+    /// Distinct directories commands ran in, in the order first seen
+    pub fn directories(&self) -> Vec<&str> {
+        let mut dirs: Vec<&str> = Vec::new();
+        for dir in self.entries.iter().filter_map(|e| e.working_dir.as_deref()) {
+            if !dir.is_empty() && !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        dirs
+    }
+
+    pub fn of_type(&self, entry_type: EntryType) -> impl Iterator<Item = &HistoryEntry> {
+        let entry_type = entry_type.to_string();
+        self.entries
+            .iter()
+            .filter(move |e| e.entry_type == entry_type)
+    }
+}
+
+/// Resolve "today", "yesterday" or a YYYY-MM-DD date to the date and its
+/// [start, end) unix range in the configured timezone
+pub fn day_range(date: Option<&str>, tz: &QueryTimezone) -> Result<(NaiveDate, i64, i64), String> {
+    let now = Utc::now();
+    let today = tz.local_time(now).date();
+
+    let day = match date.map(str::trim).unwrap_or("today") {
+        "" | "today" => today,
+        "yesterday" => today - Duration::days(1),
+        value => NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            format!(
+                "Invalid date: {} (expected YYYY-MM-DD, today or yesterday)",
+                value
+            )
+        })?,
+    };
+
+    let days_back = (today - day).num_days();
+    if days_back < 0 {
+        return Err(format!("{} is in the future", day));
+    }
+
+    Ok((
+        day,
+        tz.day_start(now, days_back),
+        tz.day_start(now, days_back - 1),
+    ))
+}
+
+/// Markdown journal for a day (defaults to today)
+pub fn journal(date: Option<&str>) -> Result<String, String> {
+    let tz = QueryTimezone::from_config();
+    let (day, start, end) = day_range(date, &tz)?;
+
+    Ok(render_journal(
+        &journal_title(day),
+        &split_sessions(day_entries(start, end)?),
+        &tz,
+    ))
+}
+
+/// Entries captured in [start, end), oldest first
+pub(crate) fn day_entries(start: i64, end: i64) -> Result<Vec<HistoryEntry>, String> {
+    let params = QueryParams {
+        since: Some(start),
+        until: Some(end),
+        ..Default::default()
+    };
+    let mut entries: Vec<HistoryEntry> = {
+        let db = lock_or_recover("database", &USER_DB);
+        db.query_entries(&params)
+            .map_err(|e| format!("Failed to load history: {}", e))?
+            .into_iter()
+            .map(HistoryEntry::from)
+            .collect()
+    };
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Split entries (oldest first) wherever captures are more than `SESSION_GAP_SECS` apart
+pub(crate) fn split_sessions(entries: Vec<HistoryEntry>) -> Vec<Session> {
+    let mut sessions: Vec<Session> = Vec::new();
+    for entry in entries {
+        match sessions.last_mut() {
+            Some(session) if entry.timestamp - session.end() <= SESSION_GAP_SECS => {
+                session.entries.push(entry)
+            }
+            _ => sessions.push(Session {
+                entries: vec![entry],
+            }),
+        }
+    }
+    sessions
+}
+
+pub(crate) fn journal_title(day: NaiveDate) -> String {
+    format!("Journal: {}", day.format("%A, %Y-%m-%d"))
+}
+
+/// Markdown for a list of sessions under a `# title` heading
+pub(crate) fn render_journal(title: &str, sessions: &[Session], tz: &QueryTimezone) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}", title);
+    let _ = writeln!(out);
+
+    if sessions.is_empty() {
+        let _ = writeln!(out, "_Nothing captured._");
+        return out;
+    }
+
+    let active: i64 = sessions.iter().map(|s| s.end() - s.start()).sum();
+    let commands: usize = sessions
+        .iter()
+        .map(|s| s.of_type(EntryType::Shell).count())
+        .sum();
+    let clips: usize = sessions
+        .iter()
+        .map(|s| s.of_type(EntryType::Clipboard).count())
+        .sum();
+    let _ = writeln!(
+        out,
+        "{} session{} · {} active · {} commands · {} clipboard items",
+        sessions.len(),
+        if sessions.len() == 1 { "" } else { "s" },
+        format_duration(active),
+        commands,
+        clips
+    );
+
+    for session in sessions {
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "## {}–{} ({})",
+            tz.format_timestamp(session.start(), "%H:%M"),
+            tz.format_timestamp(session.end(), "%H:%M"),
+            format_duration(session.end() - session.start())
+        );
+
+        let dirs = session.directories();
+        if !dirs.is_empty() {
+            let dirs: Vec<String> = dirs.iter().map(|d| inline_code(d)).collect();
+            let _ = writeln!(out);
+            let _ = writeln!(out, "**Directories:** {}", dirs.join(", "));
+        }
+
+        let mut seen: Vec<&str> = Vec::new();
+        for entry in session.of_type(EntryType::Shell) {
+            if !seen.contains(&entry.content.as_str()) {
+                seen.push(&entry.content);
+            }
+        }
+        if !seen.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "**Commands**");
+            let _ = writeln!(out);
+            for command in seen.iter().take(MAX_COMMANDS_PER_SESSION) {
+                let _ = writeln!(out, "- {}", inline_code(&one_line(command)));
+            }
+            if seen.len() > MAX_COMMANDS_PER_SESSION {
+                let _ = writeln!(
+                    out,
+                    "- … and {} more",
+                    seen.len() - MAX_COMMANDS_PER_SESSION
+                );
+            }
+        }
+
+        let notable: Vec<String> = session
+            .of_type(EntryType::Clipboard)
+            .filter(|e| !is_vaulted(e) && e.content.trim().chars().count() >= MIN_CLIP_CHARS)
+            .map(|e| one_line(e.content.trim()))
+            .take(MAX_CLIPS_PER_SESSION)
+            .collect();
+        if !notable.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "**Clipboard**");
+            let _ = writeln!(out);
+            for clip in notable {
+                let _ = writeln!(out, "- {}", inline_code(&clip));
+            }
+        }
+    }
+
+    out
+}
+
+fn is_vaulted(entry: &HistoryEntry) -> bool {
+    entry.content == vault_placeholder(entry.id)
+}
+
+/// First line, cut to `MAX_LINE_CHARS`, with an ellipsis when anything was dropped
+fn one_line(content: &str) -> String {
+    let first = content.lines().next().unwrap_or_default();
+    let mut line: String = first.chars().take(MAX_LINE_CHARS).collect();
+    if line.len() < content.len() {
+        line.push('…');
+    }
+    line
+}
+
+/// Wrap in backticks, using a longer run than any inside the text
+fn inline_code(text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let ticks = "`".repeat(longest_run + 1);
+    if longest_run > 0 {
+        format!("{} {} {}", ticks, text, ticks)
+    } else {
+        format!("{}{}{}", ticks, text, ticks)
+    }
+}
+
+fn format_duration(secs: i64) -> String {
+    let minutes = secs / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => "<1m".to_string(),
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        id: i64,
+        entry_type: &str,
+        content: &str,
+        timestamp: i64,
+        dir: Option<&str>,
+    ) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            entry_type: entry_type.to_string(),
+            content: content.to_string(),
+            timestamp,
+            times_run: 1,
+            working_dir: dir.map(str::to_string),
+            user: None,
+            host: None,
+            app_name: None,
+            window_title: None,
+            exit_code: None,
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_journal_splits_sessions_and_renders_markdown() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 11).unwrap();
+        let nine = day.and_hms_opt(9, 0, 0).unwrap().and_utc().timestamp();
+        let entries = vec![
+            entry(1, "shell", "cargo build", nine, Some("/home/dev/jot")),
+            entry(2, "clipboard", "https://docs.rs/rusqlite", nine + 300, None),
+            entry(3, "shell", "cargo test", nine + 1200, Some("/home/dev/jot")),
+            entry(
+                4,
+                "shell",
+                "cargo build",
+                nine + 1500,
+                Some("/home/dev/jot"),
+            ),
+            entry(5, "clipboard", "ok", nine + 1600, None),
+            entry(6, "clipboard", &vault_placeholder(6), nine + 1700, None),
+            // Lunch
+            entry(7, "shell", "echo `date`", nine + 4 * 3600, Some("/tmp")),
+        ];
+
+        let sessions = split_sessions(entries);
+        assert_eq!(sessions.len(), 2);
+
+        let markdown = render_journal(&journal_title(day), &sessions, &QueryTimezone::Utc);
+        assert!(markdown.starts_with("# Journal: Monday, 2024-03-11\n"));
+        assert!(markdown.contains("2 sessions · 28m active · 4 commands · 3 clipboard items"));
+        assert!(markdown.contains("## 09:00–09:28 (28m)"));
+        assert!(markdown.contains("**Directories:** `/home/dev/jot`"));
+        assert_eq!(markdown.matches("- `cargo build`").count(), 1);
+        assert!(markdown.contains("- `https://docs.rs/rusqlite`"));
+        assert!(!markdown.contains("- `ok`"));
+        assert!(!markdown.contains("vault #"));
+        assert!(markdown.contains("## 13:00–13:00 (<1m)"));
+        assert!(markdown.contains("- `` echo `date` ``"));
+    }
+
+    #[test]
+    fn test_day_range_rejects_bad_dates() {
+        let tz = QueryTimezone::Utc;
+        let (today, start, end) = day_range(None, &tz).unwrap();
+        assert_eq!(today, Utc::now().date_naive());
+        assert_eq!(end - start, 86400);
+        assert_eq!(day_range(Some("yesterday"), &tz).unwrap().2, start);
+        assert!(day_range(Some("last tuesday"), &tz).is_err());
+        assert!(day_range(Some("2999-01-01"), &tz).is_err());
+    }
+}
@@ -1,16 +1,41 @@
+pub mod api;
+pub mod backup;
 pub mod ask;
+pub mod bench;
+pub mod browse;
 pub mod clipboard;
 pub mod commands;
 pub mod config;
 pub mod context;
 pub mod db;
 pub mod embeds;
+pub mod export;
+pub mod git_hooks;
+pub mod heatmap;
+pub mod import;
+pub mod integrations;
+pub mod ipc;
+pub mod journal;
 pub mod llm;
+pub mod logging;
 pub mod managers;
+pub mod metrics;
+pub mod normalize;
+pub mod notes;
+pub mod permissions;
 pub mod pid_controller;
 pub mod plugin;
+pub mod privacy;
+pub mod project_config;
+pub mod remote;
+pub mod service;
 pub mod settings;
 pub mod shell;
+pub mod snippets;
+pub mod sync;
+pub mod timezone;
 pub mod types;
 pub mod utils;
+pub mod webhooks;
+pub mod workflows;
 pub mod setup;
\ No newline at end of file
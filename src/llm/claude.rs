@@ -0,0 +1,284 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::get_working_directory;
+use crate::llm::prompt::{AdaptivePromptBuilder, ModelSize};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::plugin::{GLOBAL_PLUGIN_MANAGER, LlmContext};
+
+use super::{LLMQueryParams, LlmModel, answer_prompt, parse_query_params};
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Keeps `interpret_query` answers to the bare JSON object `parse_query_params` expects
+const JSON_SYSTEM_PROMPT: &str = "You turn searches of a user's shell and clipboard history into \
+     search parameters. Reply with exactly one JSON object in the requested format and nothing \
+     else: no explanation, no Markdown, no code fences.";
+
+/// Claude through the Anthropic Messages API. Like `OpenAiModel`, only the query is
+/// sent; no history samples go in the prompt.
+pub struct ClaudeModel {
+    client: Client,
+    api_base: String,
+    api_key: Option<String>,
+    model: String,
+    prompt_builder: AdaptivePromptBuilder,
+}
+
+#[derive(Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    messages: Vec<Message<'a>>,
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+/// The `data:` of a streamed event; only text deltas and the end are of interest
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    ContentBlockDelta {
+        delta: StreamDelta,
+    },
+    MessageStop,
+    Error {
+        error: StreamError,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct StreamDelta {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct StreamError {
+    message: String,
+}
+
+impl ClaudeModel {
+    pub fn new(api_base: String, api_key: Option<String>, model: String) -> Self {
+        Self {
+            client: Client::new(),
+            api_base: api_base.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+            prompt_builder: AdaptivePromptBuilder::with_size(ModelSize::Large),
+        }
+    }
+
+    async fn send(
+        &self,
+        prompt: &str,
+        system: Option<&str>,
+        max_tokens: u32,
+        temperature: f32,
+        stream: bool,
+        timeout_secs: u64,
+    ) -> Result<reqwest::Response, Box<dyn std::error::Error>> {
+        let api_key = self
+            .api_key
+            .as_deref()
+            .ok_or("No Anthropic API key: set llm.api_key or ANTHROPIC_API_KEY")?;
+        let url = format!("{}/messages", self.api_base);
+
+        self.trigger_llm_before(prompt);
+
+        let request = MessagesRequest {
+            model: &self.model,
+            max_tokens,
+            // The API accepts 0.0 to 1.0
+            temperature: temperature.clamp(0.0, 1.0),
+            system,
+            messages: vec![Message {
+                role: "user",
+                content: prompt,
+            }],
+            stream,
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .header("x-api-key", api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Anthropic API error {}: {}", status, error_text).into());
+        }
+        Ok(response)
+    }
+
+    async fn generate(
+        &self,
+        prompt: &str,
+        system: Option<&str>,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let response = self
+            .send(prompt, system, max_tokens, temperature, false, 30)
+            .await?;
+        let message: MessagesResponse = response.json().await?;
+
+        Ok(message
+            .content
+            .into_iter()
+            .filter(|block| block.kind == "text")
+            .map(|block| block.text)
+            .collect())
+    }
+
+    /// Same as `generate`, but streams the response and calls `on_token` for every piece
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let mut response = self
+            .send(prompt, None, max_tokens, temperature, true, 120)
+            .await?;
+
+        // Server-sent events; the JSON after `data:` carries its own type
+        let mut full_response = String::new();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                let Some(data) = line.trim_ascii().strip_prefix(b"data:") else {
+                    continue;
+                };
+
+                match serde_json::from_slice::<StreamEvent>(data.trim_ascii())? {
+                    StreamEvent::ContentBlockDelta { delta } if !delta.text.is_empty() => {
+                        on_token(&delta.text);
+                        full_response.push_str(&delta.text);
+                    }
+                    StreamEvent::MessageStop => return Ok(full_response),
+                    StreamEvent::Error { error } => {
+                        return Err(format!("Anthropic API error: {}", error.message).into());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(full_response)
+    }
+
+    fn trigger_llm_before(&self, prompt: &str) {
+        let context = LlmContext {
+            model: self.model.clone(),
+            provider: "anthropic".to_string(),
+            working_dir: get_working_directory(),
+        };
+        lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER).trigger_llm_before(prompt, &context);
+    }
+}
+
+#[async_trait]
+impl LlmModel for ClaudeModel {
+    async fn interpret_query(
+        &self,
+        query: &str,
+        directory: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<LLMQueryParams, Box<dyn std::error::Error>> {
+        let prompt = self.prompt_builder.build_prompt(query, directory, &[]);
+        let response = self
+            .generate(&prompt, Some(JSON_SYSTEM_PROMPT), max_tokens, temperature)
+            .await?;
+        parse_query_params(&response)
+    }
+
+    async fn answer_question(
+        &self,
+        query: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = answer_prompt(query);
+        self.generate(&prompt, None, max_tokens, temperature).await
+    }
+
+    async fn answer_question_stream(
+        &self,
+        query: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = answer_prompt(query);
+        self.generate_stream(&prompt, max_tokens, temperature, on_token)
+            .await
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    async fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stream_events() {
+        let delta: StreamEvent = serde_json::from_str(
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"git"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(delta, StreamEvent::ContentBlockDelta { delta } if delta.text == "git"));
+
+        let ping: StreamEvent = serde_json::from_str(r#"{"type":"ping"}"#).unwrap();
+        assert!(matches!(ping, StreamEvent::Other));
+
+        let message: MessagesResponse = serde_json::from_str(
+            r#"{"id":"msg_1","type":"message","role":"assistant","content":[{"type":"text","text":"{\"keywords\":[\"ssh\"]}"}],"stop_reason":"end_turn"}"#,
+        )
+        .unwrap();
+        assert_eq!(message.content[0].text, r#"{"keywords":["ssh"]}"#);
+    }
+}
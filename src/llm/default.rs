@@ -5,9 +5,10 @@ use serde::{Deserialize, Serialize};
 use crate::commands::get_working_directory;
 use crate::db::{SampleSelector, SampleStrategy};
 use crate::llm::prompt::AdaptivePromptBuilder;
+use crate::managers::lock_manager::lock_or_recover;
 use crate::plugin::{GLOBAL_PLUGIN_MANAGER, LlmContext};
 
-use super::{LLMQueryParams, LlmModel};
+use super::{LLMQueryParams, LlmModel, answer_prompt, parse_query_params};
 
 pub struct OllamaModel {
     client: Client,
@@ -35,6 +36,15 @@ struct OllamaResponse {
     response: String,
 }
 
+/// One line of a streamed (`stream: true`) generate response
+#[derive(Deserialize)]
+struct OllamaStreamChunk {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    done: bool,
+}
+
 impl OllamaModel {
     pub fn new(api_base: String, model: String) -> Self {
         Self {
@@ -53,14 +63,7 @@ impl OllamaModel {
     ) -> Result<String, Box<dyn std::error::Error>> {
         let url = format!("{}/api/generate", self.api_base);
 
-        if let Ok(plugins) = GLOBAL_PLUGIN_MANAGER.lock() {
-            let context = LlmContext {
-                model: self.model.clone(),
-                provider: "ollama".to_string(),
-                working_dir: get_working_directory(),
-            };
-            plugins.trigger_llm_before(prompt, &context);
-        }
+        self.trigger_llm_before(prompt);
 
         let request = OllamaRequest {
             model: self.model.clone(),
@@ -90,6 +93,79 @@ impl OllamaModel {
         Ok(ollama_response.response)
     }
 
+    /// Same as `generate`, but streams the response and calls `on_token` for every piece
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let url = format!("{}/api/generate", self.api_base);
+
+        self.trigger_llm_before(prompt);
+
+        let request = OllamaRequest {
+            model: self.model.clone(),
+            prompt: prompt.to_string(),
+            stream: true,
+            options: OllamaOptions {
+                temperature,
+                num_predict: max_tokens,
+            },
+        };
+
+        let mut response = self
+            .client
+            .post(&url)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(120))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Ollama API error {}: {}", status, error_text).into());
+        }
+
+        // Ollama streams newline-delimited JSON objects
+        let mut full_response = String::new();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                if line.trim_ascii().is_empty() {
+                    continue;
+                }
+
+                let part: OllamaStreamChunk = serde_json::from_slice(&line)?;
+                if !part.response.is_empty() {
+                    on_token(&part.response);
+                    full_response.push_str(&part.response);
+                }
+
+                if part.done {
+                    return Ok(full_response);
+                }
+            }
+        }
+
+        Ok(full_response)
+    }
+
+    fn trigger_llm_before(&self, prompt: &str) {
+        let context = LlmContext {
+            model: self.model.clone(),
+            provider: "ollama".to_string(),
+            working_dir: get_working_directory(),
+        };
+        lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER).trigger_llm_before(prompt, &context);
+    }
+
     // fn build_interpret_prompt(&self, query: &str, directory: &str) -> String {
     //     format!(
     //         r#"Convert query to JSON. Output ONLY valid JSON.
@@ -121,16 +197,6 @@ impl OllamaModel {
         prompt
     }
 
-    fn build_answer_prompt(&self, query: &str) -> String {
-        format!(
-            r#"You are a helpful command-line assistant. Answer this question concisely in 1-2 sentences. If the question requires a simple command answer. Give the command only.
-
-Question: {}
-
-Answer:"#,
-            query
-        )
-    }
 }
 
 #[async_trait]
@@ -146,32 +212,7 @@ impl LlmModel for OllamaModel {
 
         println!("Prompt: {}", prompt);
         let response = self.generate(&prompt, max_tokens, temperature).await?;
-
-        // More aggressive cleaning
-        let cleaned = response
-            .trim()
-            .trim_start_matches("```json")
-            .trim_start_matches("```")
-            .trim_end_matches("```")
-            .trim()
-            // Remove any text before the first {
-            .split_once('{')
-            .map(|(_, after)| format!("{{{}", after))
-            .unwrap_or(response.to_string())
-            // Remove any text after the last }
-            .rsplit_once('}')
-            .map(|(before, _)| format!("{}}}", before))
-            .unwrap_or(response.to_string());
-
-        // Parse JSON response
-        let params: LLMQueryParams = serde_json::from_str(&cleaned).map_err(|e| {
-        format!(
-            "Failed to parse LLM response as JSON: {}\n\nCleaned response:\n{}\n\nOriginal response:\n{}",
-            e, cleaned, response
-        )
-    })?;
-
-        Ok(params)
+        parse_query_params(&response)
     }
 
     async fn answer_question(
@@ -180,10 +221,22 @@ impl LlmModel for OllamaModel {
         max_tokens: u32,
         temperature: f32,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        let prompt = self.build_answer_prompt(query);
+        let prompt = answer_prompt(query);
         self.generate(&prompt, max_tokens, temperature).await
     }
 
+    async fn answer_question_stream(
+        &self,
+        query: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = answer_prompt(query);
+        self.generate_stream(&prompt, max_tokens, temperature, on_token)
+            .await
+    }
+
     fn model_name(&self) -> &str {
         &self.model
     }
@@ -0,0 +1,272 @@
+//! A GGUF model run in-process with llama.cpp (`provider = "llamacpp"`, needs a build
+//! with the `llama` feature), for machines where running Ollama isn't an option.
+
+use async_trait::async_trait;
+use llama_cpp_2::context::params::LlamaContextParams;
+use llama_cpp_2::llama_backend::LlamaBackend;
+use llama_cpp_2::llama_batch::LlamaBatch;
+use llama_cpp_2::model::params::LlamaModelParams;
+use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+use llama_cpp_2::sampling::LlamaSampler;
+use once_cell::sync::OnceCell;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::commands::get_working_directory;
+use crate::db::{SampleSelector, SampleStrategy};
+use crate::llm::prompt::AdaptivePromptBuilder;
+use crate::managers::lock_manager::lock_or_recover;
+use crate::plugin::{GLOBAL_PLUGIN_MANAGER, LlmContext};
+
+use super::{LLMQueryParams, LlmModel, answer_prompt, parse_query_params};
+
+type GenerateError = Box<dyn std::error::Error + Send + Sync>;
+
+/// llama.cpp may only be initialized once per process
+static BACKEND: OnceCell<LlamaBackend> = OnceCell::new();
+
+pub struct LlamaCppModel {
+    model: Arc<LlamaModel>,
+    /// File stem of the GGUF, which usually carries the parameter count
+    name: String,
+    prompt_builder: AdaptivePromptBuilder,
+}
+
+impl LlamaCppModel {
+    /// Load the GGUF at `path`. This reads the whole model into memory, so call it off
+    /// the async runtime.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let backend = BACKEND
+            .get_or_try_init(LlamaBackend::init)
+            .map_err(|e| format!("Failed to initialize llama.cpp: {}", e))?;
+        let model = LlamaModel::load_from_file(backend, path, &LlamaModelParams::default())
+            .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+
+        Ok(Self {
+            model: Arc::new(model),
+            prompt_builder: AdaptivePromptBuilder::new(name.clone()),
+            name,
+        })
+    }
+
+    async fn generate(
+        &self,
+        prompt: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        self.trigger_llm_before(prompt);
+
+        let model = Arc::clone(&self.model);
+        let prompt = prompt.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            run(&model, &prompt, max_tokens, temperature, |_| {})
+        })
+        .await?;
+
+        Ok(result.map_err(|e| e.to_string())?)
+    }
+
+    /// Same as `generate`, but calls `on_token` for every piece as it is sampled
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        self.trigger_llm_before(prompt);
+
+        // Sampling runs on a blocking thread; pieces come back over a channel
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let model = Arc::clone(&self.model);
+        let prompt = prompt.to_string();
+        let handle = tokio::task::spawn_blocking(move || {
+            run(&model, &prompt, max_tokens, temperature, |piece| {
+                let _ = tx.send(piece.to_string());
+            })
+        });
+
+        while let Some(piece) = rx.recv().await {
+            on_token(&piece);
+        }
+
+        Ok(handle.await?.map_err(|e| e.to_string())?)
+    }
+
+    fn trigger_llm_before(&self, prompt: &str) {
+        let context = LlmContext {
+            model: self.name.clone(),
+            provider: "llamacpp".to_string(),
+            working_dir: get_working_directory(),
+        };
+        lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER).trigger_llm_before(prompt, &context);
+    }
+
+    fn build_interpret_prompt(&self, query: &str, directory: &str) -> String {
+        let sample_count = self.prompt_builder.get_recommended_sample_count();
+        let mut sample_selector = SampleSelector {};
+        let samples = sample_selector
+            .get_samples(query, sample_count, SampleStrategy::Adaptive)
+            .unwrap_or_default();
+        self.prompt_builder.build_prompt(query, directory, &samples)
+    }
+}
+
+/// Wrap `prompt` in the model's chat template, when the GGUF ships one
+fn chat_prompt(model: &LlamaModel, prompt: &str) -> String {
+    let templated = model.chat_template(None).ok().and_then(|template| {
+        let message = LlamaChatMessage::new("user".to_string(), prompt.to_string()).ok()?;
+        model.apply_chat_template(&template, &[message], true).ok()
+    });
+    templated.unwrap_or_else(|| prompt.to_string())
+}
+
+/// Evaluate `prompt` and sample up to `max_tokens` tokens, passing each complete piece
+/// of text to `on_piece`
+fn run(
+    model: &LlamaModel,
+    prompt: &str,
+    max_tokens: u32,
+    temperature: f32,
+    mut on_piece: impl FnMut(&str),
+) -> Result<String, GenerateError> {
+    let backend = BACKEND.get().ok_or("llama.cpp is not initialized")?;
+    let tokens = model.str_to_token(&chat_prompt(model, prompt), AddBos::Always)?;
+
+    let n_prompt = tokens.len() as u32;
+    let n_ctx = NonZeroU32::new(n_prompt + max_tokens.max(1));
+    let ctx_params = LlamaContextParams::default()
+        .with_n_ctx(n_ctx)
+        .with_n_batch(n_prompt.max(512));
+    let mut ctx = model.new_context(backend, ctx_params)?;
+
+    let mut batch = LlamaBatch::new(tokens.len().max(512), 1);
+    let last = tokens.len() as i32 - 1;
+    for (i, token) in (0_i32..).zip(tokens) {
+        batch.add(token, i, &[0], i == last)?;
+    }
+    ctx.decode(&mut batch)?;
+
+    let mut sampler = if temperature <= 0.0 {
+        LlamaSampler::greedy()
+    } else {
+        LlamaSampler::chain_simple([
+            LlamaSampler::temp(temperature),
+            LlamaSampler::dist(rand::random()),
+        ])
+    };
+
+    let mut output = String::new();
+    // Tokens can end partway through a UTF-8 character
+    let mut pending: Vec<u8> = Vec::new();
+    let mut position = batch.n_tokens();
+
+    for _ in 0..max_tokens {
+        let token = sampler.sample(&ctx, batch.n_tokens() - 1);
+        sampler.accept(token);
+        if model.is_eog_token(token) {
+            break;
+        }
+
+        pending.extend(model.token_to_bytes(token, Special::Tokenize)?);
+        let piece = take_utf8(&mut pending);
+        if !piece.is_empty() {
+            on_piece(&piece);
+            output.push_str(&piece);
+        }
+
+        batch.clear();
+        batch.add(token, position, &[0], true)?;
+        position += 1;
+        ctx.decode(&mut batch)?;
+    }
+
+    Ok(output)
+}
+
+/// Remove and return the complete UTF-8 text at the start of `bytes`, leaving any
+/// unfinished character behind. Invalid bytes are replaced.
+fn take_utf8(bytes: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        // An unfinished character at the end: keep it for the next token
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    };
+    let text = String::from_utf8_lossy(&bytes[..valid]).to_string();
+    bytes.drain(..valid);
+    text
+}
+
+#[async_trait]
+impl LlmModel for LlamaCppModel {
+    async fn interpret_query(
+        &self,
+        query: &str,
+        directory: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<LLMQueryParams, Box<dyn std::error::Error>> {
+        let prompt = self.build_interpret_prompt(query, directory);
+        let response = self.generate(&prompt, max_tokens, temperature).await?;
+        parse_query_params(&response)
+    }
+
+    async fn answer_question(
+        &self,
+        query: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = answer_prompt(query);
+        self.generate(&prompt, max_tokens, temperature).await
+    }
+
+    async fn answer_question_stream(
+        &self,
+        query: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = answer_prompt(query);
+        self.generate_stream(&prompt, max_tokens, temperature, on_token)
+            .await
+    }
+
+    fn model_name(&self) -> &str {
+        &self.name
+    }
+
+    async fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_utf8_keeps_partial_characters() {
+        // "é" is 0xC3 0xA9; the second byte arrives with the next token
+        let mut bytes = vec![b'a', 0xC3];
+        assert_eq!(take_utf8(&mut bytes), "a");
+        assert_eq!(bytes, vec![0xC3]);
+
+        bytes.extend([0xA9, b'b']);
+        assert_eq!(take_utf8(&mut bytes), "éb");
+        assert!(bytes.is_empty());
+
+        let mut invalid = vec![0xFF, b'c'];
+        assert_eq!(take_utf8(&mut invalid), "\u{FFFD}c");
+    }
+}
@@ -2,10 +2,16 @@ use once_cell::sync::Lazy;
 use reqwest::Client;
 use std::process::Command;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::Mutex;
 
-use super::{LlmModel, default::OllamaModel};
+#[cfg(feature = "llama")]
+use super::llama::LlamaCppModel;
+use super::{LlmModel, claude::ClaudeModel, default::OllamaModel, openai::OpenAiModel};
 use crate::config::{Config, GLOBAL_CONFIG, LlmConfig};
+use crate::metrics::METRICS;
+
+const OLLAMA_API_BASE: &str = "http://localhost:11434";
 
 pub struct LlmManager {
     model: Option<Arc<Box<dyn LlmModel>>>,
@@ -50,6 +56,14 @@ impl LlmManager {
         }
     }
 
+    /// Re-read the LLM settings from the global config; the model is rebuilt on next use
+    pub fn reload_config(&mut self) {
+        if let Ok(cfg) = GLOBAL_CONFIG.read() {
+            self.config = cfg.llm.clone();
+            self.model = None;
+        }
+    }
+
     /// Check if Ollama is installed
     pub fn is_ollama_installed(&self) -> bool {
         Command::new("which")
@@ -65,7 +79,7 @@ impl LlmManager {
             .config
             .api_base
             .clone()
-            .unwrap_or_else(|| "http://localhost:11434".to_string());
+            .unwrap_or_else(|| OLLAMA_API_BASE.to_string());
 
         Client::new()
             .get(&api_base)
@@ -116,6 +130,12 @@ impl LlmManager {
             .unwrap_or(Vec::new())
     }
 
+    /// Whether the configured provider is a local Ollama (the default) rather than a
+    /// chat completions API
+    pub fn uses_ollama(&self) -> bool {
+        matches!(self.config.provider.to_lowercase().as_str(), "" | "ollama")
+    }
+
     /// Get or initialize the LLM model
     pub async fn get_llm(&mut self) -> Result<Arc<Box<dyn LlmModel>>, LlmError> {
         // Return existing model if already initialized
@@ -123,6 +143,19 @@ impl LlmManager {
             return Ok(Arc::clone(model));
         }
 
+        let model: Box<dyn LlmModel> = if self.uses_ollama() {
+            self.ollama_model().await?
+        } else if self.config.provider.eq_ignore_ascii_case("llamacpp") {
+            self.llama_model().await?
+        } else {
+            self.chat_api_model()?
+        };
+
+        self.model = Some(Arc::new(model));
+        Ok(Arc::clone(self.model.as_ref().unwrap()))
+    }
+
+    async fn ollama_model(&self) -> Result<Box<dyn LlmModel>, LlmError> {
         // Check if Ollama is installed
         if !self.is_ollama_installed() {
             return Err(LlmError::OllamaNotInstalled);
@@ -151,13 +184,82 @@ impl LlmManager {
             .config
             .api_base
             .clone()
-            .unwrap_or_else(|| "http://localhost:11434".to_string());
+            .unwrap_or_else(|| OLLAMA_API_BASE.to_string());
 
-        let model: Box<dyn LlmModel> =
-            Box::new(OllamaModel::new(api_base, self.config.model.clone()));
+        Ok(Box::new(OllamaModel::new(
+            api_base,
+            self.config.model.clone(),
+        )))
+    }
 
-        self.model = Some(Arc::new(model));
-        Ok(Arc::clone(self.model.as_ref().unwrap()))
+    /// "llamacpp": the GGUF at `model_path`, run in-process without Ollama
+    #[cfg(feature = "llama")]
+    async fn llama_model(&self) -> Result<Box<dyn LlmModel>, LlmError> {
+        let path = self
+            .config
+            .model_path
+            .as_deref()
+            .map(crate::notes::expand_home)
+            .ok_or_else(|| {
+                LlmError::Other("llm.model_path must point to a .gguf file".to_string())
+            })?;
+        if !path.is_file() {
+            return Err(LlmError::ModelNotFound(path.display().to_string()));
+        }
+
+        let model = tokio::task::spawn_blocking(move || LlamaCppModel::load(&path))
+            .await
+            .map_err(|e| LlmError::Other(e.to_string()))?
+            .map_err(LlmError::Other)?;
+        Ok(Box::new(model))
+    }
+
+    #[cfg(not(feature = "llama"))]
+    async fn llama_model(&self) -> Result<Box<dyn LlmModel>, LlmError> {
+        Err(LlmError::Other(
+            "provider = \"llamacpp\" needs a jotx built with the llama feature \
+             (cargo install jotx --features llama)"
+                .to_string(),
+        ))
+    }
+
+    /// "openai" (any chat completions API, chosen with `api_base`) or "anthropic" (the
+    /// Messages API). The key comes from `api_key` or the provider's usual variable.
+    fn chat_api_model(&self) -> Result<Box<dyn LlmModel>, LlmError> {
+        let provider = self.config.provider.to_lowercase();
+        let (default_base, key_var) = match provider.as_str() {
+            "openai" => ("https://api.openai.com/v1", "OPENAI_API_KEY"),
+            "anthropic" => ("https://api.anthropic.com/v1", "ANTHROPIC_API_KEY"),
+            other => {
+                return Err(LlmError::Other(format!(
+                    "Unknown LLM provider '{}' (use ollama, llamacpp, openai or anthropic)",
+                    other
+                )));
+            }
+        };
+
+        // The Ollama address from the default config doesn't apply to other providers
+        let api_base = self
+            .config
+            .api_base
+            .clone()
+            .filter(|base| base.trim_end_matches('/') != OLLAMA_API_BASE)
+            .unwrap_or_else(|| default_base.to_string());
+        let api_key = self
+            .config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var(key_var).ok())
+            .filter(|key| !key.trim().is_empty());
+
+        let model = self.config.model.clone();
+        if provider == "anthropic" {
+            Ok(Box::new(ClaudeModel::new(api_base, api_key, model)))
+        } else {
+            Ok(Box::new(OpenAiModel::new(
+                provider, api_base, api_key, model,
+            )))
+        }
     }
 
     /// Interpret a natural language query into search parameters
@@ -167,14 +269,17 @@ impl LlmManager {
         directory: &str,
     ) -> Result<super::LLMQueryParams, Box<dyn std::error::Error>> {
         let model = self.get_llm().await?;
-        model
+        let start = Instant::now();
+        let params = model
             .interpret_query(
                 query,
                 directory,
                 self.config.max_tokens,
                 self.config.temperature,
             )
-            .await
+            .await;
+        METRICS.llm_requests.record(start.elapsed());
+        params
     }
 
     /// Answer a knowledge question directly
@@ -183,10 +288,32 @@ impl LlmManager {
         query: &str,
     ) -> Result<String, Box<dyn std::error::Error>> {
         let model = self.get_llm().await?;
-
-        model
+        let start = Instant::now();
+        let answer = model
             .answer_question(query, self.config.max_tokens, self.config.temperature)
-            .await
+            .await;
+        METRICS.llm_requests.record(start.elapsed());
+        answer
+    }
+
+    /// Answer a knowledge question, streaming tokens to `on_token` as they arrive
+    pub async fn answer_question_stream(
+        &mut self,
+        query: &str,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let model = self.get_llm().await?;
+        let start = Instant::now();
+        let answer = model
+            .answer_question_stream(
+                query,
+                self.config.max_tokens,
+                self.config.temperature,
+                on_token,
+            )
+            .await;
+        METRICS.llm_requests.record(start.elapsed());
+        answer
     }
 
     /// Get the current model name
@@ -1,15 +1,21 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+mod claude;
 mod default;
 mod handle_llm;
+#[cfg(feature = "llama")]
+mod llama;
 mod manager;
+mod openai;
 mod prompt;
+mod pull;
 
 pub use handle_llm::{
     download_model_with_string, handle_llm, install_ollama, remove_model_with_string,
     start_ollama_service,
 };
 pub use manager::GLOBAL_LLM;
+pub use pull::{PullProgress, pull_model_with_progress};
 
 /// Query parameters that the LLM extracts from natural language
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,10 +43,60 @@ pub enum SimpleTimeRange {
     Custom, // No data here, just a marker
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct QueryFilters {
     pub working_dir: Option<String>,
     pub app_name: Option<String>,
+    /// true: only commands that exited non-zero; false: only ones that didn't
+    #[serde(default)]
+    pub failed: Option<bool>,
+    /// Repository the commands ran in, as its root path or directory name
+    #[serde(default)]
+    pub git_repo: Option<String>,
+    #[serde(default)]
+    pub git_branch: Option<String>,
+}
+
+/// Parse the JSON an LLM answered `interpret_query` with, ignoring code fences and
+/// any text around the object
+pub(super) fn parse_query_params(
+    response: &str,
+) -> Result<LLMQueryParams, Box<dyn std::error::Error>> {
+    let cleaned = response
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim()
+        // Remove any text before the first {
+        .split_once('{')
+        .map(|(_, after)| format!("{{{}", after))
+        .unwrap_or(response.to_string())
+        // Remove any text after the last }
+        .rsplit_once('}')
+        .map(|(before, _)| format!("{}}}", before))
+        .unwrap_or(response.to_string());
+
+    let params: LLMQueryParams = serde_json::from_str(&cleaned).map_err(|e| {
+        format!(
+            "Failed to parse LLM response as JSON: {}\n\nCleaned response:\n{}\n\nOriginal response:\n{}",
+            e, cleaned, response
+        )
+    })?;
+
+    Ok(params)
+}
+
+/// Prompt for answering a knowledge question directly
+pub(super) fn answer_prompt(query: &str) -> String {
+    format!(
+        r#"You are a helpful command-line assistant. Answer this question concisely in 1-2 sentences. If the question requires a simple command answer. Give the command only.
+
+Question: {}
+
+Answer:"#,
+        query
+    )
 }
 
 /// Base trait for LLM implementations
@@ -67,6 +123,20 @@ pub trait LlmModel: Send + Sync {
         temperature: f32,
     ) -> Result<String, Box<dyn std::error::Error>>;
 
+    /// Answer a knowledge question, calling `on_token` as pieces of the answer arrive.
+    /// Models without streaming support emit the whole answer at once.
+    async fn answer_question_stream(
+        &self,
+        query: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let answer = self.answer_question(query, max_tokens, temperature).await?;
+        on_token(&answer);
+        Ok(answer)
+    }
+
     /// Get model identifier
     fn model_name(&self) -> &str;
 }
@@ -0,0 +1,256 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::get_working_directory;
+use crate::llm::prompt::{AdaptivePromptBuilder, ModelSize};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::plugin::{GLOBAL_PLUGIN_MANAGER, LlmContext};
+
+use super::{LLMQueryParams, LlmModel, answer_prompt, parse_query_params};
+
+/// A model behind an OpenAI-style chat completions endpoint: OpenAI itself or
+/// servers like LM Studio, vLLM and llama.cpp.
+/// Only the query is sent; unlike the local model, no history samples go in the prompt.
+pub struct OpenAiModel {
+    client: Client,
+    /// Provider name as configured, reported to plugins
+    provider: String,
+    api_base: String,
+    api_key: Option<String>,
+    model: String,
+    prompt_builder: AdaptivePromptBuilder,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+    max_tokens: u32,
+    temperature: f32,
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatContent,
+}
+
+/// One `data:` event of a streamed (`stream: true`) completion
+#[derive(Deserialize)]
+struct ChatStreamChunk {
+    #[serde(default)]
+    choices: Vec<ChatStreamChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatStreamChoice {
+    delta: ChatContent,
+}
+
+#[derive(Deserialize)]
+struct ChatContent {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+impl OpenAiModel {
+    pub fn new(provider: String, api_base: String, api_key: Option<String>, model: String) -> Self {
+        Self {
+            client: Client::new(),
+            provider,
+            api_base: api_base.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+            // Hosted models follow the detailed prompt without trouble
+            prompt_builder: AdaptivePromptBuilder::with_size(ModelSize::Large),
+        }
+    }
+
+    async fn send(
+        &self,
+        prompt: &str,
+        max_tokens: u32,
+        temperature: f32,
+        stream: bool,
+        timeout_secs: u64,
+    ) -> Result<reqwest::Response, Box<dyn std::error::Error>> {
+        let url = format!("{}/chat/completions", self.api_base);
+
+        self.trigger_llm_before(prompt);
+
+        let request = ChatRequest {
+            model: &self.model,
+            messages: vec![ChatMessage {
+                role: "user",
+                content: prompt,
+            }],
+            max_tokens,
+            temperature,
+            stream,
+        };
+
+        let mut builder = self
+            .client
+            .post(&url)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(timeout_secs));
+        if let Some(ref key) = self.api_key {
+            builder = builder.bearer_auth(key);
+        }
+        let response = builder.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("{} API error {}: {}", self.provider, status, error_text).into());
+        }
+        Ok(response)
+    }
+
+    async fn generate(
+        &self,
+        prompt: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let response = self
+            .send(prompt, max_tokens, temperature, false, 30)
+            .await?;
+        let chat: ChatResponse = response.json().await?;
+
+        chat.choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| format!("{} returned no completion", self.provider).into())
+    }
+
+    /// Same as `generate`, but streams the response and calls `on_token` for every piece
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let mut response = self
+            .send(prompt, max_tokens, temperature, true, 120)
+            .await?;
+
+        // Server-sent events: `data: {...}` lines, ending with `data: [DONE]`
+        let mut full_response = String::new();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                let Some(data) = line.trim_ascii().strip_prefix(b"data:") else {
+                    continue;
+                };
+                let data = data.trim_ascii();
+                if data == b"[DONE]" {
+                    return Ok(full_response);
+                }
+
+                let part: ChatStreamChunk = serde_json::from_slice(data)?;
+                for choice in part.choices {
+                    if let Some(content) = choice.delta.content
+                        && !content.is_empty()
+                    {
+                        on_token(&content);
+                        full_response.push_str(&content);
+                    }
+                }
+            }
+        }
+
+        Ok(full_response)
+    }
+
+    fn trigger_llm_before(&self, prompt: &str) {
+        let context = LlmContext {
+            model: self.model.clone(),
+            provider: self.provider.clone(),
+            working_dir: get_working_directory(),
+        };
+        lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER).trigger_llm_before(prompt, &context);
+    }
+}
+
+#[async_trait]
+impl LlmModel for OpenAiModel {
+    async fn interpret_query(
+        &self,
+        query: &str,
+        directory: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<LLMQueryParams, Box<dyn std::error::Error>> {
+        let prompt = self.prompt_builder.build_prompt(query, directory, &[]);
+        let response = self.generate(&prompt, max_tokens, temperature).await?;
+        parse_query_params(&response)
+    }
+
+    async fn answer_question(
+        &self,
+        query: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = answer_prompt(query);
+        self.generate(&prompt, max_tokens, temperature).await
+    }
+
+    async fn answer_question_stream(
+        &self,
+        query: &str,
+        max_tokens: u32,
+        temperature: f32,
+        on_token: &(dyn for<'t> Fn(&'t str) + Send + Sync),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = answer_prompt(query);
+        self.generate_stream(&prompt, max_tokens, temperature, on_token)
+            .await
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    async fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chat_responses() {
+        let chat: ChatResponse = serde_json::from_str(
+            r#"{"id":"x","choices":[{"index":0,"message":{"role":"assistant","content":"ls -la"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(chat.choices[0].message.content.as_deref(), Some("ls -la"));
+
+        let chunk: ChatStreamChunk =
+            serde_json::from_str(r#"{"choices":[{"index":0,"delta":{"role":"assistant"}}]}"#)
+                .unwrap();
+        assert_eq!(chunk.choices[0].delta.content, None);
+    }
+}
@@ -32,7 +32,11 @@ struct FewShotExample {
 
 impl AdaptivePromptBuilder {
     pub fn new(model_name: String) -> Self {
-        let model_params = Self::detect_model_size(&model_name);
+        Self::with_size(Self::detect_model_size(&model_name))
+    }
+
+    /// Builder for a model of known size, e.g. a hosted model whose name says nothing
+    pub fn with_size(model_params: ModelSize) -> Self {
         let max_cache = match model_params {
             ModelSize::Tiny => 3,
             ModelSize::Small => 5,
@@ -155,11 +159,13 @@ JSON:"#,
 r#"Convert the natural language query into structured search parameters. Return ONLY valid JSON.
 
 Output format:
-{{"keywords":[],"time_range":null,"custom_start":null,"custom_end":null,"filters":{{"working_dir":null,"app_name":null}},"use_semantic":false}}
+{{"keywords":[],"time_range":null,"custom_start":null,"custom_end":null,"filters":{{"working_dir":null,"app_name":null,"failed":null,"git_repo":null,"git_branch":null}},"use_semantic":false}}
 
 Field definitions:
 - keywords: Array of search terms (expand abbreviations, e.g., "push code" → ["git", "push"])
 - time_range: "today", "yesterday", "last_week", "last_month", or null
+- filters.failed: true if the query asks for commands that failed or errored, otherwise null
+- filters.git_repo / filters.git_branch: repository name and branch, only if the query names them
 - use_semantic: true for vague queries (should only be true if entry type is clipboard)
 
 {}Commands in user's history (for context):
@@ -191,7 +197,7 @@ JSON output:"#,
 r#"You are a terminal history search assistant. Convert natural language queries into structured search parameters.
 
 Output format (JSON only, no additional text):
-{{"keywords":[],"time_range":null,"custom_start":null,"custom_end":null,"filters":{{"working_dir":null,"app_name":null}},"use_semantic":false}}
+{{"keywords":[],"time_range":null,"custom_start":null,"custom_end":null,"filters":{{"working_dir":null,"app_name":null,"failed":null,"git_repo":null,"git_branch":null}},"use_semantic":false}}
 
 Parameter specifications:
 - keywords: Extract search terms. Expand common abbreviations (e.g., "push code" → ["git", "push", "origin"])
@@ -199,6 +205,9 @@ Parameter specifications:
 - custom_start/custom_end: Unix timestamps for custom date ranges (usually null)
 - filters.working_dir: Directory context (use if query mentions location)
 - filters.app_name: Application filter (use if query mentions specific app)
+- filters.failed: true for commands that failed (non-zero exit), false for ones that succeeded, null otherwise
+- filters.git_repo: Repository name (use if query mentions a repo or project, e.g. "in the jot repo" → "jot")
+- filters.git_branch: Branch name (use if query mentions a branch, e.g. "on main")
 - use_semantic: Set to true for vague/abstract queries (should only be true if it's a vague clipboard entry type)
 
 {}User's command history context (top similar commands):
@@ -0,0 +1,108 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::{Config, GLOBAL_CONFIG};
+
+/// Progress of a model download, aggregated over all layers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullProgress {
+    pub model: String,
+    /// Ollama's status line, e.g. "pulling manifest", "downloading", "success"
+    pub status: String,
+    pub completed: u64,
+    pub total: u64,
+    /// 0-100, based on the layers seen so far
+    pub percent: f32,
+}
+
+/// One line of the streamed /api/pull response
+#[derive(Deserialize)]
+struct PullChunk {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    digest: Option<String>,
+    #[serde(default)]
+    total: Option<u64>,
+    #[serde(default)]
+    completed: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Download a model through the Ollama HTTP pull API, reporting progress as it streams in
+pub async fn pull_model_with_progress<F>(
+    model: &str,
+    on_progress: F,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn(PullProgress),
+{
+    let api_base = match GLOBAL_CONFIG.read() {
+        Ok(config) => config.llm.api_base.clone(),
+        Err(_) => Config::default().llm.api_base,
+    }
+    .unwrap_or_else(|| "http://localhost:11434".to_string());
+
+    let mut response = Client::new()
+        .post(format!("{}/api/pull", api_base))
+        .json(&serde_json::json!({ "model": model, "stream": true }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Ollama API error {}: {}", status, error_text).into());
+    }
+
+    // (completed, total) per layer digest
+    let mut layers: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut buffer: Vec<u8> = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+
+            let part: PullChunk = serde_json::from_slice(&line)?;
+            if let Some(error) = part.error {
+                return Err(format!("Failed to pull {}: {}", model, error).into());
+            }
+
+            if let (Some(digest), Some(total)) = (part.digest, part.total) {
+                layers.insert(digest, (part.completed.unwrap_or(0), total));
+            }
+
+            let completed: u64 = layers.values().map(|(c, _)| c).sum();
+            let total: u64 = layers.values().map(|(_, t)| t).sum();
+            let percent = if part.status == "success" {
+                100.0
+            } else if total > 0 {
+                completed as f32 / total as f32 * 100.0
+            } else {
+                0.0
+            };
+
+            let done = part.status == "success";
+            on_progress(PullProgress {
+                model: model.to_string(),
+                status: part.status,
+                completed,
+                total,
+                percent,
+            });
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(format!("Download of {} ended before completing", model).into())
+}
@@ -0,0 +1,248 @@
+//! The daemon's log: `tracing` events written to ~/.jotx/logs/jotx.log at
+//! `logging.level`. The file is rotated when the day changes and once it grows past
+//! `logging.max_file_mb`; rotated logs are named `jotx-<time>.log` and only the newest
+//! `logging.keep_files` are kept. Under systemd (`jotx service install`) events also
+//! go to stderr, which is the journal. Other jotx processes print warnings and errors
+//! to stderr instead.
+
+use chrono::{DateTime, Local, NaiveDate};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt;
+use tracing_subscriber::prelude::*;
+
+use crate::config::LoggingConfig;
+use crate::permissions::{create_private_dir, open_private_append};
+
+pub const LOG_NAME: &str = "jotx.log";
+const ROTATED_PREFIX: &str = "jotx-";
+const ROTATED_SUFFIX: &str = ".log";
+const FOLLOW_POLL: Duration = Duration::from_millis(500);
+
+pub fn logs_dir() -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".jotx").join("logs")
+}
+
+/// Send the daemon's events to its log file
+pub fn init_daemon_logging(config: &LoggingConfig) -> Result<(), String> {
+    let level: LevelFilter = config
+        .level
+        .parse()
+        .map_err(|_| format!("Invalid logging.level: {}", config.level))?;
+    let dir = logs_dir();
+    let file = RollingFile::open(
+        &dir,
+        config.max_file_mb.max(1) * 1024 * 1024,
+        config.keep_files,
+    )
+    .map_err(|e| format!("Failed to open the log in {}: {}", dir.display(), e))?;
+
+    // systemd sets JOURNAL_STREAM when stderr is the journal, which adds its own times
+    let journal = std::env::var_os("JOURNAL_STREAM").map(|_| {
+        fmt::layer()
+            .with_writer(io::stderr)
+            .with_ansi(false)
+            .without_time()
+    });
+
+    tracing_subscriber::registry()
+        .with(fmt::layer().with_writer(Mutex::new(file)).with_ansi(false))
+        .with(journal)
+        .with(level)
+        .try_init()
+        .map_err(|e| e.to_string())
+}
+
+/// Print warnings and errors on stderr as plain messages
+pub fn init_cli_logging() {
+    let _ = tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .without_time()
+        .with_level(false)
+        .with_target(false)
+        .with_max_level(LevelFilter::WARN)
+        .try_init();
+}
+
+/// A log file that rotates itself by day and size
+pub struct RollingFile {
+    dir: PathBuf,
+    file: File,
+    /// Day the current file was started
+    day: NaiveDate,
+    size: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RollingFile {
+    /// Append to `dir`/jotx.log, rotating it first if it is from an earlier day
+    pub fn open(dir: &Path, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        create_private_dir(dir)?;
+        let path = dir.join(LOG_NAME);
+        let file = open_private_append(&path)?;
+        let meta = file.metadata()?;
+        let day = meta
+            .modified()
+            .map(|time| DateTime::<Local>::from(time).date_naive())
+            .unwrap_or_else(|_| Local::now().date_naive());
+
+        let mut rolling = Self {
+            dir: dir.to_path_buf(),
+            file,
+            day,
+            size: meta.len(),
+            max_bytes,
+            keep,
+        };
+        if rolling.size > 0 && rolling.day != Local::now().date_naive() {
+            rolling.rotate()?;
+        }
+        Ok(rolling)
+    }
+
+    /// Move the current file aside, start a new one and delete the oldest rotated logs
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let path = self.dir.join(LOG_NAME);
+        fs::rename(&path, self.rotated_path())?;
+        self.file = open_private_append(&path)?;
+        self.day = Local::now().date_naive();
+        self.size = 0;
+
+        let rotated = rotated_logs(&self.dir)?;
+        let excess = rotated.len().saturating_sub(self.keep);
+        for old in rotated.into_iter().take(excess) {
+            fs::remove_file(old)?;
+        }
+        Ok(())
+    }
+
+    /// `jotx-<local time>.log`, named so they sort oldest first
+    fn rotated_path(&self) -> PathBuf {
+        let stamp = Local::now().format("%Y%m%d-%H%M%S-%3f");
+        self.dir
+            .join(format!("{}{}{}", ROTATED_PREFIX, stamp, ROTATED_SUFFIX))
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let full = self.size > 0 && self.size + buf.len() as u64 > self.max_bytes;
+        if full || self.day != Local::now().date_naive() {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Rotated logs in `dir`, oldest first
+pub fn rotated_logs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut logs: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.starts_with(ROTATED_PREFIX) && name.ends_with(ROTATED_SUFFIX)
+                    })
+            })
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    logs.sort();
+    Ok(logs)
+}
+
+/// The last `count` lines of `path`
+pub fn last_lines(path: &Path, count: usize) -> io::Result<Vec<String>> {
+    let mut lines = VecDeque::with_capacity(count);
+    for line in BufReader::new(File::open(path)?).lines() {
+        if lines.len() == count {
+            lines.pop_front();
+        }
+        lines.push_back(line?);
+    }
+    Ok(lines.into())
+}
+
+/// Print what is appended to `path` until interrupted, starting at its current end.
+/// A rotation shows up as the file getting shorter, and the new file is read from
+/// its start.
+pub fn follow(path: &Path, out: &mut impl Write) -> io::Result<()> {
+    let mut file = File::open(path)?;
+    let mut position = file.seek(SeekFrom::End(0))?;
+    let mut buf = Vec::new();
+
+    loop {
+        let on_disk = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        if on_disk < position {
+            file = File::open(path)?;
+            position = 0;
+        }
+
+        buf.clear();
+        position += file.read_to_end(&mut buf)? as u64;
+        if !buf.is_empty() {
+            out.write_all(&buf)?;
+            out.flush()?;
+        }
+        std::thread::sleep(FOLLOW_POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotates_by_size_and_keeps_newest() {
+        let dir = std::env::temp_dir().join(format!("jotx-logs-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+
+        let mut log = RollingFile::open(&dir, 20, 2).unwrap();
+        for i in 0..5 {
+            log.write_all(format!("event number {}\n", i).as_bytes())
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        log.flush().unwrap();
+
+        // Each 15-byte line fills a file, so four were rotated and two kept
+        let rotated = rotated_logs(&dir).unwrap();
+        assert_eq!(rotated.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(&rotated[1]).unwrap(),
+            "event number 3\n"
+        );
+        assert_eq!(
+            last_lines(&dir.join(LOG_NAME), 10).unwrap(),
+            ["event number 4"]
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_last_lines() {
+        let path = std::env::temp_dir().join(format!("jotx-last-lines-{}.log", std::process::id()));
+        std::fs::write(&path, "a\nb\nc\n").unwrap();
+        assert_eq!(last_lines(&path, 2).unwrap(), ["b", "c"]);
+        assert_eq!(last_lines(&path, 5).unwrap(), ["a", "b", "c"]);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,55 +1,105 @@
+use chrono::{DateTime, Datelike, Local};
 use clap::Parser;
 use ctrlc;
-use std::process::{Command, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+use tracing::{debug, error, info, warn};
 
-use jotx::types::{Cli, Commands};
+use jotx::types::{Cli, Commands, EntryType, ServiceAction, SnippetAction, WorkflowAction};
 
-use jotx::ask::{AskResponse, ask, search};
+use jotx::api::spawn_api_server;
+use jotx::ask::{
+    AskResponse, SearchFormat, ask, prompt_info, search, search_formatted, suggest,
+};
+use jotx::bench::run_bench;
+use jotx::browse::browse;
 use jotx::clipboard::clip_mon::GLOBAL_CLIP_MON;
-use jotx::commands::{get_plugin_dir, get_working_directory, show_privacy_settings, show_settings};
-use jotx::config::GLOBAL_CONFIG;
+use jotx::commands::{
+    backup, delete_snippet, encrypt_db, export, forget, import_history, fts_index, get_plugin_dir, get_working_directory, list_peers,
+    list_snippets, list_vault, pair_device, reindex, run_doctor, run_snippet, save_snippet,
+    show_privacy_audit, show_privacy_settings, show_settings, show_stats, next_commands, list_workflows, run_workflow,
+    restore_backup, show_logs, pause_capture, resume_capture, show_plugins, unlock_vault, vault_entries, install_service, uninstall_service,
+    service_status, edit_tags, show_tags, pin, note,
+};
+use jotx::config::{DaemonConfig, GLOBAL_CONFIG, LoggingConfig, WebhookEvent};
 use jotx::config::reload_config;
-use jotx::db::{DB_WRITER, USER_DB};
-use jotx::llm::handle_llm;
+use jotx::db::DB_WRITER;
+use jotx::git_hooks::{git_event_entry, install_git_hooks, uninstall_git_hooks};
+use jotx::heatmap::{day_counts, heatmap_counts, render_svg, render_text};
+use jotx::ipc::{
+    IpcRequest, IpcResponse, daemon_metrics_via_socket, daemon_status_via_socket, reload_daemon,
+    remove_socket, request, spawn_ipc_server,
+};
+use jotx::integrations::{container_shim, integration_snippet, tmux_search, write_completions};
+use jotx::journal::journal;
+use jotx::logging::{init_cli_logging, init_daemon_logging};
+use jotx::notes::{export_notes, export_notes_on_maintenance};
+use jotx::llm::{QueryFilters, handle_llm};
 use jotx::plugin::{
-    CommandContext, DaemonContext, GLOBAL_PLUGIN_MANAGER, SensitiveCommandFilter,
-    check_plugin_functions, create_new_plugin_script,
+    DaemonContext, GLOBAL_PLUGIN_MANAGER, SensitiveCommandFilter,
+    check_plugin_functions, create_new_plugin_script, edit_plugin_config,
 };
 use jotx::settings::GLOBAL_SETTINGS;
-use jotx::setup::{clean_data, full_setup, install_llm, setup_hooks, uninstall, update};
+use jotx::setup::{clean_data, full_setup, install_llm, purge, setup_hooks, uninstall, update};
+use jotx::shell::capture::capture_command;
 use jotx::shell::shell_mon::GLOBAL_SHELL_MON;
 
+use jotx::managers::lock_manager::lock_or_recover;
 use jotx::managers::shutdown_manager::{on_shutdown, shutdown};
-use jotx::pid_controller::{PID_FILE, is_running, remove_pid, save_pid};
-
+use jotx::managers::task_manager::{
+    check_workers, shutdown_requested, spawn_interval_task, spawn_reload_on_hangup,
+};
+use jotx::privacy::schedule::refresh_quiet_state;
+use jotx::pid_controller::{
+    capture_suspended, clear_problems, is_running,
+    remove_pid, save_pid, stop_daemon,
+};
+use jotx::service::start_daemon;
+use jotx::utils::{get_daemon_status, run_maintenance, set_plugin_enabled};
+use jotx::webhooks::{WebhookEvents, notify, previous_daemon_crashed};
 
 
-const CLIP_SLEEP_DURATION_SECS: u64 = 1;
-const SHELL_SLEEP_DURATION_SECS: u64 = 60; // This is multiplied by 60 to get 3600 seconds
-const APP_LOOP_SECS: u64 = 10;
-const DB_LOOP_SECS: u64 = 5; // This is multiplied by 60 to get 300 seconds
 
-const SERVICE_NAME: &str = "jotx";
-const SERVICE_NAME_SHORT: &str = "js";
-const SERVICE_NAME_SHORT2: &str = "ja";
+const DB_MONITOR_SECS: u64 = 300; // How often the DB writer queue size is checked
+const WATCHDOG_SECS: u64 = 30; // How often the other tasks are checked for stalls
+const PID_CHECK_SECS: u64 = 5; // How often the daemon checks its PID file is still there
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
 
+    if matches!(cli.command, Commands::InternalDaemon) {
+        let logging = match GLOBAL_CONFIG.read() {
+            Ok(config) => config.logging.clone(),
+            Err(_) => LoggingConfig::default(),
+        };
+        if let Err(e) = init_daemon_logging(&logging) {
+            eprintln!("Logging to stderr instead: {}", e);
+            init_cli_logging();
+        }
+    } else {
+        init_cli_logging();
+    }
+
     on_shutdown(|| {
         println!("  🌐 Closing network connections...");
     });
 
     match cli.command {
         Commands::Run => start_service(),
-        Commands::Ask { query, clipboard, print_only } => {
+        Commands::Ask { query, clipboard, repo, branch, print_only } => {
             let pwd = get_working_directory();
 
-            let ask_result = ask(&query, clipboard, &pwd, print_only, false).await;
+            let entry_type = EntryType::from_clipboard_flag(clipboard);
+            let git_scope = QueryFilters {
+                git_repo: repo,
+                git_branch: branch,
+                ..Default::default()
+            };
+
+            let ask_result = ask(&query, entry_type, &pwd, &git_scope, print_only, false).await;
             match ask_result {
                 Ok(value) => {
                     if let Some(result) = ask_to_string(value) {
@@ -66,13 +116,239 @@ async fn main() {
                 }
             }
         }
-        Commands::Cleanup => maintain(),
-        Commands::Search { query, clipboard, print_only } => {
+        Commands::Cleanup => {
+            maintain();
+            println!("Database maintenance completed");
+        }
+        Commands::Fts { rebuild, check } => {
+            if let Err(e) = fts_index(rebuild, check) {
+                eprintln!("❌ Full-text index error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Bench { entries, queries } => {
+            if let Err(e) = run_bench(&entries, queries) {
+                eprintln!("❌ Benchmark failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Doctor { fix } => {
+            if !run_doctor(fix) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Pair { code, list } => {
+            if list {
+                list_peers();
+            } else if let Err(e) = pair_device(code.as_deref()) {
+                eprintln!("❌ Pairing failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Journal { date } => match journal(date.as_deref()) {
+            Ok(markdown) => print!("{}", markdown),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Encrypt { decrypt } => {
+            if let Err(e) = encrypt_db(decrypt) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Forget { ids, pattern, since, until, interactive, yes } => {
+            if let Err(e) = forget(
+                &ids,
+                pattern.as_deref(),
+                since.as_deref(),
+                until.as_deref(),
+                interactive,
+                yes,
+            ) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Reindex { all } => {
+            if let Err(e) = reindex(all) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Browse { query } => {
+            if let Err(e) = browse(query) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Stats { days, top, json } => {
+            if let Err(e) = show_stats(days, top, json) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Heatmap { year, svg, json } => {
+            let year = year.unwrap_or_else(|| Local::now().year());
+            match heatmap_counts(year) {
+                Ok(counts) if json => println!(
+                    "{}",
+                    serde_json::to_string(&day_counts(year, &counts)).unwrap_or_default()
+                ),
+                Ok(counts) if svg => print!("{}", render_svg(year, &counts)),
+                Ok(counts) => print!("{}", render_text(year, &counts)),
+                Err(e) => {
+                    eprintln!("❌ Failed to read history: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Notes { days, dir } => match export_notes(days, dir.as_deref()) {
+            Ok(report) => println!(
+                "✅ {} notes written to {} ({} unchanged)",
+                report.written,
+                report.dir.display(),
+                report.unchanged
+            ),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Snippet { action } => {
+            let result = match action {
+                SnippetAction::Save { id, name } => save_snippet(id, &name),
+                SnippetAction::Run { name, print } => run_snippet(&name, print),
+                SnippetAction::List => list_snippets(),
+                SnippetAction::Delete { name } => delete_snippet(&name),
+            };
+            if let Err(e) = result {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Tag { id, tag, remove } => {
+            let result = match (id, tag) {
+                (Some(id), Some(tag)) => edit_tags(id, &tag, remove),
+                _ => show_tags(),
+            };
+            if let Err(e) = result {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Note { text } => {
+            if let Err(e) = note(&text.join(" ")) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Pin { id, remove } => {
+            if let Err(e) = pin(id, remove) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::GitHooks { action } => {
+            let dir = std::path::PathBuf::from(get_working_directory());
+            let result = if action == "install" {
+                install_git_hooks(&dir)
+            } else {
+                uninstall_git_hooks(&dir)
+            };
+            match result {
+                Ok(hooks) if hooks.is_empty() => println!("Nothing to {}", action),
+                Ok(hooks) => {
+                    for hook in hooks {
+                        println!("✅ {}", hook.display());
+                    }
+                }
+                Err(e) => {
+                    eprintln!("❌ {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Shim { kind } => match container_shim(&kind) {
+            Ok(snippet) => print!("{}", snippet),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Completions { shell } => write_completions(shell, &mut std::io::stdout()),
+        Commands::Integrations { tool, shell } => match integration_snippet(&tool, shell.as_deref()) {
+            Ok(snippet) => print!("{}", snippet),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Tmux { pane, clipboard } => {
+            if let Err(e) = tmux_search(pane.as_deref(), clipboard) {
+                eprintln!("❌ tmux search failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::PromptInfo { json, pwd } => {
+            // Runs on every prompt; print nothing rather than an error
+            let pwd = pwd.unwrap_or_else(get_working_directory);
+            if let Ok(info) = prompt_info(&pwd) {
+                if json {
+                    println!("{}", serde_json::to_string(&info).unwrap_or_default());
+                } else {
+                    print!("{}", info.line());
+                }
+            }
+        }
+        Commands::Suggest { prefix, pwd } => {
+            // Called on every keystroke; any failure just means no suggestion
+            let pwd = pwd.unwrap_or_else(get_working_directory);
+            if let Ok(Some(command)) = suggest(&prefix, &pwd) {
+                print!("{}", command);
+            }
+        }
+        Commands::Next { command, limit, json } => {
+            let command = (!command.is_empty()).then(|| command.join(" "));
+            if let Err(e) = next_commands(command.as_deref(), limit, json) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Workflows { action, days, min_runs, json } => {
+            let result = match action {
+                Some(WorkflowAction::Run { number }) => run_workflow(number, days, min_runs),
+                None => list_workflows(days, min_runs, json),
+            };
+            if let Err(e) = result {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Search { query, clipboard, tag, print_only, json, format } => {
             let pwd = std::env::current_dir()
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_else(|_| String::from(""));
 
-            if let Some(result) = search(&query, clipboard, &pwd, print_only) {
+            let entry_type = EntryType::from_clipboard_flag(clipboard);
+
+            let format = match format.as_deref().map(str::parse::<SearchFormat>) {
+                Some(Ok(format)) => Some(format),
+                Some(Err(e)) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                None => json.then_some(SearchFormat::Json),
+            };
+
+            if let Some(format) = format {
+                let mut out = std::io::stdout().lock();
+                if let Err(e) = search_formatted(&query, entry_type, &pwd, tag.as_deref(), format, &mut out) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            } else if let Some(result) = search(&query, entry_type, &pwd, tag.as_deref(), print_only) {
                 if print_only {
                     print!("{}", result);
                 }
@@ -80,9 +356,54 @@ async fn main() {
                 std::process::exit(1);
             }
         }
-        Commands::Status => {
-            if is_running() {
-                println!("✅ Jotx is running");
+        Commands::Status { metrics: true } => match daemon_metrics_via_socket() {
+            Some(metrics) => print!("{}", metrics.to_prometheus()),
+            None => {
+                eprintln!("❌ The daemon is not running or not answering on its control socket");
+                std::process::exit(1);
+            }
+        },
+        Commands::Status { metrics: false } => {
+            let status = get_daemon_status().unwrap_or_else(|e| {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            });
+            if status.running {
+                match (status.pid, status.uptime_secs) {
+                    (Some(pid), Some(uptime)) => {
+                        println!("✅ Jotx is running (PID {}, up {}s)", pid, uptime)
+                    }
+                    _ => println!("✅ Jotx is running"),
+                }
+                if status.paused {
+                    match status.paused_until.and_then(|end| DateTime::from_timestamp(end, 0)) {
+                        Some(end) => println!(
+                            "⏸️  Capture paused until {}",
+                            end.with_timezone(&Local).format("%H:%M")
+                        ),
+                        None => println!("⏸️  Capture paused until `jotx resume`"),
+                    }
+                }
+                if let Some(window) = status.quiet {
+                    println!("🔕 Capture off for quiet window: {}", window);
+                }
+                for problem in status.problems {
+                    println!("⚠️  {}", problem);
+                }
+                if status.dropped_captures > 0 {
+                    println!(
+                        "⚠️  {} captures dropped while the database writer was behind",
+                        status.dropped_captures
+                    );
+                }
+                for worker in status.workers.iter().filter(|w| w.restarts > 0) {
+                    println!(
+                        "🔁 {} worker restarted {} times after panics (last: {})",
+                        worker.name,
+                        worker.restarts,
+                        worker.last_panic.as_deref().unwrap_or("unknown")
+                    );
+                }
                 std::process::exit(0);
             } else {
                 println!("⏹️ Jotx is stopped");
@@ -106,6 +427,44 @@ async fn main() {
                 } else {
                     eprintln!("Error: --create requires a plugin name.");
                 }
+            } else if args.config {
+                // Logic for jotx plugin --config <NAME>
+                let Some(name) = args.name else {
+                    eprintln!("Error: --config requires a plugin name.");
+                    std::process::exit(1);
+                };
+                match edit_plugin_config(&get_plugin_dir(), &name) {
+                    Ok(path) => println!(
+                        "✅ Saved {}; run jotx reload to apply it to the daemon",
+                        path.display()
+                    ),
+                    Err(e) => {
+                        eprintln!("❌ {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else if args.list {
+                register_builtin_plugins();
+                show_plugins();
+            } else if let Some((name, enabled)) = args
+                .enable
+                .map(|name| (name, true))
+                .or(args.disable.map(|name| (name, false)))
+            {
+                register_builtin_plugins();
+                if let Err(e) = set_plugin_enabled(&name, enabled) {
+                    eprintln!("❌ {}", e);
+                    std::process::exit(1);
+                }
+                let state = if enabled { "enabled" } else { "disabled" };
+                // Apply it to the running daemon too
+                match request(IpcRequest::Reload) {
+                    Ok(IpcResponse::Done { .. }) => println!("✅ Plugin {} {}", name, state),
+                    _ => println!(
+                        "✅ Plugin {} {}; the daemon picks it up when it (re)starts",
+                        name, state
+                    ),
+                }
             } else if let Some(target) = args.check {
                 // Logic for jotx plugin --check <NAME> or --check all
                 let result;
@@ -119,16 +478,47 @@ async fn main() {
                     Err(e) => eprintln!("❌ Plugin check failed: {}", e),
                 }
             } else {
-                println!("Plugin command requires --create or --check.");
+                println!(
+                    "Plugin command requires --list, --enable, --disable, --create, --config or --check."
+                );
             }
         }
         Commands::Reload => reload(),
+        Commands::Pause { duration } => {
+            if let Err(e) = pause_capture(duration.as_deref()) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Resume => {
+            if let Err(e) = resume_capture() {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
         Commands::Settings => show_settings(),
-        Commands::Privacy => {
+        Commands::Privacy { audit: true } => show_privacy_audit(),
+        Commands::Privacy { audit: false } => {
             if let Err(e) = show_privacy_settings() {
                 eprintln!("Error updating privacy settings: {}", e);
             }
         }
+        Commands::Vault(args) => {
+            let result = if args.list {
+                list_vault()
+            } else {
+                vault_entries(&args.ids)
+            };
+            if let Err(e) = result {
+                eprintln!("❌ Vault error: {}", e);
+            }
+        }
+        Commands::Unlock { id, restore } => {
+            if let Err(e) = unlock_vault(id, restore) {
+                eprintln!("❌ Unlock failed: {}", e);
+                std::process::exit(1);
+            }
+        }
         Commands::Update => {
             if let Err(e) = update() {
                 eprintln!("Error updating: {}", e);
@@ -136,22 +526,96 @@ async fn main() {
         }
         Commands::Exit => stop_service(),
         Commands::InternalDaemon => {
+            let crashed = previous_daemon_crashed();
             save_pid();
-            run_service();
+            clear_problems();
+            if let Some(previous_pid) = crashed {
+                notify(
+                    WebhookEvent::DaemonRestarted,
+                    serde_json::json!({ "previous_pid": previous_pid, "pid": std::process::id() }),
+                );
+            }
+            run_service().await;
         }
         Commands::Capture {
             cmd,
             pwd,
             user,
             host,
+            exit_code,
+            duration_ms,
         } => {
-            capture_command(&cmd, pwd, user, host);
+            capture_command(&cmd, pwd, user, host, exit_code, duration_ms);
+        }
+        Commands::GitEvent { event, args } => {
+            // Runs from a git hook in the background; failures stay silent
+            let dir = std::path::PathBuf::from(get_working_directory());
+            if let Ok(Some((content, root))) = git_event_entry(&dir, &event, &args) {
+                let user = std::env::var("USER").ok();
+                let host = std::env::var("HOSTNAME").ok();
+                capture_command(&content, Some(root), user, host, None, None);
+            }
         }
         Commands::CleanData => {
             if let Err(e) = clean_data(false) {
                 eprintln!("Error cleaning data: {}", e);
             }
         }
+        Commands::Export { format, output, entry_type, since, until } => {
+            if let Err(e) = export(
+                &format,
+                output.as_deref(),
+                entry_type.as_deref(),
+                since.as_deref(),
+                until.as_deref(),
+            ) {
+                eprintln!("❌ Export failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Backup { list, keep } => {
+            if let Err(e) = backup(list, keep) {
+                eprintln!("❌ Backup failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Service { action } => {
+            let result = match action {
+                ServiceAction::Install => install_service(),
+                ServiceAction::Uninstall => uninstall_service(),
+                ServiceAction::Status => service_status(),
+            };
+            if let Err(e) = result {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Logs { lines, tail } => {
+            if let Err(e) = show_logs(lines, tail) {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Restore { file, yes } => {
+            if let Err(e) = restore_backup(&file, yes) {
+                eprintln!("❌ Restore failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Import { from, path } => {
+            if let Err(e) = import_history(&from, path.as_deref()) {
+                eprintln!("❌ Import failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Purge { verify, export, yes } => match purge(export.as_deref(), verify, yes) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("❌ Purge failed: {}", e);
+                std::process::exit(1);
+            }
+        },
         Commands::Uninstall => {
             if let Err(e) = uninstall(false) {
                 eprintln!("Error uninstalling: {}", e);
@@ -200,21 +664,8 @@ fn start_service() {
 
     let exe = std::env::current_exe().expect("Failed to get exe path");
 
-    // Spawn detached background process
-    let stdout = std::fs::File::create("/tmp/jotx.log")
-        .map(Stdio::from)
-        .unwrap_or_else(|_| Stdio::null());
-
-    let stderr = std::fs::File::create("/tmp/jotx.err")
-        .map(Stdio::from)
-        .unwrap_or_else(|_| Stdio::null());
-
-    Command::new(exe)
-        .arg("internal-daemon")
-        .stdout(stdout)
-        .stderr(stderr)
-        .spawn()
-        .expect("Failed to spawn daemon");
+    // Through systemd/launchd when `jotx service install` was run, detached otherwise
+    start_daemon(&exe).expect("Failed to spawn daemon");
 
     thread::sleep(Duration::from_millis(200));
     println!("Service started. Use 'jotx exit' to stop.\n");
@@ -222,170 +673,204 @@ fn start_service() {
 
 // Stop service
 fn stop_service() {
-    if !is_running() {
+    if !is_running() && daemon_status_via_socket().is_none() {
         println!("Service not running.");
         return;
     }
 
     println!("Stopping service...");
-    if let Ok(pid_str) = std::fs::read_to_string(PID_FILE) {
-        if let Ok(pid) = pid_str.trim().parse::<u32>() {
-            let _ = std::process::Command::new("kill")
-                .arg(pid.to_string())
-                .status();
-        }
-    }
-    remove_pid();
+    stop_daemon();
 
     println!("Service stopped.");
 }
 
 // The actual long-running service
-pub fn run_service() {
-    println!("Running service...\n");
-    println!("run_service started, PID: {}", std::process::id());
+pub async fn run_service() {
+    info!("Daemon started, PID {}", std::process::id());
 
-    println!("Initial data load from terminal histories...");
-    let shell_case_sensitive = {
-        if let Ok(settings) = GLOBAL_SETTINGS.lock() {
-            settings.shell_case_sensitive
-        } else {
-            false
-        }
-    };
+    info!("Initial data load from terminal histories");
+    let shell_case_sensitive = lock_or_recover("settings", &GLOBAL_SETTINGS).shell_case_sensitive;
 
-    println!("Starting DB writer thread...");
+    debug!("Starting DB writer thread");
 
     let _ = &*DB_WRITER;
 
-    if let Ok(mut monitor) = GLOBAL_SHELL_MON.lock() {
+    let replayed = DB_WRITER.replay_journals();
+    if replayed > 0 {
+        info!("Replaying {} journaled entries that were never written", replayed);
+    }
+
+    // Scoped so the guard is gone before the next await
+    {
+        let mut monitor = lock_or_recover("shell monitor", &GLOBAL_SHELL_MON);
         if let Err(e) = monitor.read_all_histories(shell_case_sensitive) {
-            eprintln!("Shell error: {}", e);
+            error!("Shell error: {}", e);
         }
     }
 
-    // Clipboard thread
-    thread::spawn(move || {
-        while is_running() {
+    if let Err(e) = spawn_api_server() {
+        warn!("HTTP API disabled: {}", e);
+    }
+
+    if let Err(e) = spawn_ipc_server() {
+        warn!("Control socket disabled: {}", e);
+    }
+
+    // Every loop below listens on this channel and exits at its next await point
+    let (shutdown_tx, _) = broadcast::channel::<()>(1);
+
+    // Clipboard task (also keeps the quiet-window flag current, as it ticks the fastest)
+    let clipboard = spawn_interval_task(
+        "Clipboard",
+        shutdown_tx.subscribe(),
+        || daemon_config().clipboard_poll_secs,
+        || {
+            refresh_quiet_state();
+
             let (should_capture, clipboard_case_sensitive) = {
-                if let Ok(settings) = GLOBAL_SETTINGS.lock() {
-                    (
-                        settings.capture_clipboard,
-                        settings.clipboard_case_sensitive,
-                    )
-                } else {
-                    (false, false)
-                }
+                let settings = lock_or_recover("settings", &GLOBAL_SETTINGS);
+                (
+                    settings.capture_clipboard,
+                    settings.clipboard_case_sensitive,
+                )
             };
 
-            if should_capture {
-                // Lock the mutex to get mutable access
-                if let Ok(mut monitor) = GLOBAL_CLIP_MON.lock() {
-                    if let Err(e) = monitor.check(clipboard_case_sensitive) {
-                        eprintln!("Clipboard error: {}", e);
-                    }
+            if should_capture && !capture_suspended() {
+                let mut monitor = lock_or_recover("clipboard monitor", &GLOBAL_CLIP_MON);
+                if let Err(e) = monitor.check(clipboard_case_sensitive) {
+                    error!("Clipboard error: {}", e);
                 }
             }
-            thread::sleep(Duration::from_secs(CLIP_SLEEP_DURATION_SECS));
-        }
-    });
-
-    // Shell thread
-    thread::spawn(move || {
-        while is_running() {
+        },
+    );
+
+    // Shell history task
+    let shell = spawn_interval_task(
+        "Shell",
+        shutdown_tx.subscribe(),
+        || daemon_config().shell_scan_interval_secs,
+        || {
             let (should_capture, should_capture_files, shell_case_sensitive) = {
-                if let Ok(settings) = GLOBAL_SETTINGS.lock() {
-                    (
-                        settings.capture_shell,
-                        settings.capture_shell_history_with_files,
-                        settings.shell_case_sensitive,
-                    )
-                } else {
-                    (false, false, false)
-                }
+                let settings = lock_or_recover("settings", &GLOBAL_SETTINGS);
+                (
+                    settings.capture_shell,
+                    settings.capture_shell_history_with_files,
+                    settings.shell_case_sensitive,
+                )
             };
 
-            if should_capture && should_capture_files {
-                // Lock the mutex to get mutable access
-                if let Ok(mut monitor) = GLOBAL_SHELL_MON.lock() {
-                    if let Err(e) = monitor.read_all_histories(shell_case_sensitive) {
-                        eprintln!("Shell error: {}", e);
-                    }
-                }
-            }
-
-            for _ in 0..60 {
-                if !is_running() {
-                    break;
+            if should_capture && should_capture_files && !capture_suspended() {
+                let mut monitor = lock_or_recover("shell monitor", &GLOBAL_SHELL_MON);
+                if let Err(e) = monitor.read_all_histories(shell_case_sensitive) {
+                    error!("Shell error: {}", e);
                 }
-                thread::sleep(Duration::from_secs(SHELL_SLEEP_DURATION_SECS));
             }
-        }
-    });
-
-    // DB writer
-    thread::spawn(move || {
-        while is_running() {
+        },
+    );
+
+    // DB writer queue monitor
+    let db_monitor = spawn_interval_task(
+        "DB monitor",
+        shutdown_tx.subscribe(),
+        || DB_MONITOR_SECS,
+        || {
             let queue_size = DB_WRITER.queue_len();
 
             // Warn if queue is backing up
             if queue_size > 500 {
-                eprintln!("⚠ DB writer queue is large: {} entries pending", queue_size);
+                warn!("DB writer queue is large: {} entries pending", queue_size);
             } else if queue_size > 0 {
-                // Optional: log normal activity (only in verbose mode)
-                #[cfg(debug_assertions)]
-                println!("DB writer queue: {} entries", queue_size);
-            }
-
-            for _ in 0..60 {
-                if !is_running() {
-                    break;
-                }
-                thread::sleep(Duration::from_secs(DB_LOOP_SECS));
+                debug!("DB writer queue: {} entries", queue_size);
             }
+        },
+    );
+
+    // Watchdog: reports tasks that stopped finishing runs
+    let watchdog = spawn_interval_task(
+        "Watchdog",
+        shutdown_tx.subscribe(),
+        || WATCHDOG_SECS,
+        check_workers,
+    );
+
+    // SIGHUP reloads like `jotx reload`
+    let hangup = spawn_reload_on_hangup(shutdown_tx.subscribe(), || {
+        if let Err(e) = reload_daemon() {
+            error!("{}", e);
         }
     });
 
-    // Main service loop — checks global flag
+    // Main service task: maintenance and plugin daemon ticks
     let mut last_maintenance = Instant::now();
 
+    let mut webhook_events = WebhookEvents::new();
+
     let mut daemon_context = DaemonContext {
         iteration: 0,
         uptime_secs: 0,
     };
 
-    while is_running() {
-        if let Ok(config) = GLOBAL_CONFIG.read() {
-            if last_maintenance.elapsed().as_secs()
-                >= config.storage.maintenance_interval_days * 86400
-            {
-                maintain();
-                last_maintenance = Instant::now();
+    let main_loop = spawn_interval_task(
+        "Main",
+        shutdown_tx.subscribe(),
+        || daemon_config().app_loop_secs,
+        move || {
+            if let Ok(config) = GLOBAL_CONFIG.read() {
+                if last_maintenance.elapsed().as_secs()
+                    >= config.storage.maintenance_interval_days * 86400
+                {
+                    maintain();
+                    last_maintenance = Instant::now();
+                }
             }
-        }
 
-        daemon_context.iteration += 1;
-        daemon_context.uptime_secs = get_uptime();
+            // Pick up entries from shell hooks that exited before their write landed
+            DB_WRITER.replay_journals();
 
-        if let Ok(plugins) = GLOBAL_PLUGIN_MANAGER.lock() {
-            plugins.trigger_daemon_tick(&daemon_context);
-        }
+            webhook_events.tick();
+
+            daemon_context.iteration += 1;
+            daemon_context.uptime_secs = get_uptime();
 
-        thread::sleep(Duration::from_secs(APP_LOOP_SECS));
+            lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER).trigger_daemon_tick(&daemon_context);
+        },
+    );
+
+    shutdown_requested(PID_CHECK_SECS, is_running).await;
+    info!("Shutdown requested, stopping tasks");
+    let _ = shutdown_tx.send(());
+
+    for task in [clipboard, shell, db_monitor, watchdog, hangup, main_loop] {
+        if let Err(e) = task.await {
+            error!("Daemon task failed: {}", e);
+        }
     }
 
     shutdown();
+    remove_socket();
     remove_pid();
-    println!("\nGoodbye!");
+    info!("Daemon stopped");
+}
+
+fn daemon_config() -> DaemonConfig {
+    match GLOBAL_CONFIG.read() {
+        Ok(config) => config.daemon.clone(),
+        Err(_) => DaemonConfig::default(),
+    }
 }
 
 pub fn initialize_plugins() {
-    let mut pm = GLOBAL_PLUGIN_MANAGER.lock().unwrap();
+    register_builtin_plugins();
+
+    let pm = lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER);
+    println!("✅ Loaded {} plugins", pm.list().len());
+}
+
+fn register_builtin_plugins() {
+    let mut pm = lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER);
 
     // Register built-in plugins
     pm.register(Box::new(SensitiveCommandFilter));
-
-    println!("✅ Loaded {} plugins", pm.list().len());
 }
 
 pub fn get_uptime() -> u64 {
@@ -394,90 +879,34 @@ pub fn get_uptime() -> u64 {
     since_the_epoch.as_secs()
 }
 
-fn capture_command(cmd: &str, pwd: Option<String>, user: Option<String>, host: Option<String>) {
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-
-    // Avoid capturing our own commands
-    if cmd.starts_with(SERVICE_NAME)
-        || cmd.starts_with(SERVICE_NAME_SHORT)
-        || cmd.starts_with(SERVICE_NAME_SHORT2)
-    {
-        return;
-    }
-
-    // ---- SETTINGS (non-blocking)
-    let (should_capture, shell_case_sensitive) = match GLOBAL_SETTINGS.try_lock() {
-        Ok(settings) => (settings.capture_shell, settings.shell_case_sensitive),
-        Err(_) => return, // lock busy → do nothing
-    };
-
-    if !should_capture {
-        return;
-    }
-
-    // ---- PLUGINS (non-blocking)
-    let should_add = match GLOBAL_PLUGIN_MANAGER.try_lock() {
-        Ok(plugins) => plugins.trigger_command_captured(&CommandContext {
-            command: cmd.to_string(),
-            working_dir: pwd.clone().unwrap_or_default(),
-            user: user.clone().unwrap_or_default(),
-            host: host.clone().unwrap_or_default(),
-            timestamp,
-        }),
-        Err(_) => true, // assume success if busy
-    };
-
-    if !should_add {
-        return;
-    }
-
-    // ---- MONITOR (non-blocking)
-    if let Ok(mut monitor) = GLOBAL_SHELL_MON.try_lock() {
-        let cmd = if shell_case_sensitive {
-            cmd.to_string()
-        } else {
-            cmd.to_lowercase()
-        };
-
-        monitor.add_command(cmd, timestamp, pwd, user, host);
+fn maintain() {
+    if let Err(e) = run_maintenance() {
+        error!("{}", e);
     }
+    export_notes_on_maintenance();
+    info!("Database maintenance completed");
 }
 
-fn maintain() {
-    let (clipboard_limit, shell_limit) = {
-        let settings = GLOBAL_SETTINGS.lock().unwrap();
-        (settings.clipboard_limit, settings.shell_limit)
-    };
-
-    if let Ok(db) = USER_DB.lock() {
-        // Always clean up old entries (this is cheap and frequent)
-        if let Err(e) = db.cleanup_old_entries(clipboard_limit, shell_limit) {
-            eprintln!("Cleanup error: {}", e);
+/// Have the running daemon re-read its config and settings
+pub fn reload() {
+    match request(IpcRequest::Reload) {
+        Ok(IpcResponse::Done { message }) => println!("✅ {}", message),
+        Ok(IpcResponse::Error { message }) => {
+            eprintln!("❌ {}", message);
+            std::process::exit(1);
         }
-
-        // Only run full maintenance if it's been a while (expensive)
-        if db.should_run_maintenance() {
-            if let Err(e) = db.run_maintenance() {
-                eprintln!("Maintenance error: {}", e);
-            } else {
-                // Update last maintenance timestamp
-                if let Err(e) = db.update_last_maintenance() {
-                    eprintln!("Failed to update maintenance timestamp: {}", e);
-                }
+        Ok(IpcResponse::Status { .. } | IpcResponse::Metrics { .. }) => {
+            eprintln!("❌ Unexpected response from the daemon")
+        }
+        Err(_) => {
+            // No daemon to tell; check the config still parses so the next start works
+            if let Err(e) = reload_config() {
+                eprintln!("Failed to reload settings: {}", e);
+                std::process::exit(1);
             }
+            println!("Daemon not running; the config will be read when it starts");
         }
     }
-
-    print!("Database maintenance completed\n");
-}
-
-pub fn reload() {
-    if let Err(e) = reload_config() {
-        eprintln!("Failed to reload settings: {}", e);
-    }
 }
 
 fn ask_to_string(resp: AskResponse) -> Option<String> {
@@ -500,6 +929,8 @@ mod tests {
             Some("/home/user".to_string()),
             Some("user".to_string()),
             Some("host".to_string()),
+            Some(0),
+            Some(12),
         );
     }
 }
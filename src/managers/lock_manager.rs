@@ -0,0 +1,66 @@
+use std::fmt::Display;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::pid_controller::{clear_problem, report_problem};
+
+const RETRY_BASE_MS: u64 = 100;
+
+/// Lock `mutex`, taking the data back if a thread panicked while holding it.
+/// The globals guarded this way (settings, monitors, plugins, DB connection) stay
+/// usable after a panic, so recovering beats skipping every capture from then on.
+pub fn lock_or_recover<'a, T>(name: &str, mutex: &'a Mutex<T>) -> MutexGuard<'a, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            warn!("{} lock was poisoned by a panic; recovering", name);
+            mutex.clear_poison();
+            report_problem(name, "recovered after a panic in another thread");
+            poisoned.into_inner()
+        }
+    }
+}
+
+/// Like `lock_or_recover`, but returns None instead of waiting when the lock is held
+pub fn try_lock_or_recover<'a, T>(name: &str, mutex: &'a Mutex<T>) -> Option<MutexGuard<'a, T>> {
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::WouldBlock) => None,
+        Err(TryLockError::Poisoned(poisoned)) => {
+            warn!("{} lock was poisoned by a panic; recovering", name);
+            mutex.clear_poison();
+            report_problem(name, "recovered after a panic in another thread");
+            Some(poisoned.into_inner())
+        }
+    }
+}
+
+/// Run `op` up to `attempts` times with exponential backoff (100ms, 200ms, ...).
+/// Exhausting the attempts is reported to `jotx status`; a later success clears it.
+pub fn retry_with_backoff<T, E: Display>(
+    name: &str,
+    attempts: u32,
+    mut op: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Ok(value) => {
+                if attempt > 0 {
+                    clear_problem(name);
+                }
+                return Ok(value);
+            }
+            Err(e) => {
+                attempt += 1;
+                if attempt >= attempts.max(1) {
+                    report_problem(name, &format!("failed after {} attempts: {}", attempt, e));
+                    return Err(e);
+                }
+                thread::sleep(Duration::from_millis(RETRY_BASE_MS << (attempt - 1)));
+            }
+        }
+    }
+}
@@ -1 +1,3 @@
-pub mod shutdown_manager;
\ No newline at end of file
+pub mod lock_manager;
+pub mod shutdown_manager;
+pub mod task_manager;
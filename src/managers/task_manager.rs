@@ -0,0 +1,316 @@
+use once_cell::sync::Lazy;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior, interval_at};
+use tracing::{debug, error, warn};
+
+use crate::ipc::exit_requested;
+use crate::managers::lock_manager::lock_or_recover;
+use crate::pid_controller::{clear_problem, report_problem};
+use crate::types::WorkerHealth;
+
+/// Wait before running a worker again after it panicked, doubled for every panic in
+/// a row up to `MAX_RESTART_BACKOFF`
+const RESTART_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(300);
+/// A worker is stalled once no run has finished for this many periods, and at least
+/// `MIN_STALL_SECS`
+const STALL_PERIODS: u64 = 3;
+const MIN_STALL_SECS: u64 = 120;
+
+/// What the watchdog knows about each interval task, by name
+struct Heartbeat {
+    last_run: Instant,
+    period_secs: u64,
+    restarts: u32,
+    last_panic: Option<String>,
+    /// A problem is reported for this worker (panic or stall)
+    reported: bool,
+}
+
+static HEARTBEATS: Lazy<Mutex<BTreeMap<&'static str, Heartbeat>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Run `work` every `period()` seconds on the tokio runtime until `shutdown` fires.
+/// The first run happens immediately. `work` is blocking code (database, clipboard,
+/// history files), so it runs via `block_in_place` rather than stalling the runtime.
+/// The period is re-read after every run so config reloads take effect.
+///
+/// A run that panics doesn't end the task: it is retried after a backoff, and the
+/// panic is reported as a problem until a run succeeds again. Every finished run is
+/// a heartbeat for `check_workers`.
+pub fn spawn_interval_task<P, F>(
+    name: &'static str,
+    mut shutdown: broadcast::Receiver<()>,
+    period: P,
+    mut work: F,
+) -> JoinHandle<()>
+where
+    P: Fn() -> u64 + Send + 'static,
+    F: FnMut() + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut secs = period().max(1);
+        let mut ticker = interval_at(Instant::now(), Duration::from_secs(secs));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        beat(name, secs, None);
+        let mut panics_in_a_row = 0;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.recv() => break,
+            }
+
+            match catch_unwind(AssertUnwindSafe(|| tokio::task::block_in_place(&mut work))) {
+                Ok(()) => {
+                    panics_in_a_row = 0;
+                    beat(name, secs, None);
+                }
+                Err(panic) => {
+                    panics_in_a_row += 1;
+                    let delay = restart_backoff(panics_in_a_row);
+                    let message = panic_message(panic.as_ref());
+                    error!(
+                        "{} worker panicked: {}; restarting in {}s",
+                        name,
+                        message,
+                        delay.as_secs()
+                    );
+                    beat(name, secs, Some(message));
+
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = shutdown.recv() => break,
+                    }
+                }
+            }
+
+            let next = period().max(1);
+            if next != secs {
+                secs = next;
+                let period = Duration::from_secs(secs);
+                ticker = interval_at(Instant::now() + period, period);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            }
+        }
+
+        debug!("{} task stopped", name);
+    })
+}
+
+fn restart_backoff(panics_in_a_row: u32) -> Duration {
+    let doublings = panics_in_a_row.saturating_sub(1).min(16);
+    (RESTART_BACKOFF * 2u32.pow(doublings)).min(MAX_RESTART_BACKOFF)
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+fn problem_component(name: &str) -> String {
+    format!("{} worker", name)
+}
+
+/// Record a finished run of `name`, or with `panic`, a run that panicked. A problem
+/// reported for the worker is cleared once a run succeeds.
+fn beat(name: &'static str, period_secs: u64, panic: Option<String>) {
+    let mut heartbeats = lock_or_recover("worker heartbeats", &HEARTBEATS);
+    let heartbeat = heartbeats.entry(name).or_insert_with(|| Heartbeat {
+        last_run: Instant::now(),
+        period_secs,
+        restarts: 0,
+        last_panic: None,
+        reported: false,
+    });
+    heartbeat.period_secs = period_secs;
+
+    match panic {
+        Some(message) => {
+            heartbeat.restarts += 1;
+            report_problem(
+                &problem_component(name),
+                &format!(
+                    "panicked ({}), restarted {} times",
+                    message, heartbeat.restarts
+                ),
+            );
+            heartbeat.last_panic = Some(message);
+            heartbeat.reported = true;
+        }
+        None => {
+            heartbeat.last_run = Instant::now();
+            if heartbeat.reported {
+                clear_problem(&problem_component(name));
+                heartbeat.reported = false;
+            }
+        }
+    }
+}
+
+/// Report workers that haven't finished a run in much longer than their period
+/// (hung on a lock, a slow disk, a stuck clipboard), and clear ones that recovered
+pub fn check_workers() {
+    let mut heartbeats = lock_or_recover("worker heartbeats", &HEARTBEATS);
+    for (name, heartbeat) in heartbeats.iter_mut() {
+        let silent = heartbeat.last_run.elapsed().as_secs();
+        if silent > stall_after(heartbeat.period_secs) {
+            warn!("{} worker has not finished a run in {}s", name, silent);
+            report_problem(
+                &problem_component(name),
+                &format!("stalled, no run finished in {}s", silent),
+            );
+            heartbeat.reported = true;
+        }
+    }
+}
+
+fn stall_after(period_secs: u64) -> u64 {
+    (period_secs * STALL_PERIODS).max(MIN_STALL_SECS)
+}
+
+/// Every interval task, as `jotx status` shows them
+pub fn worker_health() -> Vec<WorkerHealth> {
+    lock_or_recover("worker heartbeats", &HEARTBEATS)
+        .iter()
+        .map(|(name, heartbeat)| {
+            let last_run_secs_ago = heartbeat.last_run.elapsed().as_secs();
+            WorkerHealth {
+                name: name.to_string(),
+                last_run_secs_ago,
+                restarts: heartbeat.restarts,
+                last_panic: heartbeat.last_panic.clone(),
+                stalled: last_run_secs_ago > stall_after(heartbeat.period_secs),
+            }
+        })
+        .collect()
+}
+
+/// Resolves when the daemon is asked to stop: `exit` on the control socket (`jotx exit`),
+/// SIGTERM, Ctrl+C, or the PID file disappearing (checked every `pid_check_secs`)
+pub async fn shutdown_requested<F>(pid_check_secs: u64, still_running: F)
+where
+    F: Fn() -> bool,
+{
+    let mut pid_check = tokio::time::interval(Duration::from_secs(pid_check_secs.max(1)));
+    pid_check.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let pid_gone = async {
+        loop {
+            pid_check.tick().await;
+            if !still_running() {
+                break;
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = exit_requested() => {}
+        _ = terminate_signal() => {}
+        _ = tokio::signal::ctrl_c() => {}
+        _ = pid_gone => {}
+    }
+}
+
+#[cfg(unix)]
+async fn terminate_signal() {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut term) => {
+            term.recv().await;
+        }
+        Err(e) => {
+            error!("Failed to listen for SIGTERM: {}", e);
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn terminate_signal() {
+    std::future::pending::<()>().await;
+}
+
+/// Call `reload` each time the daemon gets SIGHUP (`kill -HUP`), until `shutdown` fires
+pub fn spawn_reload_on_hangup<F>(mut shutdown: broadcast::Receiver<()>, reload: F) -> JoinHandle<()>
+where
+    F: Fn() + Send + 'static,
+{
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+
+            let mut hangup = match signal(SignalKind::hangup()) {
+                Ok(hangup) => hangup,
+                Err(e) => {
+                    error!("Failed to listen for SIGHUP: {}", e);
+                    return;
+                }
+            };
+            loop {
+                tokio::select! {
+                    Some(()) = hangup.recv() => tokio::task::block_in_place(&reload),
+                    _ = shutdown.recv() => break,
+                }
+            }
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = reload;
+            let _ = shutdown.recv().await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_restart_backoff() {
+        assert_eq!(restart_backoff(1), Duration::from_secs(1));
+        assert_eq!(restart_backoff(3), Duration::from_secs(4));
+        assert_eq!(restart_backoff(40), MAX_RESTART_BACKOFF);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_panicking_worker_keeps_running() {
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let runs = Arc::new(AtomicU32::new(0));
+        let counted = runs.clone();
+
+        let task = spawn_interval_task(
+            "Flaky test",
+            shutdown_tx.subscribe(),
+            || 1,
+            move || {
+                if counted.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("first run fails");
+                }
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        let _ = shutdown_tx.send(());
+        task.await.unwrap();
+
+        assert!(runs.load(Ordering::SeqCst) >= 2);
+        let health = worker_health();
+        let flaky = health.iter().find(|w| w.name == "Flaky test").unwrap();
+        assert_eq!(flaky.restarts, 1);
+        assert_eq!(flaky.last_panic.as_deref(), Some("first run fails"));
+    }
+}
@@ -0,0 +1,255 @@
+//! Counters the daemon keeps about its own work, printed by `jotx status --metrics` and
+//! served at `/metrics` by the HTTP API, both in Prometheus' text format. Every process
+//! has its own; only the daemon's are reported, so captures made by shell hooks in
+//! their own processes show up in the written entries but not the capture counts.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::db::DB_WRITER;
+use crate::pid_controller::dropped_captures;
+
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Number and total duration of timed calls
+pub struct Timer {
+    count: Counter,
+    micros: Counter,
+}
+
+impl Timer {
+    const fn new() -> Self {
+        Self {
+            count: Counter::new(),
+            micros: Counter::new(),
+        }
+    }
+
+    pub fn record(&self, elapsed: Duration) {
+        self.count.inc();
+        self.micros.add(elapsed.as_micros() as u64);
+    }
+
+    /// Run `f` and record how long it took
+    pub fn time<T>(&self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(start.elapsed());
+        result
+    }
+
+    fn seconds(&self) -> f64 {
+        self.micros.get() as f64 / 1_000_000.0
+    }
+}
+
+pub struct Metrics {
+    pub clipboard_captures: Counter,
+    pub shell_captures: Counter,
+    /// Entries the DB writer has processed (inserts, run count bumps, imports)
+    pub entries_written: Counter,
+    pub embeddings: Timer,
+    pub llm_requests: Timer,
+    pub search_cache_hits: Counter,
+    pub search_cache_misses: Counter,
+}
+
+pub static METRICS: Metrics = Metrics {
+    clipboard_captures: Counter::new(),
+    shell_captures: Counter::new(),
+    entries_written: Counter::new(),
+    embeddings: Timer::new(),
+    llm_requests: Timer::new(),
+    search_cache_hits: Counter::new(),
+    search_cache_misses: Counter::new(),
+};
+
+/// The counters at one moment, as sent over the control socket
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub uptime_secs: u64,
+    pub clipboard_captures: u64,
+    pub shell_captures: u64,
+    pub dropped_captures: u64,
+    pub entries_written: u64,
+    pub writer_queue_depth: u64,
+    pub embeddings: u64,
+    pub embedding_seconds: f64,
+    pub llm_requests: u64,
+    pub llm_seconds: f64,
+    pub search_cache_hits: u64,
+    pub search_cache_misses: u64,
+}
+
+/// Read every counter, plus the writer's queue depth. Only call this in the daemon:
+/// it starts a DB writer if the process has none.
+pub fn snapshot(uptime: Duration) -> MetricsSnapshot {
+    MetricsSnapshot {
+        uptime_secs: uptime.as_secs(),
+        clipboard_captures: METRICS.clipboard_captures.get(),
+        shell_captures: METRICS.shell_captures.get(),
+        dropped_captures: dropped_captures(),
+        entries_written: METRICS.entries_written.get(),
+        writer_queue_depth: DB_WRITER.queue_len() as u64,
+        embeddings: METRICS.embeddings.count.get(),
+        embedding_seconds: METRICS.embeddings.seconds(),
+        llm_requests: METRICS.llm_requests.count.get(),
+        llm_seconds: METRICS.llm_requests.seconds(),
+        search_cache_hits: METRICS.search_cache_hits.get(),
+        search_cache_misses: METRICS.search_cache_misses.get(),
+    }
+}
+
+impl MetricsSnapshot {
+    /// Captures per second since the daemon started
+    pub fn captures_per_second(&self) -> f64 {
+        let captures = self.clipboard_captures + self.shell_captures;
+        captures as f64 / self.uptime_secs.max(1) as f64
+    }
+
+    /// Share of searches answered from the result cache, 0 before any search
+    pub fn cache_hit_ratio(&self) -> f64 {
+        let lookups = self.search_cache_hits + self.search_cache_misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.search_cache_hits as f64 / lookups as f64
+    }
+
+    /// The Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, String)]| {
+            let _ = writeln!(out, "# HELP jotx_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE jotx_{} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "jotx_{}{} {}", name, labels, value);
+            }
+        };
+
+        metric(
+            "uptime_seconds",
+            "gauge",
+            "Seconds since the daemon started.",
+            &[("", self.uptime_secs.to_string())],
+        );
+        metric(
+            "captures_total",
+            "counter",
+            "Entries captured by the daemon.",
+            &[
+                (
+                    r#"{source="clipboard"}"#,
+                    self.clipboard_captures.to_string(),
+                ),
+                (r#"{source="shell"}"#, self.shell_captures.to_string()),
+            ],
+        );
+        metric(
+            "captures_per_second",
+            "gauge",
+            "Average captures per second since the daemon started.",
+            &[("", format!("{:.4}", self.captures_per_second()))],
+        );
+        metric(
+            "dropped_captures_total",
+            "counter",
+            "Captures dropped because the DB writer queue was full.",
+            &[("", self.dropped_captures.to_string())],
+        );
+        metric(
+            "db_writer_entries_total",
+            "counter",
+            "Entries the DB writer has processed.",
+            &[("", self.entries_written.to_string())],
+        );
+        metric(
+            "db_writer_queue_depth",
+            "gauge",
+            "Entries waiting in the DB writer queue.",
+            &[("", self.writer_queue_depth.to_string())],
+        );
+        metric(
+            "embedding_seconds",
+            "summary",
+            "Time spent generating embeddings.",
+            &[
+                ("_sum", format!("{:.6}", self.embedding_seconds)),
+                ("_count", self.embeddings.to_string()),
+            ],
+        );
+        metric(
+            "llm_request_seconds",
+            "summary",
+            "Time spent waiting on the LLM.",
+            &[
+                ("_sum", format!("{:.6}", self.llm_seconds)),
+                ("_count", self.llm_requests.to_string()),
+            ],
+        );
+        metric(
+            "search_cache_lookups_total",
+            "counter",
+            "Searches looked up in the result cache.",
+            &[
+                (r#"{result="hit"}"#, self.search_cache_hits.to_string()),
+                (r#"{result="miss"}"#, self.search_cache_misses.to_string()),
+            ],
+        );
+        metric(
+            "search_cache_hit_ratio",
+            "gauge",
+            "Share of searches answered from the result cache.",
+            &[("", format!("{:.4}", self.cache_hit_ratio()))],
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prometheus_text() {
+        let snapshot = MetricsSnapshot {
+            uptime_secs: 10,
+            shell_captures: 15,
+            clipboard_captures: 5,
+            embeddings: 2,
+            embedding_seconds: 0.5,
+            search_cache_hits: 3,
+            search_cache_misses: 1,
+            ..Default::default()
+        };
+        assert_eq!(snapshot.captures_per_second(), 2.0);
+        assert_eq!(snapshot.cache_hit_ratio(), 0.75);
+
+        let text = snapshot.to_prometheus();
+        assert!(text.contains("# TYPE jotx_captures_total counter\n"));
+        assert!(text.contains("jotx_captures_total{source=\"shell\"} 15\n"));
+        assert!(text.contains("jotx_embedding_seconds_sum 0.500000\n"));
+        assert!(text.contains("jotx_embedding_seconds_count 2\n"));
+        assert!(text.contains("jotx_search_cache_hit_ratio 0.7500\n"));
+    }
+}
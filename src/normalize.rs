@@ -0,0 +1,40 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Canonical composition (NFC), so text that looks the same compares equal whether it
+/// was typed, pasted, or read from a history file in decomposed form (e.g. "é" vs "e\u{301}")
+pub fn normalize(text: &str) -> String {
+    text.nfc().collect()
+}
+
+/// NFC plus Unicode case folding, used wherever content is stored or matched
+/// case-insensitively. Unlike `to_lowercase`, "STRASSE" and "straße" fold the same.
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.nfc() {
+        match c {
+            'ß' | 'ẞ' => folded.push_str("ss"),
+            'ς' => folded.push('σ'),
+            c => folded.extend(c.to_lowercase()),
+        }
+    }
+    folded.nfc().collect()
+}
+
+/// Case-insensitive, normalization-insensitive substring check
+pub fn contains_folded(haystack: &str, needle: &str) -> bool {
+    fold(haystack).contains(&fold(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(normalize("cafe\u{301}"), "café");
+        assert_eq!(fold("CAFE\u{301}"), "café");
+        assert_eq!(fold("STRASSE"), fold("straße"));
+        assert_eq!(fold("ΟΔΟΣ"), fold("οδος"));
+        assert!(contains_folded("echo 'Grüße'", "GRÜSSE"));
+    }
+}
@@ -0,0 +1,338 @@
+//! `jotx notes`: write captured history into a notes vault (e.g. Obsidian) as Markdown
+//! files with YAML frontmatter, one per day or one per project per day.
+
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{GLOBAL_CONFIG, NotesConfig, NotesLayout};
+use crate::db::USER_DB;
+use crate::journal::{journal_title, render_journal, split_sessions};
+use crate::managers::lock_manager::lock_or_recover;
+use crate::timezone::QueryTimezone;
+use crate::types::{Entry, EntryType, HistoryEntry, QueryParams};
+
+/// What an export wrote
+#[derive(Debug, Default)]
+pub struct NotesReport {
+    pub dir: PathBuf,
+    /// Notes created or changed
+    pub written: usize,
+    /// Notes that already had the same content
+    pub unchanged: usize,
+}
+
+/// Export the last `days` days (from `notes.days` when None) to `notes.dir`, or `dir` if given
+pub fn export_notes(days: Option<i64>, dir: Option<&str>) -> Result<NotesReport, String> {
+    let config: NotesConfig = GLOBAL_CONFIG
+        .read()
+        .map_err(|_| "Config lock poisoned".to_string())?
+        .notes
+        .clone();
+
+    let dir = dir
+        .map(str::to_string)
+        .or(config.dir)
+        .filter(|d| !d.is_empty())
+        .ok_or("No notes folder: set notes.dir in config.toml or pass --dir")?;
+    let dir = expand_home(&dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let tz = QueryTimezone::from_config();
+    let now = Utc::now();
+    let today = tz.local_time(now).date();
+    let mut report = NotesReport {
+        dir: dir.clone(),
+        ..Default::default()
+    };
+    let mut projects = ProjectNames::default();
+
+    for days_back in 0..days.unwrap_or(config.days).max(1) {
+        let day = today - Duration::days(days_back);
+        let params = QueryParams {
+            since: Some(tz.day_start(now, days_back)),
+            until: Some(tz.day_start(now, days_back - 1)),
+            ..Default::default()
+        };
+        let mut entries = {
+            let db = lock_or_recover("database", &USER_DB);
+            db.query_entries(&params)
+                .map_err(|e| format!("Failed to load history: {}", e))?
+        };
+        if entries.is_empty() {
+            continue;
+        }
+        entries.reverse();
+
+        for (path, note) in day_notes(day, entries, config.layout, &mut projects, &tz) {
+            if write_if_changed(&dir.join(path), &note)? {
+                report.written += 1;
+            } else {
+                report.unchanged += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Export from the daemon's maintenance tick when `notes.on_maintenance` is set
+pub fn export_notes_on_maintenance() {
+    let enabled = GLOBAL_CONFIG
+        .read()
+        .map(|config| config.notes.on_maintenance && config.notes.dir.is_some())
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+
+    match export_notes(None, None) {
+        Ok(report) => println!(
+            "Notes exported: {} written to {}",
+            report.written,
+            report.dir.display()
+        ),
+        Err(e) => eprintln!("Notes export failed: {}", e),
+    }
+}
+
+/// The notes for one day's entries (oldest first), as (path relative to the notes folder, content)
+pub(crate) fn day_notes(
+    day: NaiveDate,
+    entries: Vec<Entry>,
+    layout: NotesLayout,
+    projects: &mut ProjectNames,
+    tz: &QueryTimezone,
+) -> Vec<(PathBuf, String)> {
+    let date = day.format("%Y-%m-%d").to_string();
+
+    // Clipboard items have no directory, so they only appear in day notes
+    let mut by_project: BTreeMap<String, Vec<HistoryEntry>> = BTreeMap::new();
+    let mut all = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let project = entry
+            .working_dir
+            .as_deref()
+            .and_then(|dir| projects.name(dir));
+        let entry = HistoryEntry::from(entry);
+        if let Some(project) = project {
+            by_project.entry(project).or_default().push(entry.clone());
+        }
+        all.push(entry);
+    }
+
+    match layout {
+        NotesLayout::Day => {
+            let project_names: Vec<&str> = by_project.keys().map(String::as_str).collect();
+            let mut note = frontmatter(&date, None, &all, &project_names);
+            note.push_str(&render_journal(
+                &journal_title(day),
+                &split_sessions(all),
+                tz,
+            ));
+            vec![(PathBuf::from(format!("{}.md", date)), note)]
+        }
+        NotesLayout::Project => by_project
+            .into_iter()
+            .map(|(project, entries)| {
+                let mut note = frontmatter(&date, Some(&project), &entries, &[]);
+                let title = format!("{}: {}", project, day.format("%A, %Y-%m-%d"));
+                note.push_str(&render_journal(&title, &split_sessions(entries), tz));
+                let _ = writeln!(note);
+                let _ = writeln!(note, "Day: [[{}]]", date);
+                (PathBuf::from(&project).join(format!("{}.md", date)), note)
+            })
+            .collect(),
+    }
+}
+
+fn frontmatter(
+    date: &str,
+    project: Option<&str>,
+    entries: &[HistoryEntry],
+    projects: &[&str],
+) -> String {
+    let shell = EntryType::Shell.to_string();
+    let commands = entries.iter().filter(|e| e.entry_type == shell).count();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "---");
+    let _ = writeln!(out, "date: {}", date);
+    if let Some(project) = project {
+        let _ = writeln!(out, "project: {}", yaml_string(project));
+    }
+    if !projects.is_empty() {
+        let links: Vec<String> = projects.iter().map(|p| yaml_string(p)).collect();
+        let _ = writeln!(out, "projects: [{}]", links.join(", "));
+    }
+    let _ = writeln!(out, "commands: {}", commands);
+    let _ = writeln!(out, "clipboard_items: {}", entries.len() - commands);
+    let _ = writeln!(out, "tags: [jotx]");
+    let _ = writeln!(out, "---");
+    let _ = writeln!(out);
+    out
+}
+
+/// Double-quoted YAML scalar
+fn yaml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Project a directory belongs to: the repository containing it (the nearest folder
+/// with a `.git`), or the folder itself outside of one. Cached per directory.
+#[derive(Default)]
+pub(crate) struct ProjectNames {
+    cache: HashMap<String, Option<String>>,
+}
+
+impl ProjectNames {
+    pub fn name(&mut self, dir: &str) -> Option<String> {
+        if let Some(name) = self.cache.get(dir) {
+            return name.clone();
+        }
+
+        let path = Path::new(dir);
+        let root = path
+            .ancestors()
+            .find(|p| p.join(".git").exists())
+            .unwrap_or(path);
+        let name = root
+            .file_name()
+            .map(|n| sanitize_file_name(&n.to_string_lossy()))
+            .filter(|n| !n.is_empty());
+
+        self.cache.insert(dir.to_string(), name.clone());
+        name
+    }
+}
+
+/// Characters vaults and filesystems reject in note names become '-'
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => '-',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+pub(crate) fn expand_home(dir: &str) -> PathBuf {
+    match dir.strip_prefix("~/") {
+        Some(rest) => PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(rest),
+        None => PathBuf::from(dir),
+    }
+}
+
+/// Skip rewriting notes whose content is unchanged, so vault sync doesn't see churn
+fn write_if_changed(path: &Path, content: &str) -> Result<bool, String> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        id: i64,
+        entry_type: EntryType,
+        content: &str,
+        timestamp: i64,
+        dir: Option<&Path>,
+    ) -> Entry {
+        Entry {
+            id,
+            entry_type,
+            content: content.to_string(),
+            timestamp,
+            times_run: 1,
+            working_dir: dir.map(|d| d.to_string_lossy().into_owned()),
+            git_repo: None,
+            git_branch: None,
+            user: None,
+            host: None,
+            app_name: None,
+            window_title: None,
+            exit_code: None,
+            duration_ms: None,
+            embedding: None,
+        }
+    }
+
+    #[test]
+    fn test_project_notes_link_back_to_the_day() {
+        let root = std::env::temp_dir().join(format!("jotx-notes-{}", std::process::id()));
+        let repo = root.join("jot");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("src")).unwrap();
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 11).unwrap();
+        let nine = day.and_hms_opt(9, 0, 0).unwrap().and_utc().timestamp();
+        let entries = || {
+            vec![
+                entry(
+                    1,
+                    EntryType::Shell,
+                    "cargo build",
+                    nine,
+                    Some(&repo.join("src")),
+                ),
+                entry(
+                    2,
+                    EntryType::Clipboard,
+                    "https://docs.rs/chrono",
+                    nine + 60,
+                    None,
+                ),
+                entry(3, EntryType::Shell, "ls", nine + 120, Some(&root)),
+            ]
+        };
+
+        let mut projects = ProjectNames::default();
+        let tz = QueryTimezone::Utc;
+
+        let notes = day_notes(day, entries(), NotesLayout::Day, &mut projects, &tz);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].0, PathBuf::from("2024-03-11.md"));
+        let root_name = root.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(notes[0].1.starts_with("---\ndate: 2024-03-11\n"));
+        assert!(
+            notes[0]
+                .1
+                .contains(&format!("projects: [\"jot\", \"{}\"]", root_name))
+        );
+        assert!(notes[0].1.contains("commands: 2\nclipboard_items: 1\n"));
+        assert!(notes[0].1.contains("https://docs.rs/chrono"));
+
+        let notes = day_notes(day, entries(), NotesLayout::Project, &mut projects, &tz);
+        let (path, note) = notes
+            .iter()
+            .find(|(_, n)| n.contains("project: \"jot\""))
+            .unwrap();
+        assert_eq!(path, &PathBuf::from("jot/2024-03-11.md"));
+        assert!(note.contains("# jot: Monday, 2024-03-11"));
+        assert!(note.contains("`cargo build`"));
+        assert!(!note.contains("`ls`"));
+        assert!(!note.contains("docs.rs"));
+        assert!(note.ends_with("Day: [[2024-03-11]]\n"));
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("feat/login: v2"), "feat-login- v2");
+        assert_eq!(sanitize_file_name(".config"), "config");
+    }
+}
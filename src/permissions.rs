@@ -0,0 +1,189 @@
+//! Keep everything jotx stores readable by the current user only: the data directory
+//! is created 0700 and files 0600, regardless of the umask. `jotx doctor` uses
+//! `exposed_paths` to find files created before this (or loosened by hand).
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+#[cfg(unix)]
+const PRIVATE_FILE_MODE: u32 = 0o600;
+#[cfg(unix)]
+const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// A file or directory that group or other users can access
+#[derive(Debug, Clone)]
+pub struct ExposedPath {
+    pub path: PathBuf,
+    pub mode: u32,
+}
+
+pub fn create_private_dir(path: &Path) -> io::Result<()> {
+    fs::create_dir_all(path)?;
+    #[cfg(unix)]
+    fs::set_permissions(path, fs::Permissions::from_mode(PRIVATE_DIR_MODE))?;
+    Ok(())
+}
+
+fn private_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    options.mode(PRIVATE_FILE_MODE);
+    options
+}
+
+/// Like `fs::write`, but the file is only readable by its owner
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut file = private_options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    restrict(path)?;
+    file.write_all(contents.as_ref())
+}
+
+/// Like `File::create`, but the file is only readable by its owner
+pub fn create_private_file(path: &Path) -> io::Result<File> {
+    let file = private_options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    restrict(path)?;
+    Ok(file)
+}
+
+/// Write `contents` to a new file in the temp dir that only the owner can read. The
+/// name is random and the file must not exist yet, so another user can't swap in a
+/// symlink or their own file (unlike a fixed /tmp path).
+pub fn write_private_temp(prefix: &str, contents: impl AsRef<[u8]>) -> io::Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!("{}-{:016x}", prefix, rand::random::<u64>()));
+    let mut file = private_options().write(true).create_new(true).open(&path)?;
+    file.write_all(contents.as_ref())?;
+    Ok(path)
+}
+
+pub fn open_private_append(path: &Path) -> io::Result<File> {
+    let file = private_options().create(true).append(true).open(path)?;
+    restrict(path)?;
+    Ok(file)
+}
+
+/// Remove group/other permission bits from an existing path (owner bits are kept,
+/// so executable plugins stay executable). Missing paths are ignored.
+pub fn restrict(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mode = match fs::metadata(path) {
+            Ok(meta) => meta.permissions().mode(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if mode & 0o077 != 0 {
+            fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o700))?;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Restrict a SQLite database together with its WAL and shared-memory files
+pub fn restrict_db(path: &Path) -> io::Result<()> {
+    restrict(path)?;
+    for suffix in ["-wal", "-shm"] {
+        let mut side = path.as_os_str().to_owned();
+        side.push(suffix);
+        restrict(Path::new(&side))?;
+    }
+    Ok(())
+}
+
+/// Every path under `roots` (recursively) that group or other users can access
+pub fn exposed_paths(roots: &[PathBuf]) -> Vec<ExposedPath> {
+    let mut exposed = Vec::new();
+    for root in roots {
+        collect_exposed(root, &mut exposed);
+    }
+    exposed
+}
+
+fn collect_exposed(path: &Path, exposed: &mut Vec<ExposedPath>) {
+    // symlink_metadata: don't follow links out of the data directory
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return;
+    };
+    if meta.file_type().is_symlink() {
+        return;
+    }
+
+    #[cfg(unix)]
+    {
+        let mode = meta.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            exposed.push(ExposedPath {
+                path: path.to_path_buf(),
+                mode,
+            });
+        }
+    }
+
+    if meta.is_dir()
+        && let Ok(entries) = fs::read_dir(path)
+    {
+        for entry in entries.flatten() {
+            collect_exposed(&entry.path(), exposed);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_files_and_exposed_paths() {
+        let dir = std::env::temp_dir().join(format!("jotx-perm-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        create_private_dir(&dir).unwrap();
+
+        let private = dir.join("private.txt");
+        write_private(&private, "secret").unwrap();
+        assert_eq!(
+            fs::metadata(&private).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        let open = dir.join("open.txt");
+        fs::write(&open, "history").unwrap();
+        fs::set_permissions(&open, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let exposed = exposed_paths(std::slice::from_ref(&dir));
+        assert_eq!(exposed.len(), 1);
+        assert_eq!(exposed[0].path, open);
+
+        restrict(&open).unwrap();
+        assert!(exposed_paths(std::slice::from_ref(&dir)).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_private_temp_files_are_new() {
+        let first = write_private_temp("jotx-temp-test", "echo hi").unwrap();
+        let second = write_private_temp("jotx-temp-test", "echo hi").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(fs::read_to_string(&first).unwrap(), "echo hi");
+        assert_eq!(
+            fs::metadata(&first).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        fs::remove_file(&first).unwrap();
+        fs::remove_file(&second).unwrap();
+    }
+}
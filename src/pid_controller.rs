@@ -1,20 +1,35 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+use crate::ipc::{IpcRequest, IpcResponse, request};
+use crate::permissions::create_private_file;
+
 pub const PID_FILE: &str = "/tmp/jotx.pid";
+pub const LOG_FILE: &str = "/tmp/jotx.log";
+pub const ERR_FILE: &str = "/tmp/jotx.err";
 
 pub fn is_running() -> bool {
     if let Ok(pid_str) = std::fs::read_to_string(PID_FILE) {
         if let Ok(pid) = pid_str.trim().parse::<u32>() {
-            // Check if process exists
-            return std::process::Command::new("kill")
-                .arg("-0")
-                .arg(pid.to_string())
-                .status()
-                .map(|s| s.success())
-                .unwrap_or(false);
+            return pid_alive(pid);
         }
     }
     false
 }
 
+/// Check if a process with `pid` exists
+pub fn pid_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
 pub fn save_pid() {
     let pid = std::process::id();
     let _ = std::fs::write(PID_FILE, pid.to_string());
@@ -22,4 +37,247 @@ pub fn save_pid() {
 
 pub fn remove_pid() {
     let _ = std::fs::remove_file(PID_FILE);
-}
\ No newline at end of file
+}
+
+/// Spawn `<exe> internal-daemon` in the background, logging to LOG_FILE/ERR_FILE
+pub fn spawn_daemon(exe: &std::path::Path) -> std::io::Result<()> {
+    let stdout = create_private_file(std::path::Path::new(LOG_FILE))
+        .map(Stdio::from)
+        .unwrap_or_else(|_| Stdio::null());
+
+    let stderr = create_private_file(std::path::Path::new(ERR_FILE))
+        .map(Stdio::from)
+        .unwrap_or_else(|_| Stdio::null());
+
+    Command::new(exe)
+        .arg("internal-daemon")
+        .stdout(stdout)
+        .stderr(stderr)
+        .spawn()?;
+
+    Ok(())
+}
+
+/// How long `stop_daemon` waits for a daemon asked over the control socket to exit
+const STOP_TIMEOUT_MS: u64 = 5000;
+const STOP_POLL_MS: u64 = 100;
+
+/// Ask the daemon to exit over the control socket and wait for it to finish. Daemons
+/// that don't answer there (or don't stop in time) are terminated via the PID file.
+pub fn stop_daemon() {
+    let pid = read_pid();
+    if let Ok(IpcResponse::Done { .. }) = request(IpcRequest::Exit) {
+        let mut waited = 0;
+        while pid.is_some_and(pid_alive) && waited < STOP_TIMEOUT_MS {
+            std::thread::sleep(std::time::Duration::from_millis(STOP_POLL_MS));
+            waited += STOP_POLL_MS;
+        }
+    }
+
+    if let Some(pid) = pid.filter(|pid| pid_alive(*pid)) {
+        let _ = Command::new("kill").arg(pid.to_string()).status();
+    }
+    remove_pid();
+}
+
+pub const PAUSE_FILE: &str = "/tmp/jotx.paused";
+
+/// Read the daemon PID, if a PID file exists
+pub fn read_pid() -> Option<u32> {
+    std::fs::read_to_string(PID_FILE)
+        .ok()
+        .and_then(|pid_str| pid_str.trim().parse::<u32>().ok())
+}
+
+/// Capture is paused while the pause file exists. The daemon threads and the
+/// shell hook check this on every pass, so other processes (CLI, GUI) can
+/// pause and resume capture without restarting the daemon. The file holds the
+/// time the pause ends (RFC 3339), or nothing if it lasts until `jotx resume`;
+/// the first check after that time removes it.
+pub fn is_paused() -> bool {
+    let Ok(content) = std::fs::read_to_string(PAUSE_FILE) else {
+        return false;
+    };
+
+    match pause_end(&content) {
+        Some(end) if end <= Utc::now() => {
+            let _ = set_paused(false);
+            false
+        }
+        _ => true,
+    }
+}
+
+/// When the current pause ends; `None` when capture isn't paused or the pause has no end
+pub fn paused_until() -> Option<DateTime<Utc>> {
+    std::fs::read_to_string(PAUSE_FILE)
+        .ok()
+        .and_then(|content| pause_end(&content))
+        .filter(|end| *end > Utc::now())
+}
+
+fn pause_end(content: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(content.trim())
+        .ok()
+        .map(|end| end.with_timezone(&Utc))
+}
+
+/// Pause capture until `end`, or until resumed when there is none
+pub fn pause_until(end: Option<DateTime<Utc>>) -> std::io::Result<()> {
+    let content = end.map(|end| end.to_rfc3339()).unwrap_or_default();
+    std::fs::write(PAUSE_FILE, content)
+}
+
+/// Parse how long to pause for: a number with a unit (s, m, h or d), e.g. "30m",
+/// or several of them, e.g. "1h30m"
+pub fn parse_pause_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration '{}': use e.g. 30m, 2h or 1h30m", text);
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in text.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let n: i64 = number.parse().map_err(|_| invalid())?;
+        number.clear();
+        let part = match c.to_ascii_lowercase() {
+            's' => Duration::try_seconds(n),
+            'm' => Duration::try_minutes(n),
+            'h' => Duration::try_hours(n),
+            'd' => Duration::try_days(n),
+            _ => None,
+        };
+        total = part
+            .and_then(|part| total.checked_add(&part))
+            .ok_or_else(invalid)?;
+    }
+
+    if !number.is_empty() || total <= Duration::zero() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Written by the daemon while a `privacy.quiet_windows` rule is active; holds the
+/// description of the matching window
+pub const QUIET_FILE: &str = "/tmp/jotx.quiet";
+
+/// The active quiet window, if any
+pub fn quiet_reason() -> Option<String> {
+    std::fs::read_to_string(QUIET_FILE).ok()
+}
+
+/// Update the quiet flag, touching the file only when the state changes
+pub fn set_quiet(reason: Option<&str>) -> std::io::Result<()> {
+    if quiet_reason().as_deref() == reason {
+        return Ok(());
+    }
+
+    match reason {
+        Some(reason) => std::fs::write(QUIET_FILE, reason),
+        None => match std::fs::remove_file(QUIET_FILE) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Capture is off while paused by hand or inside a quiet window
+pub fn capture_suspended() -> bool {
+    is_paused() || std::path::Path::new(QUIET_FILE).exists()
+}
+
+pub fn set_paused(paused: bool) -> std::io::Result<()> {
+    if paused {
+        pause_until(None)
+    } else {
+        match std::fs::remove_file(PAUSE_FILE) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Running total of captures dropped because the DB writer queue was full
+pub const DROPPED_FILE: &str = "/tmp/jotx.dropped";
+
+/// Captures dropped since the daemon started
+pub fn dropped_captures() -> u64 {
+    std::fs::read_to_string(DROPPED_FILE)
+        .ok()
+        .and_then(|count| count.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+pub fn record_dropped(count: u64) {
+    let total = dropped_captures() + count;
+    if total == count {
+        warn!("DB writer queue is full; dropping the oldest captures");
+    }
+    let _ = std::fs::write(DROPPED_FILE, total.to_string());
+}
+
+/// Problems the daemon could not recover from on its own (component -> message),
+/// shown by `jotx status`. Cleared when the daemon starts.
+pub const HEALTH_FILE: &str = "/tmp/jotx.health";
+
+/// Current daemon problems, by component
+pub fn problems() -> BTreeMap<String, String> {
+    std::fs::read_to_string(HEALTH_FILE)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn report_problem(component: &str, message: &str) {
+    let mut problems = problems();
+    if problems.get(component).map(String::as_str) == Some(message) {
+        return;
+    }
+    problems.insert(component.to_string(), message.to_string());
+    write_problems(&problems);
+}
+
+pub fn clear_problem(component: &str) {
+    let mut problems = problems();
+    if problems.remove(component).is_some() {
+        write_problems(&problems);
+    }
+}
+
+/// Reset the health and drop counters for a fresh daemon run
+pub fn clear_problems() {
+    let _ = std::fs::remove_file(HEALTH_FILE);
+    let _ = std::fs::remove_file(DROPPED_FILE);
+}
+
+fn write_problems(problems: &BTreeMap<String, String>) {
+    if problems.is_empty() {
+        clear_problems();
+    } else if let Ok(json) = serde_json::to_string(problems) {
+        let _ = std::fs::write(HEALTH_FILE, json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pause_duration() {
+        assert_eq!(parse_pause_duration("30m"), Ok(Duration::minutes(30)));
+        assert_eq!(parse_pause_duration("1h30m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_pause_duration(" 2H "), Ok(Duration::hours(2)));
+        assert_eq!(parse_pause_duration("1d"), Ok(Duration::days(1)));
+        for text in ["", "30", "m", "0m", "10x", "1h30", "-5m"] {
+            assert!(
+                parse_pause_duration(text).is_err(),
+                "{:?} should not parse",
+                text
+            );
+        }
+    }
+}
@@ -16,6 +16,12 @@ pub trait Plugin: Send + Sync {
     fn name(&self) -> &str;
     fn version(&self) -> &str;
     fn description(&self) -> &str;
+
+    /// "native" for plugins built into jotx, "rhai" or "external" for ones loaded from
+    /// the plugins directory
+    fn kind(&self) -> &str {
+        "native"
+    }
     
     /// Hook implementations (optional - plugins only implement what they need)
     
@@ -24,6 +30,18 @@ pub trait Plugin: Send + Sync {
         Ok(PluginAction::Continue)
     }
     
+    /// Called when a clipboard entry is captured, after the app blocklist and secret
+    /// patterns let it through
+    fn on_clipboard_captured(&self, _context: &ClipboardContext) -> Result<PluginAction, String> {
+        Ok(PluginAction::Continue)
+    }
+    
+    /// Called right before a captured entry is written. Plugins may rewrite
+    /// `entry.content` and add `entry.tags`; `Skip` or `Stop` drops the entry.
+    fn on_entry_store(&self, _entry: &mut EntryContext) -> Result<PluginAction, String> {
+        Ok(PluginAction::Continue)
+    }
+    
     /// Called before search is executed
     fn on_search_before(&self, _query: &str) -> Result<PluginAction, String> {
         Ok(PluginAction::Continue)
@@ -48,6 +66,35 @@ pub trait Plugin: Send + Sync {
     fn on_daemon_tick(&self, _context: &DaemonContext) -> Result<PluginAction, String> {
         Ok(PluginAction::Continue)
     }
+
+    /// Human readable reason for skipping a command (e.g. the rule that matched).
+    /// Only called after `on_command_captured` skipped or stopped the capture.
+    fn skip_reason(&self, _context: &CommandContext) -> Option<String> {
+        None
+    }
+
+    /// Replacement command text. Only called after `on_command_captured` returned
+    /// `PluginAction::ModifyData`; later plugins and storage see the new command.
+    fn modified_command(&self, _context: &CommandContext) -> Option<String> {
+        None
+    }
+
+    /// Human readable reason for rewriting a command, for the audit log. Only called
+    /// after `on_command_captured` returned `PluginAction::ModifyData`.
+    fn modify_reason(&self, _context: &CommandContext) -> Option<String> {
+        None
+    }
+
+    /// Like `skip_reason`, for a clip `on_clipboard_captured` skipped or stopped
+    fn clipboard_skip_reason(&self, _context: &ClipboardContext) -> Option<String> {
+        None
+    }
+
+    /// Like `modified_command`, for a clip `on_clipboard_captured` returned
+    /// `PluginAction::ModifyData` for
+    fn modified_clipboard(&self, _context: &ClipboardContext) -> Option<String> {
+        None
+    }
 }
 
 // ============================================================================
@@ -63,6 +110,31 @@ pub struct CommandContext {
     pub timestamp: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardContext {
+    /// The clip as it would be stored (folded unless case sensitive, secrets redacted)
+    pub content: String,
+    /// Application the clip was copied from
+    pub app: String,
+    pub window_title: String,
+    pub timestamp: u64,
+}
+
+/// An entry about to be written. Only `content` and `tags` are used afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryContext {
+    /// "shell" or "clipboard"
+    pub entry_type: String,
+    pub content: String,
+    pub timestamp: u64,
+    pub working_dir: Option<String>,
+    pub app_name: String,
+    pub window_title: String,
+    /// Tags to put on the stored entry
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmContext {
     pub provider: String,
@@ -85,6 +157,8 @@ pub struct ExternalPlugin {
     name: String,
     path: PathBuf,
     hooks: Vec<String>,  // Which hooks this plugin wants to listen to
+    /// The plugin's `<name>.toml`, sent as the "config" field of every input
+    config: serde_json::Value,
 }
 
 impl ExternalPlugin {
@@ -92,7 +166,14 @@ impl ExternalPlugin {
         // Read plugin manifest to see which hooks it subscribes to
         let hooks = Self::read_hooks(&path);
         
-        Self { name, path, hooks }
+        Self { name, path, hooks, config: serde_json::Value::Object(Default::default()) }
+    }
+
+    /// Send the plugin its settings with every hook
+    pub fn with_config(mut self, config: &toml::Table) -> Result<Self, String> {
+        self.config = serde_json::to_value(config)
+            .map_err(|e| format!("Invalid config for {}: {}", self.name, e))?;
+        Ok(self)
     }
     
     fn read_hooks(path: &PathBuf) -> Vec<String> {
@@ -108,7 +189,11 @@ impl ExternalPlugin {
         vec![]  // Default: no hooks
     }
     
-    fn execute(&self, hook: &str, input: serde_json::Value) -> Result<PluginResponse, String> {
+    fn execute(&self, hook: &str, mut input: serde_json::Value) -> Result<PluginResponse, String> {
+        if let Some(fields) = input.as_object_mut() {
+            fields.insert("config".to_string(), self.config.clone());
+        }
+        
         let mut  output = Command::new(&self.path)
             .arg(hook)  // Pass hook name as first argument
             .stdin(Stdio::piped())
@@ -165,6 +250,10 @@ impl Plugin for ExternalPlugin {
     fn description(&self) -> &str {
         "External plugin"
     }
+
+    fn kind(&self) -> &str {
+        "external"
+    }
     
     fn on_command_captured(&self, context: &CommandContext) -> Result<PluginAction, String> {
         if !self.hooks.contains(&"on_command_captured".to_string()) {
@@ -181,6 +270,45 @@ impl Plugin for ExternalPlugin {
         }
     }
     
+    fn on_clipboard_captured(&self, context: &ClipboardContext) -> Result<PluginAction, String> {
+        if !self.hooks.contains(&"on_clipboard_captured".to_string()) {
+            return Ok(PluginAction::Continue);
+        }
+        
+        let input = serde_json::to_value(context).unwrap();
+        let response = self.execute("on_clipboard_captured", input)?;
+        
+        match response.action.as_str() {
+            "stop" => Ok(PluginAction::Stop),
+            "skip" => Ok(PluginAction::Skip),
+            _ => Ok(PluginAction::Continue),
+        }
+    }
+    
+    fn on_entry_store(&self, entry: &mut EntryContext) -> Result<PluginAction, String> {
+        if !self.hooks.contains(&"on_entry_store".to_string()) {
+            return Ok(PluginAction::Continue);
+        }
+        
+        let input = serde_json::to_value(&*entry).unwrap();
+        let response = self.execute("on_entry_store", input)?;
+        
+        match response.action.as_str() {
+            "stop" => Ok(PluginAction::Stop),
+            "skip" => Ok(PluginAction::Skip),
+            "modify" => {
+                let modified = response
+                    .data
+                    .and_then(|data| serde_json::from_value::<EntryContext>(data).ok())
+                    .ok_or("on_entry_store returned \"modify\" without an entry")?;
+                entry.content = modified.content;
+                entry.tags = modified.tags;
+                Ok(PluginAction::ModifyData)
+            }
+            _ => Ok(PluginAction::Continue),
+        }
+    }
+    
     fn on_search_after(&self, _query: &str, results: &mut Vec<SearchResult>) -> Result<PluginAction, String> {
         if !self.hooks.contains(&"on_search_after".to_string()) {
             return Ok(PluginAction::Continue);
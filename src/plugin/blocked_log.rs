@@ -0,0 +1,130 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use crate::config::GLOBAL_CONFIG;
+use crate::permissions::open_private_append;
+use crate::privacy::salted_hash;
+use crate::types::BlockedCapture;
+
+const MAX_LOG_BYTES: u64 = 256 * 1024; // Rotated to blocked.jsonl.old past this size
+
+/// Blocked captures are appended here as JSON lines so other processes (the GUI)
+/// can follow them. The captured content itself is never written.
+pub fn get_blocked_log_path() -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".jotx").join("blocked.jsonl")
+}
+
+/// Append `event` to the log. Redactions are only kept with `privacy.audit_redactions`.
+pub fn record_blocked(event: &BlockedCapture) {
+    if event.redacted && !audit_redactions() {
+        return;
+    }
+
+    let path = get_blocked_log_path();
+
+    if let Ok(meta) = fs::metadata(&path)
+        && meta.len() > MAX_LOG_BYTES
+    {
+        let _ = fs::rename(&path, path.with_extension("jsonl.old"));
+    }
+
+    let line = match serde_json::to_string(event) {
+        Ok(line) => line,
+        Err(_) => return,
+    };
+
+    if let Ok(mut file) = open_private_append(&path) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// Hash of blocked content for the audit log, if `privacy.audit_hash_content` is on
+pub fn audit_content_hash(content: &str) -> Option<String> {
+    let enabled = GLOBAL_CONFIG
+        .read()
+        .map(|c| c.privacy.audit_hash_content)
+        .unwrap_or(false);
+
+    enabled.then(|| salted_hash(content))
+}
+
+fn audit_redactions() -> bool {
+    GLOBAL_CONFIG
+        .read()
+        .map(|c| c.privacy.audit_redactions)
+        .unwrap_or(false)
+}
+
+/// Every blocked or redacted capture still on disk (including the rotated log), oldest first
+pub fn read_blocked_log() -> Vec<BlockedCapture> {
+    let path = get_blocked_log_path();
+
+    [path.with_extension("jsonl.old"), path]
+        .iter()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect::<Vec<BlockedCapture>>()
+        })
+        .collect()
+}
+
+/// Follows the blocked log, returning captures blocked since the last poll.
+/// Redactions are left out: those captures were still stored.
+pub struct BlockedCaptureWatcher {
+    path: PathBuf,
+    offset: u64,
+}
+
+impl BlockedCaptureWatcher {
+    /// Start watching from the current end of the log
+    pub fn new() -> Self {
+        let path = get_blocked_log_path();
+        let offset = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self { path, offset }
+    }
+
+    pub fn poll(&mut self) -> Vec<BlockedCapture> {
+        let mut file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return Vec::new(),
+        };
+
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < self.offset {
+            // Log was rotated
+            self.offset = 0;
+        }
+        if len == self.offset {
+            return Vec::new();
+        }
+
+        let mut buf = String::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err() || file.read_to_string(&mut buf).is_err() {
+            return Vec::new();
+        }
+
+        // Only consume complete lines; a partial last line is picked up next time
+        let complete = match buf.rfind('\n') {
+            Some(pos) => &buf[..=pos],
+            None => return Vec::new(),
+        };
+        self.offset += complete.len() as u64;
+
+        complete
+            .lines()
+            .filter_map(|line| serde_json::from_str::<BlockedCapture>(line).ok())
+            .filter(|event| !event.redacted)
+            .collect()
+    }
+}
+
+impl Default for BlockedCaptureWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
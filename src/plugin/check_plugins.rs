@@ -1,9 +1,13 @@
 use rhai::AST;
-use std::{collections::HashMap, fs, path::Path};
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+
+use crate::types::PluginCheckReport;
 
 // Define all expected function hooks and their required arity (number of arguments)
-const EXPECTED_HOOKS: [(&str, usize); 6] = [
+const EXPECTED_HOOKS: [(&str, usize); 8] = [
     ("on_command_captured", 1), // (context)
+    ("on_clipboard_captured", 1), // (context)
+    ("on_entry_store", 1),      // (entry)
     ("on_search_before", 1),    // (query)
     ("on_search_after", 2),     // (query, results)
     ("on_llm_before", 2),       // (prompt, context)
@@ -12,7 +16,7 @@ const EXPECTED_HOOKS: [(&str, usize); 6] = [
 ];
 
 /// Checks the functions exported by a single plugin script.
-fn check_single_plugin(path: &Path, engine: &rhai::Engine) -> Result<(), String> {
+fn check_single_plugin(path: &Path, engine: &rhai::Engine) -> Result<PluginCheckReport, String> {
     let script = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read script: {}", e))?;
 
@@ -28,17 +32,20 @@ fn check_single_plugin(path: &Path, engine: &rhai::Engine) -> Result<(), String>
         let arity = fn_def.params.len();
         found_hooks.insert(name.to_string(), arity);
     }
-    
-    println!("\n🔍 Checking script: {}", path.file_name().unwrap_or_default().to_string_lossy());
+
+    let mut report = PluginCheckReport {
+        name: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+        ..Default::default()
+    };
 
     // 2. Check if the found functions match the expected contract
     for (expected_name, expected_arity) in EXPECTED_HOOKS.iter() {
         if let Some(actual_arity) = found_hooks.get(*expected_name) {
             if actual_arity == expected_arity {
-                println!("  ✅ Found hook: {} (Args: {})", expected_name, expected_arity);
+                report.hooks.push(format!("{} (Args: {})", expected_name, expected_arity));
             } else {
-                println!("  ❌ Arity Mismatch for {}: Expected {} arguments, found {}", 
-                    expected_name, expected_arity, actual_arity);
+                report.errors.push(format!("Arity Mismatch for {}: Expected {} arguments, found {}", 
+                    expected_name, expected_arity, actual_arity));
             }
         }
     }
@@ -46,45 +53,81 @@ fn check_single_plugin(path: &Path, engine: &rhai::Engine) -> Result<(), String>
     // 3. (Optional) Check for unused/unknown functions
     for (name, _) in found_hooks.iter() {
         if !EXPECTED_HOOKS.iter().any(|(n, _)| n == name) {
-            println!("  ⚠️ Warning: Function '{}' is defined but not a recognized hook.", name);
+            report.warnings.push(format!("Function '{}' is defined but not a recognized hook.", name));
         }
     }
 
-    Ok(())
+    Ok(report)
 }
 
-/// Main function to check all or a specific plugin script.
-pub fn check_plugin_functions(plugin_dir: &Path, target_name: Option<&str>) -> Result<(), String> {
-    
-    // Use the engine creator from your setup, ensure it's thread safe (sync feature)
-    let engine = crate::plugin::script_engine::create_engine(); 
+fn print_report(path: &Path, report: &PluginCheckReport) {
+    println!("\n🔍 Checking script: {}", path.file_name().unwrap_or_default().to_string_lossy());
+
+    for hook in &report.hooks {
+        println!("  ✅ Found hook: {}", hook);
+    }
+    for error in &report.errors {
+        println!("  ❌ {}", error);
+    }
+    for warning in &report.warnings {
+        println!("  ⚠️ Warning: {}", warning);
+    }
+}
 
+/// Check all or a specific plugin script and return the reports instead of printing them.
+pub fn check_plugin_reports(plugin_dir: &Path, target_name: Option<&str>) -> Result<Vec<PluginCheckReport>, String> {
+    let engine = crate::plugin::script_engine::create_engine();
+
+    let mut reports = Vec::new();
+    for path in plugin_scripts(plugin_dir, target_name)? {
+        reports.push(check_single_plugin(&path, &engine)?);
+    }
+
+    Ok(reports)
+}
+
+fn plugin_scripts(plugin_dir: &Path, target_name: Option<&str>) -> Result<Vec<PathBuf>, String> {
     if let Some(name) = target_name {
-        // --- jotx plugin --check init ---
         let path = plugin_dir.join(format!("{}.rhai", name));
-        if path.exists() {
-            check_single_plugin(&path, &engine)?;
-        } else {
-            println!("No plugin found with the name '{}'. Skipping check.", name);
+        if !path.exists() {
+            return Err(format!("No plugin found with the name '{}'.", name));
         }
-    } else {
-        // --- jotx plugin --check (all) ---
-        let mut checked_count = 0;
-        
-        if let Ok(entries) = fs::read_dir(plugin_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "rhai") {
-                    check_single_plugin(&path, &engine)?;
-                    checked_count += 1;
-                }
+        return Ok(vec![path]);
+    }
+
+    let mut scripts = Vec::new();
+    if let Ok(entries) = fs::read_dir(plugin_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "rhai") {
+                scripts.push(path);
             }
         }
+    }
+
+    Ok(scripts)
+}
 
-        if checked_count == 0 {
-            println!("No .rhai plugin scripts found in {}.", plugin_dir.display());
+/// Main function to check all or a specific plugin script.
+pub fn check_plugin_functions(plugin_dir: &Path, target_name: Option<&str>) -> Result<(), String> {
+    // Use the engine creator from your setup, ensure it's thread safe (sync feature)
+    let engine = crate::plugin::script_engine::create_engine();
+
+    // --- jotx plugin --check init, or --check for all of them
+    let scripts = match plugin_scripts(plugin_dir, target_name) {
+        Ok(scripts) => scripts,
+        Err(e) => {
+            println!("{} Skipping check.", e);
+            return Ok(());
         }
+    };
+
+    if scripts.is_empty() {
+        println!("No .rhai plugin scripts found in {}.", plugin_dir.display());
     }
-    
+    for path in scripts {
+        print_report(&path, &check_single_plugin(&path, &engine)?);
+    }
+
     Ok(())
 }
\ No newline at end of file
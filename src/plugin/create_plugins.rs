@@ -26,6 +26,14 @@ pub fn create_new_plugin_script(plugin_dir: &Path, name: &str) -> Result<String,
 // 
 // Rhai Syntax is very similar to Rust/JS.
 // Data Types: objects (maps), arrays, numbers, strings, bool.
+//
+// Settings: `jotx plugin --config {}` edits {}.toml next to this script;
+// every hook can read it as the map `this` (e.g. `this.min_length`).
+//
+// Helpers: text.regex_match(pattern), regex_find, regex_find_all, regex_replace;
+// http_get(url) and http_post(url, body) for hosts in plugins.http_allowlist;
+// kv_get/kv_set/kv_delete/kv_keys for data kept between runs;
+// log_debug/log_info/log_warn/log_error to write to the jotx log.
 // ------------------------------------------------------------------
 
 // Hook: Called when a shell command is captured
@@ -39,6 +47,28 @@ pub fn create_new_plugin_script(plugin_dir: &Path, name: &str) -> Result<String,
 //     return "continue";
 // }}
 
+// Hook: Called when a clipboard entry is captured
+// Context: ClipboardContext (read-only: content, app, window_title, timestamp)
+// Returns: "continue", "stop", or "skip"
+// fn on_clipboard_captured(ctx) {{
+//     if ctx.app == "Terminal" && ctx.content.contains("token") {{
+//         return "skip";
+//     }}
+//     return "continue";
+// }}
+
+// Hook: Called right before a captured entry is written
+// Arguments: EntryContext (content can be changed, add_tag adds a tag)
+// Returns: the entry to store it as changed, or "continue", "stop", or "skip"
+// fn on_entry_store(entry) {{
+//     if entry.content.contains("ghp_") {{
+//         entry.content = "*** redacted by plugin ***";
+//         entry.add_tag("redacted");
+//         return entry;
+//     }}
+//     return "continue";
+// }}
+
 // Hook: Called after search results are returned
 // Arguments: query (string), results (array of SearchResult)
 // Returns: The modified array of SearchResult objects
@@ -73,7 +103,7 @@ pub fn create_new_plugin_script(plugin_dir: &Path, name: &str) -> Result<String,
 
 // NOTE: Ensure your function names and arguments match the contract!
 "#,
-        name
+        name, name, name
     );
     // ---------------------------
 
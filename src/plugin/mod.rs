@@ -3,13 +3,18 @@ pub mod plugin_manager;
 pub mod sensitive_info_plugin;
 pub mod script_plugin;
 pub mod script_engine;
+pub mod script_stdlib;
 pub mod create_plugins;
 pub mod check_plugins;
+pub mod blocked_log;
+pub mod plugin_config;
 
 pub use plugin_manager::GLOBAL_PLUGIN_MANAGER;
-pub use base_plugin::{DaemonContext, CommandContext};
+pub use base_plugin::{ClipboardContext, DaemonContext, CommandContext, EntryContext};
 pub use sensitive_info_plugin::SensitiveCommandFilter;
 pub use base_plugin::Plugin;
 pub use base_plugin::LlmContext;
 pub use create_plugins::create_new_plugin_script;
-pub use check_plugins::check_plugin_functions;
\ No newline at end of file
+pub use blocked_log::{BlockedCaptureWatcher, audit_content_hash, read_blocked_log, record_blocked};
+pub use check_plugins::{check_plugin_functions, check_plugin_reports};
+pub use plugin_config::{edit_plugin_config, load_plugin_config};
\ No newline at end of file
@@ -0,0 +1,98 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::permissions::write_private;
+
+/// `<plugin dir>/<name>.toml`, the settings of plugin `name`
+pub fn plugin_config_path(plugin_dir: &Path, name: &str) -> PathBuf {
+    plugin_dir.join(format!("{}.toml", name))
+}
+
+/// Read the settings of plugin `name`; a plugin without a config file gets an empty table
+pub fn load_plugin_config(plugin_dir: &Path, name: &str) -> Result<toml::Table, String> {
+    let path = plugin_config_path(plugin_dir, name);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(toml::Table::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    toml::from_str(&content).map_err(|e| format!("{} is not valid TOML: {}", path.display(), e))
+}
+
+/// Open the config of plugin `name` in $VISUAL or $EDITOR (vi by default), creating it
+/// first if needed, and check it still parses afterwards. Returns the file's path.
+pub fn edit_plugin_config(plugin_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let exists = [format!("{}.rhai", name), name.to_string()]
+        .iter()
+        .any(|file| plugin_dir.join(file).is_file());
+    if !exists {
+        return Err(format!(
+            "No plugin named '{}' in {}",
+            name,
+            plugin_dir.display()
+        ));
+    }
+
+    let path = plugin_config_path(plugin_dir, name);
+    if !path.exists() {
+        write_private(&path, config_template(name))
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    }
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Editors are often given with flags, e.g. "code --wait"
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status));
+    }
+
+    load_plugin_config(plugin_dir, name)?;
+    Ok(path)
+}
+
+fn config_template(name: &str) -> String {
+    format!(
+        "# Settings for the {} plugin.
+# Rhai hooks read them from `this` (e.g. `this.min_length`), external plugins
+# from the \"config\" field of their JSON input.
+",
+        name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_plugin_config() {
+        let dir = std::env::temp_dir().join(format!("jotx-plugin-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(load_plugin_config(&dir, "missing").unwrap().is_empty());
+
+        std::fs::write(
+            dir.join("redact.toml"),
+            config_template("redact") + "words = [\"hunter2\"]\n",
+        )
+        .unwrap();
+        let config = load_plugin_config(&dir, "redact").unwrap();
+        assert_eq!(config["words"][0].as_str(), Some("hunter2"));
+
+        std::fs::write(dir.join("broken.toml"), "words = [").unwrap();
+        assert!(load_plugin_config(&dir, "broken").is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -4,15 +4,21 @@ use std::sync::Mutex;
 use once_cell::sync::Lazy;
 
 use crate::commands::get_plugin_dir;
-use crate::plugin::base_plugin::{ExternalPlugin, Plugin, CommandContext, DaemonContext, LlmContext};
-use crate::types::{SearchResult, PluginAction};
+use crate::config::GLOBAL_CONFIG;
+use crate::plugin::base_plugin::{ExternalPlugin, Plugin, ClipboardContext, CommandContext, DaemonContext, EntryContext, LlmContext};
+use crate::types::{BlockedCapture, SearchResult, PluginAction, PluginInfo};
 
+use super::blocked_log::{audit_content_hash, record_blocked};
+use super::plugin_config::load_plugin_config;
 use super::script_plugin::ScriptPlugin;
 
 
 pub struct PluginManager {
     plugins: Vec<Box<dyn Plugin>>,
     plugin_dir: PathBuf,
+    disabled: Vec<String>,
+    /// Names of the plugins registered from Rust, which a reload keeps
+    builtin: Vec<String>,
 }
 
 impl PluginManager {
@@ -21,9 +27,16 @@ impl PluginManager {
         
         fs::create_dir_all(&plugin_dir).ok();
         
+        let disabled = match GLOBAL_CONFIG.read() {
+            Ok(config) => config.plugins.disabled.clone(),
+            Err(_) => Vec::new(),
+        };
+
         let mut manager = Self {
             plugins: Vec::new(),
             plugin_dir,
+            disabled,
+            builtin: Vec::new(),
         };
         
         // Load all plugins from directory
@@ -32,19 +45,28 @@ impl PluginManager {
         manager
     }
     
-    /// Load all plugins from the plugins directory
+    /// Load all plugins from the plugins directory, skipping ones already loaded and
+    /// disabled ones
     fn load_plugins(&mut self) {
         if let Ok(entries) = fs::read_dir(&self.plugin_dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 
-                // Skip non-executable files
-                if !path.is_file() {
+                // Skip non-executable files and plugin configs (<name>.toml, plugin.toml)
+                if !path.is_file() || path.extension().is_some_and(|e| e == "toml") {
                     continue;
                 }
 
                 if path.extension().map_or(false, |e| e == "rhai") {
-                    match ScriptPlugin::new(path.clone()) {
+                    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                    if self.is_loaded(&stem) || !self.is_enabled(&stem) {
+                        continue;
+                    }
+
+                    let loaded = ScriptPlugin::new(path.clone())
+                        .map_err(|e| e.to_string())
+                        .and_then(|plugin| plugin.with_config(&load_plugin_config(&self.plugin_dir, &stem)?));
+                    match loaded {
                         Ok(plugin) => {
                             println!("🔌 Loaded script: {}", Plugin::name(&plugin));
                             self.plugins.push(Box::new(plugin));
@@ -59,24 +81,191 @@ impl PluginManager {
                     .unwrap()
                     .to_string_lossy()
                     .to_string();
+
+                if self.is_loaded(&name) || !self.is_enabled(&name) {
+                    continue;
+                }
                 
-                let plugin = ExternalPlugin::new(name, path);
-                self.plugins.push(Box::new(plugin));
+                let config = load_plugin_config(&self.plugin_dir, &name);
+                match config.and_then(|config| ExternalPlugin::new(name, path.clone()).with_config(&config)) {
+                    Ok(plugin) => self.plugins.push(Box::new(plugin)),
+                    Err(e) => eprintln!("❌ Error loading plugin {:?}: {}", path, e),
+                }
             }
         }
     }
     
+    /// Pick up plugins added to the plugins directory since startup
+    pub fn rescan(&mut self) {
+        self.load_plugins();
+    }
+
+    /// Re-read which plugins are disabled and load the plugins directory from scratch,
+    /// so edited scripts are recompiled and deleted ones dropped
+    pub fn reload(&mut self) {
+        self.disabled = match GLOBAL_CONFIG.read() {
+            Ok(config) => config.plugins.disabled.clone(),
+            Err(_) => Vec::new(),
+        };
+
+        let builtin = &self.builtin;
+        self.plugins.retain(|p| builtin.iter().any(|b| b == p.name()));
+        self.load_plugins();
+    }
+
+    fn is_loaded(&self, name: &str) -> bool {
+        self.plugins.iter().any(|p| p.name() == name)
+    }
+
+    fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.iter().any(|d| d == name)
+    }
+
+    /// Plugins that hooks should run on
+    fn active_plugins(&self) -> impl Iterator<Item = &Box<dyn Plugin>> {
+        self.plugins.iter().filter(|p| self.is_enabled(p.name()))
+    }
+
+    /// Kind of the file plugin `name` is loaded from, if it is in the plugins directory
+    fn file_kind(&self, name: &str) -> Option<&'static str> {
+        if self.plugin_dir.join(format!("{}.rhai", name)).is_file() {
+            Some("rhai")
+        } else if !name.ends_with(".toml") && self.plugin_dir.join(name).is_file() {
+            Some("external")
+        } else {
+            None
+        }
+    }
+
+    /// Enable or disable a plugin and persist the choice to config. A disabled plugin
+    /// from the plugins directory is unloaded; built-in ones stay loaded but skipped.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        if !self.is_loaded(name) && self.file_kind(name).is_none() {
+            return Err(format!("No plugin named '{}'", name));
+        }
+
+        self.disabled.retain(|d| d != name);
+        if !enabled {
+            self.disabled.push(name.to_string());
+        }
+
+        {
+            let mut config = GLOBAL_CONFIG
+                .write()
+                .map_err(|e| format!("Failed to access config: {}", e))?;
+            config.plugins.disabled = self.disabled.clone();
+            config
+                .save()
+                .map_err(|e| format!("Failed to save config: {}", e))?;
+        }
+
+        if enabled {
+            self.load_plugins();
+        } else if !self.builtin.iter().any(|b| b == name) {
+            self.plugins.retain(|p| p.name() != name);
+        }
+        Ok(())
+    }
+
     /// Register a Rust-native plugin
     pub fn register(&mut self, plugin: Box<dyn Plugin>) {
+        self.builtin.push(plugin.name().to_string());
         self.plugins.push(plugin);
     }
     
-    /// Trigger hook on all plugins
-    pub fn trigger_command_captured(&self, context: &CommandContext) -> bool {
-        for plugin in &self.plugins {
+    /// Trigger hook on all plugins. Blocked captures are recorded in the blocked log and
+    /// plugins returning `ModifyData` may rewrite `context.command` (e.g. redaction).
+    pub fn trigger_command_captured(&self, context: &mut CommandContext) -> bool {
+        for plugin in self.active_plugins() {
             match plugin.on_command_captured(context) {
-                Ok(PluginAction::Stop) => return false,
-                Ok(PluginAction::Skip) => return false,
+                Ok(PluginAction::ModifyData) => {
+                    if let Some(command) = plugin.modified_command(context) {
+                        if command != context.command {
+                            record_blocked(&BlockedCapture {
+                                timestamp: context.timestamp,
+                                source: "shell".to_string(),
+                                plugin: plugin.name().to_string(),
+                                rule: plugin.modify_reason(context),
+                                content_hash: audit_content_hash(&context.command),
+                                redacted: true,
+                            });
+                        }
+                        context.command = command;
+                    }
+                }
+                Ok(PluginAction::Stop) | Ok(PluginAction::Skip) => {
+                    record_blocked(&BlockedCapture {
+                        timestamp: context.timestamp,
+                        source: "shell".to_string(),
+                        plugin: plugin.name().to_string(),
+                        rule: plugin.skip_reason(context),
+                        content_hash: audit_content_hash(&context.command),
+                        redacted: false,
+                    });
+                    return false;
+                }
+                Err(e) => eprintln!("Plugin {} error: {}", plugin.name(), e),
+                _ => {}
+            }
+        }
+        true
+    }
+    
+    /// Trigger the clipboard hook on all plugins, like `trigger_command_captured`:
+    /// `false` means the clip was blocked (and logged), and plugins returning
+    /// `ModifyData` may rewrite `context.content`.
+    pub fn trigger_clipboard_captured(&self, context: &mut ClipboardContext) -> bool {
+        for plugin in self.active_plugins() {
+            match plugin.on_clipboard_captured(context) {
+                Ok(PluginAction::ModifyData) => {
+                    if let Some(content) = plugin.modified_clipboard(context) {
+                        if content != context.content {
+                            record_blocked(&BlockedCapture {
+                                timestamp: context.timestamp,
+                                source: "clipboard".to_string(),
+                                plugin: plugin.name().to_string(),
+                                rule: None,
+                                content_hash: audit_content_hash(&context.content),
+                                redacted: true,
+                            });
+                        }
+                        context.content = content;
+                    }
+                }
+                Ok(PluginAction::Stop) | Ok(PluginAction::Skip) => {
+                    record_blocked(&BlockedCapture {
+                        timestamp: context.timestamp,
+                        source: "clipboard".to_string(),
+                        plugin: plugin.name().to_string(),
+                        rule: plugin.clipboard_skip_reason(context),
+                        content_hash: audit_content_hash(&context.content),
+                        redacted: false,
+                    });
+                    return false;
+                }
+                Err(e) => eprintln!("Plugin {} error: {}", plugin.name(), e),
+                _ => {}
+            }
+        }
+        true
+    }
+    
+    /// Trigger the store hook on all plugins, each seeing the entry as the previous
+    /// one left it. `false` means a plugin dropped it (and it was logged as blocked).
+    pub fn trigger_entry_store(&self, entry: &mut EntryContext) -> bool {
+        for plugin in self.active_plugins() {
+            match plugin.on_entry_store(entry) {
+                Ok(PluginAction::Stop) | Ok(PluginAction::Skip) => {
+                    record_blocked(&BlockedCapture {
+                        timestamp: entry.timestamp,
+                        source: entry.entry_type.clone(),
+                        plugin: plugin.name().to_string(),
+                        rule: None,
+                        content_hash: audit_content_hash(&entry.content),
+                        redacted: false,
+                    });
+                    return false;
+                }
                 Err(e) => eprintln!("Plugin {} error: {}", plugin.name(), e),
                 _ => {}
             }
@@ -98,7 +287,7 @@ impl PluginManager {
     ///
     /// 
     pub fn trigger_search_after(&self, query: &str, results: &mut Vec<SearchResult>) {
-        for plugin in &self.plugins {
+        for plugin in self.active_plugins() {
             if let Err(e) = plugin.on_search_after(query, results) {
                 eprintln!("Plugin {} error: {}", plugin.name(), e);
             }
@@ -106,7 +295,7 @@ impl PluginManager {
     }
     
     pub fn trigger_llm_before(&self, prompt: &str, context: &LlmContext) -> bool {
-        for plugin in &self.plugins {
+        for plugin in self.active_plugins() {
             match plugin.on_llm_before(prompt, context) {
                 Ok(PluginAction::Stop) => return false,
                 Ok(PluginAction::Skip) => return false,
@@ -118,7 +307,7 @@ impl PluginManager {
     }
     
     pub fn trigger_daemon_tick(&self, context: &DaemonContext) {
-        for plugin in &self.plugins {
+        for plugin in self.active_plugins() {
             if let Err(e) = plugin.on_daemon_tick(context) {
                 eprintln!("Plugin {} error: {}", plugin.name(), e);
             }
@@ -129,6 +318,35 @@ impl PluginManager {
     pub fn list(&self) -> Vec<String> {
         self.plugins.iter().map(|p| p.name().to_string()).collect()
     }
+
+    /// Metadata and enabled state for all loaded plugins, plus the disabled ones in the
+    /// plugins directory (which aren't loaded, so their version is unknown)
+    pub fn info(&self) -> Vec<PluginInfo> {
+        let mut info: Vec<PluginInfo> = self
+            .plugins
+            .iter()
+            .map(|p| PluginInfo {
+                name: p.name().to_string(),
+                version: p.version().to_string(),
+                description: p.description().to_string(),
+                kind: p.kind().to_string(),
+                enabled: self.is_enabled(p.name()),
+            })
+            .collect();
+
+        for name in self.disabled.iter().filter(|name| !self.is_loaded(name)) {
+            if let Some(kind) = self.file_kind(name) {
+                info.push(PluginInfo {
+                    name: name.clone(),
+                    version: String::new(),
+                    description: String::new(),
+                    kind: kind.to_string(),
+                    enabled: false,
+                });
+            }
+        }
+        info
+    }
 }
 
 // Global plugin manager singleton
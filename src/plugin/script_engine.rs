@@ -1,8 +1,7 @@
 use rhai::{Engine, Dynamic};
 use crate::types::{SearchResult, PluginAction};
-use crate::plugin::base_plugin::{CommandContext, LlmContext, DaemonContext, };
-use std::sync::Arc;
-use once_cell::sync::Lazy;
+use crate::plugin::base_plugin::{ClipboardContext, CommandContext, EntryContext, LlmContext, DaemonContext, };
+use super::script_stdlib::{register_plugin_api, register_regex};
 
 pub fn create_engine() -> Engine {
     let mut engine = Engine::new();
@@ -16,6 +15,23 @@ pub fn create_engine() -> Engine {
         .register_get("user", |c: &mut CommandContext| c.user.clone())
         .register_get("working_dir", |c: &mut CommandContext| c.working_dir.clone());
 
+    engine.register_type_with_name::<ClipboardContext>("ClipboardContext")
+        .register_get("content", |c: &mut ClipboardContext| c.content.clone())
+        .register_get("app", |c: &mut ClipboardContext| c.app.clone())
+        .register_get("window_title", |c: &mut ClipboardContext| c.window_title.clone())
+        .register_get("timestamp", |c: &mut ClipboardContext| c.timestamp);
+
+    // Entries about to be stored: content is writable and tags can be added
+    engine.register_type_with_name::<EntryContext>("EntryContext")
+        .register_get("entry_type", |e: &mut EntryContext| e.entry_type.clone())
+        .register_get("content", |e: &mut EntryContext| e.content.clone())
+        .register_set("content", |e: &mut EntryContext, v: String| e.content = v)
+        .register_get("working_dir", |e: &mut EntryContext| e.working_dir.clone().unwrap_or_default())
+        .register_get("app_name", |e: &mut EntryContext| e.app_name.clone())
+        .register_get("window_title", |e: &mut EntryContext| e.window_title.clone())
+        .register_get("timestamp", |e: &mut EntryContext| e.timestamp)
+        .register_fn("add_tag", |e: &mut EntryContext, tag: String| e.tags.push(tag));
+
     // 3. Register SearchResult (Needs Getters AND Setters for mutation)
     engine.register_type_with_name::<SearchResult>("SearchResult")
         .register_get("content", |s: &mut SearchResult| s.content.clone())
@@ -31,6 +47,8 @@ pub fn create_engine() -> Engine {
     engine.register_type_with_name::<DaemonContext>("DaemonContext")
         .register_get("iteration", |c: &mut DaemonContext| c.iteration)
         .register_get("uptime_secs", |c: &mut DaemonContext| c.uptime_secs);
+
+    register_regex(&mut engine);
         
     // ... Register other types (LlmContext, etc) similarly ...
 
@@ -46,7 +64,10 @@ pub fn parse_plugin_action(result: Dynamic) -> PluginAction {
     }
 }
 
-// Create one shared engine for the whole app
-pub static SHARED_RHAI_ENGINE: Lazy<Arc<Engine>> = Lazy::new(|| {
-    Arc::new(create_engine())
-});
\ No newline at end of file
+/// The engine a script plugin runs in: the shared types plus HTTP, storage and
+/// logging functions bound to the plugin `name`
+pub fn create_plugin_engine(name: &str) -> Engine {
+    let mut engine = create_engine();
+    register_plugin_api(&mut engine, name);
+    engine
+}
\ No newline at end of file
@@ -1,49 +1,65 @@
 use crate::types::{PluginAction, SearchResult};
-use rhai::{AST, Dynamic, Engine, Scope};
+use rhai::{AST, CallFnOptions, Dynamic, Engine, EvalAltResult, FuncArgs, Scope};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use super::base_plugin::{CommandContext, DaemonContext, LlmContext, Plugin};
-use super::script_engine::parse_plugin_action;
+use super::base_plugin::{ClipboardContext, CommandContext, DaemonContext, EntryContext, LlmContext, Plugin};
+use super::script_engine::{create_plugin_engine, parse_plugin_action};
 
 pub struct ScriptPlugin {
     plugin_name: String,
-    engine: Arc<Engine>, // Store a reference to the shared engine
+    engine: Arc<Engine>,
     ast: AST,
+    /// The plugin's `<name>.toml`, bound as `this` in every hook
+    config: Dynamic,
 }
 
 impl ScriptPlugin {
     pub fn new(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
-        let engine = crate::plugin::script_engine::SHARED_RHAI_ENGINE.clone();
-
-        // Compile using shared engine
-        let script = std::fs::read_to_string(&path)?;
-        let ast = engine.compile(&script)?;
-
         let name = path
             .file_stem()
             .unwrap_or_default()
             .to_string_lossy()
             .to_string();
 
+        // Each script gets its own engine, so its storage and log lines are its own
+        let engine = Arc::new(create_plugin_engine(&name));
+        let script = std::fs::read_to_string(&path)?;
+        let ast = engine.compile(&script)?;
+
         Ok(Self {
             plugin_name: name,
             engine,
             ast,
+            config: Dynamic::from_map(rhai::Map::new()),
         })
     }
 
+    /// Give the hooks the plugin's settings, as a map they read through `this`
+    pub fn with_config(mut self, config: &toml::Table) -> Result<Self, String> {
+        self.config = rhai::serde::to_dynamic(config)
+            .map_err(|e| format!("Invalid config for {}: {}", self.plugin_name, e))?;
+        Ok(self)
+    }
+
+    /// Call hook `fn_name` with the config bound as `this`
+    fn call(&self, fn_name: &str, args: impl FuncArgs) -> Result<Dynamic, Box<EvalAltResult>> {
+        let mut scope = Scope::new();
+        let mut this = self.config.clone();
+        let options = CallFnOptions::new().bind_this_ptr(&mut this);
+
+        self.engine
+            .call_fn_with_options(options, &mut scope, &self.ast, fn_name, args)
+    }
+
     // Helper to call a script function safely
     fn call_script_fn(
         &self,
         fn_name: &str,
         args: impl rhai::FuncArgs, // This handles the tuple args
     ) -> PluginAction {
-        // Create a scope (holds variables for the script execution)
-        let mut scope = Scope::new();
-
         // Call the function
-        let result: Result<Dynamic, _> = self.engine.call_fn(&mut scope, &self.ast, fn_name, args);
+        let result = self.call(fn_name, args);
 
         match result {
             Ok(val) => parse_plugin_action(val),
@@ -66,26 +82,43 @@ impl Plugin for ScriptPlugin {
     fn description(&self) -> &str {
         "User script"
     }
+    fn kind(&self) -> &str {
+        "rhai"
+    }
 
     fn on_command_captured(&self, context: &CommandContext) -> Result<PluginAction, String> {
         // Pass context as a clone (it's read-only effectively)
         Ok(self.call_script_fn("on_command_captured", (context.clone() as CommandContext,)))
     }
 
+    fn on_clipboard_captured(&self, context: &ClipboardContext) -> Result<PluginAction, String> {
+        Ok(self.call_script_fn("on_clipboard_captured", (context.clone() as ClipboardContext,)))
+    }
+
+    fn on_entry_store(&self, entry: &mut EntryContext) -> Result<PluginAction, String> {
+        // The script gets a copy; returning it (changed) is how changes come back
+        let result = self.call("on_entry_store", (entry.clone(),));
+
+        match result {
+            Ok(value) if value.is::<EntryContext>() => {
+                *entry = value.cast::<EntryContext>();
+                Ok(PluginAction::ModifyData)
+            }
+            Ok(value) => Ok(parse_plugin_action(value)),
+            Err(_) => Ok(PluginAction::Continue),
+        }
+    }
+
     fn on_search_after(
         &self,
         query: &str,
         results: &mut Vec<SearchResult>,
     ) -> Result<PluginAction, String> {
-        let mut scope = Scope::new();
-
         // CRITICAL: In Rhai, we pass the vector directly.
         // Because we registered SearchResult type, the script can iterate and modify.
         // We use `call_fn` with the vector.
 
-        let result: Result<Dynamic, _> = self.engine.call_fn(
-            &mut scope,
-            &self.ast,
+        let result = self.call(
             "on_search_after",
             (query.to_string(), results.clone()), // Pass a clone to script
         );
@@ -124,3 +157,50 @@ impl Plugin for ScriptPlugin {
         Ok(PluginAction::Continue)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_store_script_rewrites_entry() {
+        let path = std::env::temp_dir().join(format!("jotx-store-{}.rhai", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"fn on_entry_store(entry) {
+                if entry.content.contains("ghp_") {
+                    entry.content = "***";
+                    entry.add_tag("redacted");
+                    return entry;
+                }
+                if entry.app_name == "KeePassXC" { return "skip"; }
+                return "continue";
+            }"#,
+        )
+        .unwrap();
+        let plugin = ScriptPlugin::new(path.clone()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let mut entry = EntryContext {
+            entry_type: "shell".to_string(),
+            content: "export TOKEN=ghp_abc".to_string(),
+            timestamp: 1,
+            working_dir: None,
+            app_name: "Terminal".to_string(),
+            window_title: String::new(),
+            tags: Vec::new(),
+        };
+        assert!(matches!(
+            plugin.on_entry_store(&mut entry),
+            Ok(PluginAction::ModifyData)
+        ));
+        assert_eq!(entry.content, "***");
+        assert_eq!(entry.tags, ["redacted"]);
+
+        entry.app_name = "KeePassXC".to_string();
+        assert!(matches!(
+            plugin.on_entry_store(&mut entry),
+            Ok(PluginAction::Skip)
+        ));
+    }
+}
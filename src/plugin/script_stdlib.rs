@@ -0,0 +1,347 @@
+//! Functions Rhai plugins can call besides the hooks' context types:
+//!
+//! - regex: `text.regex_match(pattern)`, `regex_find`, `regex_find_all`, `regex_replace`
+//! - HTTP: `http_get(url)` and `http_post(url, body)` return `#{status, body}`. Only
+//!   hosts in `plugins.http_allowlist` can be reached, redirects are not followed, and
+//!   the hook waits for the response, so keep them to `on_daemon_tick` where possible.
+//! - storage: `kv_get`, `kv_set`, `kv_delete` and `kv_keys`, kept per plugin in
+//!   `~/.jotx/plugin_data/<name>/kv.json`
+//! - logging: `log_debug`, `log_info`, `log_warn` and `log_error` write to the jotx log
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Method, Url};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::config::GLOBAL_CONFIG;
+use crate::managers::lock_manager::lock_or_recover;
+use crate::permissions::{create_private_dir, write_private};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longer responses are cut off
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+/// Compiled patterns kept for reuse; the cache starts over once it is full
+const REGEX_CACHE_SIZE: usize = 64;
+
+static REGEX_CACHE: Lazy<Mutex<HashMap<String, Regex>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+type RhaiResult<T> = Result<T, Box<EvalAltResult>>;
+
+pub fn plugin_data_dir(name: &str) -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home)
+        .join(".jotx")
+        .join("plugin_data")
+        .join(name)
+}
+
+/// Regex helpers, which don't depend on the plugin calling them
+pub fn register_regex(engine: &mut Engine) {
+    engine
+        .register_fn(
+            "regex_match",
+            |text: &str, pattern: &str| -> RhaiResult<bool> {
+                Ok(compiled(pattern)?.is_match(text))
+            },
+        )
+        .register_fn(
+            "regex_find",
+            |text: &str, pattern: &str| -> RhaiResult<Dynamic> {
+                Ok(compiled(pattern)?
+                    .find(text)
+                    .map(|m| Dynamic::from(m.as_str().to_string()))
+                    .unwrap_or(Dynamic::UNIT))
+            },
+        )
+        .register_fn(
+            "regex_find_all",
+            |text: &str, pattern: &str| -> RhaiResult<Array> {
+                Ok(compiled(pattern)?
+                    .find_iter(text)
+                    .map(|m| Dynamic::from(m.as_str().to_string()))
+                    .collect())
+            },
+        )
+        .register_fn(
+            "regex_replace",
+            |text: &str, pattern: &str, replacement: &str| -> RhaiResult<String> {
+                Ok(compiled(pattern)?
+                    .replace_all(text, replacement)
+                    .into_owned())
+            },
+        );
+}
+
+fn compiled(pattern: &str) -> RhaiResult<Regex> {
+    let mut cache = lock_or_recover("plugin regex cache", &REGEX_CACHE);
+    if let Some(regex) = cache.get(pattern) {
+        return Ok(regex.clone());
+    }
+
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid regex {}: {}", pattern, e))?;
+    if cache.len() >= REGEX_CACHE_SIZE {
+        cache.clear();
+    }
+    cache.insert(pattern.to_string(), regex.clone());
+    Ok(regex)
+}
+
+/// HTTP, storage and logging for plugin `name`
+pub fn register_plugin_api(engine: &mut Engine, name: &str) {
+    engine
+        .register_fn("http_get", |url: &str| -> RhaiResult<Map> {
+            Ok(http_request(Method::GET, url, None)?)
+        })
+        .register_fn("http_post", |url: &str, body: Dynamic| -> RhaiResult<Map> {
+            Ok(http_request(Method::POST, url, Some(body))?)
+        });
+
+    let store = Arc::new(KvStore::open(&plugin_data_dir(name)));
+    let get = store.clone();
+    let set = store.clone();
+    let delete = store.clone();
+    engine
+        .register_fn("kv_get", move |key: &str| -> RhaiResult<Dynamic> {
+            match get.get(key) {
+                Some(value) => rhai::serde::to_dynamic(value),
+                None => Ok(Dynamic::UNIT),
+            }
+        })
+        .register_fn(
+            "kv_set",
+            move |key: &str, value: Dynamic| -> RhaiResult<()> {
+                let value: Value = rhai::serde::from_dynamic(&value)?;
+                Ok(set.set(key, value)?)
+            },
+        )
+        .register_fn("kv_delete", move |key: &str| -> RhaiResult<bool> {
+            Ok(delete.delete(key)?)
+        })
+        .register_fn("kv_keys", move || -> Array {
+            store.keys().into_iter().map(Dynamic::from).collect()
+        });
+
+    let plugin = name.to_string();
+    let (debug_name, info_name, warn_name) = (plugin.clone(), plugin.clone(), plugin.clone());
+    engine
+        .register_fn("log_debug", move |message: &str| {
+            debug!("[plugin {}] {}", debug_name, message)
+        })
+        .register_fn("log_info", move |message: &str| {
+            info!("[plugin {}] {}", info_name, message)
+        })
+        .register_fn("log_warn", move |message: &str| {
+            warn!("[plugin {}] {}", warn_name, message)
+        })
+        .register_fn("log_error", move |message: &str| {
+            error!("[plugin {}] {}", plugin, message)
+        });
+}
+
+/// Whether `plugins.http_allowlist` lets plugins reach `host`: an entry allows that
+/// host and its subdomains
+fn host_allowed(host: &str, allowlist: &[String]) -> bool {
+    let host = host.to_lowercase();
+    allowlist.iter().any(|allowed| {
+        let allowed = allowed.trim().trim_end_matches('.').to_lowercase();
+        !allowed.is_empty() && (host == allowed || host.ends_with(&format!(".{}", allowed)))
+    })
+}
+
+/// Send a request and wait for it. Strings are sent as text, anything else as JSON.
+fn http_request(method: Method, url: &str, body: Option<Dynamic>) -> Result<Map, String> {
+    let url = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Only http and https URLs are allowed, not {}", url));
+    }
+    let allowlist = GLOBAL_CONFIG
+        .read()
+        .map(|config| config.plugins.http_allowlist.clone())
+        .unwrap_or_default();
+    let host = url.host_str().unwrap_or_default();
+    if !host_allowed(host, &allowlist) {
+        return Err(format!(
+            "{} is not in plugins.http_allowlist in config.toml",
+            host
+        ));
+    }
+
+    let body = match body {
+        None => None,
+        Some(body) if body.is_string() => Some((body.to_string(), "text/plain")),
+        Some(body) => {
+            let json: Value = rhai::serde::from_dynamic(&body).map_err(|e| e.to_string())?;
+            Some((json.to_string(), "application/json"))
+        }
+    };
+
+    // Hooks run on threads that may or may not be inside the daemon's runtime, so the
+    // request gets a small runtime of its own on a separate thread
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+        runtime.block_on(send(method, url, body))
+    })
+    .join()
+    .map_err(|_| "The HTTP request panicked".to_string())?
+}
+
+async fn send(
+    method: Method,
+    url: Url,
+    body: Option<(String, &'static str)>,
+) -> Result<Map, String> {
+    let client = Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .redirect(Policy::none())
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut request = client.request(method, url);
+    if let Some((body, content_type)) = body {
+        request = request.header("Content-Type", content_type).body(body);
+    }
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status().as_u16();
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    let text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_RESPONSE_BYTES)]);
+
+    let mut result = Map::new();
+    result.insert("status".into(), Dynamic::from(status as i64));
+    result.insert("body".into(), Dynamic::from(text.into_owned()));
+    Ok(result)
+}
+
+/// A plugin's key-value store: a JSON object rewritten on every change
+struct KvStore {
+    path: PathBuf,
+    values: Mutex<serde_json::Map<String, Value>>,
+}
+
+impl KvStore {
+    /// Open the store in `dir`; a missing or unreadable file starts empty
+    fn open(dir: &Path) -> Self {
+        let path = dir.join("kv.json");
+        let values = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            values: Mutex::new(values),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        lock_or_recover("plugin store", &self.values)
+            .get(key)
+            .cloned()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        lock_or_recover("plugin store", &self.values)
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<(), String> {
+        let mut values = lock_or_recover("plugin store", &self.values);
+        values.insert(key.to_string(), value);
+        self.save(&values)
+            .map_err(|e| format!("Failed to save {}: {}", self.path.display(), e))
+    }
+
+    fn delete(&self, key: &str) -> Result<bool, String> {
+        let mut values = lock_or_recover("plugin store", &self.values);
+        if values.remove(key).is_none() {
+            return Ok(false);
+        }
+        self.save(&values)
+            .map_err(|e| format!("Failed to save {}: {}", self.path.display(), e))?;
+        Ok(true)
+    }
+
+    fn save(&self, values: &serde_json::Map<String, Value>) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            create_private_dir(dir)?;
+        }
+        write_private(&self.path, serde_json::to_vec_pretty(values)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_functions() {
+        let mut engine = Engine::new();
+        register_regex(&mut engine);
+
+        assert!(
+            engine
+                .eval::<bool>(r#""git push".regex_match("^git ")"#)
+                .unwrap()
+        );
+        assert_eq!(
+            engine
+                .eval::<String>(r##""token=abc123 id=7".regex_replace("\\d+", "#")"##)
+                .unwrap(),
+            "token=abc# id=#"
+        );
+        assert_eq!(
+            engine
+                .eval::<Array>(r#""a1 b22".regex_find_all("\\d+")"#)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(
+            engine
+                .eval::<Dynamic>(r#""abc".regex_find("\\d")"#)
+                .unwrap()
+                .is_unit()
+        );
+        assert!(engine.eval::<bool>(r#""abc".regex_match("(")"#).is_err());
+    }
+
+    #[test]
+    fn test_kv_store_persists() {
+        let dir = std::env::temp_dir().join(format!("jotx-plugin-kv-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+
+        let store = KvStore::open(&dir);
+        store.set("count", Value::from(3)).unwrap();
+        store.set("seen", Value::from(vec!["a", "b"])).unwrap();
+        assert!(store.delete("seen").unwrap());
+        assert!(!store.delete("seen").unwrap());
+
+        let reopened = KvStore::open(&dir);
+        assert_eq!(reopened.get("count"), Some(Value::from(3)));
+        assert_eq!(reopened.keys(), ["count"]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_host_allowed() {
+        let allowlist = vec!["hooks.slack.com".to_string(), "Example.org.".to_string()];
+        assert!(host_allowed("hooks.slack.com", &allowlist));
+        assert!(host_allowed("api.example.org", &allowlist));
+        assert!(!host_allowed("slack.com", &allowlist));
+        assert!(!host_allowed("evilexample.org", &allowlist));
+        assert!(!host_allowed("localhost", &[]));
+    }
+}
@@ -1,62 +1,109 @@
-use crate::config::GLOBAL_CONFIG;
+use crate::config::{GLOBAL_CONFIG, PrivacyConfig, PrivacyMode};
+use crate::normalize::{contains_folded, fold};
 use crate::plugin::{CommandContext, Plugin};
+use crate::privacy::{find_secret, find_secrets, hashed_placeholder, redact_ranges, words_containing};
+use crate::project_config::ProjectConfig;
 use crate::types::PluginAction;
 use regex::Regex;
 
 #[allow(dead_code)]
 pub struct SensitiveCommandFilter;
 
-impl Plugin for SensitiveCommandFilter {
-    fn name(&self) -> &str {
-        "sensitive-filter"
+impl SensitiveCommandFilter {
+    /// The privacy rule that excludes this command, if any
+    pub fn matching_rule(&self, context: &CommandContext) -> Option<String> {
+        let privacy = Self::privacy(context)?;
+
+        Self::content_rule(&privacy, context).or_else(|| Self::command_rule(&privacy, context))
     }
 
-    fn version(&self) -> &str {
-        "1.0.0"
+    /// The command with secrets and `excludes_contains_string` matches redacted,
+    /// or `None` if there was nothing to redact
+    pub fn redacted_command(&self, context: &CommandContext) -> Option<String> {
+        let privacy = Self::privacy(context)?;
+
+        let mut ranges: Vec<_> = find_secrets(&context.command)
+            .into_iter()
+            .map(|m| m.range)
+            .collect();
+        for pattern in privacy.excludes_contains_string.iter() {
+            ranges.extend(words_containing(&context.command, pattern));
+        }
+
+        if ranges.is_empty() {
+            return None;
+        }
+        Some(redact_ranges(&context.command, ranges))
     }
 
-    fn description(&self) -> &str {
-        "Blocks capturing of commands with sensitive data"
+    fn hash_only(privacy: &PrivacyConfig, context: &CommandContext) -> bool {
+        privacy.hash_excluded_folders && Self::folder_rule(privacy, context).is_some()
     }
 
-    fn on_command_captured(&self, context: &CommandContext) -> Result<PluginAction, String> {
-        let privacy = {
-            if let Ok(config) = GLOBAL_CONFIG.read() {
-                config.privacy.clone()
-            } else {
-                return Ok(PluginAction::Continue);
-            }
-        };
+    /// `privacy` from config.toml, plus the patterns of the `.jotx.toml` of the project
+    /// the command ran in
+    fn privacy(context: &CommandContext) -> Option<PrivacyConfig> {
+        let global = GLOBAL_CONFIG.read().ok()?.privacy.clone();
+        match ProjectConfig::for_dir(&context.working_dir) {
+            Some(project) => Some(project.merge_privacy(&global)),
+            None => Some(global),
+        }
+    }
+
+    /// Rules that match part of the command text (and can be redacted)
+    fn content_rule(privacy: &PrivacyConfig, context: &CommandContext) -> Option<String> {
+        if let Some(id) = find_secret(&context.command) {
+            return Some(format!("secret pattern \"{}\"", id));
+        }
 
         for pattern in privacy.excludes_contains_string.iter() {
-            if context.command.to_lowercase().contains(pattern) {
-                return Ok(PluginAction::Skip);
+            if contains_folded(&context.command, pattern) {
+                return Some(format!("contains \"{}\"", pattern));
             }
         }
 
+        None
+    }
+
+    /// Rules that exclude the command as a whole
+    fn command_rule(privacy: &PrivacyConfig, context: &CommandContext) -> Option<String> {
+        Self::folder_rule(privacy, context).or_else(|| Self::pattern_rule(privacy, context))
+    }
+
+    /// `exclude_folders` match for the command's working directory
+    fn folder_rule(privacy: &PrivacyConfig, context: &CommandContext) -> Option<String> {
         // Handle exclude_folders - check if working_dir starts with or is within excluded folders
         for pattern in privacy.exclude_folders.iter() {
-            let working_dir_lower = context.working_dir.to_lowercase();
-            let pattern_lower = pattern.to_lowercase();
+            let working_dir_lower = fold(&context.working_dir);
+            let pattern_lower = fold(pattern);
 
             // Exact match
             if working_dir_lower == pattern_lower {
-                return Ok(PluginAction::Skip);
+                return Some(format!("excluded folder \"{}\"", pattern));
             }
 
             // Check if working_dir is inside the excluded folder
             // e.g., excluded: "/home/user/private" should match "/home/user/private/subfolder"
             let normalized_pattern = pattern_lower.trim_end_matches('/');
             if working_dir_lower.starts_with(&format!("{}/", normalized_pattern)) {
-                return Ok(PluginAction::Skip);
+                return Some(format!("excluded folder \"{}\"", pattern));
             }
         }
 
+        None
+    }
+
+    /// Host, regex and starts/ends-with rules
+    fn pattern_rule(privacy: &PrivacyConfig, context: &CommandContext) -> Option<String> {
+        if let Some(rule) = privacy.excluded_host(&context.host, &context.user) {
+            return Some(format!("excluded host \"{}\"", rule));
+        }
+
         for pattern in privacy.excludes_regex.iter() {
             match Regex::new(pattern) {
                 Ok(re) => {
                     if re.is_match(&context.working_dir) {
-                        return Ok(PluginAction::Skip);
+                        return Some(format!("regex \"{}\"", pattern));
                     }
                 }
                 Err(e) => {
@@ -67,17 +114,90 @@ impl Plugin for SensitiveCommandFilter {
         }
 
         for pattern in privacy.excludes_starts_with_string.iter() {
-            if context.command.to_lowercase().starts_with(pattern) {
-                return Ok(PluginAction::Skip);
+            if fold(&context.command).starts_with(&fold(pattern)) {
+                return Some(format!("starts with \"{}\"", pattern));
             }
         }
 
         for pattern in privacy.excludes_ends_with_string.iter() {
-            if context.command.to_lowercase().ends_with(pattern) {
+            if fold(&context.command).ends_with(&fold(pattern)) {
+                return Some(format!("ends with \"{}\"", pattern));
+            }
+        }
+
+        None
+    }
+}
+
+impl Plugin for SensitiveCommandFilter {
+    fn name(&self) -> &str {
+        "sensitive-filter"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn description(&self) -> &str {
+        "Blocks capturing of commands with sensitive data"
+    }
+
+    fn on_command_captured(&self, context: &CommandContext) -> Result<PluginAction, String> {
+        let Some(privacy) = Self::privacy(context) else {
+            return Ok(PluginAction::Continue);
+        };
+
+        // Commands in excluded folders are kept as a salted hash so counts and sessions still work
+        if Self::hash_only(&privacy, context) {
+            return match Self::pattern_rule(&privacy, context) {
+                Some(_) => Ok(PluginAction::Skip),
+                None => Ok(PluginAction::ModifyData),
+            };
+        }
+
+        if privacy.mode == PrivacyMode::Redact {
+            if Self::command_rule(&privacy, context).is_some() {
                 return Ok(PluginAction::Skip);
             }
+            if Self::content_rule(&privacy, context).is_some() {
+                return Ok(PluginAction::ModifyData);
+            }
+            return Ok(PluginAction::Continue);
+        }
+
+        match self.matching_rule(context) {
+            Some(_) => Ok(PluginAction::Skip),
+            None => Ok(PluginAction::Continue),
+        }
+    }
+
+    fn skip_reason(&self, context: &CommandContext) -> Option<String> {
+        let privacy = Self::privacy(context)?;
+        if Self::hash_only(&privacy, context) {
+            return Self::pattern_rule(&privacy, context);
+        }
+
+        match privacy.mode {
+            PrivacyMode::Redact => Self::command_rule(&privacy, context),
+            PrivacyMode::Skip => self.matching_rule(context),
+        }
+    }
+
+    fn modify_reason(&self, context: &CommandContext) -> Option<String> {
+        let privacy = Self::privacy(context)?;
+        if Self::hash_only(&privacy, context) {
+            return Self::folder_rule(&privacy, context).map(|rule| format!("{} (hashed)", rule));
+        }
+
+        Self::content_rule(&privacy, context)
+    }
+
+    fn modified_command(&self, context: &CommandContext) -> Option<String> {
+        let privacy = Self::privacy(context)?;
+        if Self::hash_only(&privacy, context) {
+            return Some(hashed_placeholder(&context.command));
         }
 
-        Ok(PluginAction::Continue)
+        self.redacted_command(context)
     }
 }
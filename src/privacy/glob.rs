@@ -0,0 +1,51 @@
+/// Case-insensitive shell-style glob match supporting `*` (any run of characters)
+/// and `?` (exactly one character), e.g. `prod-*` matches `prod-db-01`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` in the pattern and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character and retry
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("prod-*", "prod-db-01"));
+        assert!(glob_match("PROD-*", "prod-web"));
+        assert!(glob_match("*.internal", "db.internal"));
+        assert!(glob_match("web-??", "web-01"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("prod-*", "staging-prod-1"));
+        assert!(!glob_match("web-??", "web-1"));
+        assert!(!glob_match("db", "db2"));
+    }
+}
@@ -0,0 +1,49 @@
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::permissions::{create_private_dir, write_private};
+
+pub fn get_salt_path() -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".jotx").join("hash.salt")
+}
+
+/// Per-install random salt, so hashes of short commands can't be looked up in a
+/// precomputed table. Created on first use.
+static SALT: Lazy<Vec<u8>> = Lazy::new(|| {
+    let path = get_salt_path();
+
+    if let Ok(salt) = fs::read(&path)
+        && !salt.is_empty()
+    {
+        return salt;
+    }
+
+    let salt = rand::random::<[u8; 32]>().to_vec();
+    if let Some(parent) = path.parent() {
+        let _ = create_private_dir(parent);
+    }
+    if let Err(e) = write_private(&path, &salt) {
+        eprintln!("Failed to save hash salt: {}", e);
+    }
+    salt
+});
+
+/// Hex encoded SHA-256 of the salt followed by `content`
+pub fn salted_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(&*SALT);
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Prefix of commands stored as a hash only (see `privacy.hash_excluded_folders`)
+pub const HASHED_PREFIX: &str = "#hashed:";
+
+/// Stand-in stored instead of `content`. The same content always gives the same
+/// placeholder, so repeated commands are still counted together.
+pub fn hashed_placeholder(content: &str) -> String {
+    format!("{}{}", HASHED_PREFIX, &salted_hash(content)[..16])
+}
@@ -0,0 +1,11 @@
+pub mod glob;
+pub mod hash;
+pub mod redact;
+pub mod schedule;
+pub mod secrets;
+pub mod vault;
+
+pub use redact::{REDACTED, lowercase_redacted, redact_ranges, redact_secrets, words_containing};
+pub use secrets::{SecretMatch, find_secret, find_secrets};
+pub use hash::{HASHED_PREFIX, hashed_placeholder, salted_hash};
+pub use glob::glob_match;
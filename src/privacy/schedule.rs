@@ -0,0 +1,187 @@
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Utc, Weekday};
+
+use crate::config::{GLOBAL_CONFIG, QuietWindow};
+use crate::context::get_context;
+use crate::pid_controller::set_quiet;
+use crate::timezone::QueryTimezone;
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", value))
+}
+
+fn parse_days(day: &str) -> Result<Vec<Weekday>, String> {
+    use Weekday::*;
+
+    let days = match day.trim().to_lowercase().as_str() {
+        "weekdays" => vec![Mon, Tue, Wed, Thu, Fri],
+        "weekends" => vec![Sat, Sun],
+        other => vec![other.parse::<Weekday>().map_err(|_| format!("Invalid day '{}'", day))?],
+    };
+    Ok(days)
+}
+
+/// Check a window's days and times parse, and that start/end come together
+pub fn validate(window: &QuietWindow) -> Result<(), String> {
+    for day in &window.days {
+        parse_days(day)?;
+    }
+
+    match (&window.start, &window.end) {
+        (Some(start), Some(end)) => {
+            parse_time(start)?;
+            parse_time(end)?;
+        }
+        (None, None) => {}
+        _ => return Err("Quiet window needs both start and end".to_string()),
+    }
+
+    if window.days.is_empty() && window.start.is_none() && window.app.is_none() {
+        return Err("Quiet window needs days, a time range or an app".to_string());
+    }
+
+    Ok(())
+}
+
+/// Whether `window` applies at local time `now` with `focused_app` in front.
+/// Invalid windows never match.
+pub fn window_matches(window: &QuietWindow, now: NaiveDateTime, focused_app: Option<&str>) -> bool {
+    if validate(window).is_err() {
+        return false;
+    }
+
+    if !window.days.is_empty() {
+        let today = now.weekday();
+        let on_day = window
+            .days
+            .iter()
+            .filter_map(|day| parse_days(day).ok())
+            .any(|days| days.contains(&today));
+        if !on_day {
+            return false;
+        }
+    }
+
+    if let (Some(start), Some(end)) = (&window.start, &window.end) {
+        let (Ok(start), Ok(end)) = (parse_time(start), parse_time(end)) else {
+            return false;
+        };
+        let time = now.time();
+        let inside = if start <= end {
+            time >= start && time < end
+        } else {
+            time >= start || time < end
+        };
+        if !inside {
+            return false;
+        }
+    }
+
+    if let Some(app) = &window.app {
+        let focused = focused_app.unwrap_or_default().to_lowercase();
+        if app.is_empty() || !focused.contains(&app.to_lowercase()) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Human readable description, e.g. `weekdays 09:00-10:00` or `while "zoom" is focused`
+pub fn describe(window: &QuietWindow) -> String {
+    let mut parts = Vec::new();
+    if !window.days.is_empty() {
+        parts.push(window.days.join(","));
+    }
+    if let (Some(start), Some(end)) = (&window.start, &window.end) {
+        parts.push(format!("{}-{}", start, end));
+    }
+    if let Some(app) = &window.app {
+        parts.push(format!("while \"{}\" is focused", app));
+    }
+    parts.join(" ")
+}
+
+/// Evaluate `privacy.quiet_windows` now and update the quiet flag read by every
+/// capture path. Called from the daemon loop.
+pub fn refresh_quiet_state() {
+    let windows = match GLOBAL_CONFIG.read() {
+        Ok(config) => config.privacy.quiet_windows.clone(),
+        Err(_) => return,
+    };
+
+    let now = QueryTimezone::from_config().local_time(Utc::now());
+
+    // Only ask the window manager when a rule depends on the focused app
+    let focused_app = if windows.iter().any(|w| w.app.is_some()) {
+        get_context().ok().map(|ctx| ctx.info.name)
+    } else {
+        None
+    };
+
+    let active = windows
+        .iter()
+        .find(|w| window_matches(w, now, focused_app.as_deref()))
+        .map(describe);
+
+    if let Err(e) = set_quiet(active.as_deref()) {
+        eprintln!("Failed to update quiet state: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-01-01 was a Monday
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_weekday_time_window() {
+        let window = QuietWindow {
+            days: vec!["weekdays".to_string()],
+            start: Some("09:00".to_string()),
+            end: Some("10:00".to_string()),
+            app: None,
+        };
+
+        assert!(window_matches(&window, at(1, 9, 30), None));
+        assert!(!window_matches(&window, at(1, 10, 0), None));
+        assert!(!window_matches(&window, at(6, 9, 30), None)); // Saturday
+    }
+
+    #[test]
+    fn test_overnight_and_app_windows() {
+        let overnight = QuietWindow {
+            start: Some("22:00".to_string()),
+            end: Some("06:00".to_string()),
+            ..Default::default()
+        };
+        assert!(window_matches(&overnight, at(2, 23, 0), None));
+        assert!(window_matches(&overnight, at(2, 5, 59), None));
+        assert!(!window_matches(&overnight, at(2, 12, 0), None));
+
+        let zoom = QuietWindow {
+            app: Some("Zoom".to_string()),
+            ..Default::default()
+        };
+        assert!(window_matches(&zoom, at(2, 12, 0), Some("zoom.us")));
+        assert!(!window_matches(&zoom, at(2, 12, 0), Some("Terminal")));
+    }
+
+    #[test]
+    fn test_validate() {
+        let half_open = QuietWindow {
+            start: Some("09:00".to_string()),
+            ..Default::default()
+        };
+        assert!(validate(&half_open).is_err());
+        assert!(validate(&QuietWindow::default()).is_err());
+    }
+}
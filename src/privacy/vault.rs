@@ -0,0 +1,83 @@
+use age::secrecy::{ExposeSecret, SecretString};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::permissions::{create_private_dir, write_private};
+
+/// Vault entries are encrypted to an age X25519 key. The public half lives in
+/// `vault.pub` so entries can be sealed without a prompt; the private half is stored
+/// in `vault.key`, itself encrypted with the user's passphrase (age scrypt).
+fn get_vault_dir() -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".jotx")
+}
+
+pub fn get_vault_key_path() -> PathBuf {
+    get_vault_dir().join("vault.key")
+}
+
+pub fn get_vault_pub_path() -> PathBuf {
+    get_vault_dir().join("vault.pub")
+}
+
+pub fn vault_exists() -> bool {
+    get_vault_key_path().exists() && get_vault_pub_path().exists()
+}
+
+/// Generate the vault key pair, protecting the private key with `passphrase`
+pub fn create_vault_key(passphrase: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if vault_exists() {
+        return Err("Vault key already exists".into());
+    }
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".into());
+    }
+
+    let identity = age::x25519::Identity::generate();
+    let recipient = identity.to_public();
+
+    let wrapping = age::scrypt::Recipient::new(SecretString::from(passphrase.to_string()));
+    let sealed_key = age::encrypt(&wrapping, identity.to_string().expose_secret().as_bytes())?;
+
+    create_private_dir(&get_vault_dir())?;
+    write_private(&get_vault_key_path(), sealed_key)?;
+    write_private(&get_vault_pub_path(), recipient.to_string())?;
+
+    Ok(())
+}
+
+/// Encrypt `plaintext` for the vault. Does not need the passphrase.
+pub fn seal(plaintext: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let public = fs::read_to_string(get_vault_pub_path())
+        .map_err(|_| "No vault key yet, create one with `jotx vault <ID>`")?;
+    let recipient = age::x25519::Recipient::from_str(public.trim())
+        .map_err(|e| format!("Invalid vault public key: {}", e))?;
+
+    Ok(age::encrypt(&recipient, plaintext.as_bytes())?)
+}
+
+/// The decrypted vault key, used to open any number of entries after one passphrase prompt
+pub struct VaultKey {
+    identity: age::x25519::Identity,
+}
+
+impl VaultKey {
+    pub fn unlock(passphrase: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let sealed_key = fs::read(get_vault_key_path()).map_err(|_| "No vault key found")?;
+
+        let wrapping = age::scrypt::Identity::new(SecretString::from(passphrase.to_string()));
+        let key = age::decrypt(&wrapping, &sealed_key).map_err(|_| "Wrong vault passphrase")?;
+        let key = String::from_utf8(key).map_err(|_| "Corrupt vault key")?;
+
+        let identity = age::x25519::Identity::from_str(key.trim())
+            .map_err(|e| format!("Corrupt vault key: {}", e))?;
+
+        Ok(VaultKey { identity })
+    }
+
+    pub fn open(&self, ciphertext: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+        let plaintext = age::decrypt(&self.identity, ciphertext)?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
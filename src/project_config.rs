@@ -0,0 +1,171 @@
+//! `.jotx.toml`, settings a project keeps next to its code. The nearest one found
+//! walking up from a command's working directory applies on top of config.toml: it can
+//! keep the project's commands out of jotx, add privacy patterns, and scope searches
+//! run inside it.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::config::PrivacyConfig;
+
+pub const PROJECT_CONFIG_NAME: &str = ".jotx.toml";
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ProjectConfig {
+    /// Store commands run in the project; false keeps them all out
+    #[serde(default = "default_true")]
+    pub capture: bool,
+    #[serde(default)]
+    pub privacy: ProjectPrivacy,
+    #[serde(default)]
+    pub search: ProjectSearch,
+    /// Directory holding the file
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+/// Patterns added to the `privacy.excludes_*` lists of config.toml
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ProjectPrivacy {
+    #[serde(default)]
+    pub excludes_contains_string: Vec<String>,
+    #[serde(default)]
+    pub excludes_starts_with_string: Vec<String>,
+    #[serde(default)]
+    pub excludes_ends_with_string: Vec<String>,
+    #[serde(default)]
+    pub excludes_regex: Vec<String>,
+}
+
+/// Defaults for `jotx search` run inside the project
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ProjectSearch {
+    #[serde(default)]
+    pub scope: SearchScope,
+    /// Tag searched when `--tag` isn't given
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    /// The whole history
+    #[default]
+    Global,
+    /// Entries captured in the project's directory or below it
+    Project,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ProjectConfig {
+    /// The nearest `.jotx.toml` in `dir` or above it. A file that can't be read or
+    /// parsed is skipped with a warning, so a typo never breaks capture.
+    pub fn find(dir: &Path) -> Option<Self> {
+        let root = dir
+            .ancestors()
+            .find(|d| d.join(PROJECT_CONFIG_NAME).is_file())?;
+        match Self::load(root) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                warn!("Ignoring {}", e);
+                None
+            }
+        }
+    }
+
+    /// `find` for a captured working directory, which is empty when unknown
+    pub fn for_dir(dir: &str) -> Option<Self> {
+        if dir.is_empty() {
+            return None;
+        }
+        Self::find(Path::new(dir))
+    }
+
+    fn load(root: &Path) -> Result<Self, String> {
+        let path = root.join(PROJECT_CONFIG_NAME);
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut config: ProjectConfig = toml::from_str(&content)
+            .map_err(|e| format!("{}: not valid TOML: {}", path.display(), e))?;
+        config.root = root.to_path_buf();
+        Ok(config)
+    }
+
+    /// `privacy` from config.toml with this project's patterns added
+    pub fn merge_privacy(&self, global: &PrivacyConfig) -> PrivacyConfig {
+        let mut privacy = global.clone();
+        let extra = &self.privacy;
+        privacy
+            .excludes_contains_string
+            .extend(extra.excludes_contains_string.iter().cloned());
+        privacy
+            .excludes_starts_with_string
+            .extend(extra.excludes_starts_with_string.iter().cloned());
+        privacy
+            .excludes_ends_with_string
+            .extend(extra.excludes_ends_with_string.iter().cloned());
+        privacy
+            .excludes_regex
+            .extend(extra.excludes_regex.iter().cloned());
+        privacy
+    }
+
+    /// The directory searches are limited to, if the project asks for it
+    pub fn search_root(&self) -> Option<String> {
+        (self.search.scope == SearchScope::Project)
+            .then(|| self.root.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_project_config() {
+        let dir = std::env::temp_dir().join(format!("jotx-project-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        let nested = dir.join("app/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert!(ProjectConfig::find(&nested).is_none());
+
+        std::fs::write(
+            dir.join("app").join(PROJECT_CONFIG_NAME),
+            "[privacy]\nexcludes_contains_string = [\"STAGING_KEY\"]\n\n[search]\nscope = \"project\"\n",
+        )
+        .unwrap();
+        let project = ProjectConfig::find(&nested).unwrap();
+        assert!(project.capture);
+        assert_eq!(project.root, dir.join("app"));
+        assert_eq!(
+            project.search_root().as_deref(),
+            Some(dir.join("app").to_string_lossy().as_ref())
+        );
+
+        let privacy = project.merge_privacy(&crate::config::Config::default().privacy);
+        assert!(
+            privacy
+                .excludes_contains_string
+                .contains(&"password".to_string())
+        );
+        assert!(
+            privacy
+                .excludes_contains_string
+                .contains(&"STAGING_KEY".to_string())
+        );
+
+        // The nearest file wins, and a broken one is ignored
+        std::fs::write(nested.join(PROJECT_CONFIG_NAME), "capture = false\n").unwrap();
+        assert!(!ProjectConfig::find(&nested).unwrap().capture);
+        std::fs::write(nested.join(PROJECT_CONFIG_NAME), "capture = [").unwrap();
+        assert!(ProjectConfig::find(&nested).is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
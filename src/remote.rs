@@ -0,0 +1,101 @@
+use std::net::{IpAddr, SocketAddr};
+
+use crate::config::{GLOBAL_CONFIG, RemoteConfig};
+
+const MIN_TOKEN_LEN: usize = 16;
+
+/// Gate for every surface that exposes history outside the CLI (HTTP API, MCP, D-Bus).
+/// Loopback binds are always allowed. Anything else has to be listed in
+/// `remote.allowed_interfaces` and needs `remote.token`, so turning on an
+/// integration never publishes history to the LAN by accident.
+pub fn check_bind(remote: &RemoteConfig, addr: &SocketAddr) -> Result<(), String> {
+    if addr.ip().is_loopback() {
+        return Ok(());
+    }
+
+    let listed = remote
+        .allowed_interfaces
+        .iter()
+        .filter_map(|allowed| allowed.parse::<IpAddr>().ok())
+        .any(|allowed| allowed == addr.ip());
+    if !listed {
+        return Err(format!(
+            "Refusing to listen on {}: only localhost is allowed unless the address is in remote.allowed_interfaces",
+            addr
+        ));
+    }
+
+    match remote.token.as_deref() {
+        Some(token) if token.len() >= MIN_TOKEN_LEN => Ok(()),
+        _ => Err(format!(
+            "Refusing to listen on {}: set remote.token (at least {} characters) to serve beyond localhost",
+            addr, MIN_TOKEN_LEN
+        )),
+    }
+}
+
+/// Same as `check_bind` using the global config
+pub fn check_bind_global(addr: &SocketAddr) -> Result<(), String> {
+    let remote = GLOBAL_CONFIG
+        .read()
+        .map(|config| config.remote.clone())
+        .map_err(|_| "Failed to read config".to_string())?;
+    check_bind(&remote, addr)
+}
+
+/// Whether a request to a listener bound on `addr` carrying `presented` (e.g. a bearer
+/// token) may proceed. Loopback listeners only need a token when one is configured.
+pub fn authorized(remote: &RemoteConfig, addr: &SocketAddr, presented: Option<&str>) -> bool {
+    match (remote.token.as_deref(), presented) {
+        (Some(expected), Some(presented)) => {
+            constant_time_eq(expected.as_bytes(), presented.as_bytes())
+        }
+        (Some(_), None) => false,
+        (None, _) => addr.ip().is_loopback(),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// A random token suitable for `remote.token`
+pub fn generate_token() -> String {
+    rand::random::<[u8; 24]>()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_gating() {
+        let local: SocketAddr = "127.0.0.1:7777".parse().unwrap();
+        let lan: SocketAddr = "192.168.1.20:7777".parse().unwrap();
+        let any: SocketAddr = "0.0.0.0:7777".parse().unwrap();
+
+        let mut remote = RemoteConfig::default();
+        assert!(check_bind(&remote, &local).is_ok());
+        assert!(check_bind(&remote, &lan).is_err());
+        assert!(check_bind(&remote, &any).is_err());
+
+        // Listed but no token is still refused
+        remote.allowed_interfaces = vec!["192.168.1.20".to_string()];
+        assert!(check_bind(&remote, &lan).is_err());
+
+        remote.token = Some(generate_token());
+        assert!(check_bind(&remote, &lan).is_ok());
+        assert!(check_bind(&remote, &any).is_err());
+
+        let token = remote.token.clone();
+        assert!(authorized(&remote, &lan, token.as_deref()));
+        assert!(!authorized(&remote, &lan, Some("wrong")));
+        assert!(!authorized(&remote, &local, None));
+    }
+}
@@ -0,0 +1,14 @@
+# jotx + fzf: Ctrl-R searches jotx history (needs fzf and jq)
+# Add to ~/.bashrc: eval "$(jotx integrations fzf --shell bash)"
+__jotx_fzf_history() {
+    local selected
+    selected=$(command jotx search --json "" 2>/dev/null |
+        jq -j '"\(.timestamp | strflocaltime("%Y-%m-%d %H:%M"))\t\(.working_dir // "-")\t\(.times_run)\t\(.content)\u0000"' |
+        fzf --read0 --delimiter '\t' --with-nth '4..' --tiebreak index --query "$READLINE_LINE" \
+            --preview 'printf "Time: %s\nDir:  %s\nRuns: %s\n\n%s\n" {1} {2} {3} {4..}' \
+            --preview-window 'down:6:wrap') || return
+    READLINE_LINE=${selected#*$'\t'*$'\t'*$'\t'}
+    READLINE_POINT=${#READLINE_LINE}
+}
+bind -m emacs-standard -x '"\C-r": __jotx_fzf_history'
+bind -m vi-insert -x '"\C-r": __jotx_fzf_history'
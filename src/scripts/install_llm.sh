@@ -88,6 +88,12 @@ fi
 
 echo ""
 
+# JOTX_SKIP_MODEL=1 installs and starts Ollama only (the GUI setup pulls the model as its own stage)
+if [ "${JOTX_SKIP_MODEL:-0}" = "1" ]; then
+    echo -e "${GREEN}✓ Ollama ready (model download skipped)${NC}"
+    exit 0
+fi
+
 DEFAULT_MODEL="qwen2.5:3b"
 
 echo -e "${YELLOW}→ Checking for model: ${DEFAULT_MODEL}${NC}"
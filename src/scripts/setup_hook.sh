@@ -5,11 +5,20 @@ set -e  # Exit on error
 SHELL_NAME=$(basename "$SHELL")
 
 # Hook configuration - SAFE versions that fail silently
-# Bash hook with context - fails silently if jotx isn't available/running
-BASH_HOOK='PROMPT_COMMAND='\''if command -v jotx >/dev/null 2>&1; then history -a; cmd=$(history 1 | sed "s/^[ ]*[0-9]*[ ]*//"); jotx capture --cmd "$cmd" --pwd "$PWD" --user "$USER" --host "$HOSTNAME" 2>/dev/null || true; fi'\'''
+# Bash hook with context - fails silently if jotx isn't available/running.
+# Nothing is sent from sessions with JOTX_IGNORE set (to anything but 0).
+# PS0 stamps the start of each command (bash 5+, for the duration); PROMPT_COMMAND
+# reports the command with its exit code as soon as it finishes.
+BASH_HOOK='[ -n "$EPOCHREALTIME" ] && [[ "$PS0" != *_jotx_start* ]] && PS0="$PS0"'\''${PS1:0:$((_jotx_start=${EPOCHREALTIME/[.,]/}, 0))}'\''
+PROMPT_COMMAND='\''_jotx_ec=$?; if [ "${JOTX_IGNORE:-0}" = 0 ] && command -v jotx >/dev/null 2>&1; then history -a; cmd=$(history 1 | sed "s/^[ ]*[0-9]*[ ]*//"); _jotx_ms=; [ -n "$_jotx_start" ] && _jotx_ms=$(( (${EPOCHREALTIME/[.,]/} - _jotx_start) / 1000 )); jotx capture --cmd "$cmd" --pwd "$PWD" --user "$USER" --host "$HOSTNAME" --exit-code "$_jotx_ec" ${_jotx_ms:+--duration-ms "$_jotx_ms"} 2>/dev/null || true; fi; unset _jotx_start'\'''
 
-# Zsh hook with context - fails silently if jotx isn't available/running
-ZSH_HOOK='precmd() { if command -v jotx >/dev/null 2>&1; then cmd=$(fc -ln -1 | sed "s/^[ ]*//"); jotx capture --cmd "$cmd" --pwd "$PWD" --user "$USER" --host "$HOSTNAME" 2>/dev/null || true; fi }'
+# Zsh hook with context - fails silently if jotx isn't available/running.
+# Nothing is sent from sessions with JOTX_IGNORE set (to anything but 0).
+# preexec stamps the start of each command; precmd reports it with its exit code.
+ZSH_HOOK='zmodload zsh/datetime 2>/dev/null
+_jotx_preexec() { _jotx_start=$EPOCHREALTIME; }
+(( ${preexec_functions[(Ie)_jotx_preexec]} )) || preexec_functions+=(_jotx_preexec)
+precmd() { local exit_code=$? ms=; if [ "${JOTX_IGNORE:-0}" = 0 ] && command -v jotx >/dev/null 2>&1; then cmd=$(fc -ln -1 | sed "s/^[ ]*//"); if [ -n "$_jotx_start" ]; then ms=$(( (EPOCHREALTIME - _jotx_start) * 1000 )); ms=${ms%.*}; fi; jotx capture --cmd "$cmd" --pwd "$PWD" --user "$USER" --host "$HOSTNAME" --exit-code "$exit_code" ${ms:+--duration-ms "$ms"} 2>/dev/null || true; fi; unset _jotx_start; }'
 
 BASH_SEARCH_WRAPPER='export GIN_MODE=release
 export LLAMA_LOG_LEVEL=0
@@ -58,7 +67,15 @@ jotx() {
     fi
 }
 js() { jotx search "$@"; }
-ja() { jotx ask "$@"; }'
+ja() { jotx ask "$@"; }
+
+# zsh-autosuggestions strategy: suggest from jotx history, then plain history
+_zsh_autosuggest_strategy_jotx() {
+    emulate -L zsh
+    typeset -g suggestion
+    suggestion=$(command jotx suggest --prefix "$1" --pwd "$PWD" 2>/dev/null)
+}
+(( ${ZSH_AUTOSUGGEST_STRATEGY[(Ie)jotx]} )) || ZSH_AUTOSUGGEST_STRATEGY=(jotx ${ZSH_AUTOSUGGEST_STRATEGY:-history})'
 
 add_hook() {
     local rc_file=$1
@@ -0,0 +1,304 @@
+//! `jotx service`: run the daemon under the user's service manager, a systemd user
+//! unit on Linux or a launchd agent on macOS, so it starts on login and is restarted
+//! after a crash. Under systemd the daemon's output also goes to the journal
+//! (`journalctl --user -u jotx`).
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::logging::logs_dir;
+use crate::permissions::create_private_dir;
+use crate::pid_controller::{is_running, spawn_daemon, stop_daemon};
+
+const SYSTEMD_UNIT: &str = "jotx.service";
+const LAUNCHD_LABEL: &str = "com.jotx.daemon";
+/// Variables the clipboard needs, copied into the systemd user manager on install
+const SESSION_VARS: &[&str] = &["DISPLAY", "WAYLAND_DISPLAY", "XAUTHORITY"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceManager {
+    Systemd,
+    Launchd,
+}
+
+#[derive(Debug)]
+pub struct ServiceStatus {
+    pub manager: ServiceManager,
+    pub path: PathBuf,
+    pub installed: bool,
+    /// Starts on login
+    pub enabled: bool,
+    pub running: bool,
+}
+
+impl ServiceManager {
+    /// The manager used on this platform, if jotx supports one
+    pub fn current() -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            Some(ServiceManager::Launchd)
+        } else if cfg!(target_os = "linux") {
+            Some(ServiceManager::Systemd)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServiceManager::Systemd => "systemd",
+            ServiceManager::Launchd => "launchd",
+        }
+    }
+
+    /// Where the unit or agent is written
+    pub fn definition_path(&self) -> PathBuf {
+        let home = PathBuf::from(std::env::var("HOME").expect("HOME not set"));
+        match self {
+            ServiceManager::Systemd => std::env::var("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| home.join(".config"))
+                .join("systemd/user")
+                .join(SYSTEMD_UNIT),
+            ServiceManager::Launchd => home
+                .join("Library/LaunchAgents")
+                .join(format!("{}.plist", LAUNCHD_LABEL)),
+        }
+    }
+
+    pub fn definition(&self, exe: &Path) -> String {
+        match self {
+            ServiceManager::Systemd => systemd_unit(exe),
+            ServiceManager::Launchd => launchd_plist(exe, &logs_dir()),
+        }
+    }
+
+    pub fn is_installed(&self) -> bool {
+        self.definition_path().exists()
+    }
+
+    /// Write the unit or agent for `exe`, then enable and start it. A daemon started
+    /// by `jotx run` is stopped first, as it holds the control socket.
+    pub fn install(&self, exe: &Path) -> Result<PathBuf, String> {
+        let path = self.definition_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        fs::write(&path, self.definition(exe))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+        if is_running() {
+            stop_daemon();
+        }
+
+        match self {
+            ServiceManager::Systemd => {
+                let vars: Vec<&str> = SESSION_VARS
+                    .iter()
+                    .copied()
+                    .filter(|var| std::env::var_os(var).is_some())
+                    .collect();
+                if !vars.is_empty() {
+                    let mut args = vec!["--user", "import-environment"];
+                    args.extend(vars);
+                    run("systemctl", &args)?;
+                }
+                run("systemctl", &["--user", "daemon-reload"])?;
+                run("systemctl", &["--user", "enable", "--now", SYSTEMD_UNIT])?;
+            }
+            ServiceManager::Launchd => {
+                create_private_dir(&logs_dir())
+                    .map_err(|e| format!("Failed to create {}: {}", logs_dir().display(), e))?;
+                let path = path.to_string_lossy();
+                // Reinstalling: unload the old agent first
+                let _ = run("launchctl", &["bootout", &launchd_target()?]);
+                run("launchctl", &["bootstrap", &launchd_domain()?, &path])?;
+            }
+        }
+        Ok(path)
+    }
+
+    /// Stop and disable the service and delete its definition. Returns the deleted
+    /// file, or None if it wasn't installed.
+    pub fn uninstall(&self) -> Result<Option<PathBuf>, String> {
+        let path = self.definition_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        match self {
+            ServiceManager::Systemd => {
+                run("systemctl", &["--user", "disable", "--now", SYSTEMD_UNIT])?;
+            }
+            ServiceManager::Launchd => {
+                // Fails when the agent isn't loaded, which is fine
+                let _ = run("launchctl", &["bootout", &launchd_target()?]);
+            }
+        }
+        fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        if *self == ServiceManager::Systemd {
+            run("systemctl", &["--user", "daemon-reload"])?;
+        }
+        Ok(Some(path))
+    }
+
+    pub fn status(&self) -> ServiceStatus {
+        let installed = self.is_installed();
+        let (enabled, running) = match self {
+            ServiceManager::Systemd => (
+                run("systemctl", &["--user", "is-enabled", SYSTEMD_UNIT]).is_ok(),
+                run("systemctl", &["--user", "is-active", SYSTEMD_UNIT]).is_ok(),
+            ),
+            ServiceManager::Launchd => {
+                let loaded =
+                    launchd_target().and_then(|target| run("launchctl", &["print", &target]));
+                match loaded {
+                    Ok(info) => (true, info.contains("state = running")),
+                    Err(_) => (false, false),
+                }
+            }
+        };
+
+        ServiceStatus {
+            manager: *self,
+            path: self.definition_path(),
+            installed,
+            enabled: installed && enabled,
+            running,
+        }
+    }
+
+    fn start(&self) -> Result<(), String> {
+        match self {
+            ServiceManager::Systemd => run("systemctl", &["--user", "start", SYSTEMD_UNIT]),
+            ServiceManager::Launchd => run("launchctl", &["kickstart", &launchd_target()?]),
+        }
+        .map(|_| ())
+    }
+}
+
+/// Start the daemon through the installed service if there is one, so it stays
+/// supervised, and as a detached `exe internal-daemon` otherwise
+pub fn start_daemon(exe: &Path) -> io::Result<()> {
+    match ServiceManager::current().filter(ServiceManager::is_installed) {
+        Some(manager) => manager.start().map_err(io::Error::other),
+        None => spawn_daemon(exe),
+    }
+}
+
+/// A user unit started with the login session. It is only restarted after failures,
+/// so `jotx exit` still stops it.
+fn systemd_unit(exe: &Path) -> String {
+    // systemd expands % specifiers, even inside quotes
+    let exe = exe.to_string_lossy().replace('%', "%%");
+    format!(
+        "[Unit]
+Description=jotx clipboard and shell history daemon
+After=graphical-session.target
+
+[Service]
+Type=simple
+ExecStart=\"{}\" internal-daemon
+Restart=on-failure
+RestartSec=5
+
+[Install]
+WantedBy=default.target
+",
+        exe
+    )
+}
+
+/// A launch agent loaded at login and relaunched unless it exited cleanly
+fn launchd_plist(exe: &Path, logs: &Path) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{exe}</string>
+        <string>internal-daemon</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>{out}</string>
+    <key>StandardErrorPath</key>
+    <string>{err}</string>
+</dict>
+</plist>
+"#,
+        label = LAUNCHD_LABEL,
+        exe = xml_escape(&exe.to_string_lossy()),
+        out = xml_escape(&logs.join("launchd.out.log").to_string_lossy()),
+        err = xml_escape(&logs.join("launchd.err.log").to_string_lossy()),
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `gui/<uid>`, the launchd domain of the logged-in user
+fn launchd_domain() -> Result<String, String> {
+    let uid = run("id", &["-u"])?;
+    Ok(format!("gui/{}", uid.trim()))
+}
+
+fn launchd_target() -> Result<String, String> {
+    Ok(format!("{}/{}", launchd_domain()?, LAUNCHD_LABEL))
+}
+
+/// Run `program` and return its stdout, or an error holding its stderr
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_definitions() {
+        let unit = systemd_unit(Path::new("/home/dev/.local/bin/jotx"));
+        assert!(unit.contains("ExecStart=\"/home/dev/.local/bin/jotx\" internal-daemon\n"));
+        assert!(unit.contains("Restart=on-failure\n"));
+        assert!(unit.contains("WantedBy=default.target\n"));
+        assert!(systemd_unit(Path::new("/opt/100%/jotx")).contains("/opt/100%%/jotx"));
+
+        let plist = launchd_plist(
+            Path::new("/Users/dev/R&D/jotx"),
+            Path::new("/Users/dev/.jotx/logs"),
+        );
+        assert!(plist.contains("<string>/Users/dev/R&amp;D/jotx</string>"));
+        assert!(plist.contains("<string>com.jotx.daemon</string>"));
+        assert!(plist.contains("<string>/Users/dev/.jotx/logs/launchd.err.log</string>"));
+    }
+}
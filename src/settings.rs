@@ -1,9 +1,12 @@
 use once_cell::sync::Lazy;
-use rusqlite::{Connection, Result};
+use rusqlite::{Connection, OpenFlags, Result};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+use crate::db::open_db;
+use crate::permissions::create_private_dir;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub capture_clipboard: bool,
@@ -135,9 +138,9 @@ impl Settings {
     fn get_connection() -> Result<Connection> {
         let db_path = Self::get_db_path();
         if let Some(parent) = db_path.parent() {
-            std::fs::create_dir_all(parent).ok();
+            create_private_dir(parent).ok();
         }
-        Connection::open(db_path)
+        open_db(&db_path, OpenFlags::default())
     }
 
     fn get_db_path() -> PathBuf {
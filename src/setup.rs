@@ -1,12 +1,25 @@
 // src/setup.rs
 use std::fs;
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::str::FromStr;
+
+use clap_complete::Shell;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GLOBAL_CONFIG;
+use crate::export::ExportFormat;
+use crate::integrations::{TMUX_CONF, write_completions};
+use crate::llm::{PullProgress, pull_model_with_progress};
+use crate::permissions::{create_private_dir, write_private, write_private_temp};
+use crate::pid_controller::{DROPPED_FILE, ERR_FILE, HEALTH_FILE, LOG_FILE, PAUSE_FILE, PID_FILE, QUIET_FILE, stop_daemon};
+use crate::service::ServiceManager;
+use crate::types::HistoryFilters;
+use crate::utils::export_history;
 
 const SETUP_HOOK_SCRIPT: &str = include_str!("scripts/setup_hook.sh");
 const INSTALL_LLM_SCRIPT: &str = include_str!("scripts/install_llm.sh");
-const INSTALL_SQLITE_VEC_SCRIPT: &str = include_str!("scripts/install_sqlite_vec.sh");
 
 // ============================================================================
 // INSTALL (make install)
@@ -85,43 +98,57 @@ pub fn setup_hooks() -> Result<(), Box<dyn std::error::Error>> {
         println!(
             "Please run: source ~/.zshrc  (or ~/.bashrc) for all terminal sessions or restart your terminal"
         );
+        if io::stdin().is_terminal() {
+            offer_completions()?;
+        }
+        if Command::new("tmux").arg("-V").output().is_ok() {
+            println!("\n💡 tmux found. For a search popup on prefix + j, add to ~/.tmux.conf:\n");
+            print!("{}", TMUX_CONF);
+        }
         Ok(())
     } else {
         Err("Failed to setup hooks".into())
     }
 }
 
-// ============================================================================
-// INSTALL SQLITE-VEC
-// ============================================================================
-pub fn install_sqlite_vec() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔗 Setting up sqlite-vec...");
-
-    // Write the embedded script to a temp file
-    let temp_script = "/tmp/jotx_sqlite_vec.sh";
-    fs::write(temp_script, INSTALL_SQLITE_VEC_SCRIPT)?;
-
-    // Make it executable
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(temp_script)?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(temp_script, perms)?;
+/// Where tab completions are installed, relative to $HOME: directories bash-completion,
+/// zsh (once on fpath) and fish load completions from
+const COMPLETION_FILES: &[(Shell, &str)] = &[
+    (Shell::Bash, ".local/share/bash-completion/completions/jotx"),
+    (Shell::Zsh, ".zfunc/_jotx"),
+    (Shell::Fish, ".config/fish/completions/jotx.fish"),
+];
+
+/// Ask whether to install tab completions for the login shell, and do it
+fn offer_completions() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(shell) = Shell::from_env() else {
+        return Ok(());
+    };
+    let Some((_, relative)) = COMPLETION_FILES.iter().find(|(s, _)| *s == shell) else {
+        return Ok(());
+    };
+
+    print!("Install tab completions for {}? (y/N) ", shell);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    if !input.trim().eq_ignore_ascii_case("y") {
+        println!("Skipped (run jotx completions {} to print them)", shell);
+        return Ok(());
     }
 
-    // Run the script
-    let status = Command::new("bash").arg(temp_script).status()?;
-
-    // Clean up temp file
-    let _ = fs::remove_file(temp_script);
+    let path = PathBuf::from(std::env::var("HOME")?).join(relative);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::File::create(&path)?;
+    write_completions(shell, &mut file);
+    println!("✅ Completions installed to {}", path.display());
 
-    if status.success() {
-        println!("✅ sqlite-vec installed");
-        Ok(())
-    } else {
-        Err("Failed to setup hooks".into())
+    if shell == Shell::Zsh {
+        println!("   Make sure ~/.zshrc has fpath=(~/.zfunc $fpath) before compinit");
     }
+    Ok(())
 }
 
 // ============================================================================
@@ -179,24 +206,15 @@ pub fn install_llm(force: bool) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Write embedded script to temp file
-    let temp_script = "/tmp/jotx_install_llm.sh";
-    fs::write(temp_script, INSTALL_LLM_SCRIPT)?;
-
-    // Make executable
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(temp_script)?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(temp_script, perms)?;
-    }
+    // Write embedded script to a private temp file
+    let temp_script = write_private_temp("jotx_install_llm", INSTALL_LLM_SCRIPT)?;
 
     // Run script
-    let status = Command::new("bash").arg(temp_script).status()?;
+    let status = Command::new("bash").arg(&temp_script).status();
 
     // Clean up
-    let _ = fs::remove_file(temp_script);
+    let _ = fs::remove_file(&temp_script);
+    let status = status?;
 
     if status.success() {
         println!();
@@ -228,16 +246,13 @@ pub fn full_setup(force: bool, gui: bool) -> Result<(), Box<dyn std::error::Erro
     install_llm(force)?;
     println!();
 
-    install_sqlite_vec()?;
-    println!();
-
     // 4. Create jotx directory and save path
     let jotx_dir = PathBuf::from(std::env::var("HOME")?).join(".jotx");
-    fs::create_dir_all(&jotx_dir)?;
+    create_private_dir(&jotx_dir)?;
 
     let current_dir = std::env::current_dir()?;
-    fs::write(
-        jotx_dir.join("path"),
+    write_private(
+        &jotx_dir.join("path"),
         current_dir.to_string_lossy().as_bytes(),
     )?;
     println!("✅ Full setup complete!");
@@ -245,6 +260,170 @@ pub fn full_setup(force: bool, gui: bool) -> Result<(), Box<dyn std::error::Erro
     Ok(())
 }
 
+// ============================================================================
+// STAGED SETUP (GUI first-run wizard)
+// ============================================================================
+
+/// Steps of the GUI setup wizard. Each stage can be run (and retried) on its own;
+/// completed stages are remembered so an interrupted setup resumes where it stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStage {
+    Directories,
+    Hooks,
+    Ollama,
+    Model,
+}
+
+impl SetupStage {
+    pub const ALL: [SetupStage; 4] = [
+        SetupStage::Directories,
+        SetupStage::Hooks,
+        SetupStage::Ollama,
+        SetupStage::Model,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            SetupStage::Directories => "directories",
+            SetupStage::Hooks => "hooks",
+            SetupStage::Ollama => "ollama",
+            SetupStage::Model => "model",
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            SetupStage::Directories => "Create data directory",
+            SetupStage::Hooks => "Install shell hooks",
+            SetupStage::Ollama => "Install and start Ollama",
+            SetupStage::Model => "Download language model",
+        }
+    }
+}
+
+impl std::str::FromStr for SetupStage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SetupStage::ALL
+            .iter()
+            .find(|stage| stage.as_str() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown setup stage: {}", s))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SetupState {
+    #[serde(deserialize_with = "known_stages")]
+    completed: Vec<SetupStage>,
+}
+
+/// Completed stages, skipping ones that no longer exist (e.g. the old sqlite_vec stage,
+/// now that sqlite-vec is built in) instead of forgetting the whole state
+fn known_stages<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<SetupStage>, D::Error> {
+    let stages = Vec::<String>::deserialize(deserializer)?;
+    Ok(stages
+        .iter()
+        .filter_map(|stage| stage.parse().ok())
+        .collect())
+}
+
+fn get_setup_state_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(PathBuf::from(std::env::var("HOME")?)
+        .join(".jotx")
+        .join("setup_state.json"))
+}
+
+fn load_setup_state() -> SetupState {
+    get_setup_state_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Stages that have already completed successfully
+pub fn completed_setup_stages() -> Vec<SetupStage> {
+    load_setup_state().completed
+}
+
+/// Run a single setup stage and record it as completed on success. The model stage
+/// reports its download through `on_model_progress`.
+pub fn run_setup_stage<F>(
+    stage: SetupStage,
+    on_model_progress: F,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn(PullProgress) + Sync,
+{
+    match stage {
+        SetupStage::Directories => {
+            let jotx_dir = PathBuf::from(std::env::var("HOME")?).join(".jotx");
+            create_private_dir(&jotx_dir)?;
+
+            let current_dir = std::env::current_dir()?;
+            write_private(
+                &jotx_dir.join("path"),
+                current_dir.to_string_lossy().as_bytes(),
+            )?;
+        }
+        SetupStage::Hooks => setup_hooks()?,
+        SetupStage::Ollama => {
+            let temp_script = write_private_temp("jotx_install_ollama", INSTALL_LLM_SCRIPT)?;
+
+            let status = Command::new("bash")
+                .arg(&temp_script)
+                .env("JOTX_SKIP_MODEL", "1")
+                .status();
+            let _ = fs::remove_file(&temp_script);
+
+            if !status?.success() {
+                return Err("Ollama installation failed".into());
+            }
+        }
+        SetupStage::Model => {
+            let model = match crate::config::GLOBAL_CONFIG.read() {
+                Ok(config) => config.llm.model.clone(),
+                Err(_) => crate::config::Config::default().llm.model,
+            };
+
+            // Called from the GUI's runtime, so the download gets a runtime of its own on
+            // a separate thread
+            std::thread::scope(|scope| {
+                scope
+                    .spawn(|| {
+                        let runtime = tokio::runtime::Builder::new_current_thread()
+                            .enable_all()
+                            .build()
+                            .map_err(|e| e.to_string())?;
+                        runtime
+                            .block_on(pull_model_with_progress(&model, &on_model_progress))
+                            .map_err(|e| e.to_string())
+                    })
+                    .join()
+                    .map_err(|_| format!("Downloading model {} panicked", model))?
+            })?;
+        }
+    }
+
+    let mut state = load_setup_state();
+    if !state.completed.contains(&stage) {
+        state.completed.push(stage);
+    }
+
+    let path = get_setup_state_path()?;
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)?;
+    }
+    write_private(&path, serde_json::to_string_pretty(&state)?)?;
+
+    Ok(())
+}
+
 // ============================================================================
 // CLEAN (make clean)
 // ============================================================================
@@ -290,6 +469,169 @@ pub fn clean_data(force: bool) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+// ============================================================================
+// PURGE (jotx purge)
+// ============================================================================
+
+/// Files jotx writes outside ~/.jotx
+const TEMP_FILES: &[&str] = &[
+    PID_FILE,
+    LOG_FILE,
+    ERR_FILE,
+    PAUSE_FILE,
+    QUIET_FILE,
+    HEALTH_FILE,
+    DROPPED_FILE,
+    "/tmp/jotx_setup_hook.sh",
+];
+
+const RC_FILES: &[&str] = &[".zshrc", ".bashrc"];
+
+/// Stop the daemon, optionally export all history to `export`, then remove the data
+/// directory (DB, cache, models, logs, keys), temp files and shell hooks. With `verify`,
+/// everything is checked afterwards and a report is printed.
+/// Returns false if verification found leftovers.
+pub fn purge(export: Option<&str>, verify: bool, force: bool) -> Result<bool, Box<dyn std::error::Error>> {
+    if !force {
+        println!("⚠️  This will permanently delete all jotx data, logs and shell hooks!");
+        print!("Are you sure? (y/N) ");
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("❌ Cancelled");
+            return Ok(true);
+        }
+    }
+
+    let jotx_dir = PathBuf::from(std::env::var("HOME")?).join(".jotx");
+
+    if let Some(path) = export {
+        if std::env::current_dir()?.join(path).starts_with(&jotx_dir) {
+            return Err("Export path is inside ~/.jotx, which is about to be deleted".into());
+        }
+
+        let format = path.rsplit('.').next().unwrap_or("json");
+        let format = if ExportFormat::from_str(format).is_ok() { format } else { "json" };
+        let result = export_history(format, HistoryFilters::default(), Some(path.to_string()))?;
+        println!("✅ Exported {} entries to {}", result.count, path);
+    }
+
+    // The daemon would recreate files while we delete them, and the service would
+    // start it again on the next login
+    remove_service();
+    stop_daemon();
+
+    let model = GLOBAL_CONFIG.read().map(|c| c.llm.model.clone()).ok();
+
+    remove_hooks()?;
+
+    for file in TEMP_FILES {
+        let _ = fs::remove_file(file);
+    }
+
+    if jotx_dir.exists() {
+        fs::remove_dir_all(&jotx_dir)?;
+    }
+    println!("✅ Data deleted");
+
+    if let Some(model) = model {
+        println!(
+            "ℹ️  The Ollama model {} is shared with Ollama and was kept (remove with: ollama rm {})",
+            model, model
+        );
+    }
+
+    if !verify {
+        return Ok(true);
+    }
+
+    Ok(print_purge_report(&jotx_dir))
+}
+
+fn print_purge_report(jotx_dir: &Path) -> bool {
+    let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+    let mut clean = true;
+
+    println!();
+    println!("🔎 Verifying purge...");
+
+    let mut check = |label: String, ok: bool| {
+        println!("  {} {}", if ok { "✅" } else { "❌" }, label);
+        clean &= ok;
+    };
+
+    check(format!("{} removed", jotx_dir.display()), !jotx_dir.exists());
+
+    if let Some(manager) = ServiceManager::current() {
+        let path = manager.definition_path();
+        check(format!("{} removed", path.display()), !path.exists());
+    }
+
+    for file in TEMP_FILES {
+        check(format!("{} removed", file), !PathBuf::from(file).exists());
+    }
+
+    for rc in RC_FILES {
+        let path = home.join(rc);
+        let hooked = fs::read_to_string(&path)
+            .map(|content| content.contains("# JOTX_START") || content.contains("jotx capture"))
+            .unwrap_or(false);
+        check(format!("no hooks in {}", path.display()), !hooked);
+    }
+
+    for (_, relative) in COMPLETION_FILES {
+        let path = home.join(relative);
+        check(format!("{} removed", path.display()), !path.exists());
+    }
+
+    // Backups written by remove_hooks still contain the old hook lines. They are the
+    // user's own rc files, so they are reported rather than deleted.
+    let backups: Vec<PathBuf> = fs::read_dir(&home)
+        .map(|dir| {
+            dir.filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| {
+                    let name = p.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+                    RC_FILES.iter().any(|rc| name.starts_with(&format!("{}.backup.", rc)))
+                })
+                .filter(|p| {
+                    fs::read_to_string(p)
+                        .map(|c| c.contains("# JOTX_START"))
+                        .unwrap_or(false)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for backup in &backups {
+        println!("  ⚠️  {} is a shell config backup that still contains jotx hooks", backup.display());
+    }
+
+    println!();
+    if clean {
+        println!("✅ Verified: no jotx data remains on disk");
+    } else {
+        println!("❌ Some jotx files could not be removed (see above)");
+    }
+
+    clean
+}
+
+/// Stop and remove the systemd unit or launchd agent, if one is installed
+fn remove_service() {
+    let Some(manager) = ServiceManager::current() else {
+        return;
+    };
+    match manager.uninstall() {
+        Ok(Some(path)) => println!("✅ Removed {}", path.display()),
+        Ok(None) => {}
+        Err(e) => eprintln!("⚠️  Failed to remove the {} service: {}", manager.name(), e),
+    }
+}
+
 // ============================================================================
 // REMOVE HOOKS (for uninstall)
 // ============================================================================
@@ -308,6 +650,10 @@ pub fn remove_hooks() -> Result<(), Box<dyn std::error::Error>> {
         remove_hooks_from_file(&bashrc)?;
     }
 
+    for (_, relative) in COMPLETION_FILES {
+        let _ = fs::remove_file(PathBuf::from(&home).join(relative));
+    }
+
     Ok(())
 }
 
@@ -370,6 +716,8 @@ pub fn uninstall(force: bool) -> Result<(), Box<dyn std::error::Error>> {
 
     println!("🗑️  Uninstalling jotx...");
 
+    remove_service();
+
     // Stop daemon
     Command::new("jotx")
         .arg("exit")
@@ -0,0 +1,113 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::managers::lock_manager::try_lock_or_recover;
+use crate::pid_controller::capture_suspended;
+use crate::plugin::{CommandContext, GLOBAL_PLUGIN_MANAGER};
+use crate::privacy::lowercase_redacted;
+use crate::project_config::ProjectConfig;
+use crate::settings::GLOBAL_SETTINGS;
+use crate::shell::git_context::git_context;
+use crate::shell::shell_mon::GLOBAL_SHELL_MON;
+use crate::types::CommandRun;
+
+const SERVICE_NAME: &str = "jotx";
+const SERVICE_NAME_SHORT: &str = "js";
+const SERVICE_NAME_SHORT2: &str = "ja";
+/// Set to anything but "0" (e.g. `export JOTX_IGNORE=1`) to keep a terminal's
+/// commands out of jotx
+pub const IGNORE_ENV: &str = "JOTX_IGNORE";
+
+/// Store a command reported by a shell hook, git hook or container, after the
+/// capture settings and plugins have had their say. Never blocks on a busy lock.
+/// `exit_code` and `duration_ms` describe the run, when the reporter knows them. The
+/// git repository and branch are looked up from `pwd`, as is the `.jotx.toml` of the
+/// project it belongs to.
+pub fn capture_command(
+    cmd: &str,
+    pwd: Option<String>,
+    user: Option<String>,
+    host: Option<String>,
+    exit_code: Option<i32>,
+    duration_ms: Option<u64>,
+) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    // Avoid capturing our own commands
+    if is_own_command(cmd) {
+        return;
+    }
+
+    // The hook skips these sessions itself; this catches hooks from older setups
+    if ignored_by_env() {
+        return;
+    }
+
+    // A project can opt out with `capture = false` in its .jotx.toml
+    let project = pwd.as_deref().and_then(ProjectConfig::for_dir);
+    if project.is_some_and(|project| !project.capture) {
+        return;
+    }
+
+    // ---- SETTINGS (non-blocking)
+    let (should_capture, shell_case_sensitive) =
+        match try_lock_or_recover("settings", &GLOBAL_SETTINGS) {
+            Some(settings) => (settings.capture_shell, settings.shell_case_sensitive),
+            None => return, // lock busy → do nothing
+        };
+
+    if !should_capture || capture_suspended() {
+        return;
+    }
+
+    // ---- PLUGINS (non-blocking, may redact the command)
+    let mut context = CommandContext {
+        command: cmd.to_string(),
+        working_dir: pwd.clone().unwrap_or_default(),
+        user: user.clone().unwrap_or_default(),
+        host: host.clone().unwrap_or_default(),
+        timestamp,
+    };
+    let should_add = match try_lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER) {
+        Some(plugins) => plugins.trigger_command_captured(&mut context),
+        None => true, // assume success if busy
+    };
+
+    if !should_add {
+        return;
+    }
+
+    // ---- MONITOR (non-blocking)
+    if let Some(mut monitor) = try_lock_or_recover("shell monitor", &GLOBAL_SHELL_MON) {
+        let cmd = if shell_case_sensitive {
+            context.command
+        } else {
+            lowercase_redacted(&context.command)
+        };
+
+        let git = pwd.as_deref().and_then(|pwd| git_context(Path::new(pwd)));
+        let run = CommandRun {
+            exit_code,
+            duration_ms,
+            git_repo: git.as_ref().map(|git| git.repo.clone()),
+            git_branch: git.and_then(|git| git.branch),
+        };
+        monitor.add_command(cmd, timestamp, pwd, user, host, run);
+    }
+}
+
+/// Whether this process was started from a session marked off the record with
+/// `JOTX_IGNORE`
+pub fn ignored_by_env() -> bool {
+    std::env::var(IGNORE_ENV).is_ok_and(|value| !matches!(value.trim(), "" | "0"))
+}
+
+/// jotx's own commands (`jotx`, `js`, `ja`) are never stored
+pub(crate) fn is_own_command(cmd: &str) -> bool {
+    cmd.starts_with(SERVICE_NAME)
+        || cmd.starts_with(SERVICE_NAME_SHORT)
+        || cmd.starts_with(SERVICE_NAME_SHORT2)
+}
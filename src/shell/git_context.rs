@@ -0,0 +1,77 @@
+//! The git repository a command ran in, found by walking up from its working
+//! directory. `.git` is read directly rather than running `git`, so capture stays cheap
+//! on every prompt.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitContext {
+    /// Root of the work tree
+    pub repo: String,
+    /// Checked out branch; None on a detached HEAD
+    pub branch: Option<String>,
+}
+
+/// The repository containing `dir`, if any
+pub fn git_context(dir: &Path) -> Option<GitContext> {
+    let root = dir.ancestors().find(|d| d.join(".git").exists())?;
+    let git_dir = resolve_git_dir(&root.join(".git"))?;
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+
+    Some(GitContext {
+        repo: root.to_string_lossy().into_owned(),
+        branch: head
+            .trim()
+            .strip_prefix("ref: refs/heads/")
+            .map(str::to_string),
+    })
+}
+
+/// `.git` is a directory, or in worktrees and submodules a file saying
+/// `gitdir: <path>`, relative to the work tree unless absolute
+fn resolve_git_dir(dot_git: &Path) -> Option<PathBuf> {
+    if dot_git.is_dir() {
+        return Some(dot_git.to_path_buf());
+    }
+
+    let content = fs::read_to_string(dot_git).ok()?;
+    let path = Path::new(content.trim().strip_prefix("gitdir:")?.trim());
+    Some(dot_git.parent()?.join(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_context() {
+        let dir = std::env::temp_dir().join(format!("jotx-git-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        let nested = dir.join("app/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(dir.join("app/.git")).unwrap();
+        std::fs::write(dir.join("app/.git/HEAD"), "ref: refs/heads/feature/login\n").unwrap();
+
+        let context = git_context(&nested).unwrap();
+        assert_eq!(context.repo, dir.join("app").to_string_lossy());
+        assert_eq!(context.branch.as_deref(), Some("feature/login"));
+
+        // A linked worktree points at its git dir, here on a detached HEAD
+        let worktree = dir.join("wt");
+        std::fs::create_dir_all(dir.join("app/.git/worktrees/wt")).unwrap();
+        std::fs::write(
+            dir.join("app/.git/worktrees/wt/HEAD"),
+            "4b825dc642cb6eb9a060e54bf8d69288fbee4904\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: ../app/.git/worktrees/wt\n").unwrap();
+        let context = git_context(&worktree).unwrap();
+        assert_eq!(context.repo, worktree.to_string_lossy());
+        assert_eq!(context.branch, None);
+
+        assert_eq!(git_context(&dir), None);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1 +1,3 @@
+pub mod capture;
+pub mod git_context;
 pub mod shell_mon;
\ No newline at end of file
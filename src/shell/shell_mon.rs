@@ -3,9 +3,13 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::error;
 
-use crate::db::{DB_WRITER, SHELL_DB};
-use crate::types::ShellEntry;
+use crate::db::{DB_WRITER, READ_POOL, shell_command_id};
+use crate::export::parse_zsh_history;
+use crate::metrics::METRICS;
+use crate::normalize::{fold, normalize};
+use crate::types::{CommandRun, ShellEntry};
 
 pub struct ShellMon {}
 
@@ -24,13 +28,13 @@ impl ShellMon {
         if let Ok(bash_commands) = self.read_bash_history() {
             for cmd in bash_commands {
                 let cmd = if case_sensitive {
-                    cmd
+                    normalize(&cmd)
                 } else {
-                    cmd.to_lowercase()
+                    fold(&cmd)
                 };
 
                 if let Err(e) = self.add_or_increment(cmd, timestamp) {
-                    eprintln!("Error adding bash command: {}", e);
+                    error!("Error adding bash command: {}", e);
                 }
             }
         }
@@ -39,13 +43,13 @@ impl ShellMon {
         if let Ok(zsh_commands) = self.read_zsh_history() {
             for cmd in zsh_commands {
                 let cmd = if case_sensitive {
-                    cmd
+                    normalize(&cmd)
                 } else {
-                    cmd.to_lowercase()
+                    fold(&cmd)
                 };
 
                 if let Err(e) = self.add_or_increment(cmd, timestamp) {
-                    eprintln!("Error adding zsh command: {}", e);
+                    error!("Error adding zsh command: {}", e);
                 }
             }
         }
@@ -53,8 +57,8 @@ impl ShellMon {
         // Process fish history
         if let Ok(fish_commands) = self.read_fish_history() {
             for cmd in fish_commands {
-                if let Err(e) = self.add_or_increment(cmd, timestamp) {
-                    eprintln!("Error adding fish command: {}", e);
+                if let Err(e) = self.add_or_increment(normalize(&cmd), timestamp) {
+                    error!("Error adding fish command: {}", e);
                 }
             }
         }
@@ -67,14 +71,15 @@ impl ShellMon {
         cmd: String,
         timestamp: u64,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let db = SHELL_DB
-            .lock()
-            .map_err(|e| format!("DB lock error: {}", e))?;
-
         // Check if command exists
-        if let Some(id) = db.get_shell_command_id(&cmd)? {
+        let conn = READ_POOL.get()?;
+        let existing = shell_command_id(&conn, &cmd)?;
+        drop(conn);
+
+        if let Some(id) = existing {
             // Increment existing
-            db.increment_shell_command(id)?;
+            DB_WRITER.increment_shell(id)?;
+            METRICS.shell_captures.inc();
         } else {
             let new_entry = ShellEntry {
                 timestamp,
@@ -84,7 +89,7 @@ impl ShellMon {
                 host: None,
                 context: None,
                 working_dir: None,
-                git_repo: None,
+                run: CommandRun::default(),
             };
 
             // Insert new
@@ -101,6 +106,7 @@ impl ShellMon {
         pwd: Option<String>,
         user: Option<String>,
         host: Option<String>,
+        run: CommandRun,
     ) {
         let new_entry = ShellEntry {
             timestamp,
@@ -110,12 +116,12 @@ impl ShellMon {
             host,
             context: None,
             working_dir: pwd,
-            git_repo: None,
+            run,
         };
 
         match self.add_to_db(&new_entry) {
             Ok(_) => (),
-            Err(e) => println!("Error adding command to DB: {}", e),
+            Err(e) => error!("Error adding command to DB: {}", e),
         }
     }
 
@@ -127,15 +133,8 @@ impl ShellMon {
     }
 
     fn fallback_to_writer(&self, entry: &ShellEntry) -> Result<(), Box<dyn std::error::Error>> {
-        DB_WRITER.insert_shell(
-            entry.content.clone(),
-            entry.timestamp,
-            entry.working_dir.clone(),
-            entry.user.clone(),
-            entry.host.clone(),
-            "Terminal".to_string(),
-            "unknown".to_string(),
-        )?;
+        DB_WRITER.insert_shell(entry)?;
+        METRICS.shell_captures.inc();
         Ok(())
     }
 
@@ -160,18 +159,11 @@ impl ShellMon {
         let history_path = PathBuf::from(home).join(".zsh_history");
         let contents = fs::read_to_string(history_path)?;
 
-        let commands: Vec<String> = contents
-            .lines()
-            .filter_map(|line| {
-                // Zsh format: : 1234567890:0;command here
-                // We want just the command part after the semicolon
-                if let Some(pos) = line.find(';') {
-                    Some(line[pos + 1..].to_string())
-                } else {
-                    // Some lines might not have timestamp
-                    Some(line.to_string())
-                }
-            })
+        // Zsh format: : 1234567890:0;command here
+        // We want just the command part after the header
+        let commands: Vec<String> = parse_zsh_history(&contents)
+            .into_iter()
+            .map(|(_timestamp, command)| command)
             .collect();
 
         Ok(commands)
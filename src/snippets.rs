@@ -0,0 +1,266 @@
+//! Snippets: commands from history turned into templates. Hosts, paths and URLs in
+//! the command become `{{host}}`, `{{path}}`, `{{url}}` placeholders that
+//! `jotx snippet run` asks for, offering the original values as defaults.
+
+use crate::types::{Snippet, SnippetPlaceholder};
+
+/// Last labels that make `a.b` a hostname rather than a file name like `config.yaml`
+const HOST_SUFFIXES: &[&str] = &[
+    "com",
+    "net",
+    "org",
+    "io",
+    "dev",
+    "app",
+    "cloud",
+    "co",
+    "ai",
+    "sh",
+    "local",
+    "internal",
+    "lan",
+    "corp",
+    "home",
+    "localdomain",
+];
+
+/// Snippet names are stored lowercase without surrounding whitespace, like tags
+pub fn normalize_snippet_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err("Snippet name cannot be empty".to_string());
+    }
+    if name.chars().any(char::is_whitespace) {
+        return Err(format!("Snippet name '{}' cannot contain spaces", name));
+    }
+    Ok(name)
+}
+
+/// Build a snippet from a command, replacing hosts, paths and URLs with placeholders.
+/// A value that appears twice gets the same placeholder.
+pub fn parameterize(name: &str, command: &str, source_entry_id: Option<i64>) -> Snippet {
+    let mut placeholders: Vec<SnippetPlaceholder> = Vec::new();
+    let mut template = String::with_capacity(command.len());
+    let mut last = 0;
+
+    for (start, end) in words(command) {
+        let (value_start, value_end) = value_span(command, start, end);
+        let value = &command[value_start..value_end];
+
+        // Only the user@host or host part is replaced; a `:port` or `:/path` after it stays
+        let (replacement, replaced_len) = if let Some((user, host)) = user_at_host(value) {
+            let replacement = format!(
+                "{}@{}",
+                placeholder_for(&mut placeholders, "user", user),
+                placeholder_for(&mut placeholders, "host", host)
+            );
+            (replacement, user.len() + 1 + host.len())
+        } else if let Some((kind, matched)) = classify(value) {
+            (
+                placeholder_for(&mut placeholders, kind, matched),
+                matched.len(),
+            )
+        } else {
+            continue;
+        };
+
+        template.push_str(&command[last..value_start]);
+        template.push_str(&replacement);
+        last = value_start + replaced_len;
+    }
+    template.push_str(&command[last..]);
+
+    Snippet {
+        name: name.to_string(),
+        template,
+        placeholders,
+        source_entry_id,
+    }
+}
+
+/// Fill a snippet's placeholders with `values` (by name), keeping defaults for any missing
+pub fn fill(snippet: &Snippet, values: &[(String, String)]) -> String {
+    snippet
+        .placeholders
+        .iter()
+        .fold(snippet.template.clone(), |command, placeholder| {
+            let value = values
+                .iter()
+                .find(|(name, _)| *name == placeholder.name)
+                .map(|(_, value)| value.as_str())
+                .unwrap_or(&placeholder.default);
+            command.replace(&format!("{{{{{}}}}}", placeholder.name), value)
+        })
+}
+
+/// `{{kind}}`, `{{kind2}}`, ... reusing the placeholder of an identical earlier value
+fn placeholder_for(placeholders: &mut Vec<SnippetPlaceholder>, kind: &str, value: &str) -> String {
+    let existing = placeholders
+        .iter()
+        .find(|p| p.default == value && p.name.trim_end_matches(char::is_numeric) == kind);
+    let name = match existing {
+        Some(placeholder) => placeholder.name.clone(),
+        None => {
+            let taken = placeholders
+                .iter()
+                .filter(|p| p.name.trim_end_matches(char::is_numeric) == kind)
+                .count();
+            let name = if taken == 0 {
+                kind.to_string()
+            } else {
+                format!("{}{}", kind, taken + 1)
+            };
+            placeholders.push(SnippetPlaceholder {
+                name: name.clone(),
+                default: value.to_string(),
+            });
+            name
+        }
+    };
+    format!("{{{{{}}}}}", name)
+}
+
+/// Byte ranges of the whitespace separated words
+fn words(command: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in command.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, command.len()));
+    }
+    spans
+}
+
+/// The part of a word that holds a value: without surrounding quotes, and after the
+/// `=` of `--flag=value` or `NAME=value`
+fn value_span(command: &str, mut start: usize, mut end: usize) -> (usize, usize) {
+    let word = &command[start..end];
+    if let Some(eq) = word.find('=') {
+        let key = &word[..eq];
+        let is_key = key.starts_with('-')
+            || (!key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        if is_key {
+            start += eq + 1;
+        }
+    }
+    while start < end && matches!(command.as_bytes()[start], b'"' | b'\'') {
+        start += 1;
+    }
+    while end > start && matches!(command.as_bytes()[end - 1], b'"' | b'\'') {
+        end -= 1;
+    }
+    (start, end)
+}
+
+fn user_at_host(value: &str) -> Option<(&str, &str)> {
+    if value.contains("://") {
+        return None;
+    }
+    let (user, rest) = value.split_once('@')?;
+    let host = rest.split(':').next().unwrap_or(rest);
+    let valid_user = !user.is_empty()
+        && user
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    (valid_user && is_plain_host(host)).then_some((user, host))
+}
+
+/// The kind of placeholder a value is worth ("url", "path" or "host") and the part of
+/// the value it stands for: hosts stop before a `:port` or `:/path`
+fn classify(value: &str) -> Option<(&'static str, &str)> {
+    if value.starts_with("http://") || value.starts_with("https://") {
+        return Some(("url", value));
+    }
+    if (value.starts_with('/') && value.len() > 1)
+        || value.starts_with("~/")
+        || value.starts_with("./")
+        || value.starts_with("../")
+    {
+        return Some(("path", value));
+    }
+
+    let host = value.split(':').next().unwrap_or(value);
+    if is_ipv4(host) {
+        return Some(("host", host));
+    }
+    let labels: Vec<&str> = host.split('.').collect();
+    let valid_labels = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    let last = labels.last().map(|l| l.to_lowercase()).unwrap_or_default();
+    if valid_labels && (labels.len() >= 3 || HOST_SUFFIXES.contains(&last.as_str())) {
+        return Some(("host", host));
+    }
+    None
+}
+
+/// A bare name like `prod-db-1` after `user@`
+fn is_plain_host(host: &str) -> bool {
+    !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'))
+}
+
+fn is_ipv4(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('.').collect();
+    parts.len() == 4 && parts.iter().all(|p| p.parse::<u8>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameterize_detects_hosts_and_paths() {
+        let snippet = parameterize(
+            "deploy-prod",
+            "rsync -avz ./dist/ deploy@web-1.example.com:/var/www/app && curl https://example.com/health",
+            Some(7),
+        );
+        assert_eq!(
+            snippet.template,
+            "rsync -avz {{path}} {{user}}@{{host}}:/var/www/app && curl {{url}}"
+        );
+        let names: Vec<&str> = snippet
+            .placeholders
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["path", "user", "host", "url"]);
+
+        let values = vec![("host".to_string(), "web-2.example.com".to_string())];
+        assert_eq!(
+            fill(&snippet, &values),
+            "rsync -avz ./dist/ deploy@web-2.example.com:/var/www/app && curl https://example.com/health"
+        );
+    }
+
+    #[test]
+    fn test_parameterize_reuses_and_numbers_placeholders() {
+        let snippet = parameterize(
+            "copy",
+            "scp --config=~/.ssh/config 10.0.0.5:/tmp/a.log 10.0.0.6:/tmp/a.log && ping 10.0.0.5",
+            None,
+        );
+        assert_eq!(
+            snippet.template,
+            "scp --config={{path}} {{host}}:/tmp/a.log {{host2}}:/tmp/a.log && ping {{host}}"
+        );
+        assert_eq!(
+            parameterize("x", "cat config.yaml", None).template,
+            "cat config.yaml"
+        );
+        assert!(normalize_snippet_name("deploy prod").is_err());
+    }
+}
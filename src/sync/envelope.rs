@@ -0,0 +1,134 @@
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x25519_dalek::PublicKey;
+
+use super::keys::{DeviceKey, device_id, from_hex, to_hex};
+
+const ENVELOPE_VERSION: u32 = 1;
+const KEY_INFO: &[u8] = b"jotx-sync-v1";
+
+/// An encrypted payload from one device to another. This is all a sync relay or
+/// storage target gets to see.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub version: u32,
+    /// Sending device id
+    pub from: String,
+    /// Receiving device id
+    pub to: String,
+    /// Hex encoded 24 byte XChaCha20 nonce
+    pub nonce: String,
+    /// Hex encoded ciphertext and Poly1305 tag
+    pub ciphertext: String,
+}
+
+/// Key shared by a pair of devices: HKDF-SHA256 over their X25519 secret, salted with
+/// both public keys so each pair gets its own key
+fn pair_key(device: &DeviceKey, peer: &PublicKey) -> Key {
+    let mut keys = [device.public().to_bytes(), peer.to_bytes()];
+    keys.sort();
+
+    let hk = Hkdf::<Sha256>::new(Some(&keys.concat()), &device.shared_secret(peer));
+    let mut key = [0u8; 32];
+    hk.expand(KEY_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Key::from(key)
+}
+
+fn associated_data(from: &str, to: &str) -> Vec<u8> {
+    format!("{}:{}>{}", ENVELOPE_VERSION, from, to).into_bytes()
+}
+
+/// Encrypt `plaintext` so only `peer` can read it
+pub fn seal(
+    device: &DeviceKey,
+    peer: &PublicKey,
+    plaintext: &[u8],
+) -> Result<Envelope, Box<dyn std::error::Error>> {
+    let from = device.device_id();
+    let to = device_id(peer);
+
+    let nonce = rand::random::<[u8; 24]>();
+    let cipher = XChaCha20Poly1305::new(&pair_key(device, peer));
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &associated_data(&from, &to),
+            },
+        )
+        .map_err(|_| "Failed to encrypt sync payload")?;
+
+    Ok(Envelope {
+        version: ENVELOPE_VERSION,
+        from,
+        to,
+        nonce: to_hex(&nonce),
+        ciphertext: to_hex(&ciphertext),
+    })
+}
+
+/// Decrypt an envelope sent to this device by `sender`
+pub fn open(
+    device: &DeviceKey,
+    sender: &PublicKey,
+    envelope: &Envelope,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if envelope.version != ENVELOPE_VERSION {
+        return Err(format!("Unsupported sync envelope version {}", envelope.version).into());
+    }
+    if envelope.to != device.device_id() || envelope.from != device_id(sender) {
+        return Err("Sync envelope is not addressed to this device".into());
+    }
+
+    let nonce = from_hex(&envelope.nonce)
+        .filter(|n| n.len() == 24)
+        .ok_or("Invalid sync envelope nonce")?;
+    let ciphertext = from_hex(&envelope.ciphertext).ok_or("Invalid sync envelope ciphertext")?;
+
+    let cipher = XChaCha20Poly1305::new(&pair_key(device, sender));
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &associated_data(&envelope.from, &envelope.to),
+            },
+        )
+        .map_err(|_| "Sync envelope failed authentication")?;
+
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let laptop = DeviceKey::generate();
+        let desktop = DeviceKey::generate();
+
+        let envelope = seal(&laptop, &desktop.public(), b"git push origin main").unwrap();
+        assert!(!envelope.ciphertext.contains(&to_hex(b"git push")));
+
+        let plaintext = open(&desktop, &laptop.public(), &envelope).unwrap();
+        assert_eq!(plaintext, b"git push origin main");
+
+        // Anyone else, or a tampered envelope, is rejected
+        let other = DeviceKey::generate();
+        assert!(open(&other, &laptop.public(), &envelope).is_err());
+
+        let mut bytes = from_hex(&envelope.ciphertext).unwrap();
+        bytes[0] ^= 1;
+        let tampered = Envelope {
+            ciphertext: to_hex(&bytes),
+            ..envelope
+        };
+        assert!(open(&desktop, &laptop.public(), &tampered).is_err());
+    }
+}
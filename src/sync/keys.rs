@@ -0,0 +1,223 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::permissions::{create_private_dir, write_private};
+
+fn get_sync_dir() -> PathBuf {
+    let home = std::env::var("HOME").expect("HOME not set");
+    PathBuf::from(home).join(".jotx").join("sync")
+}
+
+pub fn get_device_key_path() -> PathBuf {
+    get_sync_dir().join("device.key")
+}
+
+pub fn get_peers_path() -> PathBuf {
+    get_sync_dir().join("peers.json")
+}
+
+/// Short id of a device, derived from its public key
+pub fn device_id(public: &PublicKey) -> String {
+    let digest = Sha256::digest(public.as_bytes());
+    to_hex(&digest[..8])
+}
+
+/// This device's sync key pair
+pub struct DeviceKey {
+    secret: StaticSecret,
+}
+
+impl DeviceKey {
+    pub fn generate() -> Self {
+        DeviceKey {
+            secret: StaticSecret::from(rand::random::<[u8; 32]>()),
+        }
+    }
+
+    /// Load the device key, creating it on first use
+    pub fn load_or_create() -> Result<Self, Box<dyn std::error::Error>> {
+        let path = get_device_key_path();
+
+        if let Ok(bytes) = fs::read(&path) {
+            let bytes: [u8; 32] = bytes
+                .try_into()
+                .map_err(|_| format!("Corrupt sync device key: {}", path.display()))?;
+            return Ok(DeviceKey {
+                secret: StaticSecret::from(bytes),
+            });
+        }
+
+        let key = DeviceKey::generate();
+        create_private_dir(&get_sync_dir())?;
+        write_private(&path, key.secret.to_bytes())?;
+        Ok(key)
+    }
+
+    pub fn public(&self) -> PublicKey {
+        PublicKey::from(&self.secret)
+    }
+
+    pub fn device_id(&self) -> String {
+        device_id(&self.public())
+    }
+
+    /// Code to enter on another device to pair with this one
+    pub fn pairing_code(&self) -> String {
+        pairing_code(&self.public())
+    }
+
+    pub(crate) fn shared_secret(&self, peer: &PublicKey) -> [u8; 32] {
+        self.secret.diffie_hellman(peer).to_bytes()
+    }
+}
+
+/// A device this one has been paired with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Peer {
+    pub device_id: String,
+    /// Hex encoded X25519 public key
+    pub public_key: String,
+    pub paired_at: i64,
+}
+
+impl Peer {
+    pub fn public(&self) -> Result<PublicKey, Box<dyn std::error::Error>> {
+        let bytes: [u8; 32] = from_hex(&self.public_key)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| format!("Corrupt public key for peer {}", self.device_id))?;
+        Ok(PublicKey::from(bytes))
+    }
+}
+
+pub fn load_peers() -> Vec<Peer> {
+    fs::read_to_string(get_peers_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Remember the device behind `code`. Pairing again with a known device is a no-op.
+pub fn add_peer(code: &str) -> Result<Peer, Box<dyn std::error::Error>> {
+    let public = parse_pairing_code(code)?;
+    let id = device_id(&public);
+
+    if DeviceKey::load_or_create()?.device_id() == id {
+        return Err("That is this device's own pairing code".into());
+    }
+
+    let mut peers = load_peers();
+    if let Some(peer) = peers.iter().find(|p| p.device_id == id) {
+        return Ok(peer.clone());
+    }
+
+    let peer = Peer {
+        device_id: id,
+        public_key: to_hex(public.as_bytes()),
+        paired_at: Utc::now().timestamp(),
+    };
+    peers.push(peer.clone());
+
+    create_private_dir(&get_sync_dir())?;
+    write_private(&get_peers_path(), serde_json::to_string_pretty(&peers)?)?;
+    Ok(peer)
+}
+
+// Pairing codes are the public key plus a 2 byte checksum in Crockford base32,
+// grouped in fives so they can be read out or typed without mistakes.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+fn pairing_code(public: &PublicKey) -> String {
+    let mut bytes = public.as_bytes().to_vec();
+    bytes.extend_from_slice(&Sha256::digest(public.as_bytes())[..2]);
+
+    let mut chars = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            chars.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        chars.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+
+    chars
+        .chunks(5)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Decode a pairing code into the device's public key, rejecting typos
+pub fn parse_pairing_code(code: &str) -> Result<PublicKey, Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in code.chars().filter(|c| !c.is_whitespace() && *c != '-') {
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        let value = ALPHABET
+            .iter()
+            .position(|a| *a as char == c)
+            .ok_or_else(|| format!("Invalid character '{}' in pairing code", c))?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    if bytes.len() != 34 {
+        return Err("Pairing code has the wrong length".into());
+    }
+
+    let key: [u8; 32] = bytes[..32].try_into()?;
+    if Sha256::digest(key)[..2] != bytes[32..] {
+        return Err("Pairing code checksum mismatch, check for typos".into());
+    }
+
+    Ok(PublicKey::from(key))
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pairing_code_round_trip() {
+        let key = DeviceKey::generate();
+        let code = key.pairing_code();
+
+        let parsed = parse_pairing_code(&code.to_lowercase()).unwrap();
+        assert_eq!(parsed.as_bytes(), key.public().as_bytes());
+
+        // A single changed character is caught by the checksum
+        let mut typo: Vec<char> = code.chars().collect();
+        typo[3] = if typo[3] == 'A' { 'B' } else { 'A' };
+        assert!(parse_pairing_code(&typo.into_iter().collect::<String>()).is_err());
+    }
+}
@@ -0,0 +1,12 @@
+//! End-to-end encryption for syncing history between devices.
+//!
+//! Every device has its own X25519 key. Devices pair by exchanging pairing codes
+//! (`jotx pair`), after which payloads are sealed for one peer with XChaCha20-Poly1305
+//! under a key derived from the pair's shared secret. A relay or storage target only
+//! ever handles `Envelope`s and never sees plaintext history.
+
+pub mod envelope;
+pub mod keys;
+
+pub use envelope::{Envelope, open, seal};
+pub use keys::{DeviceKey, Peer, add_peer, load_peers, parse_pairing_code};
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::config::GLOBAL_CONFIG;
+
+/// Timezone used to interpret day boundaries ("today", "yesterday") and to display timestamps.
+/// Timestamps themselves are always stored as UTC unix seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryTimezone {
+    Local,
+    Utc,
+    Named(Tz),
+}
+
+impl QueryTimezone {
+    /// Parse a timezone setting: "local", "utc" or an IANA name like "Europe/Berlin"
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "local" => Ok(QueryTimezone::Local),
+            "utc" | "z" => Ok(QueryTimezone::Utc),
+            _ => value
+                .trim()
+                .parse::<Tz>()
+                .map(QueryTimezone::Named)
+                .map_err(|_| format!("Unknown timezone: {}", value)),
+        }
+    }
+
+    /// Timezone from `search.timezone` in config.toml, falling back to local time
+    pub fn from_config() -> Self {
+        let value = match GLOBAL_CONFIG.read() {
+            Ok(config) => config.search.timezone.clone(),
+            Err(_) => return QueryTimezone::Local,
+        };
+
+        Self::parse(&value).unwrap_or_else(|e| {
+            eprintln!("⚠️  {}, using local time", e);
+            QueryTimezone::Local
+        })
+    }
+
+    /// Unix timestamp of midnight `days_back` days before the day containing `now`
+    pub fn day_start(&self, now: DateTime<Utc>, days_back: i64) -> i64 {
+        match self {
+            QueryTimezone::Local => day_start_in(&Local, now, days_back),
+            QueryTimezone::Utc => day_start_in(&Utc, now, days_back),
+            QueryTimezone::Named(tz) => day_start_in(tz, now, days_back),
+        }
+    }
+
+    /// Unix timestamp of the midnight starting `date` in this timezone
+    pub fn date_start(&self, date: NaiveDate) -> i64 {
+        match self {
+            QueryTimezone::Local => midnight_in(&Local, date),
+            QueryTimezone::Utc => midnight_in(&Utc, date),
+            QueryTimezone::Named(tz) => midnight_in(tz, date),
+        }
+    }
+
+    /// Wall-clock date and time of `now` in this timezone
+    pub fn local_time(&self, now: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            QueryTimezone::Local => now.with_timezone(&Local).naive_local(),
+            QueryTimezone::Utc => now.naive_utc(),
+            QueryTimezone::Named(tz) => now.with_timezone(tz).naive_local(),
+        }
+    }
+
+    /// Format a unix timestamp for display in this timezone
+    pub fn format_timestamp(&self, timestamp: i64, fmt: &str) -> String {
+        let utc = match DateTime::<Utc>::from_timestamp(timestamp, 0) {
+            Some(dt) => dt,
+            None => return timestamp.to_string(),
+        };
+
+        match self {
+            QueryTimezone::Local => utc.with_timezone(&Local).format(fmt).to_string(),
+            QueryTimezone::Utc => utc.format(fmt).to_string(),
+            QueryTimezone::Named(tz) => utc.with_timezone(tz).format(fmt).to_string(),
+        }
+    }
+}
+
+fn day_start_in<T: TimeZone>(tz: &T, now: DateTime<Utc>, days_back: i64) -> i64 {
+    let date = now.with_timezone(tz).date_naive() - Duration::days(days_back);
+    midnight_in(tz, date)
+}
+
+fn midnight_in<T: TimeZone>(tz: &T, date: NaiveDate) -> i64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+
+    // Midnight can be skipped by a DST transition; fall back to treating it as UTC
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(QueryTimezone::parse("local").unwrap(), QueryTimezone::Local);
+        assert_eq!(QueryTimezone::parse("UTC").unwrap(), QueryTimezone::Utc);
+        assert_eq!(
+            QueryTimezone::parse("Europe/Berlin").unwrap(),
+            QueryTimezone::Named(chrono_tz::Europe::Berlin)
+        );
+        assert!(QueryTimezone::parse("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_day_start_respects_timezone() {
+        // 2024-03-10 02:30 UTC is still 2024-03-09 in New York
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 2, 30, 0).unwrap();
+
+        let utc_today = QueryTimezone::Utc.day_start(now, 0);
+        assert_eq!(
+            utc_today,
+            Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+
+        let ny = QueryTimezone::Named(chrono_tz::America::New_York);
+        let ny_yesterday = ny.day_start(now, 1);
+        assert_eq!(
+            ny_yesterday,
+            Utc.with_ymd_and_hms(2024, 3, 8, 5, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+    }
+}
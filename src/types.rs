@@ -12,14 +12,16 @@ pub struct ClipboardEntry {
     pub content: String,
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
+/// A command run after another one, from `Database::get_related_commands`
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RelatedCommand {
     pub id: i64,
     pub content: String,
     pub strength: i64,       // How many times seen together
     pub sequence_order: i64, // How far apart (1 = immediate next, 2 = two steps, etc)
     pub last_seen: i64,
+    /// Strength weighted by distance and recency; results are sorted by it
+    pub score: f64,
 }
 
 #[allow(dead_code)]
@@ -30,9 +32,27 @@ pub struct ShellEntry {
     pub content: String,                       // The command
     pub times_run: u32,
     pub working_dir: Option<String>, // Where it was run
-    pub git_repo: Option<String>,    // Git repo if available
     pub user: Option<String>,        // Username
     pub host: Option<String>,        // Hostname
+    pub run: CommandRun,             // Outcome and git checkout, when known
+}
+
+/// How a shell command's run ended and the git checkout it ran in. Every field is
+/// None when the reporter doesn't know it (history imports, older hooks).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandRun {
+    /// Exit status, when reported by the hook
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Run time, when reported by the hook
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// Root of the git work tree the command ran in
+    #[serde(default)]
+    pub git_repo: Option<String>,
+    /// Checked out branch in that repo
+    #[serde(default)]
+    pub git_branch: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -64,6 +84,22 @@ pub struct PluginArgs {
     #[arg(long, conflicts_with = "check")]
     pub create: bool,
 
+    /// Opens the settings of the named plugin (~/.jotx/plugins/<name>.toml) in $EDITOR.
+    #[arg(long, conflicts_with_all = ["create", "check"])]
+    pub config: bool,
+
+    /// Lists plugins with their version, type (native, rhai or external) and status.
+    #[arg(long, conflicts_with_all = ["create", "config", "check", "enable", "disable"])]
+    pub list: bool,
+
+    /// Turns a plugin back on (saved in config.toml).
+    #[arg(long, value_name = "PLUGIN_NAME", conflicts_with_all = ["create", "config", "check", "disable"])]
+    pub enable: Option<String>,
+
+    /// Turns a plugin off without deleting its file (saved in config.toml).
+    #[arg(long, value_name = "PLUGIN_NAME", conflicts_with_all = ["create", "config", "check"])]
+    pub disable: Option<String>,
+
     /// Checks the functions exported by the specified plugin, or all plugins (e.g., --check my_plugin or --check all).
     #[arg(long, value_name = "PLUGIN_NAME")]
     pub check: Option<String>,
@@ -73,6 +109,59 @@ pub struct PluginArgs {
     pub name: Option<String>,
 }
 
+#[derive(Debug, Args)]
+pub struct VaultArgs {
+    /// List vaulted entries instead of adding any
+    #[arg(long, conflicts_with = "ids")]
+    pub list: bool,
+
+    /// Ids of the entries to encrypt into the vault
+    #[arg(value_name = "ID")]
+    pub ids: Vec<i64>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SnippetAction {
+    /// Save a history entry as a snippet, replacing hosts, paths and URLs with placeholders
+    Save {
+        /// Id of the entry (shown by jotx search --json)
+        id: i64,
+
+        #[arg(long, short = 'n', help = "Name to run the snippet by")]
+        name: String,
+    },
+    /// Ask for the placeholders and run a snippet
+    Run {
+        name: String,
+
+        #[arg(long, help = "Print the filled in command instead of running it")]
+        print: bool,
+    },
+    /// List saved snippets
+    List,
+    /// Delete a snippet
+    Delete { name: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WorkflowAction {
+    /// Replay a workflow step by step, asking before each command
+    Run {
+        /// Number of the workflow (shown by jotx workflows)
+        number: usize,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ServiceAction {
+    /// Create and enable a systemd user unit (Linux) or launchd agent (macOS)
+    Install,
+    /// Stop the service and remove it
+    Uninstall,
+    /// Show whether the service is installed and running
+    Status,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Start the clipboard/shell monitor
@@ -85,36 +174,349 @@ pub enum Commands {
         #[arg(long, short = 'c', help = "Search clipboard history instead of shell")]
         clipboard: bool,
 
+        #[arg(long, help = "Only commands run in this git repository (its name or path)")]
+        repo: Option<String>,
+
+        #[arg(long, help = "Only commands run on this git branch")]
+        branch: Option<String>,
+
         #[arg(long)]
         print_only: bool,
     },
     /// Search using keywords (alternatively use js <QUERY>)
     #[command(alias = "js")]
     Search {
+        #[arg(
+            default_value = "",
+            help = "Words to look for, and filters like dir:~/work host:laptop after:7d; may be left out with --tag"
+        )]
         query: String,
 
         #[arg(long, short = 'c', help = "Search clipboard history instead of shell")]
         clipboard: bool,
 
+        #[arg(long, short = 't', help = "Only search entries with this tag")]
+        tag: Option<String>,
+
         #[arg(long)]
         print_only: bool,
+
+        #[arg(long, help = "Print results as JSON Lines; an empty query lists all history")]
+        json: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["json", "print_only"],
+            help = "json, launcher (Alfred/Raycast) or dmenu (rofi/wofi)"
+        )]
+        format: Option<String>,
+    },
+    /// Search in a tmux popup and type the selection into the pane it was opened from
+    Tmux {
+        #[arg(long, help = "Pane to send the selection to (set by the popup)")]
+        pane: Option<String>,
+
+        #[arg(long, short = 'c', help = "Search clipboard history instead of shell")]
+        clipboard: bool,
+    },
+    /// Tag an entry (e.g. deploy, snippets), or list every tag when no entry is given
+    Tag {
+        #[arg(help = "ID of the entry (shown by jotx search)", requires = "tag")]
+        id: Option<i64>,
+
+        #[arg(help = "Tag to add or remove")]
+        tag: Option<String>,
+
+        #[arg(long, short = 'r', requires = "id", help = "Remove the tag instead of adding it")]
+        remove: bool,
+    },
+    /// Remember something on purpose: saved with this directory, found by jotx ask
+    Note {
+        #[arg(required = true, num_args = 1.., help = "What to remember")]
+        text: Vec<String>,
+    },
+    /// Pin an entry so cleanup never trims it from history
+    Pin {
+        #[arg(help = "ID of the entry (shown by jotx search)")]
+        id: i64,
+
+        #[arg(long, short = 'r', help = "Unpin the entry instead")]
+        remove: bool,
+    },
+    /// Print the last command run in this directory and how many ran today (for prompts)
+    PromptInfo {
+        #[arg(long, help = "Print all fields as JSON")]
+        json: bool,
+
+        #[arg(long, help = "Directory to describe (defaults to the current one)")]
+        pwd: Option<String>,
+    },
+    /// Print the best past command starting with a prefix (for zsh-autosuggestions)
+    Suggest {
+        #[arg(long, allow_hyphen_values = true, help = "The command line typed so far")]
+        prefix: String,
+
+        #[arg(long, help = "Directory to prefer commands from (defaults to the current one)")]
+        pwd: Option<String>,
+    },
+    /// Show what you usually run after a command (the last one captured by default)
+    Next {
+        #[arg(allow_hyphen_values = true, help = "The command, e.g. jotx next git add .")]
+        command: Vec<String>,
+
+        #[arg(long, short = 'n', default_value_t = 5, help = "How many commands to show")]
+        limit: usize,
+
+        #[arg(long, help = "Print the commands as JSON")]
+        json: bool,
+    },
+    /// List command sequences you keep running together, and replay them
+    Workflows {
+        #[command(subcommand)]
+        action: Option<WorkflowAction>,
+
+        #[arg(
+            long,
+            global = true,
+            default_value_t = 90,
+            help = "Look at sessions from the last N days"
+        )]
+        days: i64,
+
+        #[arg(
+            long,
+            global = true,
+            default_value_t = 3,
+            help = "Sessions a sequence must appear in to count as a workflow"
+        )]
+        min_runs: usize,
+
+        #[arg(long, help = "Print the workflows as JSON")]
+        json: bool,
+    },
+    /// Print a day's sessions as Markdown (for standup notes)
+    Journal {
+        #[arg(long, help = "YYYY-MM-DD, today or yesterday (defaults to today)")]
+        date: Option<String>,
+    },
+    /// Encrypt the history database with a key kept in the OS keyring (needs the encryption feature)
+    Encrypt {
+        #[arg(long, help = "Turn the encrypted database back into plaintext")]
+        decrypt: bool,
+    },
+    /// Delete entries for good: by id, by content, by day range, or picked from a list
+    Forget {
+        #[arg(help = "Ids of the entries to delete", conflicts_with_all = ["pattern", "since", "until", "interactive"])]
+        ids: Vec<i64>,
+
+        #[arg(long, short = 'p', help = "Delete entries whose content contains this text")]
+        pattern: Option<String>,
+
+        #[arg(long, help = "Delete entries from this day on (YYYY-MM-DD, today or yesterday)")]
+        since: Option<String>,
+
+        #[arg(long, help = "Delete entries up to and including this day")]
+        until: Option<String>,
+
+        #[arg(long, short = 'i', help = "Pick the entries to delete from a list (narrowed by the other filters)")]
+        interactive: bool,
+
+        #[arg(long, short = 'y', help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+    /// Generate missing embeddings and rebuild the vector index used by semantic search
+    Reindex {
+        #[arg(long, help = "Re-embed every entry, e.g. after the embedding model changed")]
+        all: bool,
+    },
+    /// Browse history full-screen: type to search, Tab to switch type, Enter to copy
+    Browse {
+        #[arg(help = "Search text to start with")]
+        query: Option<String>,
+    },
+    /// Show what's in the history: counts, top commands, busiest directories and hosts,
+    /// activity per weekday, sessions, database size and cache hit rate
+    Stats {
+        #[arg(long, default_value_t = 30, help = "How many days back the daily and weekday counts go")]
+        days: i64,
+
+        #[arg(long, default_value_t = 10, help = "How many commands, directories and hosts to list")]
+        top: usize,
+
+        #[arg(long, help = "Print the statistics as JSON")]
+        json: bool,
+    },
+    /// Show a GitHub-style grid of the commands run each day of a year
+    Heatmap {
+        #[arg(long, help = "Year to show (defaults to the current one)")]
+        year: Option<i32>,
+
+        #[arg(long, conflicts_with = "json", help = "Print an SVG image instead")]
+        svg: bool,
+
+        #[arg(long, help = "Print the count for every day as JSON")]
+        json: bool,
+    },
+    /// Write history into a notes vault as Markdown (see notes.dir in config.toml)
+    Notes {
+        #[arg(long, help = "How many days back to write (defaults to notes.days)")]
+        days: Option<i64>,
+
+        #[arg(long, help = "Folder to write to instead of notes.dir")]
+        dir: Option<String>,
+    },
+    /// Record commits and branch switches of the current repository (install or uninstall)
+    GitHooks {
+        #[arg(value_parser = ["install", "uninstall"])]
+        action: String,
+    },
+    /// Save commands from history as reusable snippets with placeholders, and run them
+    Snippet {
+        #[command(subcommand)]
+        action: SnippetAction,
+    },
+    /// Print a docker exec wrapper or devcontainer.json settings that record container commands
+    Shim {
+        #[arg(help = "docker or devcontainer")]
+        kind: String,
+    },
+    /// Print the tab completion script for bash, zsh or fish
+    Completions {
+        shell: clap_complete::Shell,
+    },
+    /// Print shell snippets integrating jotx with other tools (e.g. fzf)
+    Integrations {
+        #[arg(help = "Tool to integrate with: fzf")]
+        tool: String,
+
+        #[arg(long, help = "bash or zsh (defaults to $SHELL)")]
+        shell: Option<String>,
     },
     /// Use Plugins
     Plugin(PluginArgs),
     /// Show service status
-    Status,
-    /// Reload configs
+    Status {
+        /// Print the daemon's counters in Prometheus' text format instead
+        #[arg(long)]
+        metrics: bool,
+    },
+    /// Run the daemon as a service that starts on login and restarts after a crash
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+    /// Show the daemon's log (~/.jotx/logs/jotx.log)
+    Logs {
+        /// How many of the latest lines to show
+        #[arg(short = 'n', long, default_value_t = 50)]
+        lines: usize,
+        /// Keep printing new lines as they are written
+        #[arg(long)]
+        tail: bool,
+    },
+    /// Have the running daemon re-read config.toml, the settings and the plugins directory
     Reload,
+    /// Stop capturing clipboard and shell entries, for a while or until `jotx resume`
+    Pause {
+        #[arg(help = "How long to pause for, e.g. 30m, 2h or 1h30m (until `jotx resume` if omitted)")]
+        duration: Option<String>,
+    },
+    /// Start capturing again after `jotx pause`
+    Resume,
     /// Handle LLm setup and configuration
     HandleLlm,
     /// Show settings
     Settings,
     /// Update Exclude Privacy Settings
-    Privacy,
+    Privacy {
+        #[arg(long, help = "Show what privacy rules and plugins have blocked")]
+        audit: bool,
+    },
+    /// Encrypt entries into the passphrase protected vault
+    Vault(VaultArgs),
+    /// Decrypt vaulted entries (all of them if no id is given)
+    Unlock {
+        id: Option<i64>,
+
+        #[arg(long, help = "Store the entry in plaintext again and remove it from the vault")]
+        restore: bool,
+    },
+    /// Check the installation for problems (e.g. history readable by other users)
+    Doctor {
+        #[arg(long, help = "Repair the problems found")]
+        fix: bool,
+    },
+    /// Show this device's sync pairing code, or pair with another device's code
+    Pair {
+        code: Option<String>,
+
+        #[arg(long, conflicts_with = "code", help = "List paired devices")]
+        list: bool,
+    },
+    /// Show the full-text index status
+    Fts {
+        #[arg(long, help = "Rebuild the index with the tokenizer set in search.fts_tokenizer")]
+        rebuild: bool,
+
+        #[arg(long, help = "Verify every entry is indexed and re-index if not")]
+        check: bool,
+    },
     /// Cleanup database and optimize
     Cleanup,
     /// Clean All Data
     CleanData,
+    /// Export history as json, csv, md or atuin (zsh history for `atuin import zsh`)
+    Export {
+        #[arg(long, short = 'f', default_value = "json", help = "json, csv, md or atuin")]
+        format: String,
+
+        #[arg(long, short = 'o', value_name = "PATH", help = "Write here instead of stdout")]
+        output: Option<String>,
+
+        #[arg(long = "type", value_name = "TYPE", help = "Only export shell or clipboard entries")]
+        entry_type: Option<String>,
+
+        #[arg(long, help = "Export entries from this day on (YYYY-MM-DD, today or yesterday)")]
+        since: Option<String>,
+
+        #[arg(long, help = "Export entries up to and including this day")]
+        until: Option<String>,
+    },
+    /// Snapshot the database and config into ~/.jotx/backups, keeping the newest few
+    Backup {
+        #[arg(long, help = "List the backups instead of taking one")]
+        list: bool,
+
+        #[arg(long, help = "Backups to keep (defaults to storage.backups_to_keep)")]
+        keep: Option<usize>,
+    },
+    /// Restore the database and config from a backup, pausing capture meanwhile
+    Restore {
+        #[arg(help = "Backup file, or its name in ~/.jotx/backups")]
+        file: String,
+
+        #[arg(long, short = 'y', help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+    /// Import history from Atuin, zsh-histdb, fish or bash
+    Import {
+        #[arg(long, help = "atuin, histdb, fish or bash")]
+        from: String,
+
+        #[arg(long, value_name = "PATH", help = "History file or database (defaults to the tool's own)")]
+        path: Option<String>,
+    },
+    /// Delete all data, logs and shell hooks, optionally exporting first
+    Purge {
+        #[arg(long, help = "Check afterwards that nothing jotx-related remains and print a report")]
+        verify: bool,
+
+        #[arg(long, value_name = "PATH", help = "Export all history here first (.json, .csv or .md)")]
+        export: Option<String>,
+
+        #[arg(long, short = 'y', help = "Don't ask for confirmation")]
+        yes: bool,
+    },
     /// Update to latest version
     Update,
     /// Gracefully stop the running service
@@ -125,6 +527,14 @@ pub enum Commands {
     #[command(hide = true)] // Hide from help menu
     InternalDaemon,
 
+    #[command(hide = true)]
+    GitEvent {
+        event: String,
+
+        #[arg(allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
     #[command(hide = true)]
     Capture {
         #[arg(long)]
@@ -138,6 +548,12 @@ pub enum Commands {
 
         #[arg(long)]
         host: Option<String>,
+
+        #[arg(long)]
+        exit_code: Option<i32>,
+
+        #[arg(long)]
+        duration_ms: Option<u64>,
     },
 
     /// Setup jotx
@@ -151,6 +567,16 @@ pub enum Commands {
     /// Install LLM
     #[command(hide = true)]
     InstallLLM,
+
+    /// Benchmark inserts, search and embeddings on synthetic databases
+    #[command(hide = true)]
+    Bench {
+        #[arg(long, value_delimiter = ',', default_values_t = [1_000, 10_000], help = "Database sizes to compare")]
+        entries: Vec<usize>,
+
+        #[arg(long, default_value_t = 100, help = "Queries timed per size")]
+        queries: usize,
+    },
 }
 
 #[derive(Default)]
@@ -162,7 +588,11 @@ pub struct QueryParams {
     pub app_name: Option<String>,
     pub user: Option<String>,
     pub host: Option<String>,
+    pub tag: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
     pub limit: Option<usize>,
+    pub offset: Option<usize>,
 }
 
 #[allow(dead_code)]
@@ -180,6 +610,10 @@ pub struct Entry {
     pub host: Option<String>,
     pub app_name: Option<String>,
     pub window_title: Option<String>,
+    /// Exit status of the last run, for shell commands reported by the hook
+    pub exit_code: Option<i32>,
+    /// How long the last run took, in milliseconds
+    pub duration_ms: Option<i64>,
     pub embedding: Option<Vec<u8>>,
 }
 
@@ -187,6 +621,8 @@ pub struct Entry {
 pub enum EntryType {
     Clipboard,
     Shell,
+    /// Free text saved with `jotx note`
+    Note,
     Any,
 }
 
@@ -197,6 +633,16 @@ impl EntryType {
             EntryType::Any => "any",
             EntryType::Clipboard => "clipboard",
             EntryType::Shell => "shell",
+            EntryType::Note => "note",
+        }
+    }
+
+    /// The history searched by commands with a `-c/--clipboard` flag
+    pub fn from_clipboard_flag(clipboard: bool) -> Self {
+        if clipboard {
+            EntryType::Clipboard
+        } else {
+            EntryType::Shell
         }
     }
 }
@@ -208,6 +654,7 @@ impl fmt::Display for EntryType {
             EntryType::Any => write!(f, "any"),
             EntryType::Clipboard => write!(f, "clipboard"),
             EntryType::Shell => write!(f, "shell"),
+            EntryType::Note => write!(f, "note"),
         }
     }
 }
@@ -220,6 +667,7 @@ impl FromStr for EntryType {
         match s {
             "clipboard" => Ok(EntryType::Clipboard),
             "shell" => Ok(EntryType::Shell),
+            "note" => Ok(EntryType::Note),
             _ => Err(format!("Unknown entry type: {}", s)),
         }
     }
@@ -271,6 +719,78 @@ pub struct GUISearchResult {
     pub timestamp: i64,
 }
 
+/// Events emitted while a GUI ask is in progress
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AskStreamEvent {
+    /// A piece of a streamed LLM answer
+    Token { text: String },
+    /// A batch of search results; `stage` is "keyword" for the instant pass, "refined" after the LLM
+    Results {
+        stage: String,
+        results: Vec<GUISearchResult>,
+    },
+    /// The ask has finished
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub entry_type: String,
+    pub content: String,
+    pub timestamp: i64,
+    pub times_run: i64,
+    pub working_dir: Option<String>,
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+}
+
+impl From<Entry> for HistoryEntry {
+    fn from(entry: Entry) -> Self {
+        Self {
+            id: entry.id,
+            entry_type: entry.entry_type.to_string(),
+            content: entry.content,
+            timestamp: entry.timestamp,
+            times_run: entry.times_run,
+            working_dir: entry.working_dir,
+            user: entry.user,
+            host: entry.host,
+            app_name: entry.app_name,
+            window_title: entry.window_title,
+            exit_code: entry.exit_code,
+            duration_ms: entry.duration_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryFilters {
+    pub entry_type: Option<String>,
+    pub search: Option<String>,
+    pub working_dir: Option<String>,
+    pub host: Option<String>,
+    pub tag: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub page_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OllamaStatus {
     pub installed: bool,
@@ -284,6 +804,231 @@ pub struct PathInfo {
   pub path: String
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportResult {
+    pub count: usize,
+    /// Set when the export was written to a file
+    pub path: Option<String>,
+    /// Exported bytes, when no path was given
+    pub data: Option<Vec<u8>>,
+}
+
+/// Outcome of a delete request. When a delete is not confirmed, `deleted` is 0
+/// and `preview` shows what would be removed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteSummary {
+    pub matched: usize,
+    pub deleted: usize,
+    pub preview: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stats {
+    pub total_entries: usize,
+    pub counts_by_type: Vec<TypeCount>,
+    /// Captures per day, oldest first
+    pub daily_captures: Vec<DailyCount>,
+    pub top_commands: Vec<CommandCount>,
+    /// Working directories with the most shell command runs
+    pub top_directories: Vec<PlaceCount>,
+    /// Hosts with the most shell command runs
+    pub top_hosts: Vec<PlaceCount>,
+    /// Captures per day of the week over the same days, Monday first
+    pub weekday_captures: Vec<WeekdayCount>,
+    pub sessions: SessionStats,
+    pub db_size_bytes: u64,
+    pub cache: CacheStats,
+    /// Captures dropped since the daemon started because the DB writer fell behind
+    pub dropped_captures: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeCount {
+    pub entry_type: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyCount {
+    /// Day in YYYY-MM-DD, in the configured timezone
+    pub day: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandCount {
+    pub command: String,
+    pub times_run: i64,
+}
+
+/// A directory or host and how many commands were run there
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaceCount {
+    pub name: String,
+    pub times_run: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekdayCount {
+    /// "Mon" to "Sun"
+    pub weekday: String,
+    pub count: usize,
+}
+
+/// What `command_sessions` and `command_associations` hold
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub sessions: usize,
+    /// Average commands per session
+    pub average_length: f64,
+    /// Links between commands run close together, used for suggestions
+    pub associations: usize,
+}
+
+/// Query cache (LLM interpretation cache) effectiveness
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// A command saved with `jotx snippet save`, with `{{name}}` placeholders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub name: String,
+    pub template: String,
+    pub placeholders: Vec<SnippetPlaceholder>,
+    /// History entry the snippet was made from
+    pub source_entry_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnippetPlaceholder {
+    pub name: String,
+    /// The value in the original command, offered when prompting
+    pub default: String,
+}
+
+/// Commands that keep being run one after another, found by `jotx workflows`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workflow {
+    pub steps: Vec<String>,
+    /// Sessions the steps were run in, in this order
+    pub sessions: usize,
+}
+
+/// A command read from another tool's history by `jotx import`, with every run of the
+/// same command in the same directory merged into one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportedCommand {
+    pub content: String,
+    /// Unix seconds of the latest run; 0 until a file without timestamps is dated
+    pub timestamp: u64,
+    pub working_dir: Option<String>,
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+    pub times_run: u32,
+}
+
+/// A clipboard history row for the clipboard viewer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardItem {
+    pub id: i64,
+    /// First line(s) of the content, truncated for display
+    pub preview: String,
+    /// Length of the full content in characters
+    pub length: usize,
+    pub timestamp: i64,
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+}
+
+/// A capture that a plugin or privacy rule prevented from being stored, or (with
+/// `privacy.audit_redactions`) only let through rewritten
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedCapture {
+    pub timestamp: u64,
+    /// "shell" or "clipboard"
+    pub source: String,
+    pub plugin: String,
+    /// The rule that matched, when the plugin reports one
+    pub rule: Option<String>,
+    /// Salted hash of the blocked content, only with `privacy.audit_hash_content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// The capture was stored with the matching parts redacted (or hashed) rather than dropped
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    /// "native", "rhai" or "external"
+    pub kind: String,
+    pub enabled: bool,
+}
+
+/// Result of checking a plugin script's hooks against the plugin contract
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginCheckReport {
+    pub name: String,
+    /// Hooks found with the expected number of arguments
+    pub hooks: Vec<String>,
+    /// Hooks with the wrong number of arguments
+    pub errors: Vec<String>,
+    /// Functions that are not recognized hooks
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub running: bool,
+    pub paused: bool,
+    /// When a timed pause ends (Unix time); `None` while not paused or paused until resumed
+    #[serde(default)]
+    pub paused_until: Option<i64>,
+    pub pid: Option<u32>,
+    /// Active `privacy.quiet_windows` rule, if capture is currently off because of one
+    pub quiet: Option<String>,
+    /// Failures the daemon reported and has not recovered from, as "component: message"
+    pub problems: Vec<String>,
+    /// Captures dropped since the daemon started because the DB writer was behind
+    #[serde(default)]
+    pub dropped_captures: u64,
+    /// Seconds since the daemon started; only known when it answered on the control socket
+    #[serde(default)]
+    pub uptime_secs: Option<u64>,
+    /// The daemon's background workers; only known when it answered on the control socket
+    #[serde(default)]
+    pub workers: Vec<WorkerHealth>,
+}
+
+/// A daemon background worker as its watchdog sees it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerHealth {
+    pub name: String,
+    /// Seconds since a run last finished
+    pub last_run_secs_ago: u64,
+    /// Times a run panicked and the worker was started again
+    pub restarts: u32,
+    pub last_panic: Option<String>,
+    /// No run has finished in much longer than the worker's period
+    pub stalled: bool,
+}
+
 // ============================================================================
 // PLUGIN ACTIONS - What plugins can do
 // ============================================================================
@@ -1,31 +1,41 @@
 use std::{vec};
 
+use std::str::FromStr;
+
 use crate::{
-    config::{GLOBAL_CONFIG, PrivacyConfig, get_config_path}, db::get_db_path, settings::{GLOBAL_SETTINGS, Settings}, types::{OllamaStatus, PathInfo}
+    config::{Config, GLOBAL_CONFIG, PrivacyConfig, get_config_path}, db::{USER_DB, get_db_path, normalize_tag}, settings::{GLOBAL_SETTINGS, Settings}, types::{ClipboardItem, DaemonStatus, DeleteSummary, Entry, EntryType, ExportResult, HistoryEntry, HistoryFilters, HistoryPage, OllamaStatus, PathInfo, PluginCheckReport, PluginInfo, QueryParams, Stats, TagCount}
 };
+use crate::clipboard::clip_mon::GLOBAL_CLIP_MON;
+use crate::commands::get_plugin_dir;
+use crate::embeds::generate_embedding;
+use crate::export::{ExportFormat, export_entries};
+use crate::ipc::{IpcRequest, daemon_status_via_socket, send_command};
 use crate::llm::GLOBAL_LLM;
+use crate::managers::lock_manager::{lock_or_recover, try_lock_or_recover};
+use crate::normalize::normalize;
+use crate::plugin::{GLOBAL_PLUGIN_MANAGER, check_plugin_reports, create_new_plugin_script};
+use crate::pid_controller::{
+    dropped_captures, is_paused, is_running, paused_until, problems, quiet_reason, read_pid, spawn_daemon,
+    stop_daemon as stop_daemon_process,
+};
+use tracing::warn;
 
 pub fn load_settings() -> Result<Settings, String> {
-    match GLOBAL_SETTINGS.lock() {
-        Ok(settings) => {
-            let s = Settings {
-                capture_clipboard: settings.capture_clipboard,
-                capture_shell: settings.capture_shell,
-                capture_shell_history_with_files: settings.capture_shell_history_with_files,
-                shell_case_sensitive: settings.shell_case_sensitive,
-                clipboard_case_sensitive: settings.clipboard_case_sensitive,
-                clipboard_limit: settings.clipboard_limit,
-                shell_limit: settings.shell_limit,
-            };
-            Ok(s)
-        }
-        Err(e) => Err(format!("Failed to load settings: {}", e)),
-    }
+    let settings = lock_or_recover("settings", &GLOBAL_SETTINGS);
+    Ok(Settings {
+        capture_clipboard: settings.capture_clipboard,
+        capture_shell: settings.capture_shell,
+        capture_shell_history_with_files: settings.capture_shell_history_with_files,
+        shell_case_sensitive: settings.shell_case_sensitive,
+        clipboard_case_sensitive: settings.clipboard_case_sensitive,
+        clipboard_limit: settings.clipboard_limit,
+        shell_limit: settings.shell_limit,
+    })
 }
 
 pub fn save_settings(updated: &Settings) -> Result<(), String> {
-    match GLOBAL_SETTINGS.try_lock() {
-        Ok(mut settings) => {
+    match try_lock_or_recover("settings", &GLOBAL_SETTINGS) {
+        Some(mut settings) => {
             settings.capture_clipboard = updated.capture_clipboard;
             settings.capture_shell = updated.capture_shell;
             settings.capture_shell_history_with_files = updated.capture_shell_history_with_files;
@@ -38,7 +48,7 @@ pub fn save_settings(updated: &Settings) -> Result<(), String> {
                 .save()
                 .map_err(|e| format!("Failed to save settings: {}", e))
         }
-        Err(e) => Err(format!("Failed to save settings: {}", e)),
+        None => Err("Settings are busy, try again".to_string()),
     }
 }
 
@@ -50,6 +60,11 @@ pub fn load_privacy_config() -> Result<PrivacyConfig, String> {
 }
 
 pub fn save_privacy_config(updated: PrivacyConfig) -> Result<(), String> {
+    for pattern in &updated.clipboard_excludes_regex {
+        regex::Regex::new(pattern)
+            .map_err(|e| format!("Invalid clipboard regex '{}': {}", pattern, e))?;
+    }
+
     match GLOBAL_CONFIG.try_write() {
         Ok(mut config) => {
             config.privacy = updated;
@@ -61,6 +76,33 @@ pub fn save_privacy_config(updated: PrivacyConfig) -> Result<(), String> {
     }
 }
 
+pub fn get_config() -> Result<Config, String> {
+    match GLOBAL_CONFIG.read() {
+        Ok(config) => Ok(config.clone()),
+        Err(e) => Err(format!("Failed to load config: {}", e)),
+    }
+}
+
+/// Replace a single config section ("llm", "search", "storage", "privacy", "daemon", "plugins")
+pub fn set_config_section(section: &str, value: serde_json::Value) -> Result<(), String> {
+    {
+        let mut config = GLOBAL_CONFIG
+            .write()
+            .map_err(|e| format!("Failed to access config: {}", e))?;
+        config
+            .set_section(section, value)
+            .map_err(|e| format!("Failed to save {} config: {}", section, e))?;
+    }
+
+    if section == "llm"
+        && let Ok(mut llm_manager) = GLOBAL_LLM.try_lock()
+    {
+        llm_manager.reload_config();
+    }
+
+    Ok(())
+}
+
 pub async fn is_ollama_running() -> Result<OllamaStatus, String> {
     match GLOBAL_LLM.try_lock() {
         Ok(llm_manager) => {
@@ -91,4 +133,498 @@ pub fn get_paths() -> Result<Vec<PathInfo>, String> {
     };
 
     Ok(vec![path1, path2])
-}
\ No newline at end of file
+}
+
+const DEFAULT_HISTORY_PAGE_SIZE: usize = 50;
+
+/// Translate GUI history filters into query params (no limit/offset)
+fn history_params(filters: HistoryFilters) -> Result<QueryParams, String> {
+    let entry_type = match filters.entry_type.as_deref() {
+        None | Some("") | Some("any") => None,
+        Some(value) => Some(EntryType::from_str(value)?),
+    };
+
+    Ok(QueryParams {
+        entry_type,
+        content_search: filters.search.filter(|s| !s.is_empty()),
+        working_dir: filters.working_dir.filter(|s| !s.is_empty()),
+        host: filters.host.filter(|s| !s.is_empty()),
+        tag: match filters.tag.as_deref() {
+            None | Some("") => None,
+            Some(tag) => Some(normalize_tag(tag)?),
+        },
+        since: filters.since,
+        until: filters.until,
+        ..Default::default()
+    })
+}
+
+/// Fetch one page (0-based) of history for the GUI timeline, newest first
+pub fn get_history(page: usize, filters: HistoryFilters) -> Result<HistoryPage, String> {
+    let page_size = filters
+        .page_size
+        .filter(|size| *size > 0)
+        .unwrap_or(DEFAULT_HISTORY_PAGE_SIZE);
+
+    let params = QueryParams {
+        limit: Some(page_size),
+        offset: Some(page * page_size),
+        ..history_params(filters)?
+    };
+
+    let db = lock_or_recover("database", &USER_DB);
+
+    let total = db
+        .count_entries(&params)
+        .map_err(|e| format!("Failed to count history: {}", e))?;
+    let entries = db
+        .query_entries(&params)
+        .map_err(|e| format!("Failed to load history: {}", e))?
+        .into_iter()
+        .map(HistoryEntry::from)
+        .collect();
+
+    Ok(HistoryPage {
+        entries,
+        page,
+        page_size,
+        total,
+    })
+}
+
+pub fn get_daemon_status() -> Result<DaemonStatus, String> {
+    if let Some(status) = daemon_status_via_socket() {
+        return Ok(status);
+    }
+
+    // No answer on the control socket: fall back to the PID file
+    let running = is_running();
+
+    Ok(DaemonStatus {
+        running,
+        paused: is_paused(),
+        paused_until: paused_until().map(|end| end.timestamp()),
+        pid: if running { read_pid() } else { None },
+        quiet: if running { quiet_reason() } else { None },
+        problems: if running {
+            problems()
+                .into_iter()
+                .map(|(component, problem)| format!("{}: {}", component, problem))
+                .collect()
+        } else {
+            Vec::new()
+        },
+        dropped_captures: if running { dropped_captures() } else { 0 },
+        uptime_secs: None,
+        workers: Vec::new(),
+    })
+}
+
+const DAEMON_START_TIMEOUT_MS: u64 = 3000;
+const DAEMON_POLL_MS: u64 = 100;
+
+/// Start the background daemon (the installed `jotx` binary) and wait for it to come up
+pub fn start_daemon() -> Result<DaemonStatus, String> {
+    if is_running() {
+        return get_daemon_status();
+    }
+
+    spawn_daemon(std::path::Path::new("jotx"))
+        .map_err(|e| format!("Failed to start daemon: {}", e))?;
+
+    let mut waited = 0;
+    while !is_running() && waited < DAEMON_START_TIMEOUT_MS {
+        std::thread::sleep(std::time::Duration::from_millis(DAEMON_POLL_MS));
+        waited += DAEMON_POLL_MS;
+    }
+
+    if !is_running() {
+        return Err("Daemon did not start; check /tmp/jotx.err".to_string());
+    }
+
+    get_daemon_status()
+}
+
+pub fn stop_daemon() -> Result<DaemonStatus, String> {
+    if is_running() {
+        stop_daemon_process();
+    }
+
+    get_daemon_status()
+}
+
+/// Pause or resume capture through the running daemon's control socket
+pub fn set_capture_paused(paused: bool) -> Result<(), String> {
+    let command = if paused { IpcRequest::Pause } else { IpcRequest::Resume };
+    send_command(command).map(|_| ())
+}
+
+/// Trim entries over the configured limits and, when due, run full DB maintenance
+pub fn run_maintenance() -> Result<(), String> {
+    let (clipboard_limit, shell_limit) = {
+        let settings = lock_or_recover("settings", &GLOBAL_SETTINGS);
+        (settings.clipboard_limit, settings.shell_limit)
+    };
+
+    let db = lock_or_recover("database", &USER_DB);
+
+    // Always clean up old entries (this is cheap and frequent)
+    db.cleanup_old_entries(clipboard_limit, shell_limit)
+        .map_err(|e| format!("Cleanup error: {}", e))?;
+
+    // Only run full maintenance if it's been a while (expensive)
+    if db.should_run_maintenance() {
+        db.run_maintenance()
+            .map_err(|e| format!("Maintenance error: {}", e))?;
+        db.update_last_maintenance()
+            .map_err(|e| format!("Failed to update maintenance timestamp: {}", e))?;
+    }
+
+    Ok(())
+}
+
+pub fn list_plugins() -> Result<Vec<PluginInfo>, String> {
+    let mut plugins = lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER);
+
+    plugins.rescan();
+    Ok(plugins.info())
+}
+
+pub fn set_plugin_enabled(name: &str, enabled: bool) -> Result<(), String> {
+    let mut plugins = lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER);
+
+    plugins.set_enabled(name, enabled)
+}
+
+/// Check one plugin script, or all of them when `name` is None
+pub fn check_plugin(name: Option<&str>) -> Result<Vec<PluginCheckReport>, String> {
+    check_plugin_reports(&get_plugin_dir(), name.filter(|n| !n.is_empty() && *n != "all"))
+}
+
+pub fn create_plugin(name: &str) -> Result<String, String> {
+    let message = create_new_plugin_script(&get_plugin_dir(), name)?;
+
+    lock_or_recover("plugins", &GLOBAL_PLUGIN_MANAGER).rescan();
+
+    Ok(message)
+}
+
+const DEFAULT_STATS_DAYS: i64 = 30;
+const DEFAULT_TOP_COMMANDS: usize = 10;
+
+/// Usage statistics for the dashboard; `days` defaults to the last 30 days
+pub fn get_stats(days: Option<i64>) -> Result<Stats, String> {
+    let db = lock_or_recover("database", &USER_DB);
+
+    db.get_stats(days.unwrap_or(DEFAULT_STATS_DAYS), DEFAULT_TOP_COMMANDS)
+        .map_err(|e| format!("Failed to load stats: {}", e))
+}
+
+const DELETE_PREVIEW_SIZE: usize = 20;
+
+pub fn delete_entries(ids: &[i64]) -> Result<DeleteSummary, String> {
+    let db = lock_or_recover("database", &USER_DB);
+
+    let deleted = db
+        .delete_entries(ids)
+        .map_err(|e| format!("Failed to delete entries: {}", e))?;
+
+    Ok(DeleteSummary {
+        matched: ids.len(),
+        deleted,
+        preview: Vec::new(),
+    })
+}
+
+/// Delete every entry whose content contains `pattern`. Without `confirm`, nothing is
+/// deleted and the summary only reports how many entries match plus a preview of them.
+pub fn delete_matching(pattern: &str, confirm: bool) -> Result<DeleteSummary, String> {
+    if pattern.trim().is_empty() {
+        return Err("Pattern cannot be empty".to_string());
+    }
+
+    let mut params = QueryParams {
+        content_search: Some(pattern.to_string()),
+        ..Default::default()
+    };
+
+    let db = lock_or_recover("database", &USER_DB);
+
+    let matched = db
+        .count_entries(&params)
+        .map_err(|e| format!("Failed to count entries: {}", e))?;
+
+    if !confirm {
+        params.limit = Some(DELETE_PREVIEW_SIZE);
+        let preview = db
+            .query_entries(&params)
+            .map_err(|e| format!("Failed to load entries: {}", e))?
+            .into_iter()
+            .map(HistoryEntry::from)
+            .collect();
+
+        return Ok(DeleteSummary {
+            matched,
+            deleted: 0,
+            preview,
+        });
+    }
+
+    let ids: Vec<i64> = db
+        .query_entries(&params)
+        .map_err(|e| format!("Failed to load entries: {}", e))?
+        .into_iter()
+        .map(|entry| entry.id)
+        .collect();
+
+    let deleted = db
+        .delete_entries(&ids)
+        .map_err(|e| format!("Failed to delete entries: {}", e))?;
+
+    Ok(DeleteSummary {
+        matched,
+        deleted,
+        preview: Vec::new(),
+    })
+}
+
+/// Write history matching `filters` to `out` as it is formatted, e.g. straight to
+/// stdout. Returns the number of entries written.
+pub fn write_history<W: std::io::Write>(
+    format: ExportFormat,
+    filters: HistoryFilters,
+    out: &mut W,
+) -> Result<usize, String> {
+    let params = history_params(filters)?;
+
+    let entries = {
+        let db = lock_or_recover("database", &USER_DB);
+        db.query_entries(&params)
+            .map_err(|e| format!("Failed to load history: {}", e))?
+    };
+    let entries = entries.into_iter().map(HistoryEntry::from);
+
+    export_entries(entries, format, out).map_err(|e| format!("Export failed: {}", e))
+}
+
+/// Export history matching `filters`. With a `path` the export is written to that file
+/// (e.g. one picked in a save dialog); otherwise the bytes are returned to the caller.
+pub fn export_history(
+    format: &str,
+    filters: HistoryFilters,
+    path: Option<String>,
+) -> Result<ExportResult, String> {
+    let format = ExportFormat::from_str(format)?;
+
+    match path.filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let file = std::fs::File::create(&path)
+                .map_err(|e| format!("Failed to create {}: {}", path, e))?;
+            let mut writer = std::io::BufWriter::new(file);
+            let count = write_history(format, filters, &mut writer)?;
+
+            Ok(ExportResult {
+                count,
+                path: Some(path),
+                data: None,
+            })
+        }
+        None => {
+            let mut data = Vec::new();
+            let count = write_history(format, filters, &mut data)?;
+
+            Ok(ExportResult {
+                count,
+                path: None,
+                data: Some(data),
+            })
+        }
+    }
+}
+
+const DEFAULT_CLIPBOARD_LIMIT: usize = 100;
+const CLIPBOARD_PREVIEW_CHARS: usize = 200;
+
+/// Recent clipboard entries, newest first, optionally filtered by content
+pub fn list_clipboard(limit: Option<usize>, search: Option<String>) -> Result<Vec<ClipboardItem>, String> {
+    let params = QueryParams {
+        entry_type: Some(EntryType::Clipboard),
+        content_search: search.filter(|s| !s.is_empty()),
+        limit: Some(limit.unwrap_or(DEFAULT_CLIPBOARD_LIMIT)),
+        ..Default::default()
+    };
+
+    let db = lock_or_recover("database", &USER_DB);
+
+    let entries = db
+        .query_entries(&params)
+        .map_err(|e| format!("Failed to load clipboard history: {}", e))?;
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let length = entry.content.chars().count();
+            let mut preview: String = entry.content.chars().take(CLIPBOARD_PREVIEW_CHARS).collect();
+            if length > CLIPBOARD_PREVIEW_CHARS {
+                preview.push('…');
+            }
+
+            ClipboardItem {
+                id: entry.id,
+                preview,
+                length,
+                timestamp: entry.timestamp,
+                app_name: entry.app_name,
+                window_title: entry.window_title,
+            }
+        })
+        .collect())
+}
+
+/// Shown after copying an entry whose text was case-folded when it was captured
+pub const CASE_FOLDED_NOTE: &str = "This entry was captured case-insensitively and stored in lowercase, so its capitalization may differ from the original";
+
+/// Copy a stored entry back onto the system clipboard. The running daemon does the
+/// copy, so its monitor knows the clip and doesn't store it as a new entry; without a
+/// daemon nothing captures it and it is copied here. Vaulted entries are refused.
+/// Returns whether the stored text is case-folded (see `CASE_FOLDED_NOTE`).
+pub fn copy_entry_to_clipboard(id: i64) -> Result<bool, String> {
+    let entry = load_copyable_entry(id)?;
+
+    if is_running() {
+        send_command(IpcRequest::CopyEntry { id })?;
+    } else {
+        copy_stored_entry(id)?;
+    }
+
+    let settings = lock_or_recover("settings", &GLOBAL_SETTINGS);
+    Ok(match entry.entry_type {
+        EntryType::Shell => !settings.shell_case_sensitive,
+        EntryType::Clipboard => !settings.clipboard_case_sensitive,
+        _ => false,
+    })
+}
+
+/// Put entry `id` on the clipboard through this process's monitor. Answers
+/// `IpcRequest::CopyEntry` in the daemon.
+pub(crate) fn copy_stored_entry(id: i64) -> Result<(), String> {
+    let entry = load_copyable_entry(id)?;
+    let case_sensitive = lock_or_recover("settings", &GLOBAL_SETTINGS).clipboard_case_sensitive;
+
+    lock_or_recover("clipboard monitor", &GLOBAL_CLIP_MON)
+        .copy_back(&entry.content, case_sensitive)
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))
+}
+
+fn load_copyable_entry(id: i64) -> Result<Entry, String> {
+    let db = lock_or_recover("database", &USER_DB);
+    if db
+        .is_vaulted(id)
+        .map_err(|e| format!("Failed to load entry: {}", e))?
+    {
+        return Err(format!(
+            "Entry {} is in the vault; restore it with `jotx unlock {} --restore`",
+            id, id
+        ));
+    }
+    db.get_entry(id)
+        .map_err(|e| format!("Failed to load entry: {}", e))?
+        .ok_or_else(|| format!("No entry with id {}", id))
+}
+
+pub fn list_tags() -> Result<Vec<TagCount>, String> {
+    let db = lock_or_recover("database", &USER_DB);
+
+    db.list_tags()
+        .map_err(|e| format!("Failed to load tags: {}", e))
+}
+
+/// Add a tag to an entry; returns the entry's tags afterwards
+pub fn tag_entry(id: i64, tag: &str) -> Result<Vec<String>, String> {
+    let tag = normalize_tag(tag)?;
+    let db = lock_or_recover("database", &USER_DB);
+
+    if db
+        .get_entry(id)
+        .map_err(|e| format!("Failed to load entry: {}", e))?
+        .is_none()
+    {
+        return Err(format!("No entry with id {}", id));
+    }
+
+    db.add_tag(id, &tag)
+        .map_err(|e| format!("Failed to tag entry: {}", e))?;
+    db.tags_for_entry(id)
+        .map_err(|e| format!("Failed to load tags: {}", e))
+}
+
+/// Remove a tag from an entry; returns the entry's tags afterwards
+pub fn untag_entry(id: i64, tag: &str) -> Result<Vec<String>, String> {
+    let tag = normalize_tag(tag)?;
+    let db = lock_or_recover("database", &USER_DB);
+
+    db.remove_tag(id, &tag)
+        .map_err(|e| format!("Failed to untag entry: {}", e))?;
+    db.tags_for_entry(id)
+        .map_err(|e| format!("Failed to load tags: {}", e))
+}
+
+/// Pin or unpin an entry, which keeps it when cleanup trims old history
+pub fn pin_entry(id: i64, pinned: bool) -> Result<(), String> {
+    let db = lock_or_recover("database", &USER_DB);
+
+    let found = db
+        .set_pinned(id, pinned)
+        .map_err(|e| format!("Failed to pin entry: {}", e))?;
+    if !found {
+        return Err(format!("No entry with id {}", id));
+    }
+    Ok(())
+}
+
+/// Save a note made in `working_dir`, embedded so semantic search can find it. Returns
+/// the note's id and whether it got an embedding.
+pub fn add_note(text: &str, working_dir: Option<&str>) -> Result<(i64, bool), String> {
+    let content = normalize(text.trim());
+    if content.is_empty() {
+        return Err("Note cannot be empty".to_string());
+    }
+
+    let embedding = match generate_embedding(&content) {
+        Ok(embedding) => Some(embedding),
+        Err(e) => {
+            warn!("Note saved without an embedding: {}", e);
+            None
+        }
+    };
+    let embedded = embedding.is_some();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let user = std::env::var("USER").ok();
+    let host = std::env::var("HOSTNAME").ok();
+
+    let id = lock_or_recover("database", &USER_DB)
+        .insert_note(
+            &content,
+            timestamp,
+            working_dir,
+            user.as_deref(),
+            host.as_deref(),
+            embedding,
+        )
+        .map_err(|e| format!("Failed to save note: {}", e))?;
+    Ok((id, embedded))
+}
+
+/// One page of entries carrying `tag`, newest first
+pub fn search_by_tag(tag: &str, page: usize) -> Result<HistoryPage, String> {
+    get_history(
+        page,
+        HistoryFilters {
+            tag: Some(tag.to_string()),
+            ..Default::default()
+        },
+    )
+}
@@ -0,0 +1,219 @@
+//! Outbound webhooks configured as `[[webhooks]]` in config.toml. The daemon POSTs
+//! `{"event", "timestamp", "data"}` as JSON to every hook subscribed to the event,
+//! retrying network errors and 5xx/429 responses with backoff. With a `secret` the
+//! body is signed: `X-Jotx-Signature: sha256=<hex HMAC-SHA256 of the body>`.
+
+use chrono::{NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use std::time::Duration;
+use tracing::{error, warn};
+
+use crate::config::{GLOBAL_CONFIG, WebhookConfig, WebhookEvent};
+use crate::journal::{day_entries, split_sessions};
+use crate::pid_controller::{pid_alive, read_pid};
+use crate::plugin::BlockedCaptureWatcher;
+use crate::timezone::QueryTimezone;
+use crate::types::EntryType;
+
+/// Deliveries are tried this many times in total
+const MAX_ATTEMPTS: u32 = 4;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Send `event` to every webhook subscribed to it, in the background. Needs the
+/// tokio runtime (the daemon); does nothing outside of it.
+pub fn notify(event: WebhookEvent, data: Value) {
+    let hooks: Vec<WebhookConfig> = match GLOBAL_CONFIG.read() {
+        Ok(config) => config
+            .webhooks
+            .iter()
+            .filter(|hook| hook.wants(event))
+            .cloned()
+            .collect(),
+        Err(_) => return,
+    };
+    if hooks.is_empty() {
+        return;
+    }
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+
+    let body = json!({
+        "event": event,
+        "timestamp": Utc::now().timestamp(),
+        "data": data,
+    })
+    .to_string();
+
+    for hook in hooks {
+        let body = body.clone();
+        runtime.spawn(async move {
+            if let Err(e) = deliver(&hook, event, &body).await {
+                warn!("Webhook {} failed: {}", hook.url, e);
+            }
+        });
+    }
+}
+
+/// Whether any webhook wants `event`, to skip building payloads nobody receives
+pub fn subscribed(event: WebhookEvent) -> bool {
+    GLOBAL_CONFIG
+        .read()
+        .map(|config| config.webhooks.iter().any(|hook| hook.wants(event)))
+        .unwrap_or(false)
+}
+
+async fn deliver(hook: &WebhookConfig, event: WebhookEvent, body: &str) -> Result<(), String> {
+    let client = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let event_name = json!(event).as_str().unwrap_or_default().to_string();
+
+    let mut last_error = String::new();
+    for attempt in 0..MAX_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+        }
+
+        let mut request = client
+            .post(&hook.url)
+            .header("Content-Type", "application/json")
+            .header("X-Jotx-Event", &event_name)
+            .body(body.to_string());
+        if let Some(secret) = hook.secret.as_deref() {
+            request = request.header("X-Jotx-Signature", signature(secret, body));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                last_error = format!("HTTP {}", status);
+                if status.is_client_error() && status.as_u16() != 429 {
+                    break;
+                }
+            }
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+
+    Err(last_error)
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `body` keyed with `secret`
+pub(crate) fn signature(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body.as_bytes());
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+/// A daemon that is starting while the PID file names a process that no longer
+/// exists: the previous daemon died without shutting down. Call before `save_pid`.
+pub fn previous_daemon_crashed() -> Option<u32> {
+    read_pid().filter(|pid| *pid != std::process::id() && !pid_alive(*pid))
+}
+
+/// Turns what the daemon observes into webhook events; `tick` runs on the main loop
+pub struct WebhookEvents {
+    blocked: BlockedCaptureWatcher,
+    today: NaiveDate,
+}
+
+impl Default for WebhookEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebhookEvents {
+    pub fn new() -> Self {
+        Self {
+            blocked: BlockedCaptureWatcher::new(),
+            today: QueryTimezone::from_config().local_time(Utc::now()).date(),
+        }
+    }
+
+    pub fn tick(&mut self) {
+        // Always drain the log, so events from before a hook was configured aren't sent later
+        for blocked in self.blocked.poll() {
+            notify(WebhookEvent::CaptureBlocked, json!(blocked));
+        }
+
+        let tz = QueryTimezone::from_config();
+        let now = Utc::now();
+        let today = tz.local_time(now).date();
+        if today == self.today {
+            return;
+        }
+        self.today = today;
+
+        if subscribed(WebhookEvent::DailySummary) {
+            match day_summary(&tz, now) {
+                Ok(summary) => notify(WebhookEvent::DailySummary, summary),
+                Err(e) => error!("Daily summary failed: {}", e),
+            }
+        }
+    }
+}
+
+/// Totals for yesterday: sessions, active time, commands and clipboard items
+fn day_summary(tz: &QueryTimezone, now: chrono::DateTime<Utc>) -> Result<Value, String> {
+    let yesterday = tz.local_time(now).date() - chrono::Duration::days(1);
+    let entries = day_entries(tz.day_start(now, 1), tz.day_start(now, 0))?;
+    let sessions = split_sessions(entries);
+
+    let count = |entry_type: EntryType| -> usize {
+        sessions
+            .iter()
+            .map(|s| s.of_type(entry_type.clone()).count())
+            .sum()
+    };
+    Ok(json!({
+        "date": yesterday.format("%Y-%m-%d").to_string(),
+        "sessions": sessions.len(),
+        "active_secs": sessions.iter().map(|s| s.end() - s.start()).sum::<i64>(),
+        "commands": count(EntryType::Shell),
+        "clipboard_items": count(EntryType::Clipboard),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_matches_hmac_sha256() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_webhook_event_filter() {
+        let hook: WebhookConfig = toml::from_str(
+            r#"
+            url = "https://example.com/hook"
+            events = ["capture_blocked"]
+            "#,
+        )
+        .unwrap();
+        assert!(hook.wants(WebhookEvent::CaptureBlocked));
+        assert!(!hook.wants(WebhookEvent::DailySummary));
+
+        let all: WebhookConfig = toml::from_str(r#"url = "https://example.com/hook""#).unwrap();
+        assert!(all.wants(WebhookEvent::DaemonRestarted));
+    }
+}
@@ -0,0 +1,106 @@
+//! Workflows: runs of commands that keep coming up in the same order across
+//! sessions (`git add .` → `git commit` → `git push`), mined from `command_sessions`
+//! so `jotx workflows run` can replay them.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::types::Workflow;
+
+/// Shortest and longest runs of commands considered
+const MIN_STEPS: usize = 2;
+const MAX_STEPS: usize = 5;
+
+/// Find the command sequences run in at least `min_sessions` sessions, best first.
+/// A sequence only counts once per session, and one that only ever appears as part
+/// of a longer workflow is left out in favour of the longer one.
+pub fn mine_workflows(
+    sessions: &[Vec<String>],
+    min_sessions: usize,
+    limit: usize,
+) -> Vec<Workflow> {
+    // Running the same command twice in a row is one step
+    let mut deduped: Vec<Vec<String>> = sessions.to_vec();
+    for steps in &mut deduped {
+        steps.dedup();
+    }
+
+    let mut counts: HashMap<&[String], usize> = HashMap::new();
+    for steps in &deduped {
+        let mut seen: HashSet<&[String]> = HashSet::new();
+        for len in MIN_STEPS..=MAX_STEPS.min(steps.len()) {
+            seen.extend(steps.windows(len));
+        }
+        for sequence in seen {
+            *counts.entry(sequence).or_default() += 1;
+        }
+    }
+
+    let frequent: Vec<(&[String], usize)> = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= min_sessions.max(1))
+        .collect();
+
+    let mut workflows: Vec<Workflow> = frequent
+        .iter()
+        .filter(|(steps, count)| {
+            !frequent.iter().any(|(longer, longer_count)| {
+                longer.len() > steps.len()
+                    && longer_count >= count
+                    && longer.windows(steps.len()).any(|w| w == *steps)
+            })
+        })
+        .map(|(steps, count)| Workflow {
+            steps: steps.to_vec(),
+            sessions: *count,
+        })
+        .collect();
+
+    // Longer workflows save more typing, so they are weighed by their length
+    workflows.sort_by(|a, b| {
+        (b.sessions * b.steps.len())
+            .cmp(&(a.sessions * a.steps.len()))
+            .then(b.sessions.cmp(&a.sessions))
+            .then(a.steps.cmp(&b.steps))
+    });
+    workflows.truncate(limit);
+    workflows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(commands: &[&str]) -> Vec<String> {
+        commands.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_mine_workflows() {
+        let sessions = vec![
+            session(&["ls", "git add .", "git commit", "git push"]),
+            session(&["git add .", "git add .", "git commit", "git push", "ls"]),
+            session(&["cargo build", "git add .", "git commit", "git push"]),
+            session(&["cargo build", "cargo test"]),
+            session(&["cargo build", "cargo test"]),
+        ];
+
+        let workflows = mine_workflows(&sessions, 2, 10);
+        let steps: Vec<Vec<&str>> = workflows
+            .iter()
+            .map(|w| w.steps.iter().map(String::as_str).collect())
+            .collect();
+        // "git add . → git commit" is always followed by git push, so only the full run shows
+        assert_eq!(
+            steps,
+            vec![
+                vec!["git add .", "git commit", "git push"],
+                vec!["cargo build", "cargo test"],
+            ]
+        );
+        assert_eq!(workflows[0].sessions, 3);
+        assert_eq!(workflows[1].sessions, 2);
+
+        assert!(mine_workflows(&sessions, 4, 10).is_empty());
+        assert_eq!(mine_workflows(&sessions, 2, 1).len(), 1);
+    }
+}